@@ -1,307 +1,2419 @@
-// Aluminum Prelude Initialization
-// This module initializes the core components and functionality for the Aluminum web browser.
-// It sets up essential structures, handles global configurations, and prepares the browser
-// for optimal performance and user experience.
-
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
-use chrono::{DateTime, Utc};
-use serde::{Serialize, Deserialize};
-use tokio::runtime::Runtime;
-use url::Url;
-
-// Define core browser structures
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct BrowserConfig {
-    pub user_agent: String,
-    pub default_homepage: String,
-    pub max_concurrent_connections: usize,
-    pub enable_javascript: bool,
-    pub enable_cookies: bool,
-    pub enable_private_browsing: bool,
-    pub default_download_path: String,
-    pub custom_css: Option<String>,
-}
-
-#[derive(Debug)]
-pub struct TabManager {
-    tabs: Vec<Tab>,
-    active_tab_index: usize,
-}
-
-#[derive(Debug)]
-pub struct Tab {
-    id: uuid::Uuid,
-    url: Option<Url>,
-    title: String,
-    history: Vec<Url>,
-    load_progress: f32,
-}
-
-#[derive(Debug)]
-pub struct HistoryManager {
-    entries: Vec<HistoryEntry>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct HistoryEntry {
-    url: Url,
-    title: String,
-    timestamp: DateTime<Utc>,
-    visit_count: u32,
-}
-
-#[derive(Debug)]
-pub struct BookmarkManager {
-    bookmarks: HashMap<String, Bookmark>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Bookmark {
-    url: Url,
-    title: String,
-    tags: Vec<String>,
-    created_at: DateTime<Utc>,
-}
-
-#[derive(Debug)]
-pub struct DownloadManager {
-    active_downloads: Vec<Download>,
-    completed_downloads: Vec<Download>,
-}
-
-#[derive(Debug)]
-pub struct Download {
-    id: uuid::Uuid,
-    url: Url,
-    filename: String,
-    progress: f32,
-    status: DownloadStatus,
-}
-
-#[derive(Debug, PartialEq)]
-pub enum DownloadStatus {
-    Pending,
-    InProgress,
-    Completed,
-    Failed,
-    Cancelled,
-}
-
-// Initialize the Aluminum browser prelude
-pub fn initialize_aluminum_prelude() -> Result<AluminumBrowser, Box<dyn std::error::Error>> {
-    println!("Initializing Aluminum browser prelude...");
-
-    // Set up the browser configuration
-    let config = BrowserConfig {
-        user_agent: String::from("Aluminum/1.0 (https://aluminum.browser.org)"),
-        default_homepage: String::from("https://www.aluminum.browser.org"),
-        max_concurrent_connections: 6,
-        enable_javascript: true,
-        enable_cookies: true,
-        enable_private_browsing: false,
-        default_download_path: String::from("/home/user/Downloads"),
-        custom_css: None,
-    };
-
-    // Initialize tab manager
-    let tab_manager = TabManager {
-        tabs: vec![Tab {
-            id: uuid::Uuid::new_v4(),
-            url: None,
-            title: String::from("New Tab"),
-            history: Vec::new(),
-            load_progress: 0.0,
-        }],
-        active_tab_index: 0,
-    };
-
-    // Initialize history manager
-    let history_manager = HistoryManager {
-        entries: Vec::new(),
-    };
-
-    // Initialize bookmark manager
-    let bookmark_manager = BookmarkManager {
-        bookmarks: HashMap::new(),
-    };
-
-    // Initialize download manager
-    let download_manager = DownloadManager {
-        active_downloads: Vec::new(),
-        completed_downloads: Vec::new(),
-    };
-
-    // Set up the asynchronous runtime for handling concurrent operations
-    let runtime = Runtime::new()?;
-
-    // Create the main AluminumBrowser structure
-    let browser = AluminumBrowser {
-        config: Arc::new(Mutex::new(config)),
-        tab_manager: Arc::new(Mutex::new(tab_manager)),
-        history_manager: Arc::new(Mutex::new(history_manager)),
-        bookmark_manager: Arc::new(Mutex::new(bookmark_manager)),
-        download_manager: Arc::new(Mutex::new(download_manager)),
-        runtime: Arc::new(runtime),
-    };
-
-    // Initialize browser components
-    browser.initialize_network_stack()?;
-    browser.initialize_rendering_engine()?;
-    browser.initialize_javascript_engine()?;
-    browser.initialize_extension_system()?;
-    browser.initialize_security_features()?;
-
-    println!("Aluminum browser prelude initialization complete.");
-
-    Ok(browser)
-}
-
-pub struct AluminumBrowser {
-    config: Arc<Mutex<BrowserConfig>>,
-    tab_manager: Arc<Mutex<TabManager>>,
-    history_manager: Arc<Mutex<HistoryManager>>,
-    bookmark_manager: Arc<Mutex<BookmarkManager>>,
-    download_manager: Arc<Mutex<DownloadManager>>,
-    runtime: Arc<Runtime>,
-}
-
-impl AluminumBrowser {
-    // Initialize the network stack for handling HTTP(S) requests
-    fn initialize_network_stack(&self) -> Result<(), Box<dyn std::error::Error>> {
-        println!("Initializing network stack...");
-        // TODO: Implement network stack initialization
-        Ok(())
-    }
-
-    // Initialize the rendering engine for displaying web content
-    fn initialize_rendering_engine(&self) -> Result<(), Box<dyn std::error::Error>> {
-        println!("Initializing rendering engine...");
-        // TODO: Implement rendering engine initialization
-        Ok(())
-    }
-
-    // Initialize the JavaScript engine for executing client-side scripts
-    fn initialize_javascript_engine(&self) -> Result<(), Box<dyn std::error::Error>> {
-        println!("Initializing JavaScript engine...");
-        // TODO: Implement JavaScript engine initialization
-        Ok(())
-    }
-
-    // Initialize the extension system for supporting browser add-ons
-    fn initialize_extension_system(&self) -> Result<(), Box<dyn std::error::Error>> {
-        println!("Initializing extension system...");
-        // TODO: Implement extension system initialization
-        Ok(())
-    }
-
-    // Initialize security features such as HTTPS, content security policy, and sandboxing
-    fn initialize_security_features(&self) -> Result<(), Box<dyn std::error::Error>> {
-        println!("Initializing security features...");
-        // TODO: Implement security features initialization
-        Ok(())
-    }
-
-    // Public methods for interacting with the browser
-
-    pub fn create_new_tab(&self, url: Option<Url>) -> Result<uuid::Uuid, Box<dyn std::error::Error>> {
-        let mut tab_manager = self.tab_manager.lock().unwrap();
-        let new_tab = Tab {
-            id: uuid::Uuid::new_v4(),
-            url,
-            title: String::from("New Tab"),
-            history: Vec::new(),
-            load_progress: 0.0,
-        };
-        tab_manager.tabs.push(new_tab.clone());
-        tab_manager.active_tab_index = tab_manager.tabs.len() - 1;
-        Ok(new_tab.id)
-    }
-
-    pub fn close_tab(&self, tab_id: uuid::Uuid) -> Result<(), Box<dyn std::error::Error>> {
-        let mut tab_manager = self.tab_manager.lock().unwrap();
-        if let Some(index) = tab_manager.tabs.iter().position(|t| t.id == tab_id) {
-            tab_manager.tabs.remove(index);
-            if tab_manager.active_tab_index >= index && tab_manager.active_tab_index > 0 {
-                tab_manager.active_tab_index -= 1;
-            }
-        }
-        Ok(())
-    }
-
-    pub fn navigate_to_url(&self, url: Url) -> Result<(), Box<dyn std::error::Error>> {
-        let mut tab_manager = self.tab_manager.lock().unwrap();
-        if let Some(active_tab) = tab_manager.tabs.get_mut(tab_manager.active_tab_index) {
-            active_tab.url = Some(url.clone());
-            active_tab.history.push(url.clone());
-            
-            // Update history
-            let mut history_manager = self.history_manager.lock().unwrap();
-            history_manager.entries.push(HistoryEntry {
-                url,
-                title: String::from("Loading..."),
-                timestamp: Utc::now(),
-                visit_count: 1,
-            });
-        }
-        Ok(())
-    }
-
-    pub fn add_bookmark(&self, url: Url, title: String, tags: Vec<String>) -> Result<(), Box<dyn std::error::Error>> {
-        let mut bookmark_manager = self.bookmark_manager.lock().unwrap();
-        let bookmark = Bookmark {
-            url: url.clone(),
-            title,
-            tags,
-            created_at: Utc::now(),
-        };
-        bookmark_manager.bookmarks.insert(url.to_string(), bookmark);
-        Ok(())
-    }
-
-    pub fn start_download(&self, url: Url) -> Result<uuid::Uuid, Box<dyn std::error::Error>> {
-        let mut download_manager = self.download_manager.lock().unwrap();
-        let download = Download {
-            id: uuid::Uuid::new_v4(),
-            url: url.clone(),
-            filename: url.path().split('/').last().unwrap_or("download").to_string(),
-            progress: 0.0,
-            status: DownloadStatus::Pending,
-        };
-        download_manager.active_downloads.push(download.clone());
-        Ok(download.id)
-    }
-
-    // Additional methods for browser functionality can be added here
-}
-
-// Helper functions
-
-fn load_user_preferences() -> Result<BrowserConfig, Box<dyn std::error::Error>> {
-    // TODO: Implement loading user preferences from a configuration file
-    Ok(BrowserConfig {
-        user_agent: String::from("Aluminum/1.0 (https://aluminum.browser.org)"),
-        default_homepage: String::from("https://www.aluminum.browser.org"),
-        max_concurrent_connections: 6,
-        enable_javascript: true,
-        enable_cookies: true,
-        enable_private_browsing: false,
-        default_download_path: String::from("/home/user/Downloads"),
-        custom_css: None,
-    })
-}
-
-fn setup_logging() -> Result<(), Box<dyn std::error::Error>> {
-    // TODO: Implement logging setup for the browser
-    Ok(())
-}
-
-// Main function to start the Aluminum browser
-pub fn main() -> Result<(), Box<dyn std::error::Error>> {
-    setup_logging()?;
-    let browser = initialize_aluminum_prelude()?;
-    
-    // TODO: Implement the main event loop for the browser GUI
-    
-    Ok(())
-}
+// Aluminum Prelude Initialization
+// This module initializes the core components and functionality for the Aluminum web browser.
+// It sets up essential structures, handles global configurations, and prepares the browser
+// for optimal performance and user experience.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use log::warn;
+use serde::de::DeserializeOwned;
+use serde::{Serialize, Deserialize};
+use tokio::runtime::Runtime;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use url::Url;
+
+// HTTP(S) or SOCKS proxy that page loads and downloads are routed through
+#[path = "proxy.rs"]
+mod proxy;
+pub use proxy::{ProxyAuth, ProxyConfig};
+
+// Define core browser structures
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BrowserConfig {
+    pub user_agent: String,
+    pub default_homepage: String,
+    pub max_concurrent_connections: usize,
+    pub enable_javascript: bool,
+    pub enable_cookies: bool,
+    pub enable_private_browsing: bool,
+    pub default_download_path: String,
+    pub custom_css: Option<String>,
+    #[serde(default)]
+    pub proxy: Option<ProxyConfig>,
+    // Fetch each page's favicon after it loads
+    #[serde(default = "default_fetch_favicons")]
+    pub fetch_favicons: bool,
+}
+
+fn default_fetch_favicons() -> bool {
+    true
+}
+
+impl Default for BrowserConfig {
+    fn default() -> Self {
+        BrowserConfig {
+            user_agent: String::from("Aluminum/1.0 (https://aluminum.browser.org)"),
+            default_homepage: String::from("https://www.aluminum.browser.org"),
+            max_concurrent_connections: 6,
+            enable_javascript: true,
+            enable_cookies: true,
+            enable_private_browsing: false,
+            default_download_path: String::from("/home/user/Downloads"),
+            custom_css: None,
+            proxy: None,
+            fetch_favicons: true,
+        }
+    }
+}
+
+// Chainable overrides, e.g. `BrowserConfig::default().with_javascript(false)`
+impl BrowserConfig {
+    pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    pub fn with_homepage(mut self, homepage: impl Into<String>) -> Self {
+        self.default_homepage = homepage.into();
+        self
+    }
+
+    pub fn with_max_concurrent_connections(mut self, max_concurrent_connections: usize) -> Self {
+        self.max_concurrent_connections = max_concurrent_connections;
+        self
+    }
+
+    pub fn with_javascript(mut self, enabled: bool) -> Self {
+        self.enable_javascript = enabled;
+        self
+    }
+
+    pub fn with_cookies(mut self, enabled: bool) -> Self {
+        self.enable_cookies = enabled;
+        self
+    }
+
+    pub fn with_private_browsing(mut self, enabled: bool) -> Self {
+        self.enable_private_browsing = enabled;
+        self
+    }
+
+    pub fn with_download_path(mut self, path: impl Into<String>) -> Self {
+        self.default_download_path = path.into();
+        self
+    }
+
+    pub fn with_custom_css(mut self, custom_css: Option<String>) -> Self {
+        self.custom_css = custom_css;
+        self
+    }
+
+    pub fn with_proxy(mut self, proxy: Option<ProxyConfig>) -> Self {
+        self.proxy = proxy;
+        self
+    }
+
+    pub fn with_favicons(mut self, enabled: bool) -> Self {
+        self.fetch_favicons = enabled;
+        self
+    }
+}
+
+#[derive(Debug)]
+pub struct TabManager {
+    tabs: Vec<Tab>,
+    active_tab_index: usize,
+    groups: Vec<TabGroup>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TabGroup {
+    id: uuid::Uuid,
+    name: String,
+    color: String,
+    tab_ids: Vec<uuid::Uuid>,
+    collapsed: bool,
+}
+
+#[derive(Debug)]
+pub struct Tab {
+    id: uuid::Uuid,
+    url: Option<Url>,
+    title: String,
+    history: Vec<Url>,
+    load_progress: f32,
+    favicon: Option<Vec<u8>>,
+}
+
+#[derive(Debug)]
+pub struct HistoryManager {
+    entries: Vec<HistoryEntry>,
+    max_entries: usize,
+    max_age: Option<chrono::Duration>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    url: Url,
+    title: String,
+    timestamp: DateTime<Utc>,
+    visit_count: u32,
+}
+
+#[derive(Debug)]
+pub struct BookmarkManager {
+    bookmarks: HashMap<String, Bookmark>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bookmark {
+    url: Url,
+    title: String,
+    tags: Vec<String>,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Default)]
+pub struct CookieJar {
+    cookies: Vec<Cookie>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cookie {
+    name: String,
+    value: String,
+    domain: String,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+pub struct DownloadManager {
+    active_downloads: Vec<Download>,
+    completed_downloads: Vec<Download>,
+}
+
+#[derive(Debug)]
+pub struct Download {
+    id: uuid::Uuid,
+    url: Url,
+    filename: String,
+    progress: f32,
+    status: DownloadStatus,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DownloadStatus {
+    Pending,
+    InProgress,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+// How a loaded response was handled, chosen from its Content-Type
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PageDisposition {
+    // HTML or other text, rendered in the tab
+    Rendered,
+    // An image, shown in the image viewer
+    ImageViewer,
+    // Anything else, handed off to the download manager under this download id
+    Download(uuid::Uuid),
+}
+
+// Name used for downloads when no usable filename can be derived
+const DEFAULT_DOWNLOAD_NAME: &str = "download";
+
+// Default cap on the number of history entries kept in memory
+const DEFAULT_MAX_HISTORY_ENTRIES: usize = 10_000;
+
+// Storage keys for persisted browser state
+const HISTORY_STORAGE_KEY: &str = "history";
+const BOOKMARKS_STORAGE_KEY: &str = "bookmarks";
+const COOKIES_STORAGE_KEY: &str = "cookies";
+
+// Schema version written with every persisted payload. Payloads saved before
+// versioning have no envelope and are treated as version 1.
+pub const STORAGE_SCHEMA_VERSION: u32 = 2;
+
+// Envelope stored under each key: {"schema_version": N, "data": ...}
+#[derive(Serialize)]
+struct VersionedPayload<'a, T> {
+    schema_version: u32,
+    data: &'a T,
+}
+
+// Write a payload under a key, tagged with the current schema version
+fn save_versioned<T: Serialize>(storage: &dyn Storage, key: &str, data: &T) -> Result<(), Box<dyn std::error::Error>> {
+    let payload = VersionedPayload {
+        schema_version: STORAGE_SCHEMA_VERSION,
+        data,
+    };
+    storage.save(key, &serde_json::to_string_pretty(&payload)?)
+}
+
+// Load the payload under a key, upgrading it from older schema versions first
+fn load_versioned<T: DeserializeOwned>(storage: &dyn Storage, key: &str) -> Result<Option<T>, Box<dyn std::error::Error>> {
+    let raw = match storage.load(key)? {
+        Some(raw) => raw,
+        None => return Ok(None),
+    };
+    let value: serde_json::Value = serde_json::from_str(&raw)?;
+
+    let (version, data) = match value {
+        serde_json::Value::Object(mut envelope)
+            if envelope.contains_key("schema_version") && envelope.contains_key("data") =>
+        {
+            let version = envelope["schema_version"]
+                .as_u64()
+                .ok_or_else(|| format!("Stored '{}' has a non-numeric schema_version", key))?;
+            (version, envelope.remove("data").unwrap_or_default())
+        }
+        bare => (1, bare),
+    };
+
+    Ok(Some(serde_json::from_value(migrate_payload(key, version, data)?)?))
+}
+
+// Upgrade a payload one schema version at a time to STORAGE_SCHEMA_VERSION
+fn migrate_payload(key: &str, mut version: u64, mut data: serde_json::Value) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+    if version == 0 || version > STORAGE_SCHEMA_VERSION as u64 {
+        return Err(format!(
+            "Stored '{}' uses schema version {}, but this build only reads versions 1 to {}",
+            key, version, STORAGE_SCHEMA_VERSION
+        )
+        .into());
+    }
+
+    while version < STORAGE_SCHEMA_VERSION as u64 {
+        data = match version {
+            // v1 -> v2 only introduced the envelope; the data itself is unchanged
+            1 => data,
+            _ => unreachable!("no migration from schema version {}", version),
+        };
+        version += 1;
+    }
+    Ok(data)
+}
+
+// Backend for persisting browser state between sessions
+pub trait Storage: Send + Sync {
+    // Load the payload stored under a key, or None if nothing has been saved yet
+    fn load(&self, key: &str) -> Result<Option<String>, Box<dyn std::error::Error>>;
+    // Replace the payload stored under a key
+    fn save(&self, key: &str, data: &str) -> Result<(), Box<dyn std::error::Error>>;
+}
+
+// Storage backend keeping one JSON file per key in a directory
+#[derive(Debug, Clone)]
+pub struct JsonFileStorage {
+    dir: PathBuf,
+}
+
+impl JsonFileStorage {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        JsonFileStorage { dir: dir.into() }
+    }
+
+    fn path_for(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+}
+
+impl Storage for JsonFileStorage {
+    fn load(&self, key: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        let path = self.path_for(key);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(fs::read_to_string(path)?))
+    }
+
+    fn save(&self, key: &str, data: &str) -> Result<(), Box<dyn std::error::Error>> {
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.path_for(key), data)?;
+        Ok(())
+    }
+}
+
+impl HistoryManager {
+    pub fn new() -> Self {
+        HistoryManager {
+            entries: Vec::new(),
+            max_entries: DEFAULT_MAX_HISTORY_ENTRIES,
+            max_age: None,
+        }
+    }
+
+    // Restore history from storage, starting empty if none was saved
+    pub fn from_storage(storage: &dyn Storage) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut history_manager = HistoryManager::new();
+        if let Some(entries) = load_versioned(storage, HISTORY_STORAGE_KEY)? {
+            history_manager.entries = entries;
+            history_manager.prune();
+        }
+        Ok(history_manager)
+    }
+
+    // Limit how many entries are kept; the oldest are dropped first
+    pub fn set_max_entries(&mut self, max_entries: usize) {
+        self.max_entries = max_entries;
+        self.prune();
+    }
+
+    // Drop entries older than the given age, or keep them indefinitely with None
+    pub fn set_max_age(&mut self, max_age: Option<chrono::Duration>) {
+        self.max_age = max_age;
+        self.prune();
+    }
+
+    // Record an entry and prune anything now over the limits
+    pub fn add_entry(&mut self, entry: HistoryEntry) {
+        self.entries.push(entry);
+        self.prune();
+    }
+
+    // Record a visit, bumping the existing entry for the URL instead of adding a duplicate.
+    // Returns the URL's visit count after this visit.
+    pub fn record_visit(&mut self, url: Url, title: &str) -> u32 {
+        if let Some(entry) = self.entries.iter_mut().find(|e| e.url == url) {
+            entry.visit_count += 1;
+            entry.timestamp = Utc::now();
+            entry.title = title.to_string();
+            return entry.visit_count;
+        }
+
+        self.add_entry(HistoryEntry {
+            url,
+            title: title.to_string(),
+            timestamp: Utc::now(),
+            visit_count: 1,
+        });
+        1
+    }
+
+    // Remove expired entries, then the oldest entries beyond max_entries
+    pub fn prune(&mut self) {
+        if let Some(max_age) = self.max_age {
+            let cutoff = Utc::now() - max_age;
+            self.entries.retain(|entry| entry.timestamp >= cutoff);
+        }
+
+        if self.entries.len() > self.max_entries {
+            self.entries.sort_by_key(|entry| entry.timestamp);
+            let excess = self.entries.len() - self.max_entries;
+            self.entries.drain(..excess);
+        }
+    }
+
+    // Write history to storage
+    pub fn to_storage(&self, storage: &dyn Storage) -> Result<(), Box<dyn std::error::Error>> {
+        save_versioned(storage, HISTORY_STORAGE_KEY, &self.entries)
+    }
+}
+
+impl BookmarkManager {
+    // Restore bookmarks from storage, starting empty if none were saved
+    pub fn from_storage(storage: &dyn Storage) -> Result<Self, Box<dyn std::error::Error>> {
+        let bookmarks = load_versioned(storage, BOOKMARKS_STORAGE_KEY)?.unwrap_or_default();
+        Ok(BookmarkManager { bookmarks })
+    }
+
+    // Write bookmarks to storage
+    pub fn to_storage(&self, storage: &dyn Storage) -> Result<(), Box<dyn std::error::Error>> {
+        save_versioned(storage, BOOKMARKS_STORAGE_KEY, &self.bookmarks)
+    }
+}
+
+// Callback reporting bytes received so far and the expected total, if known
+pub type ProgressCallback<'a> = dyn FnMut(u64, Option<u64>) + Send + 'a;
+
+// Body and selected headers of a fetched URL
+#[derive(Debug, Clone, Default)]
+pub struct FetchResponse {
+    pub body: Vec<u8>,
+    pub content_type: Option<String>,
+    pub content_disposition: Option<String>,
+}
+
+// Abstraction over fetching a URL so page loads can be driven by any HTTP backend
+#[async_trait]
+pub trait HttpFetcher: Send + Sync {
+    async fn fetch(
+        &self,
+        url: &Url,
+        on_progress: &mut ProgressCallback<'_>,
+    ) -> Result<FetchResponse, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+// HttpFetcher backed by a shared reqwest client
+#[derive(Debug, Clone)]
+pub struct ReqwestFetcher {
+    client: reqwest::Client,
+}
+
+impl ReqwestFetcher {
+    pub fn new(user_agent: &str, proxy: Option<&ProxyConfig>) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut builder = reqwest::Client::builder().user_agent(user_agent);
+        if let Some(proxy) = proxy {
+            builder = builder.proxy(proxy.to_reqwest()?);
+        }
+        Ok(ReqwestFetcher { client: builder.build()? })
+    }
+}
+
+#[async_trait]
+impl HttpFetcher for ReqwestFetcher {
+    async fn fetch(
+        &self,
+        url: &Url,
+        on_progress: &mut ProgressCallback<'_>,
+    ) -> Result<FetchResponse, Box<dyn std::error::Error + Send + Sync>> {
+        let mut response = self.client.get(url.clone()).send().await?.error_for_status()?;
+        let header = |name: reqwest::header::HeaderName| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        let content_type = header(reqwest::header::CONTENT_TYPE);
+        let content_disposition = header(reqwest::header::CONTENT_DISPOSITION);
+        let total = response.content_length();
+        let mut body = Vec::new();
+
+        while let Some(chunk) = response.chunk().await? {
+            body.extend_from_slice(&chunk);
+            on_progress(body.len() as u64, total);
+        }
+
+        Ok(FetchResponse {
+            body,
+            content_type,
+            content_disposition,
+        })
+    }
+}
+
+// Caps how many fetches are in flight at once, sized from max_concurrent_connections.
+// A single semaphore is resized in place, so permits held across a resize still
+// count against the new limit.
+pub struct ConnectionLimiter {
+    semaphore: Arc<Semaphore>,
+    limit: Mutex<usize>,
+}
+
+impl ConnectionLimiter {
+    pub fn new(limit: usize) -> Self {
+        let limit = limit.max(1);
+        ConnectionLimiter {
+            semaphore: Arc::new(Semaphore::new(limit)),
+            limit: Mutex::new(limit),
+        }
+    }
+
+    // The number of fetches currently allowed in flight
+    pub fn limit(&self) -> usize {
+        *lock_recovering(&self.limit)
+    }
+
+    // Change the limit. Growing adds permits right away; shrinking retires the
+    // surplus permits, waiting on `runtime` for in-flight fetches to return any
+    // that are still held. New fetches queue behind that wait, so they can't start
+    // until the number in flight is under the new limit.
+    pub fn resize(&self, limit: usize, runtime: &tokio::runtime::Handle) {
+        let limit = limit.max(1);
+        let mut current = lock_recovering(&self.limit);
+        if limit > *current {
+            self.semaphore.add_permits(limit - *current);
+        } else if limit < *current {
+            let excess = (*current - limit) as u32;
+            match Arc::clone(&self.semaphore).try_acquire_many_owned(excess) {
+                Ok(permits) => permits.forget(),
+                Err(_) => {
+                    let semaphore = Arc::clone(&self.semaphore);
+                    runtime.spawn(async move {
+                        if let Ok(permits) = semaphore.acquire_many_owned(excess).await {
+                            permits.forget();
+                        }
+                    });
+                }
+            }
+        }
+        *current = limit;
+    }
+
+    // Wait for a free connection slot, held until the permit is dropped
+    pub async fn acquire(&self) -> Result<OwnedSemaphorePermit, tokio::sync::AcquireError> {
+        Arc::clone(&self.semaphore).acquire_owned().await
+    }
+}
+
+// Observer for browser activity, e.g. a UI layer tracking tabs and downloads.
+// Every method has an empty default so listeners only implement what they need.
+pub trait BrowserEventListener: Send + Sync {
+    fn on_tab_created(&self, _tab_id: uuid::Uuid) {}
+    fn on_tab_closed(&self, _tab_id: uuid::Uuid) {}
+    fn on_navigation(&self, _tab_id: uuid::Uuid, _url: &Url) {}
+    fn on_download_progress(&self, _download_id: uuid::Uuid, _progress: f32, _status: &DownloadStatus) {}
+}
+
+// Registered event listeners, shared with callbacks that outlive a borrow of the browser
+type EventListeners = Arc<Mutex<Vec<Arc<dyn BrowserEventListener>>>>;
+
+// Lock `mutex`, recovering it if an operation panicked while holding it. That
+// operation may have left its change half-applied, e.g. a tab removed before
+// active_tab_index was adjusted. Tabs, downloads and history entries are only
+// reached through get/find lookups, never by index, so such leftovers degrade to
+// "not found" instead of making every later call panic on the poisoned lock.
+fn lock_recovering<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| {
+        warn!("Recovering a lock poisoned by a panicked browser operation");
+        mutex.clear_poison();
+        poisoned.into_inner()
+    })
+}
+
+// Call `event` on every listener without holding the registry lock during the calls
+fn notify_listeners(listeners: &EventListeners, event: impl Fn(&dyn BrowserEventListener)) {
+    let snapshot = lock_recovering(&listeners).clone();
+    for listener in snapshot.iter() {
+        event(listener.as_ref());
+    }
+}
+
+// Media types that render as a page even though they're not text/*
+const RENDERABLE_APPLICATION_TYPES: [&str; 2] = ["application/xhtml+xml", "application/xml"];
+
+// Decide how to show a response; responses without a Content-Type are treated as HTML
+// and a Content-Disposition of attachment always downloads
+fn classify_response(response: &FetchResponse) -> ResponseKind {
+    let is_attachment = response
+        .content_disposition
+        .as_deref()
+        .map_or(false, |value| value.trim_start().to_lowercase().starts_with("attachment"));
+    if is_attachment {
+        return ResponseKind::Download;
+    }
+
+    let essence = match response.content_type.as_deref() {
+        Some(content_type) => content_type.split(';').next().unwrap_or("").trim().to_lowercase(),
+        None => return ResponseKind::Page,
+    };
+    if essence.is_empty() || essence.starts_with("text/") || RENDERABLE_APPLICATION_TYPES.contains(&essence.as_str()) {
+        ResponseKind::Page
+    } else if essence.starts_with("image/") {
+        ResponseKind::Image
+    } else {
+        ResponseKind::Download
+    }
+}
+
+// Outcome of classify_response, before any download has been started
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ResponseKind {
+    Page,
+    Image,
+    Download,
+}
+
+// Extract the contents of the first <title> element from an HTML document
+fn extract_title(html: &str) -> Option<String> {
+    let lower = html.to_lowercase();
+    let open = lower.find("<title")?;
+    let content_start = open + lower[open..].find('>')? + 1;
+    let content_end = content_start + lower[content_start..].find("</title")?;
+    let title = html[content_start..content_end].split_whitespace().collect::<Vec<_>>().join(" ");
+    if title.is_empty() {
+        None
+    } else {
+        Some(title)
+    }
+}
+
+// Find the href of the first <link> whose rel includes "icon", e.g. rel="icon" or rel="shortcut icon"
+fn extract_favicon_href(html: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    let mut search_from = 0;
+    while let Some(offset) = lower[search_from..].find("<link") {
+        let start = search_from + offset;
+        let end = start + lower[start..].find('>')?;
+        let tag = &html[start..end];
+        let is_icon = tag_attribute(tag, "rel")
+            .map_or(false, |rel| rel.split_whitespace().any(|token| token.eq_ignore_ascii_case("icon")));
+        if is_icon {
+            if let Some(href) = tag_attribute(tag, "href").filter(|href| !href.is_empty()) {
+                return Some(href);
+            }
+        }
+        search_from = end;
+    }
+    None
+}
+
+// Read an attribute's value from the inside of an HTML tag; values may be quoted or bare
+fn tag_attribute(tag: &str, name: &str) -> Option<String> {
+    let lower = tag.to_ascii_lowercase();
+    let mut search_from = 0;
+    while let Some(offset) = lower[search_from..].find(name) {
+        let start = search_from + offset;
+        search_from = start + name.len();
+        // Skip matches inside other names, like the "rel" in "data-rel"
+        let whole_name = lower[..start].ends_with(char::is_whitespace);
+        let after_name = lower[search_from..].trim_start();
+        if !whole_name || !after_name.starts_with('=') {
+            continue;
+        }
+        let value = tag[tag.len() - after_name.len() + 1..].trim_start();
+        let value = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => value[1..].split(quote).next().unwrap_or(""),
+            _ => value.split(char::is_whitespace).next().unwrap_or(""),
+        };
+        return Some(value.trim().to_string());
+    }
+    None
+}
+
+// Initialize the Aluminum browser prelude
+pub fn initialize_aluminum_prelude() -> Result<AluminumBrowser, Box<dyn std::error::Error>> {
+    println!("Initializing Aluminum browser prelude...");
+
+    // Set up the browser configuration
+    let config = BrowserConfig::default();
+
+    // Initialize tab manager
+    let tab_manager = TabManager {
+        tabs: vec![Tab {
+            id: uuid::Uuid::new_v4(),
+            url: None,
+            title: String::from("New Tab"),
+            history: Vec::new(),
+            load_progress: 0.0,
+            favicon: None,
+        }],
+        active_tab_index: 0,
+        groups: Vec::new(),
+    };
+
+    // Initialize history manager
+    let history_manager = HistoryManager::new();
+
+    // Initialize bookmark manager
+    let bookmark_manager = BookmarkManager {
+        bookmarks: HashMap::new(),
+    };
+
+    // Initialize download manager
+    let download_manager = DownloadManager {
+        active_downloads: Vec::new(),
+        completed_downloads: Vec::new(),
+    };
+
+    // Initialize cookie jar
+    let cookie_jar = CookieJar::default();
+
+    // Initialize the HTTP fetcher used for page loads
+    let fetcher = ReqwestFetcher::new(&config.user_agent, config.proxy.as_ref())?;
+
+    // Set up the asynchronous runtime for handling concurrent operations
+    let runtime = Runtime::new()?;
+
+    // Create the main AluminumBrowser structure
+    let connection_limiter = ConnectionLimiter::new(config.max_concurrent_connections);
+    let browser = AluminumBrowser {
+        config: Arc::new(Mutex::new(config)),
+        tab_manager: Arc::new(Mutex::new(tab_manager)),
+        history_manager: Arc::new(Mutex::new(history_manager)),
+        bookmark_manager: Arc::new(Mutex::new(bookmark_manager)),
+        download_manager: Arc::new(Mutex::new(download_manager)),
+        cookie_jar: Arc::new(Mutex::new(cookie_jar)),
+        storage: None,
+        fetcher: Mutex::new(ActiveFetcher {
+            fetcher: Arc::new(fetcher),
+            from_config: true,
+        }),
+        connection_limiter: Arc::new(connection_limiter),
+        listeners: Arc::new(Mutex::new(Vec::new())),
+        runtime: Arc::new(runtime),
+    };
+
+    // Initialize browser components, tearing down any that started if a later one fails
+    initialize_with_rollback(&browser, SUBSYSTEMS)?;
+
+    println!("Aluminum browser prelude initialization complete.");
+
+    Ok(browser)
+}
+
+pub struct AluminumBrowser {
+    config: Arc<Mutex<BrowserConfig>>,
+    tab_manager: Arc<Mutex<TabManager>>,
+    history_manager: Arc<Mutex<HistoryManager>>,
+    bookmark_manager: Arc<Mutex<BookmarkManager>>,
+    download_manager: Arc<Mutex<DownloadManager>>,
+    cookie_jar: Arc<Mutex<CookieJar>>,
+    storage: Option<Arc<dyn Storage>>,
+    fetcher: Mutex<ActiveFetcher>,
+    connection_limiter: Arc<ConnectionLimiter>,
+    listeners: EventListeners,
+    runtime: Arc<Runtime>,
+}
+
+// The HTTP backend page loads go through, and whether it was built from the config
+// (and so is rebuilt by reload_config) or installed with set_fetcher
+struct ActiveFetcher {
+    fetcher: Arc<dyn HttpFetcher>,
+    from_config: bool,
+}
+
+// A browser subsystem's start-up routine paired with the teardown that undoes it
+struct Subsystem {
+    name: &'static str,
+    initialize: fn(&AluminumBrowser) -> Result<(), Box<dyn std::error::Error>>,
+    teardown: fn(&AluminumBrowser),
+}
+
+// Subsystems in start-up order; teardown runs in the reverse order
+const SUBSYSTEMS: &[Subsystem] = &[
+    Subsystem {
+        name: "network stack",
+        initialize: AluminumBrowser::initialize_network_stack,
+        teardown: AluminumBrowser::teardown_network_stack,
+    },
+    Subsystem {
+        name: "rendering engine",
+        initialize: AluminumBrowser::initialize_rendering_engine,
+        teardown: AluminumBrowser::teardown_rendering_engine,
+    },
+    Subsystem {
+        name: "JavaScript engine",
+        initialize: AluminumBrowser::initialize_javascript_engine,
+        teardown: AluminumBrowser::teardown_javascript_engine,
+    },
+    Subsystem {
+        name: "extension system",
+        initialize: AluminumBrowser::initialize_extension_system,
+        teardown: AluminumBrowser::teardown_extension_system,
+    },
+    Subsystem {
+        name: "security features",
+        initialize: AluminumBrowser::initialize_security_features,
+        teardown: AluminumBrowser::teardown_security_features,
+    },
+];
+
+// Run each subsystem's initializer in order. If one fails, the subsystems that
+// already started are torn down in reverse order before the error is returned.
+fn initialize_with_rollback(browser: &AluminumBrowser, subsystems: &[Subsystem]) -> Result<(), Box<dyn std::error::Error>> {
+    for (index, subsystem) in subsystems.iter().enumerate() {
+        if let Err(e) = (subsystem.initialize)(browser) {
+            for started in subsystems[..index].iter().rev() {
+                (started.teardown)(browser);
+            }
+            return Err(format!("Failed to initialize {}: {}", subsystem.name, e).into());
+        }
+    }
+    Ok(())
+}
+
+impl TabManager {
+    // Create an empty tab group
+    fn create_group(&mut self, name: &str, color: &str) -> uuid::Uuid {
+        let group = TabGroup {
+            id: uuid::Uuid::new_v4(),
+            name: name.to_string(),
+            color: color.to_string(),
+            tab_ids: Vec::new(),
+            collapsed: false,
+        };
+        let id = group.id;
+        self.groups.push(group);
+        id
+    }
+
+    // Move a tab into a group; a tab belongs to at most one group
+    fn add_tab_to_group(&mut self, group_id: uuid::Uuid, tab_id: uuid::Uuid) -> Result<(), Box<dyn std::error::Error>> {
+        if !self.tabs.iter().any(|t| t.id == tab_id) {
+            return Err("Tab not found".into());
+        }
+        if !self.groups.iter().any(|g| g.id == group_id) {
+            return Err("Tab group not found".into());
+        }
+        self.remove_tab_from_groups(tab_id);
+        let group = self.groups.iter_mut().find(|g| g.id == group_id).ok_or("Tab group not found")?;
+        group.tab_ids.push(tab_id);
+        Ok(())
+    }
+
+    // Take a tab out of a group, leaving the tab itself open
+    fn remove_tab_from_group(&mut self, group_id: uuid::Uuid, tab_id: uuid::Uuid) -> Result<(), Box<dyn std::error::Error>> {
+        let group = self.groups.iter_mut().find(|g| g.id == group_id).ok_or("Tab group not found")?;
+        let before = group.tab_ids.len();
+        group.tab_ids.retain(|id| *id != tab_id);
+        if group.tab_ids.len() == before {
+            return Err("Tab is not in this group".into());
+        }
+        Ok(())
+    }
+
+    // Drop a tab from whichever group holds it, e.g. when the tab closes
+    fn remove_tab_from_groups(&mut self, tab_id: uuid::Uuid) {
+        for group in self.groups.iter_mut() {
+            group.tab_ids.retain(|id| *id != tab_id);
+        }
+    }
+
+    // Collapse or expand a group in the tab strip
+    fn collapse_group(&mut self, group_id: uuid::Uuid, collapsed: bool) -> Result<(), Box<dyn std::error::Error>> {
+        let group = self.groups.iter_mut().find(|g| g.id == group_id).ok_or("Tab group not found")?;
+        group.collapsed = collapsed;
+        Ok(())
+    }
+
+    // Close all but one tab per URL, keeping the one with the longest history (the
+    // earliest on a tie). Tabs without a URL are left alone. If the active tab is
+    // closed, the tab kept for its URL becomes active. Returns the closed tab ids.
+    fn merge_duplicates(&mut self) -> Vec<uuid::Uuid> {
+        let mut keepers: HashMap<Url, usize> = HashMap::new();
+        for (index, tab) in self.tabs.iter().enumerate() {
+            if let Some(url) = &tab.url {
+                let keeper = keepers.entry(url.clone()).or_insert(index);
+                if tab.history.len() > self.tabs[*keeper].history.len() {
+                    *keeper = index;
+                }
+            }
+        }
+
+        let active_id = self.tabs.get(self.active_tab_index).map(|tab| {
+            match tab.url.as_ref().and_then(|url| keepers.get(url)) {
+                Some(keeper) => self.tabs[*keeper].id,
+                None => tab.id,
+            }
+        });
+
+        let mut closed = Vec::new();
+        let mut index = 0;
+        self.tabs.retain(|tab| {
+            let keep = match &tab.url {
+                Some(url) => keepers[url] == index,
+                None => true,
+            };
+            if !keep {
+                closed.push(tab.id);
+            }
+            index += 1;
+            keep
+        });
+
+        for tab_id in &closed {
+            self.remove_tab_from_groups(*tab_id);
+        }
+        if let Some(active_id) = active_id {
+            self.active_tab_index = self.tabs.iter().position(|tab| tab.id == active_id).unwrap_or(0);
+        }
+        closed
+    }
+}
+
+impl CookieJar {
+    // Store a cookie, replacing any existing cookie with the same name and domain
+    fn set(&mut self, cookie: Cookie) {
+        self.cookies
+            .retain(|c| !(c.name == cookie.name && c.domain == cookie.domain));
+        self.cookies.push(cookie);
+    }
+
+    // Cookies visible to a host: exact domain matches plus parent domains
+    fn for_domain(&self, domain: &str) -> Vec<Cookie> {
+        let domain = domain.trim_start_matches('.').to_lowercase();
+        self.cookies
+            .iter()
+            .filter(|c| {
+                let cookie_domain = c.domain.trim_start_matches('.');
+                domain == cookie_domain || domain.ends_with(&format!(".{}", cookie_domain))
+            })
+            .cloned()
+            .collect()
+    }
+
+    fn clear(&mut self) {
+        self.cookies.clear();
+    }
+
+    // Restore cookies from storage, starting empty if none were saved
+    pub fn from_storage(storage: &dyn Storage) -> Result<Self, Box<dyn std::error::Error>> {
+        let cookies = load_versioned(storage, COOKIES_STORAGE_KEY)?.unwrap_or_default();
+        Ok(CookieJar { cookies })
+    }
+
+    // Write cookies to storage
+    pub fn to_storage(&self, storage: &dyn Storage) -> Result<(), Box<dyn std::error::Error>> {
+        save_versioned(storage, COOKIES_STORAGE_KEY, &self.cookies)
+    }
+}
+
+impl AluminumBrowser {
+    // Initialize the network stack for handling HTTP(S) requests
+    fn initialize_network_stack(&self) -> Result<(), Box<dyn std::error::Error>> {
+        println!("Initializing network stack...");
+        // TODO: Implement network stack initialization
+        Ok(())
+    }
+
+    // Initialize the rendering engine for displaying web content
+    fn initialize_rendering_engine(&self) -> Result<(), Box<dyn std::error::Error>> {
+        println!("Initializing rendering engine...");
+        // TODO: Implement rendering engine initialization
+        Ok(())
+    }
+
+    // Initialize the JavaScript engine for executing client-side scripts
+    fn initialize_javascript_engine(&self) -> Result<(), Box<dyn std::error::Error>> {
+        println!("Initializing JavaScript engine...");
+        // TODO: Implement JavaScript engine initialization
+        Ok(())
+    }
+
+    // Initialize the extension system for supporting browser add-ons
+    fn initialize_extension_system(&self) -> Result<(), Box<dyn std::error::Error>> {
+        println!("Initializing extension system...");
+        // TODO: Implement extension system initialization
+        Ok(())
+    }
+
+    // Initialize security features such as HTTPS, content security policy, and sandboxing
+    fn initialize_security_features(&self) -> Result<(), Box<dyn std::error::Error>> {
+        println!("Initializing security features...");
+        // TODO: Implement security features initialization
+        Ok(())
+    }
+
+    // Release resources held by the network stack
+    fn teardown_network_stack(&self) {
+        println!("Tearing down network stack...");
+        // TODO: Implement network stack teardown
+    }
+
+    // Release resources held by the rendering engine
+    fn teardown_rendering_engine(&self) {
+        println!("Tearing down rendering engine...");
+        // TODO: Implement rendering engine teardown
+    }
+
+    // Release resources held by the JavaScript engine
+    fn teardown_javascript_engine(&self) {
+        println!("Tearing down JavaScript engine...");
+        // TODO: Implement JavaScript engine teardown
+    }
+
+    // Unload extensions and release the extension system
+    fn teardown_extension_system(&self) {
+        println!("Tearing down extension system...");
+        // TODO: Implement extension system teardown
+    }
+
+    // Release resources held by the security features
+    fn teardown_security_features(&self) {
+        println!("Tearing down security features...");
+        // TODO: Implement security features teardown
+    }
+
+    // Public methods for interacting with the browser
+
+    // Attach a storage backend, replacing history, bookmarks and cookies with its saved state
+    pub fn with_storage(mut self, storage: Arc<dyn Storage>) -> Result<Self, Box<dyn std::error::Error>> {
+        *lock_recovering(&self.history_manager) = HistoryManager::from_storage(storage.as_ref())?;
+        *lock_recovering(&self.bookmark_manager) = BookmarkManager::from_storage(storage.as_ref())?;
+        *lock_recovering(&self.cookie_jar) = CookieJar::from_storage(storage.as_ref())?;
+        self.storage = Some(storage);
+        Ok(self)
+    }
+
+    // Flush history, bookmarks and cookies to the attached storage backend
+    pub fn persist(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let storage = self.storage.as_ref().ok_or("No storage backend attached")?;
+        lock_recovering(&self.history_manager).to_storage(storage.as_ref())?;
+        lock_recovering(&self.bookmark_manager).to_storage(storage.as_ref())?;
+        lock_recovering(&self.cookie_jar).to_storage(storage.as_ref())?;
+        Ok(())
+    }
+
+    // Apply a new configuration without restarting the browser.
+    //
+    // Settings that are read on use (connection limit, JavaScript and cookie
+    // toggles, download path, custom CSS, homepage) take effect immediately. A
+    // user agent or proxy change rebuilds the default HTTP fetcher; a fetcher
+    // installed with `set_fetcher` is kept as is, since it wasn't built from the
+    // config. Toggling private browsing needs a restart and is rejected without
+    // changing anything.
+    pub fn reload_config(&self, new: BrowserConfig) -> Result<(), Box<dyn std::error::Error>> {
+        if new.max_concurrent_connections == 0 {
+            return Err("max_concurrent_connections must be at least 1".into());
+        }
+
+        let fetcher_changed = {
+            let current = lock_recovering(&self.config);
+            if current.enable_private_browsing != new.enable_private_browsing {
+                return Err("enable_private_browsing cannot be changed at runtime; restart the browser to apply it".into());
+            }
+            current.user_agent != new.user_agent || current.proxy != new.proxy
+        };
+
+        // Build the replacement fetcher before touching the config so a failure leaves both unchanged
+        if fetcher_changed && lock_recovering(&self.fetcher).from_config {
+            let fetcher = ReqwestFetcher::new(&new.user_agent, new.proxy.as_ref())?;
+            let mut active = lock_recovering(&self.fetcher);
+            // set_fetcher may have installed a fetcher while this one was being built
+            if active.from_config {
+                active.fetcher = Arc::new(fetcher);
+            }
+        }
+        self.connection_limiter.resize(new.max_concurrent_connections, self.runtime.handle());
+        *lock_recovering(&self.config) = new;
+        Ok(())
+    }
+
+    // Register a listener for tab, navigation and download events
+    pub fn observe(&self, listener: Arc<dyn BrowserEventListener>) {
+        lock_recovering(&self.listeners).push(listener);
+    }
+
+    pub fn create_new_tab(&self, url: Option<Url>) -> Result<uuid::Uuid, Box<dyn std::error::Error>> {
+        let tab_id = {
+            let mut tab_manager = lock_recovering(&self.tab_manager);
+            let new_tab = Tab {
+                id: uuid::Uuid::new_v4(),
+                url,
+                title: String::from("New Tab"),
+                history: Vec::new(),
+                load_progress: 0.0,
+                favicon: None,
+            };
+            let tab_id = new_tab.id;
+            tab_manager.tabs.push(new_tab);
+            tab_manager.active_tab_index = tab_manager.tabs.len() - 1;
+            tab_id
+        };
+        notify_listeners(&self.listeners, |l| l.on_tab_created(tab_id));
+        Ok(tab_id)
+    }
+
+    pub fn close_tab(&self, tab_id: uuid::Uuid) -> Result<(), Box<dyn std::error::Error>> {
+        let closed = {
+            let mut tab_manager = lock_recovering(&self.tab_manager);
+            match tab_manager.tabs.iter().position(|t| t.id == tab_id) {
+                Some(index) => {
+                    tab_manager.tabs.remove(index);
+                    tab_manager.remove_tab_from_groups(tab_id);
+                    if tab_manager.active_tab_index >= index && tab_manager.active_tab_index > 0 {
+                        tab_manager.active_tab_index -= 1;
+                    }
+                    true
+                }
+                None => false,
+            }
+        };
+        if closed {
+            notify_listeners(&self.listeners, |l| l.on_tab_closed(tab_id));
+        }
+        Ok(())
+    }
+
+    // Close tabs open on the same URL as another tab and return how many were closed
+    pub fn merge_duplicate_tabs(&self) -> usize {
+        let closed = lock_recovering(&self.tab_manager).merge_duplicates();
+        for tab_id in &closed {
+            notify_listeners(&self.listeners, |l| l.on_tab_closed(*tab_id));
+        }
+        closed.len()
+    }
+
+    // Create a named, colored tab group and return its id
+    pub fn create_tab_group(&self, name: &str, color: &str) -> Result<uuid::Uuid, Box<dyn std::error::Error>> {
+        let mut tab_manager = lock_recovering(&self.tab_manager);
+        Ok(tab_manager.create_group(name, color))
+    }
+
+    // Move a tab into a group, taking it out of any group it was already in
+    pub fn add_tab_to_group(&self, group_id: uuid::Uuid, tab_id: uuid::Uuid) -> Result<(), Box<dyn std::error::Error>> {
+        let mut tab_manager = lock_recovering(&self.tab_manager);
+        tab_manager.add_tab_to_group(group_id, tab_id)
+    }
+
+    pub fn remove_tab_from_group(&self, group_id: uuid::Uuid, tab_id: uuid::Uuid) -> Result<(), Box<dyn std::error::Error>> {
+        let mut tab_manager = lock_recovering(&self.tab_manager);
+        tab_manager.remove_tab_from_group(group_id, tab_id)
+    }
+
+    pub fn collapse_tab_group(&self, group_id: uuid::Uuid, collapsed: bool) -> Result<(), Box<dyn std::error::Error>> {
+        let mut tab_manager = lock_recovering(&self.tab_manager);
+        tab_manager.collapse_group(group_id, collapsed)
+    }
+
+    // Get a copy of every tab group
+    pub fn tab_groups(&self) -> Vec<TabGroup> {
+        lock_recovering(&self.tab_manager).groups.clone()
+    }
+
+    pub fn navigate_to_url(&self, url: Url) -> Result<(), Box<dyn std::error::Error>> {
+        let navigated_tab = {
+            let mut tab_manager = lock_recovering(&self.tab_manager);
+            let active_tab_index = tab_manager.active_tab_index;
+            match tab_manager.tabs.get_mut(active_tab_index) {
+                Some(active_tab) => {
+                    active_tab.url = Some(url.clone());
+                    active_tab.history.push(url.clone());
+                    Some(active_tab.id)
+                }
+                None => None,
+            }
+        };
+        if let Some(tab_id) = navigated_tab {
+            self.record_visit(url.clone(), "Loading...");
+            notify_listeners(&self.listeners, |l| l.on_navigation(tab_id, &url));
+        }
+        Ok(())
+    }
+
+    // Record a visit in the shared history, safe to call from any thread.
+    // Returns the URL's visit count after this visit.
+    pub fn record_visit(&self, url: Url, title: &str) -> u32 {
+        lock_recovering(&self.history_manager).record_visit(url, title)
+    }
+
+    // Replace the HTTP backend used for page loads; reload_config leaves it in place
+    pub fn set_fetcher(&self, fetcher: Arc<dyn HttpFetcher>) {
+        *lock_recovering(&self.fetcher) = ActiveFetcher {
+            fetcher,
+            from_config: false,
+        };
+    }
+
+    // Fetch through the active fetcher, first waiting for one of the
+    // max_concurrent_connections slots shared by page loads, favicons and downloads
+    async fn fetch(
+        &self,
+        url: &Url,
+        on_progress: &mut ProgressCallback<'_>,
+    ) -> Result<FetchResponse, Box<dyn std::error::Error + Send + Sync>> {
+        let _permit = self.connection_limiter.acquire().await?;
+        let fetcher = Arc::clone(&lock_recovering(&self.fetcher).fetcher);
+        fetcher.fetch(url, on_progress).await
+    }
+
+    // Load a URL into a tab, tracking progress and filling in the page title when done.
+    // Images open in the image viewer and non-page content is saved as a download,
+    // reusing the body already fetched rather than requesting it again.
+    pub async fn load_tab(&self, tab_id: uuid::Uuid, url: Url) -> Result<PageDisposition, Box<dyn std::error::Error>> {
+        {
+            let mut tab_manager = lock_recovering(&self.tab_manager);
+            let tab = tab_manager.tabs.iter_mut().find(|t| t.id == tab_id).ok_or("Tab not found")?;
+            tab.url = Some(url.clone());
+            tab.history.push(url.clone());
+            tab.title = String::from("Loading...");
+            tab.load_progress = 0.0;
+            tab.favicon = None;
+        }
+        self.record_visit(url.clone(), "Loading...");
+        notify_listeners(&self.listeners, |l| l.on_navigation(tab_id, &url));
+
+        let tab_manager = Arc::clone(&self.tab_manager);
+        let mut on_progress = move |received: u64, total: Option<u64>| {
+            // Without a Content-Length, stay short of complete until the body ends
+            let progress = match total {
+                Some(total) if total > 0 => (received as f32 / total as f32).min(1.0),
+                _ => 0.5,
+            };
+            let mut tab_manager = lock_recovering(&tab_manager);
+            if let Some(tab) = tab_manager.tabs.iter_mut().find(|t| t.id == tab_id) {
+                tab.load_progress = progress;
+            }
+        };
+        let response = match self.fetch(&url, &mut on_progress).await {
+            Ok(response) => response,
+            Err(e) => {
+                // Don't leave the tab or its history entry stuck on "Loading..."
+                self.finish_tab_load(tab_id, &url, url.to_string(), 0.0, None);
+                return Err(e);
+            }
+        };
+
+        let mut favicon = None;
+        let (disposition, title) = match classify_response(&response) {
+            ResponseKind::Page => {
+                let html = String::from_utf8_lossy(&response.body);
+                if lock_recovering(&self.config).fetch_favicons {
+                    favicon = self.fetch_favicon(&url, &html).await;
+                }
+                (PageDisposition::Rendered, extract_title(&html).unwrap_or_else(|| url.to_string()))
+            }
+            ResponseKind::Image => (
+                PageDisposition::ImageViewer,
+                sanitize_filename(url.path()).unwrap_or_else(|| url.to_string()),
+            ),
+            ResponseKind::Download => {
+                let download_id = self.start_download(url.clone())?;
+                // A failed save is recorded on the download itself, not on the tab
+                if let Err(e) = self.save_fetched_download(download_id, response) {
+                    warn!("Failed to save download from {}: {}", url, e);
+                }
+                (PageDisposition::Download(download_id), url.to_string())
+            }
+        };
+
+        self.finish_tab_load(tab_id, &url, title, 1.0, favicon);
+        Ok(disposition)
+    }
+
+    // Settle a tab once its load ends, retitling the history entry recorded for the URL
+    fn finish_tab_load(&self, tab_id: uuid::Uuid, url: &Url, title: String, progress: f32, favicon: Option<Vec<u8>>) {
+        {
+            let mut tab_manager = lock_recovering(&self.tab_manager);
+            if let Some(tab) = tab_manager.tabs.iter_mut().find(|t| t.id == tab_id) {
+                tab.title = title.clone();
+                tab.load_progress = progress;
+                tab.favicon = favicon;
+            }
+        }
+
+        let mut history_manager = lock_recovering(&self.history_manager);
+        if let Some(entry) = history_manager.entries.iter_mut().rev().find(|e| e.url == *url) {
+            entry.title = title;
+        }
+    }
+
+    // Fetch the icon a page advertises, falling back to /favicon.ico on its origin.
+    // A missing or unreachable icon leaves the tab without one rather than failing the load.
+    async fn fetch_favicon(&self, page_url: &Url, html: &str) -> Option<Vec<u8>> {
+        let icon_url = match extract_favicon_href(html) {
+            Some(href) => page_url.join(&href).ok()?,
+            None => page_url.join("/favicon.ico").ok()?,
+        };
+        let mut ignore_progress = |_: u64, _: Option<u64>| {};
+        let response = self.fetch(&icon_url, &mut ignore_progress).await.ok()?;
+        if response.body.is_empty() {
+            None
+        } else {
+            Some(response.body)
+        }
+    }
+
+    pub fn add_bookmark(&self, url: Url, title: String, tags: Vec<String>) -> Result<(), Box<dyn std::error::Error>> {
+        let mut bookmark_manager = lock_recovering(&self.bookmark_manager);
+        let bookmark = Bookmark {
+            url: url.clone(),
+            title,
+            tags,
+            created_at: Utc::now(),
+        };
+        bookmark_manager.bookmarks.insert(url.to_string(), bookmark);
+        Ok(())
+    }
+
+    pub fn start_download(&self, url: Url) -> Result<uuid::Uuid, Box<dyn std::error::Error>> {
+        let mut download_manager = lock_recovering(&self.download_manager);
+        let download = Download {
+            id: uuid::Uuid::new_v4(),
+            url: url.clone(),
+            filename: sanitize_filename(url.path()).unwrap_or_else(|| String::from(DEFAULT_DOWNLOAD_NAME)),
+            progress: 0.0,
+            status: DownloadStatus::Pending,
+        };
+        download_manager.active_downloads.push(download.clone());
+        Ok(download.id)
+    }
+
+    // Fetch a pending download to the configured download directory
+    pub async fn run_download(&self, download_id: uuid::Uuid) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let (url, filename) = self.begin_download(download_id)?;
+
+        let download_manager = Arc::clone(&self.download_manager);
+        let listeners = Arc::clone(&self.listeners);
+        let mut on_progress = move |received: u64, total: Option<u64>| {
+            if let Some(total) = total.filter(|&total| total > 0) {
+                let progress = (received as f32 / total as f32).min(1.0);
+                {
+                    let mut download_manager = lock_recovering(&download_manager);
+                    if let Some(download) = download_manager.active_downloads.iter_mut().find(|d| d.id == download_id) {
+                        download.progress = progress;
+                    }
+                }
+                notify_listeners(&listeners, |l| l.on_download_progress(download_id, progress, &DownloadStatus::InProgress));
+            }
+        };
+
+        let result: Result<PathBuf, Box<dyn std::error::Error>> =
+            match self.fetch(&url, &mut on_progress).await {
+                Ok(response) => self.save_download(&filename, response),
+                Err(e) => Err(e),
+            };
+        self.complete_download(download_id, result)
+    }
+
+    // Save a response that has already been fetched, e.g. by load_tab, as a pending download
+    fn save_fetched_download(&self, download_id: uuid::Uuid, response: FetchResponse) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let (_, filename) = self.begin_download(download_id)?;
+        let result = self.save_download(&filename, response);
+        self.complete_download(download_id, result)
+    }
+
+    // Mark a pending download in progress, returning its URL and URL-derived filename
+    fn begin_download(&self, download_id: uuid::Uuid) -> Result<(Url, String), Box<dyn std::error::Error>> {
+        let (url, filename) = {
+            let mut download_manager = lock_recovering(&self.download_manager);
+            let download = download_manager
+                .active_downloads
+                .iter_mut()
+                .find(|d| d.id == download_id)
+                .ok_or("Download not found")?;
+            download.status = DownloadStatus::InProgress;
+            (download.url.clone(), download.filename.clone())
+        };
+
+        notify_listeners(&self.listeners, |l| l.on_download_progress(download_id, 0.0, &DownloadStatus::InProgress));
+        Ok((url, filename))
+    }
+
+    // Write a downloaded body to the configured download directory
+    fn save_download(&self, filename: &str, response: FetchResponse) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let download_dir = PathBuf::from(&lock_recovering(&self.config).default_download_path);
+        let filename = resolve_download_filename(
+            filename,
+            response.content_disposition.as_deref(),
+            response.content_type.as_deref(),
+        );
+        fs::create_dir_all(&download_dir)?;
+        let path = unique_download_path(&download_dir, &filename);
+        fs::write(&path, response.body)?;
+        Ok(path)
+    }
+
+    // Move a finished download, successful or not, to the completed list
+    fn complete_download(
+        &self,
+        download_id: uuid::Uuid,
+        result: Result<PathBuf, Box<dyn std::error::Error>>,
+    ) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let (progress, status) = match &result {
+            Ok(_) => (1.0, DownloadStatus::Completed),
+            Err(_) => (0.0, DownloadStatus::Failed),
+        };
+        {
+            let mut download_manager = lock_recovering(&self.download_manager);
+            if let Some(index) = download_manager.active_downloads.iter().position(|d| d.id == download_id) {
+                let mut download = download_manager.active_downloads.remove(index);
+                match &result {
+                    Ok(path) => {
+                        download.status = DownloadStatus::Completed;
+                        download.progress = 1.0;
+                        if let Some(name) = path.file_name() {
+                            download.filename = name.to_string_lossy().into_owned();
+                        }
+                    }
+                    Err(_) => download.status = DownloadStatus::Failed,
+                }
+                download_manager.completed_downloads.push(download);
+            }
+        }
+        notify_listeners(&self.listeners, |l| l.on_download_progress(download_id, progress, &status));
+
+        result
+    }
+
+    // Store a cookie for a domain; ignored when cookies are disabled in the config
+    pub fn set_cookie(&self, domain: &str, name: &str, value: &str) -> Result<(), Box<dyn std::error::Error>> {
+        if !lock_recovering(&self.config).enable_cookies {
+            return Ok(());
+        }
+        let mut cookie_jar = lock_recovering(&self.cookie_jar);
+        cookie_jar.set(Cookie {
+            name: name.to_string(),
+            value: value.to_string(),
+            domain: domain.trim_start_matches('.').to_lowercase(),
+            created_at: Utc::now(),
+        });
+        Ok(())
+    }
+
+    // Get the cookies that would be sent to a domain
+    pub fn get_cookies(&self, domain: &str) -> Result<Vec<Cookie>, Box<dyn std::error::Error>> {
+        if !lock_recovering(&self.config).enable_cookies {
+            return Ok(Vec::new());
+        }
+        let cookie_jar = lock_recovering(&self.cookie_jar);
+        Ok(cookie_jar.for_domain(domain))
+    }
+
+    // Remove every stored cookie; ignored when cookies are disabled in the config
+    pub fn clear_cookies(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if !lock_recovering(&self.config).enable_cookies {
+            return Ok(());
+        }
+        let mut cookie_jar = lock_recovering(&self.cookie_jar);
+        cookie_jar.clear();
+        Ok(())
+    }
+
+    // Additional methods for browser functionality can be added here
+}
+
+// Helper functions
+
+// Reduce a raw path or header value to a safe single filename component
+//
+// Percent-encoding is decoded before directory separators are stripped, so
+// encoded traversal like `..%2f..%2fetc%2fpasswd` can't escape the download directory.
+fn sanitize_filename(raw: &str) -> Option<String> {
+    let decoded = percent_encoding::percent_decode_str(raw).decode_utf8_lossy();
+    let last_segment = decoded.rsplit(|c| c == '/' || c == '\\').next().unwrap_or("");
+    let without_query = last_segment.split(|c| c == '?' || c == '#').next().unwrap_or("");
+
+    let cleaned: String = without_query
+        .chars()
+        .filter(|c| !c.is_control() && !matches!(c, '<' | '>' | ':' | '"' | '|' | '*'))
+        .collect();
+    let cleaned = cleaned.trim().trim_matches('.').trim();
+
+    if cleaned.is_empty() {
+        None
+    } else {
+        Some(cleaned.to_string())
+    }
+}
+
+// Extract the filename parameter from a Content-Disposition header
+fn filename_from_content_disposition(header: &str) -> Option<String> {
+    let params: Vec<&str> = header.split(';').map(str::trim).collect();
+
+    // RFC 5987 extended form takes precedence: filename*=UTF-8''name
+    if let Some(value) = params.iter().find_map(|p| p.strip_prefix("filename*=")) {
+        let encoded = value.splitn(3, '\'').nth(2).unwrap_or(value);
+        if let Some(name) = sanitize_filename(encoded) {
+            return Some(name);
+        }
+    }
+
+    params
+        .iter()
+        .find_map(|p| p.strip_prefix("filename="))
+        .and_then(|value| sanitize_filename(value.trim_matches('"')))
+}
+
+// Choose the final filename for a download from the URL-derived name and response headers
+fn resolve_download_filename(
+    url_filename: &str,
+    content_disposition: Option<&str>,
+    content_type: Option<&str>,
+) -> String {
+    if let Some(name) = content_disposition.and_then(filename_from_content_disposition) {
+        return name;
+    }
+
+    if url_filename != DEFAULT_DOWNLOAD_NAME {
+        return url_filename.to_string();
+    }
+
+    let extension = content_type
+        .and_then(|ct| ct.split(';').next())
+        .and_then(|essence| mime_guess::get_mime_extensions_str(essence.trim()))
+        .and_then(|extensions| extensions.first());
+    match extension {
+        Some(extension) => format!("{}.{}", DEFAULT_DOWNLOAD_NAME, extension),
+        None => url_filename.to_string(),
+    }
+}
+
+// Pick a path for a download, appending " (n)" before the extension if the name is taken
+fn unique_download_path(dir: &Path, filename: &str) -> PathBuf {
+    let candidate = dir.join(filename);
+    if !candidate.exists() {
+        return candidate;
+    }
+
+    let name = Path::new(filename);
+    let stem = name.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let extension = name.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+
+    (1..)
+        .map(|n| dir.join(format!("{} ({}){}", stem, n, extension)))
+        .find(|path| !path.exists())
+        .expect("unbounded range always yields a free name")
+}
+
+fn load_user_preferences() -> Result<BrowserConfig, Box<dyn std::error::Error>> {
+    // TODO: Implement loading user preferences from a configuration file
+    Ok(BrowserConfig::default())
+}
+
+fn setup_logging() -> Result<(), Box<dyn std::error::Error>> {
+    // TODO: Implement logging setup for the browser
+    Ok(())
+}
+
+// Main function to start the Aluminum browser
+pub fn main() -> Result<(), Box<dyn std::error::Error>> {
+    setup_logging()?;
+    let browser = initialize_aluminum_prelude()?;
+    
+    // TODO: Implement the main event loop for the browser GUI
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::future;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+    use std::time::Duration;
+
+    // A browser on the given config, skipping the subsystem start-up in initialize_aluminum_prelude
+    fn test_browser(config: BrowserConfig) -> AluminumBrowser {
+        let fetcher = ReqwestFetcher::new(&config.user_agent, config.proxy.as_ref()).expect("Failed to build fetcher");
+        AluminumBrowser {
+            connection_limiter: Arc::new(ConnectionLimiter::new(config.max_concurrent_connections)),
+            config: Arc::new(Mutex::new(config)),
+            tab_manager: Arc::new(Mutex::new(TabManager {
+                tabs: Vec::new(),
+                active_tab_index: 0,
+                groups: Vec::new(),
+            })),
+            history_manager: Arc::new(Mutex::new(HistoryManager::new())),
+            bookmark_manager: Arc::new(Mutex::new(BookmarkManager { bookmarks: HashMap::new() })),
+            download_manager: Arc::new(Mutex::new(DownloadManager {
+                active_downloads: Vec::new(),
+                completed_downloads: Vec::new(),
+            })),
+            cookie_jar: Arc::new(Mutex::new(CookieJar::default())),
+            storage: None,
+            fetcher: Mutex::new(ActiveFetcher {
+                fetcher: Arc::new(fetcher),
+                from_config: true,
+            }),
+            listeners: Arc::new(Mutex::new(Vec::new())),
+            runtime: Arc::new(Runtime::new().expect("Failed to start runtime")),
+        }
+    }
+
+    fn http_response(content_type: &str, body: &[u8]) -> Vec<u8> {
+        let mut response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            content_type,
+            body.len()
+        )
+        .into_bytes();
+        response.extend_from_slice(body);
+        response
+    }
+
+    // Local HTTP server answering each path with a canned response after `delay`,
+    // and recording the most requests it was serving at once
+    struct MockServer {
+        base: Url,
+        peak: Arc<AtomicUsize>,
+    }
+
+    impl MockServer {
+        fn start(routes: Vec<(&str, Vec<u8>)>, delay: Duration) -> MockServer {
+            let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind mock server");
+            let base = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+            let routes: Arc<HashMap<String, Vec<u8>>> =
+                Arc::new(routes.into_iter().map(|(path, response)| (path.to_string(), response)).collect());
+            let in_flight = Arc::new(AtomicUsize::new(0));
+            let peak = Arc::new(AtomicUsize::new(0));
+
+            let server_peak = Arc::clone(&peak);
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    let stream = match stream {
+                        Ok(stream) => stream,
+                        Err(_) => break,
+                    };
+                    let (routes, in_flight, peak) = (Arc::clone(&routes), Arc::clone(&in_flight), Arc::clone(&server_peak));
+                    thread::spawn(move || {
+                        let mut reader = BufReader::new(stream);
+                        let mut request_line = String::new();
+                        if reader.read_line(&mut request_line).is_err() {
+                            return;
+                        }
+                        loop {
+                            let mut header = String::new();
+                            match reader.read_line(&mut header) {
+                                Ok(0) | Err(_) => break,
+                                Ok(_) if header == "\r\n" => break,
+                                Ok(_) => {}
+                            }
+                        }
+
+                        let serving = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                        peak.fetch_max(serving, Ordering::SeqCst);
+                        thread::sleep(delay);
+                        let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+                        let response = routes.get(path).cloned().unwrap_or_else(|| {
+                            b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_vec()
+                        });
+                        // Stop counting before replying, so the client can't release its
+                        // connection slot while this request still looks in flight
+                        in_flight.fetch_sub(1, Ordering::SeqCst);
+                        let _ = reader.into_inner().write_all(&response);
+                    });
+                }
+            });
+
+            MockServer { base, peak }
+        }
+
+        fn url(&self, path: &str) -> Url {
+            self.base.join(path).unwrap()
+        }
+
+        // The peak concurrency since the last call
+        fn take_peak(&self) -> usize {
+            self.peak.swap(0, Ordering::SeqCst)
+        }
+    }
+
+    #[test]
+    fn test_shrinking_connection_limit_counts_fetches_already_in_flight() {
+        let server = MockServer::start(vec![("/slow", http_response("text/plain", b"ok"))], Duration::from_millis(300));
+        let browser = test_browser(BrowserConfig::default().with_max_concurrent_connections(3));
+        let runtime = Arc::clone(&browser.runtime);
+        let url = server.url("/slow");
+
+        let (browser_ref, url_ref) = (&browser, &url);
+        let fetch_all = |count: usize| {
+            future::join_all((0..count).map(move |_| async move {
+                let mut ignore_progress = |_: u64, _: Option<u64>| {};
+                browser_ref.fetch(url_ref, &mut ignore_progress).await.expect("Fetch failed");
+            }))
+        };
+
+        // Shrink to 1 while three fetches hold permits, then queue three more behind them
+        runtime.block_on(async {
+            let shrink_then_fetch = async {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                browser.connection_limiter.resize(1, runtime.handle());
+                fetch_all(3).await;
+            };
+            tokio::join!(fetch_all(3), shrink_then_fetch);
+        });
+        assert!(server.take_peak() <= 3, "fetches started after the shrink ran alongside the old ones");
+
+        runtime.block_on(fetch_all(4));
+        assert_eq!(server.take_peak(), 1);
+        assert_eq!(browser.connection_limiter.limit(), 1);
+
+        browser.connection_limiter.resize(2, runtime.handle());
+        runtime.block_on(fetch_all(4));
+        assert!(server.take_peak() <= 2);
+    }
+
+    // Title and load progress of a tab, and the title its URL has in history
+    fn tab_state(browser: &AluminumBrowser, tab_id: uuid::Uuid, url: &Url) -> (String, f32, String) {
+        let tab_manager = lock_recovering(&browser.tab_manager);
+        let tab = tab_manager.tabs.iter().find(|t| t.id == tab_id).expect("Tab not found");
+        let history_manager = lock_recovering(&browser.history_manager);
+        let entry = history_manager.entries.iter().find(|e| e.url == *url).expect("No history entry for URL");
+        (tab.title.clone(), tab.load_progress, entry.title.clone())
+    }
+
+    #[test]
+    fn test_load_tab_titles_tab_and_history_from_page() {
+        let page = b"<html><head><title>  Example\n Page </title></head><body>Hi</body></html>";
+        let server = MockServer::start(vec![("/page", http_response("text/html; charset=utf-8", page))], Duration::ZERO);
+        let browser = test_browser(BrowserConfig::default().with_favicons(false));
+        let tab_id = browser.create_new_tab(None).unwrap();
+        let url = server.url("/page");
+
+        let disposition = Arc::clone(&browser.runtime)
+            .block_on(browser.load_tab(tab_id, url.clone()))
+            .expect("Page load failed");
+
+        assert_eq!(disposition, PageDisposition::Rendered);
+        assert_eq!(tab_state(&browser, tab_id, &url), ("Example Page".to_string(), 1.0, "Example Page".to_string()));
+    }
+
+    #[test]
+    fn test_failed_load_tab_does_not_stay_loading() {
+        let server = MockServer::start(Vec::new(), Duration::ZERO);
+        let browser = test_browser(BrowserConfig::default());
+        let tab_id = browser.create_new_tab(None).unwrap();
+        let url = server.url("/missing");
+
+        let result = Arc::clone(&browser.runtime).block_on(browser.load_tab(tab_id, url.clone()));
+
+        assert!(result.is_err());
+        assert_eq!(tab_state(&browser, tab_id, &url), (url.to_string(), 0.0, url.to_string()));
+    }
+
+    // Fetcher answering every URL with the same response, counting how often it's asked
+    struct StaticFetcher {
+        response: FetchResponse,
+        fetches: AtomicUsize,
+    }
+
+    impl StaticFetcher {
+        fn new(content_type: &str, body: &[u8]) -> Arc<StaticFetcher> {
+            Arc::new(StaticFetcher {
+                response: FetchResponse {
+                    body: body.to_vec(),
+                    content_type: Some(content_type.to_string()),
+                    content_disposition: None,
+                },
+                fetches: AtomicUsize::new(0),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl HttpFetcher for StaticFetcher {
+        async fn fetch(
+            &self,
+            _url: &Url,
+            on_progress: &mut ProgressCallback<'_>,
+        ) -> Result<FetchResponse, Box<dyn std::error::Error + Send + Sync>> {
+            self.fetches.fetch_add(1, Ordering::SeqCst);
+            let size = self.response.body.len() as u64;
+            on_progress(size, Some(size));
+            Ok(self.response.clone())
+        }
+    }
+
+    #[test]
+    fn test_html_response_is_rendered() {
+        let fetcher = StaticFetcher::new("text/html", b"<title>Rendered</title>");
+        let browser = test_browser(BrowserConfig::default().with_favicons(false));
+        browser.set_fetcher(fetcher.clone());
+        let tab_id = browser.create_new_tab(None).unwrap();
+        let url = Url::parse("https://example.com/index.html").unwrap();
+
+        let disposition = Arc::clone(&browser.runtime).block_on(browser.load_tab(tab_id, url)).unwrap();
+
+        assert_eq!(disposition, PageDisposition::Rendered);
+        assert_eq!(fetcher.fetches.load(Ordering::SeqCst), 1);
+        assert!(lock_recovering(&browser.download_manager).active_downloads.is_empty());
+    }
+
+    #[test]
+    fn test_binary_response_is_downloaded_without_refetching() {
+        let download_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let body = [0x50, 0x4b, 0x03, 0x04, 0x00, 0xff];
+        let fetcher = StaticFetcher::new("application/zip", &body);
+        let config = BrowserConfig::default().with_download_path(download_dir.path().to_string_lossy());
+        let browser = test_browser(config);
+        browser.set_fetcher(fetcher.clone());
+        let tab_id = browser.create_new_tab(None).unwrap();
+        let url = Url::parse("https://example.com/files/archive.zip").unwrap();
+
+        let disposition = Arc::clone(&browser.runtime).block_on(browser.load_tab(tab_id, url)).unwrap();
+
+        let download_id = match disposition {
+            PageDisposition::Download(id) => id,
+            other => panic!("expected a download, got {:?}", other),
+        };
+        assert_eq!(fetcher.fetches.load(Ordering::SeqCst), 1);
+        let download_manager = lock_recovering(&browser.download_manager);
+        let download = download_manager
+            .completed_downloads
+            .iter()
+            .find(|d| d.id == download_id)
+            .expect("download was not completed");
+        assert_eq!(download.status, DownloadStatus::Completed);
+        assert_eq!(fs::read(download_dir.path().join("archive.zip")).unwrap(), body);
+    }
+
+    #[test]
+    fn test_create_new_tab_recovers_poisoned_tab_manager() {
+        let browser = test_browser(BrowserConfig::default());
+        let tab_manager = Arc::clone(&browser.tab_manager);
+        let poisoner = thread::spawn(move || {
+            let _guard = tab_manager.lock().unwrap();
+            panic!("simulated failure while holding the tab manager lock");
+        });
+        assert!(poisoner.join().is_err());
+        assert!(browser.tab_manager.is_poisoned());
+
+        let tab_id = browser.create_new_tab(None).expect("create_new_tab failed on a poisoned lock");
+
+        assert!(!browser.tab_manager.is_poisoned());
+        assert!(lock_recovering(&browser.tab_manager).tabs.iter().any(|t| t.id == tab_id));
+    }
+
+    thread_local! {
+        static TEARDOWNS: std::cell::RefCell<Vec<&'static str>> = std::cell::RefCell::new(Vec::new());
+    }
+
+    fn start_ok(_: &AluminumBrowser) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(())
+    }
+
+    fn start_failing(_: &AluminumBrowser) -> Result<(), Box<dyn std::error::Error>> {
+        Err("device unavailable".into())
+    }
+
+    fn record_teardown(name: &'static str) {
+        TEARDOWNS.with(|teardowns| teardowns.borrow_mut().push(name));
+    }
+
+    #[test]
+    fn test_failed_initialization_tears_down_started_subsystems_in_reverse() {
+        let subsystems = [
+            Subsystem { name: "first", initialize: start_ok, teardown: |_| record_teardown("first") },
+            Subsystem { name: "second", initialize: start_ok, teardown: |_| record_teardown("second") },
+            Subsystem { name: "third", initialize: start_failing, teardown: |_| record_teardown("third") },
+            Subsystem { name: "fourth", initialize: start_ok, teardown: |_| record_teardown("fourth") },
+        ];
+        let browser = test_browser(BrowserConfig::default());
+
+        let error = initialize_with_rollback(&browser, &subsystems).unwrap_err();
+
+        assert_eq!(error.to_string(), "Failed to initialize third: device unavailable");
+        assert_eq!(TEARDOWNS.with(|teardowns| teardowns.borrow().clone()), vec!["second", "first"]);
+    }
+
+    #[test]
+    fn test_reload_config_applies_through_a_shared_browser() {
+        let fetcher = StaticFetcher::new("text/html", b"<title>Custom</title>");
+        let browser = Arc::new(test_browser(BrowserConfig::default().with_favicons(false)));
+        browser.set_fetcher(fetcher.clone());
+
+        let shared = Arc::clone(&browser);
+        thread::spawn(move || {
+            let config = BrowserConfig::default()
+                .with_favicons(false)
+                .with_max_concurrent_connections(2)
+                .with_user_agent("Aluminum/2.0");
+            shared.reload_config(config).expect("reload_config failed");
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(lock_recovering(&browser.config).user_agent, "Aluminum/2.0");
+        assert_eq!(browser.connection_limiter.limit(), 2);
+
+        // The user agent changed, but the installed fetcher still serves page loads
+        let tab_id = browser.create_new_tab(None).unwrap();
+        let url = Url::parse("https://example.com/").unwrap();
+        Arc::clone(&browser.runtime).block_on(browser.load_tab(tab_id, url)).unwrap();
+        assert_eq!(fetcher.fetches.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_reload_config_rejects_private_browsing_toggle() {
+        let browser = test_browser(BrowserConfig::default());
+
+        let result = browser.reload_config(BrowserConfig::default().with_private_browsing(true).with_javascript(false));
+
+        assert!(result.is_err());
+        let config = lock_recovering(&browser.config);
+        assert!(!config.enable_private_browsing);
+        assert!(config.enable_javascript);
+    }
+
+    #[test]
+    fn test_sanitize_filename_strips_traversal_and_query() {
+        assert_eq!(sanitize_filename("/files/report.pdf?token=abc#page=2").as_deref(), Some("report.pdf"));
+        assert_eq!(sanitize_filename("..%2f..%2fetc%2fpasswd").as_deref(), Some("passwd"));
+        assert_eq!(sanitize_filename("..\\..\\boot.ini").as_deref(), Some("boot.ini"));
+        assert_eq!(sanitize_filename("/a/<report>:v2.txt").as_deref(), Some("reportv2.txt"));
+        assert_eq!(sanitize_filename("/downloads/.."), None);
+        assert_eq!(sanitize_filename("/"), None);
+    }
+
+    #[test]
+    fn test_content_disposition_names_the_download() {
+        assert_eq!(
+            filename_from_content_disposition("attachment; filename=\"annual report.pdf\"").as_deref(),
+            Some("annual report.pdf")
+        );
+        // The RFC 5987 form wins over the plain one
+        assert_eq!(
+            filename_from_content_disposition("attachment; filename=\"fallback.txt\"; filename*=UTF-8''r%C3%A9sum%C3%A9.txt")
+                .as_deref(),
+            Some("résumé.txt")
+        );
+        assert_eq!(
+            filename_from_content_disposition("attachment; filename=\"../../.bashrc\"").as_deref(),
+            Some("bashrc")
+        );
+        assert_eq!(filename_from_content_disposition("inline"), None);
+
+        assert_eq!(resolve_download_filename("page.html", Some("attachment; filename=data.csv"), None), "data.csv");
+        assert_eq!(resolve_download_filename("page.html", None, Some("application/pdf")), "page.html");
+        assert_eq!(
+            resolve_download_filename(DEFAULT_DOWNLOAD_NAME, None, Some("application/pdf; charset=binary")),
+            "download.pdf"
+        );
+    }
+
+    // Listener recording every download status it's told about
+    #[derive(Default)]
+    struct DownloadEvents {
+        statuses: Mutex<Vec<(f32, String)>>,
+    }
+
+    impl BrowserEventListener for DownloadEvents {
+        fn on_download_progress(&self, _download_id: uuid::Uuid, progress: f32, status: &DownloadStatus) {
+            lock_recovering(&self.statuses).push((progress, format!("{:?}", status)));
+        }
+    }
+
+    #[test]
+    fn test_run_download_saves_response_from_server() {
+        let body = vec![7u8; 4096];
+        let mut response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/octet-stream\r\nContent-Disposition: attachment; filename=\"data.bin\"\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        )
+        .into_bytes();
+        response.extend_from_slice(&body);
+        let server = MockServer::start(vec![("/export", response)], Duration::ZERO);
+        let download_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        // An existing file with the same name must not be overwritten
+        fs::write(download_dir.path().join("data.bin"), b"keep").unwrap();
+        let browser = test_browser(BrowserConfig::default().with_download_path(download_dir.path().to_string_lossy()));
+        let events = Arc::new(DownloadEvents::default());
+        browser.observe(events.clone());
+
+        let download_id = browser.start_download(server.url("/export")).unwrap();
+        let path = Arc::clone(&browser.runtime).block_on(browser.run_download(download_id)).expect("Download failed");
+
+        assert_eq!(path, download_dir.path().join("data (1).bin"));
+        assert_eq!(fs::read(&path).unwrap(), body);
+        assert_eq!(fs::read(download_dir.path().join("data.bin")).unwrap(), b"keep");
+        let download_manager = lock_recovering(&browser.download_manager);
+        assert!(download_manager.active_downloads.is_empty());
+        let download = &download_manager.completed_downloads[0];
+        assert_eq!(download.status, DownloadStatus::Completed);
+        assert_eq!(download.filename, "data (1).bin");
+        let statuses = lock_recovering(&events.statuses);
+        assert_eq!(statuses.first(), Some(&(0.0, "InProgress".to_string())));
+        assert_eq!(statuses.last(), Some(&(1.0, "Completed".to_string())));
+    }
+
+    #[test]
+    fn test_v1_history_is_migrated_and_resaved_with_envelope() {
+        let dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        // History as written before payloads carried a schema version
+        let v1 = r#"[{"url": "https://example.com/", "title": "Example", "timestamp": "2024-03-01T12:00:00Z", "visit_count": 3}]"#;
+        fs::write(dir.path().join("history.json"), v1).unwrap();
+        let storage = JsonFileStorage::new(dir.path());
+
+        let history = HistoryManager::from_storage(&storage).expect("v1 history was rejected");
+        assert_eq!(history.entries.len(), 1);
+        assert_eq!(history.entries[0].title, "Example");
+        assert_eq!(history.entries[0].visit_count, 3);
+
+        history.to_storage(&storage).unwrap();
+        let saved: serde_json::Value = serde_json::from_str(&fs::read_to_string(dir.path().join("history.json")).unwrap()).unwrap();
+        assert_eq!(saved["schema_version"], STORAGE_SCHEMA_VERSION);
+        assert_eq!(saved["data"][0]["url"], "https://example.com/");
+        assert_eq!(HistoryManager::from_storage(&storage).unwrap().entries.len(), 1);
+    }
+
+    #[test]
+    fn test_payload_from_newer_schema_is_rejected() {
+        let dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let newer = format!(r#"{{"schema_version": {}, "data": []}}"#, STORAGE_SCHEMA_VERSION + 1);
+        fs::write(dir.path().join("cookies.json"), newer).unwrap();
+        let storage = JsonFileStorage::new(dir.path());
+
+        let error = CookieJar::from_storage(&storage).unwrap_err();
+
+        assert!(error.to_string().contains(&format!("schema version {}", STORAGE_SCHEMA_VERSION + 1)));
+    }
+
+    #[test]
+    fn test_tab_group_membership_follows_tabs() {
+        let browser = test_browser(BrowserConfig::default());
+        let first = browser.create_new_tab(None).unwrap();
+        let second = browser.create_new_tab(None).unwrap();
+        let work = browser.create_tab_group("Work", "blue").unwrap();
+        let reading = browser.create_tab_group("Reading", "green").unwrap();
+
+        browser.add_tab_to_group(work, first).unwrap();
+        browser.add_tab_to_group(work, second).unwrap();
+        // Adding to another group moves the tab rather than sharing it
+        browser.add_tab_to_group(reading, second).unwrap();
+        browser.collapse_tab_group(work, true).unwrap();
+
+        let groups = browser.tab_groups();
+        assert_eq!(groups[0].tab_ids, vec![first]);
+        assert!(groups[0].collapsed);
+        assert_eq!(groups[1].tab_ids, vec![second]);
+
+        browser.remove_tab_from_group(reading, second).unwrap();
+        assert!(browser.remove_tab_from_group(reading, second).is_err());
+        assert!(browser.add_tab_to_group(work, uuid::Uuid::new_v4()).is_err());
+
+        browser.close_tab(first).unwrap();
+        assert!(browser.tab_groups().iter().all(|group| group.tab_ids.is_empty()));
+    }
+
+    #[test]
+    fn test_record_visit_counts_every_call_across_threads() {
+        let browser = Arc::new(test_browser(BrowserConfig::default()));
+        let url = Url::parse("https://example.com/popular").unwrap();
+        let threads = 8;
+        let visits_per_thread = 250;
+
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                let (browser, url) = (Arc::clone(&browser), url.clone());
+                thread::spawn(move || {
+                    for _ in 0..visits_per_thread {
+                        browser.record_visit(url.clone(), "Popular");
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+
+        let history_manager = lock_recovering(&browser.history_manager);
+        assert_eq!(history_manager.entries.len(), 1);
+        assert_eq!(history_manager.entries[0].visit_count, threads * visits_per_thread);
+    }
+
+    #[test]
+    fn test_merge_duplicate_tabs_keeps_active_index_on_survivor() {
+        let browser = test_browser(BrowserConfig::default());
+        let docs = Url::parse("https://example.com/docs").unwrap();
+        let first = browser.create_new_tab(Some(docs.clone())).unwrap();
+        let other = browser.create_new_tab(Some(Url::parse("https://example.com/other").unwrap())).unwrap();
+        // The newest tab is both active and the duplicate that gets closed
+        let duplicate = browser.create_new_tab(Some(docs)).unwrap();
+
+        assert_eq!(browser.merge_duplicate_tabs(), 1);
+
+        let tab_manager = lock_recovering(&browser.tab_manager);
+        let ids: Vec<_> = tab_manager.tabs.iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec![first, other]);
+        assert!(!ids.contains(&duplicate));
+        assert_eq!(tab_manager.tabs[tab_manager.active_tab_index].id, first);
+    }
+
+    fn tab_favicon(browser: &AluminumBrowser, tab_id: uuid::Uuid) -> Option<Vec<u8>> {
+        let tab_manager = lock_recovering(&browser.tab_manager);
+        tab_manager.tabs.iter().find(|t| t.id == tab_id).and_then(|t| t.favicon.clone())
+    }
+
+    #[test]
+    fn test_load_tab_stores_advertised_favicon() {
+        let icon = b"\x89PNG advertised icon".to_vec();
+        let page = b"<html><head><link data-rel=\"x\" rel=\"shortcut icon\" href=\"/static/icon.png\"><title>Icons</title></head></html>";
+        let server = MockServer::start(
+            vec![
+                ("/page", http_response("text/html", page)),
+                ("/static/icon.png", http_response("image/png", &icon)),
+                ("/favicon.ico", http_response("image/x-icon", b"fallback")),
+            ],
+            Duration::ZERO,
+        );
+        let browser = test_browser(BrowserConfig::default());
+        let tab_id = browser.create_new_tab(None).unwrap();
+
+        Arc::clone(&browser.runtime).block_on(browser.load_tab(tab_id, server.url("/page"))).unwrap();
+
+        assert_eq!(tab_favicon(&browser, tab_id), Some(icon));
+    }
+
+    #[test]
+    fn test_load_tab_falls_back_to_favicon_ico_or_none() {
+        let page = b"<html><head><title>Plain</title></head></html>";
+        let with_fallback = MockServer::start(
+            vec![
+                ("/page", http_response("text/html", page)),
+                ("/favicon.ico", http_response("image/x-icon", b"fallback")),
+            ],
+            Duration::ZERO,
+        );
+        let without_icon = MockServer::start(vec![("/page", http_response("text/html", page))], Duration::ZERO);
+        let browser = test_browser(BrowserConfig::default());
+        let runtime = Arc::clone(&browser.runtime);
+
+        let tab_id = browser.create_new_tab(None).unwrap();
+        runtime.block_on(browser.load_tab(tab_id, with_fallback.url("/page"))).unwrap();
+        assert_eq!(tab_favicon(&browser, tab_id), Some(b"fallback".to_vec()));
+
+        // A missing icon leaves the tab without one but the page still loads
+        let tab_id = browser.create_new_tab(None).unwrap();
+        let disposition = runtime.block_on(browser.load_tab(tab_id, without_icon.url("/page"))).unwrap();
+        assert_eq!(disposition, PageDisposition::Rendered);
+        assert_eq!(tab_favicon(&browser, tab_id), None);
+    }
+
+    #[test]
+    fn test_browser_config_setters_override_defaults() {
+        let defaults = BrowserConfig::default();
+        assert_eq!(defaults.max_concurrent_connections, 6);
+        assert!(defaults.enable_javascript && defaults.enable_cookies && defaults.fetch_favicons);
+        assert!(!defaults.enable_private_browsing);
+        assert_eq!((defaults.custom_css.clone(), defaults.proxy.clone()), (None, None));
+
+        // Only the two fields that were set differ from the defaults
+        let config = BrowserConfig::default().with_javascript(false).with_user_agent("Test/1.0");
+        assert_eq!(
+            config,
+            BrowserConfig {
+                enable_javascript: false,
+                user_agent: "Test/1.0".to_string(),
+                ..defaults.clone()
+            }
+        );
+
+        let proxy = ProxyConfig::new("http://127.0.0.1:8080", None).unwrap();
+        let config = BrowserConfig::default()
+            .with_homepage("about:blank")
+            .with_max_concurrent_connections(2)
+            .with_cookies(false)
+            .with_private_browsing(true)
+            .with_download_path("/tmp/downloads")
+            .with_custom_css(Some("body { color: red }".to_string()))
+            .with_proxy(Some(proxy.clone()))
+            .with_favicons(false);
+        assert_eq!(
+            config,
+            BrowserConfig {
+                default_homepage: "about:blank".to_string(),
+                max_concurrent_connections: 2,
+                enable_cookies: false,
+                enable_private_browsing: true,
+                default_download_path: "/tmp/downloads".to_string(),
+                custom_css: Some("body { color: red }".to_string()),
+                proxy: Some(proxy),
+                fetch_favicons: false,
+                ..defaults
+            }
+        );
+    }
+
+    #[test]
+    fn test_config_without_optional_fields_uses_their_defaults() {
+        let json = r#"{
+            "user_agent": "Old/0.9",
+            "default_homepage": "https://example.com",
+            "max_concurrent_connections": 4,
+            "enable_javascript": true,
+            "enable_cookies": true,
+            "enable_private_browsing": false,
+            "default_download_path": "/downloads",
+            "custom_css": null
+        }"#;
+
+        let config: BrowserConfig = serde_json::from_str(json).unwrap();
+
+        assert_eq!(config.proxy, None);
+        assert!(config.fetch_favicons);
+    }
+
+    #[test]
+    fn test_page_loads_go_through_configured_proxy() {
+        // A forward proxy receives the absolute URL as the request target
+        let page = b"<html><head><title>Via proxy</title></head></html>";
+        let proxy = MockServer::start(
+            vec![("http://aluminum.test/page", http_response("text/html", page))],
+            Duration::ZERO,
+        );
+        let proxy_config = ProxyConfig::new(proxy.url("/").as_str(), None).unwrap();
+        let browser = test_browser(BrowserConfig::default().with_proxy(Some(proxy_config)).with_favicons(false));
+        let tab_id = browser.create_new_tab(None).unwrap();
+        let url = Url::parse("http://aluminum.test/page").unwrap();
+
+        let disposition = Arc::clone(&browser.runtime)
+            .block_on(browser.load_tab(tab_id, url.clone()))
+            .expect("Proxied page load failed");
+
+        assert_eq!(disposition, PageDisposition::Rendered);
+        assert_eq!(tab_state(&browser, tab_id, &url).0, "Via proxy");
+    }
+
+    fn cookie_values(browser: &AluminumBrowser, domain: &str) -> Vec<(String, String)> {
+        let mut cookies: Vec<_> =
+            browser.get_cookies(domain).unwrap().into_iter().map(|c| (c.name, c.value)).collect();
+        cookies.sort();
+        cookies
+    }
+
+    #[test]
+    fn test_cookies_are_scoped_to_their_domain() {
+        let browser = test_browser(BrowserConfig::default());
+        browser.set_cookie("shop.example.com", "session", "shop-1").unwrap();
+        browser.set_cookie("example.com", "theme", "dark").unwrap();
+        browser.set_cookie("news.example.org", "session", "news-1").unwrap();
+
+        let pair = |name: &str, value: &str| (name.to_string(), value.to_string());
+        assert_eq!(
+            cookie_values(&browser, "shop.example.com"),
+            vec![pair("session", "shop-1"), pair("theme", "dark")]
+        );
+        // A parent domain's cookies reach subdomains, but never the other way round
+        assert_eq!(cookie_values(&browser, "example.com"), vec![pair("theme", "dark")]);
+        assert_eq!(cookie_values(&browser, "news.example.org"), vec![pair("session", "news-1")]);
+        assert_eq!(cookie_values(&browser, "notexample.com"), vec![]);
+
+        browser.clear_cookies().unwrap();
+        assert_eq!(cookie_values(&browser, "shop.example.com"), vec![]);
+    }
+
+    #[test]
+    fn test_cookie_calls_are_ignored_when_cookies_are_disabled() {
+        let browser = test_browser(BrowserConfig::default());
+        browser.set_cookie("example.com", "kept", "1").unwrap();
+        browser.reload_config(BrowserConfig::default().with_cookies(false)).unwrap();
+
+        browser.set_cookie("example.com", "ignored", "2").unwrap();
+        assert_eq!(cookie_values(&browser, "example.com"), vec![]);
+        browser.clear_cookies().unwrap();
+
+        // The jar was left exactly as it was before cookies were turned off
+        let names: Vec<String> = lock_recovering(&browser.cookie_jar).cookies.iter().map(|c| c.name.clone()).collect();
+        assert_eq!(names, vec!["kept".to_string()]);
+        browser.reload_config(BrowserConfig::default()).unwrap();
+        assert_eq!(cookie_values(&browser, "example.com"), vec![("kept".to_string(), "1".to_string())]);
+    }
+
+    #[test]
+    fn test_persisted_history_and_bookmarks_reload_into_a_new_browser() {
+        let dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let storage: Arc<dyn Storage> = Arc::new(JsonFileStorage::new(dir.path()));
+        let docs = Url::parse("https://example.com/docs").unwrap();
+        let news = Url::parse("https://example.org/news").unwrap();
+
+        let browser = test_browser(BrowserConfig::default()).with_storage(Arc::clone(&storage)).unwrap();
+        browser.record_visit(docs.clone(), "Docs");
+        browser.record_visit(docs.clone(), "Docs");
+        browser.record_visit(news.clone(), "News");
+        browser.add_bookmark(docs.clone(), "Docs".to_string(), vec!["reference".to_string()]).unwrap();
+        browser.persist().unwrap();
+
+        let reloaded = test_browser(BrowserConfig::default()).with_storage(storage).unwrap();
+
+        let history = lock_recovering(&reloaded.history_manager);
+        let mut visits: Vec<(String, String, u32)> = history
+            .entries
+            .iter()
+            .map(|entry| (entry.url.to_string(), entry.title.clone(), entry.visit_count))
+            .collect();
+        visits.sort();
+        assert_eq!(
+            visits,
+            vec![(docs.to_string(), "Docs".to_string(), 2), (news.to_string(), "News".to_string(), 1)]
+        );
+
+        let bookmarks = lock_recovering(&reloaded.bookmark_manager);
+        assert_eq!(bookmarks.bookmarks.len(), 1);
+        let bookmark = &bookmarks.bookmarks[docs.as_str()];
+        assert_eq!((&bookmark.url, bookmark.title.as_str()), (&docs, "Docs"));
+        assert_eq!(bookmark.tags, vec!["reference".to_string()]);
+    }
+
+    fn history_entry(path: &str, age: chrono::Duration) -> HistoryEntry {
+        HistoryEntry {
+            url: Url::parse(&format!("https://example.com/{}", path)).unwrap(),
+            title: path.to_string(),
+            timestamp: Utc::now() - age,
+            visit_count: 1,
+        }
+    }
+
+    fn history_titles(history: &HistoryManager) -> Vec<String> {
+        history.entries.iter().map(|entry| entry.title.clone()).collect()
+    }
+
+    #[test]
+    fn test_history_over_max_entries_drops_the_oldest() {
+        let mut history = HistoryManager::new();
+        history.set_max_entries(3);
+
+        // Inserted out of order: the cap keeps the three most recent visits, not the last three added
+        for (path, minutes_ago) in [("b", 40), ("e", 10), ("a", 50), ("d", 20), ("c", 30)] {
+            history.add_entry(history_entry(path, chrono::Duration::minutes(minutes_ago)));
+        }
+        assert_eq!(history_titles(&history), vec!["c", "d", "e"]);
+
+        history.record_visit(Url::parse("https://example.com/f").unwrap(), "f");
+        assert_eq!(history_titles(&history), vec!["d", "e", "f"]);
+
+        // Lowering the cap prunes straight away
+        history.set_max_entries(1);
+        assert_eq!(history_titles(&history), vec!["f"]);
+    }
+
+    #[test]
+    fn test_history_older_than_max_age_is_dropped() {
+        let mut history = HistoryManager::new();
+        history.add_entry(history_entry("stale", chrono::Duration::days(45)));
+        history.add_entry(history_entry("recent", chrono::Duration::days(2)));
+        history.add_entry(history_entry("today", chrono::Duration::hours(1)));
+
+        history.set_max_age(Some(chrono::Duration::days(30)));
+        assert_eq!(history_titles(&history), vec!["recent", "today"]);
+
+        // Entries that are already too old are dropped as soon as they're inserted
+        history.add_entry(history_entry("archived", chrono::Duration::days(31)));
+        assert_eq!(history_titles(&history), vec!["recent", "today"]);
+
+        history.set_max_age(Some(chrono::Duration::days(1)));
+        history.prune();
+        assert_eq!(history_titles(&history), vec!["today"]);
+    }
+
+    // Listener recording every event it receives, in order
+    #[derive(Default)]
+    struct RecordedEvents {
+        events: Mutex<Vec<String>>,
+    }
+
+    impl BrowserEventListener for RecordedEvents {
+        fn on_tab_created(&self, tab_id: uuid::Uuid) {
+            lock_recovering(&self.events).push(format!("tab created {}", tab_id));
+        }
+
+        fn on_tab_closed(&self, tab_id: uuid::Uuid) {
+            lock_recovering(&self.events).push(format!("tab closed {}", tab_id));
+        }
+
+        fn on_navigation(&self, tab_id: uuid::Uuid, url: &Url) {
+            lock_recovering(&self.events).push(format!("navigation {} {}", tab_id, url));
+        }
+
+        fn on_download_progress(&self, download_id: uuid::Uuid, _progress: f32, status: &DownloadStatus) {
+            lock_recovering(&self.events).push(format!("download {} {:?}", download_id, status));
+        }
+    }
+
+    #[test]
+    fn test_listeners_see_tab_navigation_and_download_events_in_order() {
+        let server = MockServer::start(
+            vec![("/report.pdf", http_response("application/pdf", b"%PDF-1.7 report"))],
+            Duration::ZERO,
+        );
+        let download_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let browser = test_browser(BrowserConfig::default().with_download_path(download_dir.path().to_string_lossy()));
+        let first = Arc::new(RecordedEvents::default());
+        let second = Arc::new(RecordedEvents::default());
+        browser.observe(first.clone());
+        browser.observe(second.clone());
+
+        let tab_id = browser.create_new_tab(None).unwrap();
+        let page = Url::parse("https://example.com/reports").unwrap();
+        browser.navigate_to_url(page.clone()).unwrap();
+        let download_id = browser.start_download(server.url("/report.pdf")).unwrap();
+        Arc::clone(&browser.runtime).block_on(browser.run_download(download_id)).expect("Download failed");
+        browser.close_tab(tab_id).unwrap();
+
+        // The number of progress updates depends on how the body arrives, so compare them once each
+        let mut events = lock_recovering(&first.events).clone();
+        events.dedup();
+        assert_eq!(
+            events,
+            vec![
+                format!("tab created {}", tab_id),
+                format!("navigation {} {}", tab_id, page),
+                format!("download {} InProgress", download_id),
+                format!("download {} Completed", download_id),
+                format!("tab closed {}", tab_id),
+            ]
+        );
+        assert_eq!(*lock_recovering(&second.events), *lock_recovering(&first.events));
+    }
+}
@@ -0,0 +1,2641 @@
+// Browser Core for Aluminum Web Browser
+// This module owns the page a browsing context is showing: it fetches and parses
+// documents, runs their scripts against the DOM, answers selector queries and
+// lays the page out so callers such as the test runner can reason about where
+// elements are.
+
+use std::io::{self, Cursor};
+use std::iter::Peekable;
+use std::str::Chars;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use image::{ImageFormat, Rgba, RgbaImage};
+use log::{debug, warn};
+use reqwest::Client as HttpClient;
+use rquickjs::{CatchResultExt, Context, Ctx, Function, Runtime as ScriptRuntime, Value};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::utils::error::AluminumError;
+
+// Size of the page area every document is laid out in, in CSS pixels
+const VIEWPORT_WIDTH: f64 = 1280.0;
+const VIEWPORT_HEIGHT: f64 = 720.0;
+
+// There are no fonts, so every character and line of text is the same size
+const CHAR_WIDTH: f64 = 8.0;
+const LINE_HEIGHT: f64 = 18.0;
+
+const DEFAULT_BODY_MARGIN: f64 = 8.0;
+const FORM_CONTROL_WIDTH: f64 = 150.0;
+const DEFAULT_USER_AGENT: &str = "Aluminum/1.0 (https://aluminum.browser.org)";
+
+/// Elements that never have children or an end tag
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track", "wbr",
+];
+
+/// Elements whose content is kept as a single text node instead of being parsed as markup
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style", "title", "textarea"];
+
+/// Elements whose start tag implicitly closes an open sibling of the same kind
+const CLOSED_BY_SIBLING: &[&str] = &["li", "option", "p", "td", "th", "tr"];
+
+/// Elements that are never rendered
+const HIDDEN_ELEMENTS: &[&str] = &["head", "link", "meta", "noscript", "script", "style", "template", "title"];
+
+/// Elements laid out on a line with their siblings rather than as blocks
+const INLINE_ELEMENTS: &[&str] = &[
+    "a", "abbr", "b", "br", "button", "code", "em", "i", "img", "input", "label", "select", "small", "span",
+    "strong", "sub", "sup", "textarea",
+];
+
+/// Index of a node within its `Document`
+pub type NodeId = usize;
+
+/// What a DOM node holds
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeData {
+    Document,
+    Element {
+        tag: String,
+        attributes: Vec<(String, String)>,
+    },
+    Text(String),
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    data: NodeData,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+}
+
+/// A parsed HTML page, stored as an arena of nodes under `Document::ROOT`
+///
+/// Nodes removed from the tree stay in the arena but are no longer reachable
+/// from the root, so ids handed out earlier never point at a different node.
+#[derive(Debug, Clone)]
+pub struct Document {
+    nodes: Vec<Node>,
+}
+
+impl Document {
+    /// The document node every other node descends from
+    pub const ROOT: NodeId = 0;
+
+    /// Creates a document with no content, like `about:blank`
+    pub fn new() -> Self {
+        Document {
+            nodes: vec![Node {
+                data: NodeData::Document,
+                parent: None,
+                children: Vec::new(),
+            }],
+        }
+    }
+
+    /// Parses `html` leniently, the way browsers do: unclosed elements end with
+    /// their parent, stray end tags are ignored and comments are dropped
+    pub fn parse(html: &str) -> Self {
+        let mut document = Document::new();
+        let mut open = vec![Document::ROOT];
+        let mut rest = html;
+
+        while !rest.is_empty() {
+            let parent = *open.last().unwrap();
+            if let Some(after) = rest.strip_prefix("<!--") {
+                rest = after.find("-->").map_or("", |end| &after[end + 3..]);
+            } else if rest.starts_with("<!") || rest.starts_with("<?") {
+                rest = rest.find('>').map_or("", |end| &rest[end + 1..]);
+            } else if let Some(after) = rest.strip_prefix("</") {
+                let end = after.find('>').unwrap_or(after.len());
+                let tag = after[..end].trim().to_ascii_lowercase();
+                // Close everything opened inside the matching element, or nothing if none is open
+                if let Some(position) = open.iter().rposition(|&id| document.tag(id) == Some(tag.as_str())) {
+                    open.truncate(position);
+                }
+                rest = after.get(end + 1..).unwrap_or("");
+            } else if rest.starts_with('<') && rest[1..].starts_with(|c: char| c.is_ascii_alphabetic()) {
+                let (tag, attributes, self_closing, after) = parse_start_tag(&rest[1..]);
+                if CLOSED_BY_SIBLING.contains(&tag.as_str()) && document.tag(parent) == Some(tag.as_str()) {
+                    open.pop();
+                }
+                let element = document.push(
+                    *open.last().unwrap(),
+                    NodeData::Element {
+                        tag: tag.clone(),
+                        attributes,
+                    },
+                );
+                rest = after;
+
+                if RAW_TEXT_ELEMENTS.contains(&tag.as_str()) {
+                    let end = rest.to_ascii_lowercase().find(&format!("</{}", tag)).unwrap_or(rest.len());
+                    let raw = &rest[..end];
+                    if !raw.is_empty() {
+                        let text = match tag.as_str() {
+                            "script" | "style" => raw.to_string(),
+                            _ => decode_entities(raw),
+                        };
+                        document.push(element, NodeData::Text(text));
+                    }
+                    rest = &rest[end..];
+                    rest = rest.find('>').map_or("", |close| &rest[close + 1..]);
+                } else if !self_closing && !VOID_ELEMENTS.contains(&tag.as_str()) {
+                    open.push(element);
+                }
+            } else {
+                // Text runs to the next '<'; one that doesn't start a tag is kept as text
+                let end = rest
+                    .char_indices()
+                    .skip(1)
+                    .find(|&(_, c)| c == '<')
+                    .map_or(rest.len(), |(index, _)| index);
+                document.push(parent, NodeData::Text(decode_entities(&rest[..end])));
+                rest = &rest[end..];
+            }
+        }
+        document
+    }
+
+    /// Appends a new node as the last child of `parent`
+    fn push(&mut self, parent: NodeId, data: NodeData) -> NodeId {
+        let id = self.nodes.len();
+        self.nodes.push(Node {
+            data,
+            parent: Some(parent),
+            children: Vec::new(),
+        });
+        self.nodes[parent].children.push(id);
+        id
+    }
+
+    /// The node's data
+    pub fn data(&self, id: NodeId) -> &NodeData {
+        &self.nodes[id].data
+    }
+
+    /// The lowercase tag name of an element, or `None` for other nodes
+    pub fn tag(&self, id: NodeId) -> Option<&str> {
+        match &self.nodes[id].data {
+            NodeData::Element { tag, .. } => Some(tag),
+            _ => None,
+        }
+    }
+
+    /// The value of an element's attribute, if it is set
+    pub fn attribute(&self, id: NodeId, name: &str) -> Option<&str> {
+        match &self.nodes[id].data {
+            NodeData::Element { attributes, .. } => attributes
+                .iter()
+                .find(|(attribute, _)| attribute.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Whether `id` names a node of this document, attached or not
+    pub fn contains(&self, id: NodeId) -> bool {
+        id < self.nodes.len()
+    }
+
+    /// Sets an element's attribute, replacing any previous value; other nodes are left alone
+    pub fn set_attribute(&mut self, id: NodeId, name: &str, value: &str) {
+        if let NodeData::Element { attributes, .. } = &mut self.nodes[id].data {
+            match attributes.iter_mut().find(|(attribute, _)| attribute.eq_ignore_ascii_case(name)) {
+                Some((_, existing)) => *existing = value.to_string(),
+                None => attributes.push((name.to_ascii_lowercase(), value.to_string())),
+            }
+        }
+    }
+
+    /// Removes an element's attribute if it is set
+    pub fn remove_attribute(&mut self, id: NodeId, name: &str) {
+        if let NodeData::Element { attributes, .. } = &mut self.nodes[id].data {
+            attributes.retain(|(attribute, _)| !attribute.eq_ignore_ascii_case(name));
+        }
+    }
+
+    /// The node's parent, or `None` for the root and detached nodes
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.nodes[id].parent
+    }
+
+    /// Whether a form control is disabled, by its own `disabled` attribute or a disabled
+    /// `<fieldset>` around it
+    ///
+    /// Only controls that can be disabled report so; a `<div disabled>` is still enabled.
+    pub fn is_disabled(&self, id: NodeId) -> bool {
+        if !matches!(
+            self.tag(id),
+            Some("button" | "input" | "select" | "textarea" | "option" | "optgroup" | "fieldset")
+        ) {
+            return false;
+        }
+        if self.attribute(id, "disabled").is_some() {
+            return true;
+        }
+        let mut ancestor = self.parent(id);
+        while let Some(current) = ancestor {
+            if matches!(self.tag(current), Some("fieldset" | "select" | "optgroup"))
+                && self.attribute(current, "disabled").is_some()
+            {
+                return true;
+            }
+            ancestor = self.parent(current);
+        }
+        false
+    }
+
+    /// The node's children in document order
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        &self.nodes[id].children
+    }
+
+    /// Every node below `id` in document order, not including `id` itself
+    pub fn descendants(&self, id: NodeId) -> Vec<NodeId> {
+        let mut found = Vec::new();
+        let mut stack: Vec<NodeId> = self.nodes[id].children.iter().rev().copied().collect();
+        while let Some(next) = stack.pop() {
+            found.push(next);
+            stack.extend(self.nodes[next].children.iter().rev());
+        }
+        found
+    }
+
+    /// Number of nodes in the tree, including the document node
+    pub fn node_count(&self) -> usize {
+        self.descendants(Document::ROOT).len() + 1
+    }
+
+    /// The first element with the given tag in document order
+    pub fn find_element(&self, tag: &str) -> Option<NodeId> {
+        self.descendants(Document::ROOT)
+            .into_iter()
+            .find(|&id| self.tag(id) == Some(tag))
+    }
+
+    /// The concatenated text of the node and its descendants, like DOM `textContent`
+    pub fn text_content(&self, id: NodeId) -> String {
+        if let NodeData::Text(text) = &self.nodes[id].data {
+            return text.clone();
+        }
+        self.descendants(id)
+            .into_iter()
+            .filter_map(|node| match &self.nodes[node].data {
+                NodeData::Text(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Replaces the node's children with a single text node holding `text`
+    pub fn set_text_content(&mut self, id: NodeId, text: &str) {
+        for child in std::mem::take(&mut self.nodes[id].children) {
+            self.nodes[child].parent = None;
+        }
+        if !text.is_empty() {
+            self.push(id, NodeData::Text(text.to_string()));
+        }
+    }
+
+    /// Elements under `scope` matching `selector`, in document order
+    pub fn query_selector_all(&self, scope: NodeId, selector: &str) -> Result<Vec<NodeId>, AluminumError> {
+        let selector = Selector::parse(selector)?;
+        Ok(self
+            .descendants(scope)
+            .into_iter()
+            .filter(|&id| selector.matches(self, id))
+            .collect())
+    }
+}
+
+impl Default for Document {
+    fn default() -> Self {
+        Document::new()
+    }
+}
+
+/// Parses a start tag after its '<' into its name, attributes, whether it
+/// closed itself and the input following the tag
+fn parse_start_tag(input: &str) -> (String, Vec<(String, String)>, bool, &str) {
+    let name_end = input
+        .find(|c: char| c.is_ascii_whitespace() || c == '>' || c == '/')
+        .unwrap_or(input.len());
+    let tag = input[..name_end].to_ascii_lowercase();
+    let mut attributes: Vec<(String, String)> = Vec::new();
+    let mut rest = &input[name_end..];
+
+    loop {
+        rest = rest.trim_start();
+        if let Some(after) = rest.strip_prefix("/>") {
+            return (tag, attributes, true, after);
+        }
+        if let Some(after) = rest.strip_prefix('>') {
+            return (tag, attributes, false, after);
+        }
+        if rest.is_empty() {
+            return (tag, attributes, false, rest);
+        }
+        if let Some(after) = rest.strip_prefix('/') {
+            rest = after;
+            continue;
+        }
+
+        let name_end = rest
+            .find(|c: char| c.is_ascii_whitespace() || c == '=' || c == '>' || c == '/')
+            .unwrap_or(rest.len());
+        let name = rest[..name_end].to_ascii_lowercase();
+        rest = rest[name_end..].trim_start();
+
+        let mut value = String::new();
+        if let Some(after) = rest.strip_prefix('=') {
+            let after = after.trim_start();
+            let (raw, remaining) = match after.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let body = &after[1..];
+                    let end = body.find(quote).unwrap_or(body.len());
+                    (&body[..end], body.get(end + 1..).unwrap_or(""))
+                }
+                _ => {
+                    let end = after
+                        .find(|c: char| c.is_ascii_whitespace() || c == '>')
+                        .unwrap_or(after.len());
+                    (&after[..end], &after[end..])
+                }
+            };
+            value = decode_entities(raw);
+            rest = remaining;
+        }
+
+        // The first occurrence of a repeated attribute wins
+        if !name.is_empty() && !attributes.iter().any(|(existing, _)| *existing == name) {
+            attributes.push((name, value));
+        }
+    }
+}
+
+/// Replaces character references such as `&amp;` and `&#39;`; unknown ones are kept as written
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let name = rest.find(';').filter(|&end| end <= 10).map(|end| &rest[1..end]);
+        match name.and_then(decode_entity) {
+            Some(c) => {
+                decoded.push(c);
+                rest = &rest[name.unwrap().len() + 2..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+fn decode_entity(name: &str) -> Option<char> {
+    match name {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some('\u{a0}'),
+        _ => {
+            let code = name
+                .strip_prefix("#x")
+                .or_else(|| name.strip_prefix("#X"))
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| name.strip_prefix('#').map(str::parse::<u32>))?
+                .ok()?;
+            char::from_u32(code)
+        }
+    }
+}
+
+/// A parsed CSS selector list such as `ul > li.result, #title`
+///
+/// Supports type, universal, `#id`, `.class` and attribute selectors (`[a]`,
+/// `[a=v]`, `[a~=v]`, `[a^=v]`, `[a$=v]`, `[a*=v]`) joined by descendant and
+/// child combinators. Pseudo-classes and sibling combinators are rejected.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selector {
+    alternatives: Vec<Vec<(Combinator, Compound)>>,
+}
+
+/// How a compound selector relates to the one before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Combinator {
+    Descendant,
+    Child,
+}
+
+/// Simple selectors that must all match the same element
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Compound {
+    tag: Option<String>,
+    ids: Vec<String>,
+    classes: Vec<String>,
+    attributes: Vec<AttributeTest>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct AttributeTest {
+    name: String,
+    operator: Option<(AttributeOperator, String)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AttributeOperator {
+    Equals,
+    Includes,
+    Prefix,
+    Suffix,
+    Contains,
+}
+
+impl Selector {
+    /// Parses a selector list, failing with `InvalidParameter` on syntax it doesn't support
+    pub fn parse(source: &str) -> Result<Self, AluminumError> {
+        let invalid = || AluminumError::InvalidParameter(format!("Invalid selector '{}'", source));
+        let mut chars = source.chars().peekable();
+        let mut alternatives = Vec::new();
+        let mut steps = Vec::new();
+        let mut combinator = Combinator::Descendant;
+        let mut expecting_compound = true;
+
+        loop {
+            while chars.peek().map_or(false, |c| c.is_whitespace()) {
+                chars.next();
+            }
+            match chars.peek() {
+                None => break,
+                Some(',') | Some('>') if expecting_compound => return Err(invalid()),
+                Some(',') => {
+                    chars.next();
+                    alternatives.push(std::mem::take(&mut steps));
+                    combinator = Combinator::Descendant;
+                    expecting_compound = true;
+                }
+                Some('>') => {
+                    chars.next();
+                    combinator = Combinator::Child;
+                    expecting_compound = true;
+                }
+                Some(_) => {
+                    let compound = parse_compound(&mut chars).ok_or_else(invalid)?;
+                    steps.push((combinator, compound));
+                    combinator = Combinator::Descendant;
+                    expecting_compound = false;
+                }
+            }
+        }
+
+        if expecting_compound {
+            return Err(invalid());
+        }
+        alternatives.push(steps);
+        Ok(Selector { alternatives })
+    }
+
+    /// Whether the node is an element matched by any selector in the list
+    pub fn matches(&self, document: &Document, id: NodeId) -> bool {
+        self.alternatives.iter().any(|steps| matches_steps(document, id, steps))
+    }
+}
+
+/// Matches the last compound against `id` and the earlier ones against its ancestors
+fn matches_steps(document: &Document, id: NodeId, steps: &[(Combinator, Compound)]) -> bool {
+    let Some(((combinator, compound), earlier)) = steps.split_last() else {
+        return false;
+    };
+    if !compound.matches(document, id) {
+        return false;
+    }
+    if earlier.is_empty() {
+        return true;
+    }
+
+    match combinator {
+        Combinator::Child => document.parent(id).map_or(false, |parent| matches_steps(document, parent, earlier)),
+        Combinator::Descendant => {
+            let mut ancestor = document.parent(id);
+            while let Some(current) = ancestor {
+                if matches_steps(document, current, earlier) {
+                    return true;
+                }
+                ancestor = document.parent(current);
+            }
+            false
+        }
+    }
+}
+
+impl Compound {
+    fn matches(&self, document: &Document, id: NodeId) -> bool {
+        let Some(tag) = document.tag(id) else {
+            return false;
+        };
+        if self.tag.as_deref().map_or(false, |expected| expected != tag) {
+            return false;
+        }
+        if !self.ids.iter().all(|expected| document.attribute(id, "id") == Some(expected.as_str())) {
+            return false;
+        }
+        let classes = document.attribute(id, "class").unwrap_or("");
+        if !self.classes.iter().all(|expected| classes.split_whitespace().any(|class| class == expected)) {
+            return false;
+        }
+        self.attributes.iter().all(|test| test.matches(document.attribute(id, &test.name)))
+    }
+}
+
+impl AttributeTest {
+    fn matches(&self, value: Option<&str>) -> bool {
+        let Some(value) = value else {
+            return false;
+        };
+        match &self.operator {
+            None => true,
+            Some((AttributeOperator::Equals, expected)) => value == expected,
+            Some((AttributeOperator::Includes, expected)) => value.split_whitespace().any(|word| word == expected),
+            Some((AttributeOperator::Prefix, expected)) => !expected.is_empty() && value.starts_with(expected.as_str()),
+            Some((AttributeOperator::Suffix, expected)) => !expected.is_empty() && value.ends_with(expected.as_str()),
+            Some((AttributeOperator::Contains, expected)) => !expected.is_empty() && value.contains(expected.as_str()),
+        }
+    }
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_' || !c.is_ascii()
+}
+
+fn parse_identifier(chars: &mut Peekable<Chars>) -> Option<String> {
+    let mut identifier = String::new();
+    while let Some(&c) = chars.peek() {
+        if !is_identifier_char(c) {
+            break;
+        }
+        identifier.push(c);
+        chars.next();
+    }
+    (!identifier.is_empty()).then_some(identifier)
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while chars.peek().map_or(false, |c| c.is_whitespace()) {
+        chars.next();
+    }
+}
+
+/// Parses simple selectors up to the next combinator, comma or whitespace
+fn parse_compound(chars: &mut Peekable<Chars>) -> Option<Compound> {
+    let mut compound = Compound::default();
+    let mut empty = true;
+    while let Some(&c) = chars.peek() {
+        match c {
+            '*' if empty => {
+                chars.next();
+            }
+            '#' => {
+                chars.next();
+                compound.ids.push(parse_identifier(chars)?);
+            }
+            '.' => {
+                chars.next();
+                compound.classes.push(parse_identifier(chars)?);
+            }
+            '[' => {
+                chars.next();
+                compound.attributes.push(parse_attribute_test(chars)?);
+            }
+            c if is_identifier_char(c) && empty => {
+                compound.tag = Some(parse_identifier(chars)?.to_ascii_lowercase());
+            }
+            c if c.is_whitespace() || c == '>' || c == ',' => break,
+            _ => return None,
+        }
+        empty = false;
+    }
+    (!empty).then_some(compound)
+}
+
+/// Parses an attribute selector after its '['
+fn parse_attribute_test(chars: &mut Peekable<Chars>) -> Option<AttributeTest> {
+    skip_whitespace(chars);
+    let name = parse_identifier(chars)?.to_ascii_lowercase();
+    skip_whitespace(chars);
+    let operator = match chars.next()? {
+        ']' => return Some(AttributeTest { name, operator: None }),
+        '=' => AttributeOperator::Equals,
+        prefix @ ('~' | '^' | '$' | '*') => {
+            if chars.next()? != '=' {
+                return None;
+            }
+            match prefix {
+                '~' => AttributeOperator::Includes,
+                '^' => AttributeOperator::Prefix,
+                '$' => AttributeOperator::Suffix,
+                _ => AttributeOperator::Contains,
+            }
+        }
+        _ => return None,
+    };
+
+    skip_whitespace(chars);
+    let value = match *chars.peek()? {
+        quote @ ('"' | '\'') => {
+            chars.next();
+            let mut value = String::new();
+            loop {
+                match chars.next()? {
+                    c if c == quote => break,
+                    c => value.push(c),
+                }
+            }
+            value
+        }
+        _ => parse_identifier(chars)?,
+    };
+    skip_whitespace(chars);
+    (chars.next()? == ']').then_some(AttributeTest {
+        name,
+        operator: Some((operator, value)),
+    })
+}
+
+/// Position and size of a laid-out box, in CSS pixels from the top-left of the page
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Rect {
+    /// Whether `point` lies inside the box; the right and bottom edges are outside
+    pub fn contains(&self, point: Point) -> bool {
+        point.x >= self.x && point.x < self.x + self.width && point.y >= self.y && point.y < self.y + self.height
+    }
+}
+
+/// A position in CSS pixels from the top-left of the page
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// How an element takes part in layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Display {
+    Block,
+    Inline,
+    None,
+}
+
+/// Reads one property from an element's inline `style` attribute, lowercased
+fn inline_style(document: &Document, id: NodeId, property: &str) -> Option<String> {
+    document
+        .attribute(id, "style")?
+        .split(';')
+        .filter_map(|declaration| declaration.split_once(':'))
+        .filter(|(name, _)| name.trim().eq_ignore_ascii_case(property))
+        .last()
+        .map(|(_, value)| value.trim().to_ascii_lowercase())
+}
+
+/// Reads a pixel length such as `10px` from an element's inline style
+fn style_length(document: &Document, id: NodeId, property: &str) -> Option<f64> {
+    let value = inline_style(document, id, property)?;
+    value.strip_suffix("px").unwrap_or(&value).trim().parse().ok()
+}
+
+fn display(document: &Document, id: NodeId) -> Display {
+    if let Some(value) = inline_style(document, id, "display") {
+        return match value.as_str() {
+            "none" => Display::None,
+            "inline" | "inline-block" | "inline-flex" => Display::Inline,
+            _ => Display::Block,
+        };
+    }
+    let tag = document.tag(id).unwrap_or("");
+    if document.attribute(id, "hidden").is_some()
+        || HIDDEN_ELEMENTS.contains(&tag)
+        || (tag == "input" && document.attribute(id, "type").map_or(false, |t| t.eq_ignore_ascii_case("hidden")))
+    {
+        Display::None
+    } else if INLINE_ELEMENTS.contains(&tag) {
+        Display::Inline
+    } else {
+        Display::Block
+    }
+}
+
+/// Whether the element is taken out of the flow and placed by `left`/`top`
+fn is_positioned(document: &Document, id: NodeId) -> bool {
+    matches!(inline_style(document, id, "position").as_deref(), Some("absolute") | Some("fixed"))
+}
+
+/// Width of a run of text once each stretch of whitespace is collapsed to one space
+fn text_width(text: &str) -> f64 {
+    let mut characters = 0;
+    let mut previous_was_space = false;
+    for c in text.chars() {
+        let is_space = c.is_whitespace();
+        if !(is_space && previous_was_space) {
+            characters += 1;
+        }
+        previous_was_space = is_space;
+    }
+    characters as f64 * CHAR_WIDTH
+}
+
+/// Appends the rendered text under `id` to `lines`, one entry per block of text
+///
+/// Text on one line is built up in `line`; blocks and `<br>` end it. Nodes that
+/// aren't rendered, like scripts, styles and `display:none` subtrees, add nothing.
+fn collect_readable_text(document: &Document, id: NodeId, lines: &mut Vec<String>, line: &mut String) {
+    match document.data(id) {
+        NodeData::Text(text) => {
+            // Only whitespace in the source separates words, so <b>Al</b>uminum stays one word
+            let words = text.split_whitespace().collect::<Vec<_>>().join(" ");
+            if text.starts_with(char::is_whitespace) && !line.is_empty() && !line.ends_with(' ') {
+                line.push(' ');
+            }
+            line.push_str(&words);
+            if !words.is_empty() && text.ends_with(char::is_whitespace) {
+                line.push(' ');
+            }
+        }
+        NodeData::Document => {
+            for &child in document.children(id) {
+                collect_readable_text(document, child, lines, line);
+            }
+        }
+        NodeData::Element { tag, .. } => {
+            let display = display(document, id);
+            if display == Display::None || document.attribute(id, "aria-hidden") == Some("true") {
+                return;
+            }
+            if tag == "br" {
+                finish_line(lines, line);
+                return;
+            }
+            if display == Display::Block {
+                finish_line(lines, line);
+            }
+            for &child in document.children(id) {
+                collect_readable_text(document, child, lines, line);
+            }
+            if display == Display::Block {
+                finish_line(lines, line);
+            }
+        }
+    }
+}
+
+/// Moves the line being built into `lines`, dropping it if it holds only whitespace
+fn finish_line(lines: &mut Vec<String>, line: &mut String) {
+    let finished = line.trim();
+    if !finished.is_empty() {
+        lines.push(finished.to_string());
+    }
+    line.clear();
+}
+
+/// Boxes produced by a layout pass, in paint order
+///
+/// Parents come before their children and positioned elements after the
+/// normal flow, so later boxes are drawn on top of earlier ones.
+#[derive(Debug, Clone, Default)]
+pub struct LayoutTree {
+    boxes: Vec<(NodeId, Rect)>,
+}
+
+impl LayoutTree {
+    /// The box of an element, or `None` if it isn't rendered, e.g. under `display:none`
+    pub fn rect(&self, id: NodeId) -> Option<Rect> {
+        self.boxes.iter().find(|(node, _)| *node == id).map(|(_, rect)| *rect)
+    }
+
+    /// Every rendered element with its box, in paint order
+    pub fn boxes(&self) -> &[(NodeId, Rect)] {
+        &self.boxes
+    }
+
+    /// The element painted on top at `point`, if any box covers it
+    pub fn hit_test(&self, point: Point) -> Option<NodeId> {
+        self.boxes.iter().rev().find(|(_, rect)| rect.contains(point)).map(|(id, _)| *id)
+    }
+}
+
+/// Lays documents out as stacked blocks and lines of fixed-width text
+///
+/// There is no stylesheet cascade: only inline `style` attributes are read,
+/// and of those only `display`, `position`, `left`, `top`, `width`, `height`
+/// and a single-value `margin`. That is enough for tests to reason about
+/// whether and where an element is shown.
+#[derive(Debug, Clone)]
+pub struct RenderingEngine {
+    viewport: Rect,
+}
+
+impl RenderingEngine {
+    pub fn new() -> Self {
+        RenderingEngine {
+            viewport: Rect {
+                x: 0.0,
+                y: 0.0,
+                width: VIEWPORT_WIDTH,
+                height: VIEWPORT_HEIGHT,
+            },
+        }
+    }
+
+    /// The visible page area documents are laid out in
+    pub fn viewport(&self) -> Rect {
+        self.viewport
+    }
+
+    /// Computes a box for every rendered element of `dom`
+    pub fn layout(&self, dom: &Document) -> LayoutTree {
+        let mut pass = LayoutPass {
+            document: dom,
+            tree: LayoutTree::default(),
+            positioned: Vec::new(),
+        };
+        pass.layout_children(Document::ROOT, 0.0, 0.0, self.viewport.width);
+
+        // Positioned elements may contain positioned elements of their own
+        let mut next = 0;
+        while next < pass.positioned.len() {
+            pass.layout_positioned(pass.positioned[next]);
+            next += 1;
+        }
+        pass.tree
+    }
+}
+
+impl Default for RenderingEngine {
+    fn default() -> Self {
+        RenderingEngine::new()
+    }
+}
+
+struct LayoutPass<'a> {
+    document: &'a Document,
+    tree: LayoutTree,
+    /// Positioned elements found in the flow, laid out once it is done
+    positioned: Vec<NodeId>,
+}
+
+impl LayoutPass<'_> {
+    /// Reserves a box for `id` so it paints before its children; returns its index
+    fn open_box(&mut self, id: NodeId) -> usize {
+        self.tree.boxes.push((id, Rect::default()));
+        self.tree.boxes.len() - 1
+    }
+
+    /// Lays out a block at (x, y) in `width` and returns the height it takes, margins included
+    fn layout_block(&mut self, id: NodeId, x: f64, y: f64, width: f64) -> f64 {
+        let default_margin = if self.document.tag(id) == Some("body") { DEFAULT_BODY_MARGIN } else { 0.0 };
+        let margin = style_length(self.document, id, "margin").unwrap_or(default_margin);
+        let width = style_length(self.document, id, "width").unwrap_or((width - 2.0 * margin).max(0.0));
+
+        let slot = self.open_box(id);
+        let content_height = self.layout_children(id, x + margin, y + margin, width);
+        let height = style_length(self.document, id, "height").unwrap_or(content_height);
+        self.tree.boxes[slot].1 = Rect {
+            x: x + margin,
+            y: y + margin,
+            width,
+            height,
+        };
+        height + 2.0 * margin
+    }
+
+    /// Lays out the children of `parent` in a content box at (x, y) and returns the height they take
+    ///
+    /// Text and inline elements fill lines left to right, starting a new line
+    /// when the next piece doesn't fit; blocks always start on a line of their own.
+    fn layout_children(&mut self, parent: NodeId, x: f64, y: f64, width: f64) -> f64 {
+        let document = self.document;
+        let mut cursor_y = y;
+        let mut line_x = x;
+        let mut line_open = false;
+
+        for &child in document.children(parent) {
+            let piece_width = match document.data(child) {
+                // Whitespace between blocks or at the start of a line takes no room
+                NodeData::Text(text) if !line_open && text.trim().is_empty() => continue,
+                NodeData::Text(text) => text_width(text),
+                NodeData::Element { .. } if is_positioned(document, child) => {
+                    if display(document, child) != Display::None {
+                        self.positioned.push(child);
+                    }
+                    continue;
+                }
+                NodeData::Element { .. } => match display(document, child) {
+                    Display::None => continue,
+                    Display::Inline => self.inline_width(child),
+                    Display::Block => {
+                        if line_open {
+                            cursor_y += LINE_HEIGHT;
+                            line_x = x;
+                            line_open = false;
+                        }
+                        cursor_y += self.layout_block(child, x, cursor_y, width);
+                        continue;
+                    }
+                },
+                NodeData::Document => continue,
+            };
+
+            if line_open && line_x + piece_width > x + width {
+                cursor_y += LINE_HEIGHT;
+                line_x = x;
+            }
+            if let NodeData::Element { .. } = document.data(child) {
+                self.layout_inline(child, line_x, cursor_y);
+            }
+            line_x += piece_width;
+            line_open = true;
+        }
+
+        if line_open {
+            cursor_y += LINE_HEIGHT;
+        }
+        cursor_y - y
+    }
+
+    /// Width an inline element takes up on its line
+    fn inline_width(&self, id: NodeId) -> f64 {
+        let intrinsic = match self.document.tag(id).unwrap_or("") {
+            "input" | "select" | "textarea" => FORM_CONTROL_WIDTH,
+            "img" => self.document.attribute(id, "width").and_then(|w| w.parse().ok()).unwrap_or(0.0),
+            "br" => 0.0,
+            _ => self
+                .document
+                .children(id)
+                .iter()
+                .map(|&child| match self.document.data(child) {
+                    NodeData::Text(text) => text_width(text),
+                    NodeData::Element { .. }
+                        if !is_positioned(self.document, child) && display(self.document, child) != Display::None =>
+                    {
+                        self.inline_width(child)
+                    }
+                    _ => 0.0,
+                })
+                .sum(),
+        };
+        style_length(self.document, id, "width").unwrap_or(intrinsic)
+    }
+
+    /// Lays out an inline element and its descendants on a single line starting at (x, y)
+    fn layout_inline(&mut self, id: NodeId, x: f64, y: f64) {
+        let image_height = match self.document.tag(id) {
+            Some("img") => self.document.attribute(id, "height").and_then(|h| h.parse().ok()),
+            _ => None,
+        };
+        let height = style_length(self.document, id, "height")
+            .or(image_height)
+            .unwrap_or(LINE_HEIGHT);
+        self.tree.boxes.push((
+            id,
+            Rect {
+                x,
+                y,
+                width: self.inline_width(id),
+                height,
+            },
+        ));
+
+        let document = self.document;
+        let mut cursor_x = x;
+        for &child in document.children(id) {
+            match document.data(child) {
+                NodeData::Text(text) => cursor_x += text_width(text),
+                NodeData::Element { .. } if is_positioned(document, child) => {
+                    if display(document, child) != Display::None {
+                        self.positioned.push(child);
+                    }
+                }
+                NodeData::Element { .. } if display(document, child) != Display::None => {
+                    self.layout_inline(child, cursor_x, y);
+                    cursor_x += self.inline_width(child);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Lays out a positioned element at its `left`/`top` offsets from the page origin,
+    /// as wide as its content unless it sets a width
+    fn layout_positioned(&mut self, id: NodeId) {
+        let x = style_length(self.document, id, "left").unwrap_or(0.0);
+        let y = style_length(self.document, id, "top").unwrap_or(0.0);
+        let width = self.inline_width(id);
+
+        let slot = self.open_box(id);
+        let content_height = self.layout_children(id, x, y, width);
+        let height = style_length(self.document, id, "height").unwrap_or(content_height);
+        self.tree.boxes[slot].1 = Rect { x, y, width, height };
+    }
+}
+
+/// Script run in every page before its own scripts, giving them `document`,
+/// `window`, `console` and timers on top of the `__aluminum_*` natives
+const DOM_SHIM: &str = r#"
+(() => {
+  const report = (run) => {
+    try {
+      return run();
+    } catch (error) {
+      console.error(`Uncaught ${error}`);
+    }
+  };
+
+  const format = (values) =>
+    values
+      .map((value) => {
+        if (typeof value === 'string' || value instanceof Error) return String(value);
+        try {
+          return JSON.stringify(value) ?? String(value);
+        } catch (_) {
+          return String(value);
+        }
+      })
+      .join(' ');
+  globalThis.console = {};
+  for (const level of ['log', 'info', 'debug', 'warn', 'error']) {
+    console[level] = (...values) => __aluminum_console(level, format(values));
+  }
+
+  const query = (scope, selector) => {
+    const ids = __aluminum_query(scope, String(selector));
+    if (ids === null || ids === undefined) throw new SyntaxError(`'${selector}' is not a valid selector`);
+    return ids;
+  };
+
+  const handlers = new Map();
+  const listeners = (id, type) => {
+    const key = `${id}:${type}`;
+    if (!handlers.has(key)) handlers.set(key, []);
+    return handlers.get(key);
+  };
+
+  const elements = new Map();
+  const wrap = (id) => {
+    if (id === null || id === undefined) return null;
+    if (!elements.has(id)) elements.set(id, new Element(id));
+    return elements.get(id);
+  };
+
+  class Element {
+    constructor(id) {
+      this.__id = id;
+    }
+    get tagName() {
+      return (__aluminum_tag(this.__id) ?? '').toUpperCase();
+    }
+    get id() {
+      return this.getAttribute('id') ?? '';
+    }
+    get className() {
+      return this.getAttribute('class') ?? '';
+    }
+    get textContent() {
+      return __aluminum_text(this.__id);
+    }
+    set textContent(text) {
+      __aluminum_set_text(this.__id, String(text));
+    }
+    get innerText() {
+      return this.textContent;
+    }
+    set innerText(text) {
+      this.textContent = text;
+    }
+    get value() {
+      return this.tagName === 'TEXTAREA' ? this.textContent : this.getAttribute('value') ?? '';
+    }
+    set value(value) {
+      if (this.tagName === 'TEXTAREA') this.textContent = value;
+      else this.setAttribute('value', value);
+    }
+    get disabled() {
+      return this.hasAttribute('disabled');
+    }
+    set disabled(disabled) {
+      if (disabled) this.setAttribute('disabled', '');
+      else this.removeAttribute('disabled');
+    }
+    get parentElement() {
+      return wrap(__aluminum_parent(this.__id));
+    }
+    getAttribute(name) {
+      return __aluminum_attribute(this.__id, String(name)) ?? null;
+    }
+    hasAttribute(name) {
+      return this.getAttribute(name) !== null;
+    }
+    setAttribute(name, value) {
+      __aluminum_set_attribute(this.__id, String(name), String(value));
+    }
+    removeAttribute(name) {
+      __aluminum_remove_attribute(this.__id, String(name));
+    }
+    querySelector(selector) {
+      return wrap(query(this.__id, selector)[0]);
+    }
+    querySelectorAll(selector) {
+      return query(this.__id, selector).map(wrap);
+    }
+    addEventListener(type, listener) {
+      listeners(this.__id, type).push(listener);
+    }
+    removeEventListener(type, listener) {
+      const list = listeners(this.__id, type);
+      const index = list.indexOf(listener);
+      if (index >= 0) list.splice(index, 1);
+    }
+    focus() {
+      __aluminum_focus(this.__id);
+    }
+    click() {
+      __aluminum_dispatch(this.__id, 'click');
+    }
+  }
+  globalThis.Element = Element;
+
+  globalThis.window = globalThis;
+  globalThis.document = {
+    get documentElement() {
+      return wrap(query(null, 'html')[0]);
+    },
+    get head() {
+      return wrap(query(null, 'head')[0]);
+    },
+    get body() {
+      return wrap(query(null, 'body')[0]);
+    },
+    get title() {
+      const title = query(null, 'title')[0];
+      return title === undefined ? '' : __aluminum_text(title).trim();
+    },
+    get activeElement() {
+      return wrap(__aluminum_focused()) ?? this.body;
+    },
+    getElementById(id) {
+      return wrap(__aluminum_element_by_id(String(id)));
+    },
+    querySelector(selector) {
+      return wrap(query(null, selector)[0]);
+    },
+    querySelectorAll(selector) {
+      return query(null, selector).map(wrap);
+    },
+  };
+
+  // Runs inline `on<type>` handlers and listeners from the target up to the
+  // root; returns false if any of them cancelled the event
+  globalThis.__aluminum_dispatch = (id, type, key) => {
+    const target = wrap(id);
+    const event = {
+      type,
+      key,
+      target,
+      currentTarget: target,
+      defaultPrevented: false,
+      cancelBubble: false,
+      preventDefault() {
+        this.defaultPrevented = true;
+      },
+      stopPropagation() {
+        this.cancelBubble = true;
+      },
+    };
+    for (let node = target; node !== null && !event.cancelBubble; node = node.parentElement) {
+      event.currentTarget = node;
+      const inline = node.getAttribute(`on${type}`);
+      if (inline !== null) {
+        report(() => {
+          if (new Function('event', inline).call(node, event) === false) event.preventDefault();
+        });
+      }
+      for (const listener of listeners(node.__id, type).slice()) {
+        report(() => listener.call(node, event));
+      }
+    }
+    return !event.defaultPrevented;
+  };
+
+  const timers = [];
+  let nextTimerId = 1;
+  globalThis.setTimeout = (callback, delay, ...args) => {
+    const id = nextTimerId++;
+    timers.push({ id, due: Date.now() + Math.max(0, Number(delay) || 0), callback, args });
+    return id;
+  };
+  globalThis.clearTimeout = (id) => {
+    const index = timers.findIndex((timer) => timer.id === id);
+    if (index >= 0) timers.splice(index, 1);
+  };
+
+  // Runs every timer that is due, earliest first; timers they schedule wait for the next call
+  globalThis.__aluminum_run_timers = () => {
+    const now = Date.now();
+    const limit = nextTimerId;
+    for (;;) {
+      let next = -1;
+      timers.forEach((timer, index) => {
+        if (timer.id < limit && timer.due <= now && (next < 0 || timer.due < timers[next].due)) next = index;
+      });
+      if (next < 0) return;
+      const [timer] = timers.splice(next, 1);
+      report(() =>
+        typeof timer.callback === 'function' ? timer.callback(...timer.args) : (0, eval)(String(timer.callback)),
+      );
+    }
+  };
+
+  // Runs a page's own script; like a browser, an uncaught error is logged rather than returned
+  globalThis.__aluminum_run = (source) => {
+    report(() => (0, eval)(source));
+  };
+})();
+"#;
+
+/// The document a core is showing and the URL it came from
+struct Page {
+    url: Url,
+    document: Document,
+    /// The element keyboard input goes to, if any has been focused
+    focused: Option<NodeId>,
+    /// `console.error` output and uncaught exceptions not yet taken by the caller
+    console_errors: Vec<String>,
+}
+
+impl Page {
+    fn blank() -> Self {
+        Page {
+            url: Url::parse("about:blank").unwrap(),
+            document: Document::new(),
+            focused: None,
+            console_errors: Vec::new(),
+        }
+    }
+
+    /// Converts an id received from a script, which may be anything, into a node of this page
+    fn node(&self, id: u32) -> Option<NodeId> {
+        let id = id as NodeId;
+        self.document.contains(id).then_some(id)
+    }
+
+    /// The element that receives events when nothing is focused
+    fn default_target(&self) -> Option<NodeId> {
+        ["body", "html"]
+            .iter()
+            .find_map(|tag| self.document.find_element(tag))
+    }
+
+    /// The form an element is inside, if any
+    fn form_of(&self, element: NodeId) -> Option<NodeId> {
+        let mut ancestor = self.document.parent(element);
+        while let Some(id) = ancestor {
+            if self.document.tag(id) == Some("form") {
+                return Some(id);
+            }
+            ancestor = self.document.parent(id);
+        }
+        None
+    }
+
+    /// The form Enter submits from `field`, if it is a text field inside one
+    fn enclosing_form(&self, field: NodeId) -> Option<NodeId> {
+        match self.document.tag(field) {
+            Some("input") | Some("textarea") => self.form_of(field),
+            _ => None,
+        }
+    }
+
+    /// The name/value pairs a form submits, in document order
+    fn form_fields(&self, form: NodeId) -> Vec<(String, String)> {
+        let document = &self.document;
+        document
+            .descendants(form)
+            .into_iter()
+            .filter(|&id| !document.is_disabled(id))
+            .filter_map(|id| {
+                let name = document.attribute(id, "name")?.to_string();
+                match document.tag(id)? {
+                    "textarea" => Some((name, document.text_content(id))),
+                    "input" => {
+                        let kind = document.attribute(id, "type").unwrap_or("text").to_ascii_lowercase();
+                        let skipped = matches!(kind.as_str(), "submit" | "button" | "reset" | "file" | "image");
+                        let unchecked = matches!(kind.as_str(), "checkbox" | "radio")
+                            && document.attribute(id, "checked").is_none();
+                        let default = if kind == "checkbox" || kind == "radio" { "on" } else { "" };
+                        (!skipped && !unchecked)
+                            .then(|| (name, document.attribute(id, "value").unwrap_or(default).to_string()))
+                    }
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    /// The next element in document order that Tab can focus, wrapping around at the end
+    fn next_focusable(&self, after: Option<NodeId>) -> Option<NodeId> {
+        let document = &self.document;
+        let focusable: Vec<NodeId> = document
+            .descendants(Document::ROOT)
+            .into_iter()
+            .filter(|&id| {
+                let enabled = !document.is_disabled(id);
+                match document.tag(id) {
+                    Some("a") => document.attribute(id, "href").is_some(),
+                    Some("button") | Some("select") | Some("textarea") => enabled,
+                    Some("input") => enabled && display(document, id) != Display::None,
+                    Some(_) => document.attribute(id, "tabindex").is_some(),
+                    None => false,
+                }
+            })
+            .collect();
+        let position = after.and_then(|current| focusable.iter().position(|&id| id == current));
+        match position {
+            Some(index) => focusable.get(index + 1).or(focusable.first()).copied(),
+            None => focusable.first().copied(),
+        }
+    }
+}
+
+/// Locks a page, carrying on with the data even if a script callback panicked while holding it
+fn lock_page(page: &Mutex<Page>) -> MutexGuard<'_, Page> {
+    page.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn script_error(error: impl std::fmt::Display) -> AluminumError {
+    AluminumError::ScriptError(error.to_string())
+}
+
+/// The JavaScript side of a page: a QuickJS context whose DOM natives read
+/// and write the page they were created for
+///
+/// Each navigation gets a fresh context, so globals never leak between pages.
+struct PageScripts {
+    context: Context,
+    _runtime: ScriptRuntime,
+}
+
+impl PageScripts {
+    fn new(page: &Arc<Mutex<Page>>) -> Result<Self, AluminumError> {
+        let runtime = ScriptRuntime::new().map_err(script_error)?;
+        let context = Context::full(&runtime).map_err(script_error)?;
+        context
+            .with(|ctx| -> rquickjs::Result<()> {
+                install_natives(&ctx, page)?;
+                ctx.eval::<(), _>(DOM_SHIM)
+            })
+            .map_err(script_error)?;
+        Ok(PageScripts {
+            context,
+            _runtime: runtime,
+        })
+    }
+
+    /// Evaluates `source` and returns its result as `String(result)` would,
+    /// or the message of the error it threw
+    fn evaluate(&self, source: &str) -> Result<String, AluminumError> {
+        self.context.with(|ctx| {
+            let value: Value = ctx.eval(source).catch(&ctx).map_err(script_error)?;
+            let stringify: Function = ctx.globals().get("String").map_err(script_error)?;
+            stringify.call((value,)).map_err(script_error)
+        })
+    }
+
+    /// Calls one of the shim's global functions
+    fn call<A, R>(&self, name: &str, args: A) -> Result<R, AluminumError>
+    where
+        A: for<'js> rquickjs::function::IntoArgs<'js>,
+        R: for<'js> rquickjs::FromJs<'js>,
+    {
+        self.context.with(|ctx| {
+            let function: Function = ctx.globals().get(name)?;
+            function.call(args)
+        })
+        .map_err(script_error)
+    }
+
+    /// Runs a script the page itself contains
+    fn run(&self, source: &str) -> Result<(), AluminumError> {
+        self.call("__aluminum_run", (source,))
+    }
+
+    /// Runs the timers that have come due since the last call
+    fn run_due_timers(&self) -> Result<(), AluminumError> {
+        self.call("__aluminum_run_timers", ())
+    }
+
+    /// Fires an event at `target`, returning false if a handler cancelled it
+    fn dispatch(&self, target: NodeId, event_type: &str, key: Option<&str>) -> Result<bool, AluminumError> {
+        self.call("__aluminum_dispatch", (target as u32, event_type, key))
+    }
+}
+
+/// Defines the `__aluminum_*` functions the DOM shim is built on
+///
+/// Natives never panic on bad input: unknown node ids read as empty and
+/// writes to them are ignored.
+fn install_natives(ctx: &Ctx<'_>, page: &Arc<Mutex<Page>>) -> rquickjs::Result<()> {
+    let globals = ctx.globals();
+
+    let dom = Arc::clone(page);
+    globals.set(
+        "__aluminum_query",
+        Function::new(ctx.clone(), move |scope: Option<u32>, selector: String| {
+            let page = lock_page(&dom);
+            let scope = match scope {
+                Some(id) => page.node(id)?,
+                None => Document::ROOT,
+            };
+            let ids = page.document.query_selector_all(scope, &selector).ok()?;
+            Some(ids.into_iter().map(|id| id as u32).collect::<Vec<u32>>())
+        })?,
+    )?;
+
+    let dom = Arc::clone(page);
+    globals.set(
+        "__aluminum_element_by_id",
+        Function::new(ctx.clone(), move |wanted: String| {
+            let page = lock_page(&dom);
+            let document = &page.document;
+            document
+                .descendants(Document::ROOT)
+                .into_iter()
+                .find(|&id| document.attribute(id, "id") == Some(wanted.as_str()))
+                .map(|id| id as u32)
+        })?,
+    )?;
+
+    let dom = Arc::clone(page);
+    globals.set(
+        "__aluminum_tag",
+        Function::new(ctx.clone(), move |id: u32| {
+            let page = lock_page(&dom);
+            page.node(id).and_then(|id| page.document.tag(id)).map(str::to_string)
+        })?,
+    )?;
+
+    let dom = Arc::clone(page);
+    globals.set(
+        "__aluminum_parent",
+        Function::new(ctx.clone(), move |id: u32| {
+            let page = lock_page(&dom);
+            let parent = page.document.parent(page.node(id)?)?;
+            page.document.tag(parent).map(|_| parent as u32)
+        })?,
+    )?;
+
+    let dom = Arc::clone(page);
+    globals.set(
+        "__aluminum_attribute",
+        Function::new(ctx.clone(), move |id: u32, name: String| {
+            let page = lock_page(&dom);
+            page.node(id)
+                .and_then(|id| page.document.attribute(id, &name))
+                .map(str::to_string)
+        })?,
+    )?;
+
+    let dom = Arc::clone(page);
+    globals.set(
+        "__aluminum_set_attribute",
+        Function::new(ctx.clone(), move |id: u32, name: String, value: String| {
+            let mut page = lock_page(&dom);
+            if let Some(id) = page.node(id) {
+                page.document.set_attribute(id, &name, &value);
+            }
+        })?,
+    )?;
+
+    let dom = Arc::clone(page);
+    globals.set(
+        "__aluminum_remove_attribute",
+        Function::new(ctx.clone(), move |id: u32, name: String| {
+            let mut page = lock_page(&dom);
+            if let Some(id) = page.node(id) {
+                page.document.remove_attribute(id, &name);
+            }
+        })?,
+    )?;
+
+    let dom = Arc::clone(page);
+    globals.set(
+        "__aluminum_text",
+        Function::new(ctx.clone(), move |id: u32| {
+            let page = lock_page(&dom);
+            page.node(id).map(|id| page.document.text_content(id)).unwrap_or_default()
+        })?,
+    )?;
+
+    let dom = Arc::clone(page);
+    globals.set(
+        "__aluminum_set_text",
+        Function::new(ctx.clone(), move |id: u32, text: String| {
+            let mut page = lock_page(&dom);
+            if let Some(id) = page.node(id) {
+                page.document.set_text_content(id, &text);
+            }
+        })?,
+    )?;
+
+    let dom = Arc::clone(page);
+    globals.set(
+        "__aluminum_focus",
+        Function::new(ctx.clone(), move |id: u32| {
+            let mut page = lock_page(&dom);
+            if let Some(id) = page.node(id) {
+                page.focused = Some(id);
+            }
+        })?,
+    )?;
+
+    let dom = Arc::clone(page);
+    globals.set(
+        "__aluminum_focused",
+        Function::new(ctx.clone(), move || lock_page(&dom).focused.map(|id| id as u32))?,
+    )?;
+
+    let dom = Arc::clone(page);
+    globals.set(
+        "__aluminum_console",
+        Function::new(ctx.clone(), move |level: String, message: String| match level.as_str() {
+            "error" => {
+                warn!("console.error: {}", message);
+                lock_page(&dom).console_errors.push(message);
+            }
+            "warn" => warn!("console.warn: {}", message),
+            _ => debug!("console.{}: {}", level, message),
+        })?,
+    )?;
+
+    Ok(())
+}
+
+/// Draws a box's outline, filling it too for form controls; parts outside the image are clipped
+fn paint_box(image: &mut RgbaImage, rect: Rect, filled: bool) {
+    const OUTLINE: Rgba<u8> = Rgba([160, 160, 160, 255]);
+    const FILL: Rgba<u8> = Rgba([225, 225, 225, 255]);
+
+    let (width, height) = (image.width() as f64, image.height() as f64);
+    let left = rect.x.clamp(0.0, width) as u32;
+    let top = rect.y.clamp(0.0, height) as u32;
+    let right = (rect.x + rect.width).clamp(0.0, width) as u32;
+    let bottom = (rect.y + rect.height).clamp(0.0, height) as u32;
+    for y in top..bottom {
+        for x in left..right {
+            let edge = x == left || y == top || x + 1 == right || y + 1 == bottom;
+            if edge {
+                image.put_pixel(x, y, OUTLINE);
+            } else if filled {
+                image.put_pixel(x, y, FILL);
+            }
+        }
+    }
+}
+
+/// Restricts which URLs may be navigated to, for kiosk and parental-control modes
+///
+/// Host patterns are either an exact host (`example.com`) or a wildcard
+/// (`*.example.com`) matching the domain and all of its subdomains. Blocked
+/// hosts always lose; when any host is allowed, every other host is refused.
+/// An empty scheme list allows every scheme.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UrlPolicy {
+    #[serde(default)]
+    allowed_hosts: Vec<String>,
+    #[serde(default)]
+    blocked_hosts: Vec<String>,
+    #[serde(default)]
+    allowed_schemes: Vec<String>,
+}
+
+impl UrlPolicy {
+    /// Creates a policy that allows every URL
+    pub fn new() -> Self {
+        UrlPolicy::default()
+    }
+
+    /// Allows a host pattern; once any host is allowed, unlisted hosts are refused
+    pub fn allow_host(mut self, pattern: &str) -> Self {
+        self.allowed_hosts.push(pattern.to_lowercase());
+        self
+    }
+
+    /// Refuses a host pattern, even if it is also allowed
+    pub fn block_host(mut self, pattern: &str) -> Self {
+        self.blocked_hosts.push(pattern.to_lowercase());
+        self
+    }
+
+    /// Allows a scheme such as `https`; once any scheme is allowed, others are refused
+    pub fn allow_scheme(mut self, scheme: &str) -> Self {
+        self.allowed_schemes.push(scheme.to_lowercase());
+        self
+    }
+
+    /// Returns `AluminumError::BlockedByPolicy` if the policy refuses `url`
+    pub fn check(&self, url: &Url) -> Result<(), AluminumError> {
+        if !self.allowed_schemes.is_empty() && !self.allowed_schemes.iter().any(|s| s == url.scheme()) {
+            return Err(AluminumError::BlockedByPolicy(format!(
+                "Scheme '{}' is not allowed for {}",
+                url.scheme(),
+                url
+            )));
+        }
+
+        let host = url.host_str().unwrap_or("").to_lowercase();
+        if self.blocked_hosts.iter().any(|pattern| host_matches(pattern, &host)) {
+            return Err(AluminumError::BlockedByPolicy(format!("Host '{}' is blocked", host)));
+        }
+        if !self.allowed_hosts.is_empty() && !self.allowed_hosts.iter().any(|pattern| host_matches(pattern, &host)) {
+            return Err(AluminumError::BlockedByPolicy(format!("Host '{}' is not on the allowlist", host)));
+        }
+        Ok(())
+    }
+}
+
+/// Whether `host` matches an exact host or `*.domain` pattern
+fn host_matches(pattern: &str, host: &str) -> bool {
+    match pattern.strip_prefix("*.") {
+        Some(domain) => host == domain || host.ends_with(&format!(".{}", domain)),
+        None => host == pattern,
+    }
+}
+
+/// How much of the rendering pipeline a `BrowserCore` runs
+///
+/// Both backends parse, script and lay out pages identically; only `Full`
+/// paints, so only it can take screenshots.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EngineBackend {
+    #[default]
+    Full,
+    Headless,
+}
+
+/// Refers to an element of the page a `BrowserCore` is showing
+///
+/// Handles are only meaningful for the page they were found on; navigating
+/// replaces the document they point into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ElementHandle(NodeId);
+
+impl ElementHandle {
+    /// The element's node in the page's `Document`
+    pub fn node_id(&self) -> NodeId {
+        self.0
+    }
+}
+
+/// The state of a core's page at one moment, for failure reports
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CoreSnapshot {
+    pub url: String,
+    /// The `<title>` text with whitespace collapsed, or empty if there is none
+    pub title: String,
+    pub dom_node_count: usize,
+    /// Console errors logged by the page and not yet taken
+    pub console_errors: Vec<String>,
+}
+
+/// What a request interceptor decides for an outgoing request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RequestAction {
+    /// Send the request unchanged
+    Allow,
+    /// Refuse the request with `AluminumError::BlockedByInterceptor`
+    Block,
+    /// Fetch this URL instead; the interceptor isn't consulted again for it
+    Redirect(Url),
+}
+
+type RequestInterceptor = Box<dyn Fn(&Url) -> RequestAction + Send + Sync>;
+
+/// Counts a request as in flight until dropped, so a cancelled fetch still stops counting
+struct InFlight<'a>(&'a AtomicUsize);
+
+impl<'a> InFlight<'a> {
+    fn start(counter: &'a AtomicUsize) -> Self {
+        counter.fetch_add(1, Ordering::SeqCst);
+        InFlight(counter)
+    }
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Where a page script's source comes from
+enum ScriptSource {
+    Inline(String),
+    External(Url),
+}
+
+/// A single browsing context: it shows one page at a time, runs its scripts
+/// and lays it out on demand
+pub struct BrowserCore {
+    backend: EngineBackend,
+    engine: RenderingEngine,
+    http_client: HttpClient,
+    user_agent: String,
+    page: Arc<Mutex<Page>>,
+    scripts: PageScripts,
+    interceptor: Option<RequestInterceptor>,
+    url_policy: Option<UrlPolicy>,
+    in_flight: AtomicUsize,
+    requests_started: AtomicU64,
+}
+
+impl BrowserCore {
+    /// Creates a core showing an empty page, with the full rendering backend
+    pub fn new() -> Self {
+        Self::new_with_backend(EngineBackend::Full)
+    }
+
+    /// Creates a core showing an empty page that renders with `backend`
+    pub fn new_with_backend(backend: EngineBackend) -> Self {
+        let page = Arc::new(Mutex::new(Page::blank()));
+        let scripts = PageScripts::new(&page).expect("Failed to start the JavaScript engine");
+        BrowserCore {
+            backend,
+            engine: RenderingEngine::new(),
+            http_client: HttpClient::new(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            page,
+            scripts,
+            interceptor: None,
+            url_policy: None,
+            in_flight: AtomicUsize::new(0),
+            requests_started: AtomicU64::new(0),
+        }
+    }
+
+    /// The rendering backend this core was created with
+    pub fn backend(&self) -> EngineBackend {
+        self.backend
+    }
+
+    fn page(&self) -> MutexGuard<'_, Page> {
+        lock_page(&self.page)
+    }
+
+    /// Returns the core to the state of a new one, so a pooled core can serve another test case
+    ///
+    /// The page, its scripts, the user agent, the interceptor and the URL policy
+    /// are all dropped; the request counters and the backend are kept.
+    pub fn reset(&mut self) {
+        self.page = Arc::new(Mutex::new(Page::blank()));
+        self.scripts = PageScripts::new(&self.page).expect("Failed to start the JavaScript engine");
+        self.user_agent = DEFAULT_USER_AGENT.to_string();
+        self.interceptor = None;
+        self.url_policy = None;
+    }
+
+    /// Sets the `User-Agent` header sent with every later navigation and subresource request
+    pub fn set_user_agent(&mut self, user_agent: &str) {
+        self.user_agent = user_agent.to_string();
+    }
+
+    /// Installs a hook consulted before every navigation and subresource request
+    ///
+    /// Replaces any previously installed interceptor.
+    pub fn set_request_interceptor<F>(&mut self, interceptor: F)
+    where
+        F: Fn(&Url) -> RequestAction + Send + Sync + 'static,
+    {
+        self.interceptor = Some(Box::new(interceptor));
+    }
+
+    /// Restricts where this core may navigate; `load_url` refuses anything the policy blocks
+    ///
+    /// Subresources aren't navigations and aren't checked.
+    pub fn set_url_policy(&mut self, policy: UrlPolicy) {
+        self.url_policy = Some(policy);
+    }
+
+    /// Requests sent but not yet finished
+    pub fn in_flight_requests(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Requests sent since the core was created, including failed ones
+    pub fn requests_started(&self) -> u64 {
+        self.requests_started.load(Ordering::SeqCst)
+    }
+
+    /// Fetches `url` and replaces the current page with the document it returns
+    ///
+    /// Error statuses still load their body, as a browser would show an error
+    /// page; only requests that get no response at all fail.
+    pub async fn load_url(&mut self, url: &str) -> Result<(), AluminumError> {
+        let url = Url::parse(url).map_err(|e| AluminumError::NetworkError(format!("Invalid URL '{}': {}", url, e)))?;
+        if let Some(policy) = &self.url_policy {
+            policy.check(&url)?;
+        }
+        let (final_url, html) = self.fetch(url).await?;
+        self.open_document(&html, final_url).await
+    }
+
+    /// Shows `html` as if it had been loaded from `base_url`
+    ///
+    /// Relative links, form actions and script sources resolve against
+    /// `base_url`; nothing is fetched for the document itself.
+    pub async fn load_html(&mut self, html: &str, base_url: &Url) -> Result<(), AluminumError> {
+        self.open_document(html, base_url.clone()).await
+    }
+
+    /// Fetches a navigation or subresource, returning the URL it ended up at and the body
+    ///
+    /// The request interceptor sees the URL first and may block or redirect it.
+    async fn fetch(&self, url: Url) -> Result<(Url, String), AluminumError> {
+        let url = match self.interceptor.as_ref().map(|intercept| intercept(&url)) {
+            None | Some(RequestAction::Allow) => url,
+            Some(RequestAction::Block) => return Err(AluminumError::BlockedByInterceptor(url.to_string())),
+            Some(RequestAction::Redirect(target)) => target,
+        };
+
+        self.requests_started.fetch_add(1, Ordering::SeqCst);
+        let _in_flight = InFlight::start(&self.in_flight);
+        let response = self
+            .http_client
+            .get(url.clone())
+            .header(reqwest::header::USER_AGENT, self.user_agent.as_str())
+            .send()
+            .await
+            .map_err(|e| AluminumError::NetworkError(format!("Failed to load {}: {}", url, e)))?;
+        let final_url = response.url().clone();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| AluminumError::NetworkError(format!("Failed to read {}: {}", final_url, e)))?;
+        Ok((final_url, body))
+    }
+
+    /// Replaces the current page with one parsed from `html` and runs its scripts in order
+    ///
+    /// External scripts are fetched like any other request; one that can't be
+    /// loaded is skipped, as a browser would, rather than failing the page.
+    async fn open_document(&mut self, html: &str, url: Url) -> Result<(), AluminumError> {
+        let document = Document::parse(html);
+        let sources: Vec<ScriptSource> = document
+            .query_selector_all(Document::ROOT, "script")?
+            .into_iter()
+            .filter(|&id| {
+                document.attribute(id, "type").map_or(true, |kind| {
+                    kind.is_empty() || kind.contains("javascript") || kind.eq_ignore_ascii_case("module")
+                })
+            })
+            .filter_map(|id| match document.attribute(id, "src") {
+                Some(src) => url.join(src).ok().map(ScriptSource::External),
+                None => Some(ScriptSource::Inline(document.text_content(id))),
+            })
+            .collect();
+
+        // A new page gets a new script context, so the old page's timers and globals are gone
+        self.page = Arc::new(Mutex::new(Page {
+            url,
+            document,
+            focused: None,
+            console_errors: Vec::new(),
+        }));
+        self.scripts = PageScripts::new(&self.page)?;
+        for source in sources {
+            let source = match source {
+                ScriptSource::Inline(source) => source,
+                ScriptSource::External(src) => match self.fetch(src.clone()).await {
+                    Ok((_, source)) => source,
+                    Err(e) => {
+                        warn!("Skipping script {}: {}", src, e);
+                        continue;
+                    }
+                },
+            };
+            self.scripts.run(&source)?;
+        }
+        Ok(())
+    }
+
+    /// Runs page timers that have come due, so reads see what the page has done since
+    fn run_due_timers(&self) -> Result<(), AluminumError> {
+        self.scripts.run_due_timers()
+    }
+
+    /// Clicks the first element matching `selector`
+    ///
+    /// Click handlers run first; unless one cancels the click, a link is
+    /// followed and a submit button submits its form.
+    pub async fn click_element(&mut self, selector: &str) -> Result<(), AluminumError> {
+        self.run_due_timers()?;
+        let element = self.first_match(selector)?;
+        if !self.scripts.dispatch(element, "click", None)? {
+            return Ok(());
+        }
+        self.activate(element).await
+    }
+
+    /// Performs the default action of clicking `element`
+    async fn activate(&mut self, element: NodeId) -> Result<(), AluminumError> {
+        enum Activation {
+            Follow(Url),
+            Submit(NodeId),
+        }
+
+        let activation = {
+            let page = self.page();
+            let document = &page.document;
+            let mut activation = None;
+            let mut current = Some(element);
+            while let Some(id) = current {
+                match (document.tag(id), document.attribute(id, "href")) {
+                    (Some("a"), Some(href)) => {
+                        let target = page
+                            .url
+                            .join(href)
+                            .map_err(|e| AluminumError::NetworkError(format!("Invalid link '{}': {}", href, e)))?;
+                        activation = Some(Activation::Follow(target));
+                        break;
+                    }
+                    (Some("button"), _) if document.attribute(id, "type").map_or(true, |t| t == "submit") => {
+                        activation = page.form_of(id).map(Activation::Submit);
+                        break;
+                    }
+                    _ => current = document.parent(id),
+                }
+            }
+            activation
+        };
+
+        match activation {
+            Some(Activation::Follow(target)) => self.load_url(target.as_str()).await,
+            Some(Activation::Submit(form)) => self.submit_form(form).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Submits a form by navigating to its action with its fields in the query string
+    ///
+    /// The form's method is ignored; every submission is a GET.
+    async fn submit_form(&mut self, form: NodeId) -> Result<(), AluminumError> {
+        if !self.scripts.dispatch(form, "submit", None)? {
+            return Ok(());
+        }
+        let target = {
+            let page = self.page();
+            let action = page.document.attribute(form, "action").unwrap_or("");
+            let mut target = page
+                .url
+                .join(action)
+                .map_err(|e| AluminumError::NetworkError(format!("Invalid form action '{}': {}", action, e)))?;
+            target.query_pairs_mut().clear().extend_pairs(page.form_fields(form));
+            target
+        };
+        self.load_url(target.as_str()).await
+    }
+
+    /// Replaces the value of the first text field matching `selector`
+    pub async fn input_text(&mut self, selector: &str, value: &str) -> Result<(), AluminumError> {
+        self.run_due_timers()?;
+        let element = self.first_match(selector)?;
+        {
+            let mut page = self.page();
+            let document = &mut page.document;
+            match document.tag(element) {
+                Some("input") => document.set_attribute(element, "value", value),
+                Some("textarea") => document.set_text_content(element, value),
+                _ => {
+                    return Err(AluminumError::InvalidParameter(format!(
+                        "'{}' is not a text field and can't take input",
+                        selector
+                    )))
+                }
+            }
+            page.focused = Some(element);
+        }
+        self.scripts.dispatch(element, "input", None)?;
+        Ok(())
+    }
+
+    /// Presses and releases `key`, first focusing the first element matching `selector`
+    ///
+    /// The focused element, or the body when nothing is focused, gets `keydown`
+    /// and `keyup` events. Unless `keydown` is cancelled, Enter in a text field
+    /// submits its form and Tab moves focus to the next focusable element.
+    pub async fn press_key(&mut self, selector: Option<&str>, key: &str) -> Result<(), AluminumError> {
+        self.run_due_timers()?;
+        if let Some(selector) = selector {
+            let element = self.first_match(selector)?;
+            self.page().focused = Some(element);
+        }
+
+        let (focused, target) = {
+            let page = self.page();
+            (page.focused, page.focused.or_else(|| page.default_target()))
+        };
+        let Some(target) = target else {
+            return Ok(());
+        };
+        let proceed = self.scripts.dispatch(target, "keydown", Some(key))?;
+        self.scripts.dispatch(target, "keyup", Some(key))?;
+        if !proceed {
+            return Ok(());
+        }
+
+        match key {
+            "Tab" => {
+                let mut page = self.page();
+                let next = page.next_focusable(focused);
+                page.focused = next;
+                Ok(())
+            }
+            "Enter" => match focused.and_then(|field| self.page().enclosing_form(field)) {
+                Some(form) => self.submit_form(form).await,
+                None => Ok(()),
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// Evaluates `script` in the page and returns its result converted to a string
+    ///
+    /// A script that throws fails with `AluminumError::ScriptError` carrying the
+    /// JavaScript error message.
+    pub async fn evaluate_script(&mut self, script: &str) -> Result<String, AluminumError> {
+        self.run_due_timers()?;
+        self.scripts.evaluate(script)
+    }
+
+    /// Returns the text content of the first element matching `selector`
+    pub async fn get_element_text(&self, selector: &str) -> Result<String, AluminumError> {
+        self.run_due_timers()?;
+        let element = self.first_match(selector)?;
+        Ok(self.page().document.text_content(element))
+    }
+
+    /// The page's visible text in document order, one line per block element
+    ///
+    /// Runs of whitespace collapse to one space, and scripts, styles and hidden
+    /// elements are left out, so the result reads like the rendered page.
+    pub fn extract_readable_text(&self) -> String {
+        if let Err(e) = self.run_due_timers() {
+            warn!("Failed to run timers before extracting text: {}", e);
+        }
+        let page = self.page();
+        let mut lines = Vec::new();
+        let mut line = String::new();
+        collect_readable_text(&page.document, Document::ROOT, &mut lines, &mut line);
+        finish_line(&mut lines, &mut line);
+        lines.join("\n")
+    }
+
+    /// Takes the page's console errors and uncaught exceptions logged since the last call
+    ///
+    /// Navigating starts a fresh list, so errors never carry over from an earlier page.
+    pub fn take_console_errors(&mut self) -> Vec<String> {
+        // Timers that are already due would have run in a real browser by now
+        if let Err(e) = self.run_due_timers() {
+            warn!("Failed to run timers before reading console errors: {}", e);
+        }
+        std::mem::take(&mut self.page().console_errors)
+    }
+
+    /// Records the page's URL, title, size and pending console errors without changing anything
+    pub fn debug_snapshot(&self) -> CoreSnapshot {
+        let page = self.page();
+        let document = &page.document;
+        let title = document
+            .find_element("title")
+            .map(|title| document.text_content(title).split_whitespace().collect::<Vec<_>>().join(" "))
+            .unwrap_or_default();
+        CoreSnapshot {
+            url: page.url.to_string(),
+            title,
+            dom_node_count: document.node_count(),
+            console_errors: page.console_errors.clone(),
+        }
+    }
+
+    /// Every element matching `selector`, in document order
+    pub async fn query_selector_all(&self, selector: &str) -> Result<Vec<ElementHandle>, AluminumError> {
+        self.run_due_timers()?;
+        let found = self.page().document.query_selector_all(Document::ROOT, selector)?;
+        Ok(found.into_iter().map(ElementHandle).collect())
+    }
+
+    /// Whether the first element matching `selector` can be interacted with
+    ///
+    /// Elements that can't be disabled, like links and paragraphs, are always enabled.
+    pub async fn is_element_enabled(&self, selector: &str) -> Result<bool, AluminumError> {
+        self.run_due_timers()?;
+        let element = self.first_match(selector)?;
+        Ok(!self.page().document.is_disabled(element))
+    }
+
+    /// Returns the laid-out box of the first element matching `selector`
+    ///
+    /// Elements that aren't rendered, such as those under `display:none`, get
+    /// an empty box at the origin.
+    pub async fn get_element_rect(&self, selector: &str) -> Result<Rect, AluminumError> {
+        self.run_due_timers()?;
+        let element = self.first_match(selector)?;
+        Ok(self.engine.layout(&self.page().document).rect(element).unwrap_or_default())
+    }
+
+    /// The element drawn on top at (x, y), as a click there would hit it
+    pub fn element_from_point(&self, x: f64, y: f64) -> Option<ElementHandle> {
+        if let Err(e) = self.run_due_timers() {
+            warn!("Failed to run timers before hit testing: {}", e);
+        }
+        self.engine.layout(&self.page().document).hit_test(Point { x, y }).map(ElementHandle)
+    }
+
+    /// Clicks whatever element is drawn at `point`; a point over no element does nothing
+    pub async fn click_at(&mut self, point: Point) -> Result<(), AluminumError> {
+        let Some(ElementHandle(element)) = self.element_from_point(point.x, point.y) else {
+            return Ok(());
+        };
+        if !self.scripts.dispatch(element, "click", None)? {
+            return Ok(());
+        }
+        self.activate(element).await
+    }
+
+    /// Paints the visible part of the page and returns it as PNG bytes
+    ///
+    /// Every element box is drawn as an outline, with form controls filled in,
+    /// which is enough to compare layouts by eye. Headless cores don't paint
+    /// and return `AluminumError::Unsupported`.
+    pub async fn capture_screenshot(&self) -> Result<Vec<u8>, AluminumError> {
+        if self.backend == EngineBackend::Headless {
+            return Err(AluminumError::Unsupported(
+                "screenshots need the full rendering backend".to_string(),
+            ));
+        }
+        self.run_due_timers()?;
+
+        let viewport = self.viewport_rect();
+        let mut image = RgbaImage::from_pixel(viewport.width as u32, viewport.height as u32, Rgba([255, 255, 255, 255]));
+        let page = self.page();
+        for &(id, rect) in self.engine.layout(&page.document).boxes() {
+            let control = matches!(page.document.tag(id), Some("button" | "input" | "select" | "textarea"));
+            paint_box(&mut image, rect, control);
+        }
+        drop(page);
+
+        let mut png = Vec::new();
+        image
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .map_err(|e| AluminumError::Io(io::Error::new(io::ErrorKind::Other, e)))?;
+        Ok(png)
+    }
+
+    /// The visible page area, in the same coordinates as `get_element_rect`
+    pub fn viewport_rect(&self) -> Rect {
+        self.engine.viewport()
+    }
+
+    /// The first element matching `selector`, or `ElementNotFound`
+    fn first_match(&self, selector: &str) -> Result<NodeId, AluminumError> {
+        self.page()
+            .document
+            .query_selector_all(Document::ROOT, selector)?
+            .first()
+            .copied()
+            .ok_or_else(|| AluminumError::ElementNotFound(selector.to_string()))
+    }
+}
+
+impl Default for BrowserCore {
+    fn default() -> Self {
+        BrowserCore::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    async fn core_with(html: &str) -> BrowserCore {
+        let mut core = BrowserCore::new();
+        core.open_document(html, Url::parse("https://aluminum.test/").unwrap()).await.unwrap();
+        core
+    }
+
+    // Serve each `(path, body)` pair until the test ends; other paths get an empty 404.
+    // Returns the server's base URL.
+    fn serve(pages: &[(&str, &str)]) -> String {
+        let pages: HashMap<String, String> =
+            pages.iter().map(|(path, body)| (path.to_string(), body.to_string())).collect();
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind mock server");
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut reader = BufReader::new(stream.unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header == "\r\n" || header.is_empty() {
+                        break;
+                    }
+                }
+
+                let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+                let (status, body) = match pages.get(path) {
+                    Some(body) => ("200 OK", body.as_str()),
+                    None => ("404 Not Found", ""),
+                };
+                let mut stream = reader.into_inner();
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+            }
+        });
+        base
+    }
+
+    #[test]
+    fn test_layout_stacks_blocks_inside_body_margin() {
+        let document = Document::parse(
+            "<html><head><title>Layout</title></head>\n<body>\n<h1>Title</h1>\n<p>Some <b>bold</b> text</p>\n</body></html>",
+        );
+        let tree = RenderingEngine::new().layout(&document);
+        let rect = |selector: &str| tree.rect(document.query_selector_all(Document::ROOT, selector).unwrap()[0]);
+
+        let body = rect("body").unwrap();
+        assert_eq!(body, Rect { x: 8.0, y: 8.0, width: VIEWPORT_WIDTH - 16.0, height: 2.0 * LINE_HEIGHT });
+        assert_eq!(rect("h1").unwrap(), Rect { x: 8.0, y: 8.0, width: body.width, height: LINE_HEIGHT });
+        assert_eq!(rect("p").unwrap().y, 8.0 + LINE_HEIGHT);
+        // "Some " comes before the bold run on the same line
+        assert_eq!(
+            rect("b").unwrap(),
+            Rect { x: 8.0 + 5.0 * CHAR_WIDTH, y: 8.0 + LINE_HEIGHT, width: 4.0 * CHAR_WIDTH, height: LINE_HEIGHT }
+        );
+        assert_eq!(rect("title"), None);
+    }
+
+    #[tokio::test]
+    async fn test_get_element_rect_of_laid_out_element() {
+        let core = core_with(
+            "<html><body style=\"margin:0\"><div id=\"banner\" style=\"height:40px\">Sale</div>\
+             <p id=\"intro\">Welcome</p></body></html>",
+        )
+        .await;
+
+        let banner = core.get_element_rect("#banner").await.unwrap();
+        assert_eq!(banner, Rect { x: 0.0, y: 0.0, width: VIEWPORT_WIDTH, height: 40.0 });
+        let intro = core.get_element_rect("#intro").await.unwrap();
+        assert_eq!(intro, Rect { x: 0.0, y: 40.0, width: VIEWPORT_WIDTH, height: LINE_HEIGHT });
+        assert_eq!(core.viewport_rect(), Rect { x: 0.0, y: 0.0, width: VIEWPORT_WIDTH, height: VIEWPORT_HEIGHT });
+    }
+
+    #[tokio::test]
+    async fn test_display_none_element_has_empty_rect_and_takes_no_room() {
+        let core = core_with(
+            "<html><body><div id=\"modal\" style=\"display: none\"><p id=\"inner\">Hidden</p></div>\
+             <p id=\"after\">Shown</p></body></html>",
+        )
+        .await;
+
+        assert_eq!(core.get_element_rect("#modal").await.unwrap(), Rect::default());
+        assert_eq!(core.get_element_rect("#inner").await.unwrap(), Rect::default());
+        assert_eq!(core.get_element_rect("#after").await.unwrap().y, DEFAULT_BODY_MARGIN);
+        assert!(matches!(
+            core.get_element_rect("#missing").await,
+            Err(AluminumError::ElementNotFound(selector)) if selector == "#missing"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_script_reads_the_dom_and_reports_errors() {
+        let mut core = core_with(
+            "<html><head><title>Cart</title></head><body><h1>Your cart</h1>\
+             <script>var items = 3; document.querySelector('h1').textContent += ' (' + items + ')';</script></body></html>",
+        )
+        .await;
+
+        assert_eq!(core.evaluate_script("6 * 7").await.unwrap(), "42");
+        assert_eq!(core.evaluate_script("document.title").await.unwrap(), "Cart");
+        assert_eq!(core.get_element_text("h1").await.unwrap(), "Your cart (3)");
+        assert_eq!(core.evaluate_script("items").await.unwrap(), "3");
+
+        match core.evaluate_script("throw new Error('checkout broke')").await {
+            Err(AluminumError::ScriptError(message)) => assert!(message.contains("checkout broke"), "{}", message),
+            other => panic!("expected a script error, got {:?}", other),
+        }
+        assert!(matches!(core.evaluate_script("let = ;").await, Err(AluminumError::ScriptError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_press_key_dispatches_to_focused_element_and_tabs() {
+        let mut core = core_with(
+            "<html><body><input id=\"first\" name=\"q\"><input id=\"second\"><p id=\"log\"></p>\
+             <script>document.getElementById('first').addEventListener('keydown', function (event) {\
+               document.getElementById('log').textContent += event.key + ';';\
+             });</script></body></html>",
+        )
+        .await;
+
+        core.press_key(Some("#first"), "Escape").await.unwrap();
+        core.press_key(None, "Enter").await.unwrap();
+        assert_eq!(core.get_element_text("#log").await.unwrap(), "Escape;Enter;");
+
+        core.press_key(None, "Tab").await.unwrap();
+        assert_eq!(core.evaluate_script("document.activeElement.id").await.unwrap(), "second");
+        core.press_key(None, "Tab").await.unwrap();
+        assert_eq!(core.evaluate_script("document.activeElement.id").await.unwrap(), "first");
+
+        assert!(matches!(
+            core.press_key(Some("#missing"), "Enter").await,
+            Err(AluminumError::ElementNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_request_interceptor_blocks_and_redirects() {
+        let base = serve(&[
+            ("/", "<html><body><h1>Home</h1><script src=\"/tracker.js\"></script><script src=\"/app.js\"></script></body></html>"),
+            ("/tracker.js", "window.tracked = true;"),
+            ("/app.js", "document.querySelector('h1').textContent = 'App loaded';"),
+            ("/new", "<html><body><h1>New home</h1></body></html>"),
+        ]);
+        let mut core = BrowserCore::new();
+        let new_home = Url::parse(&format!("{}/new", base)).unwrap();
+        core.set_request_interceptor(move |url| match url.path() {
+            "/tracker.js" | "/admin" => RequestAction::Block,
+            "/old" => RequestAction::Redirect(new_home.clone()),
+            _ => RequestAction::Allow,
+        });
+
+        // The blocked script is skipped while the page and its other script load
+        core.load_url(&format!("{}/", base)).await.expect("allowed page should load");
+        assert_eq!(core.get_element_text("h1").await.unwrap(), "App loaded");
+        assert_eq!(core.evaluate_script("typeof tracked").await.unwrap(), "undefined");
+        assert_eq!(core.requests_started(), 2);
+        assert_eq!(core.in_flight_requests(), 0);
+
+        match core.load_url(&format!("{}/admin", base)).await {
+            Err(AluminumError::BlockedByInterceptor(url)) => assert!(url.ends_with("/admin"), "{}", url),
+            other => panic!("expected the navigation to be blocked, got {:?}", other),
+        }
+        assert_eq!(core.get_element_text("h1").await.unwrap(), "App loaded");
+        assert_eq!(core.requests_started(), 2);
+
+        core.load_url(&format!("{}/old", base)).await.expect("redirected page should load");
+        assert_eq!(core.get_element_text("h1").await.unwrap(), "New home");
+    }
+
+    #[tokio::test]
+    async fn test_load_html_resolves_subresources_against_base_url() {
+        let base = serve(&[("/fixtures/app.js", "document.getElementById('status').textContent = 'ready';")]);
+        let mut core = BrowserCore::new();
+        let base_url = Url::parse(&format!("{}/fixtures/page.html", base)).unwrap();
+
+        core.load_html("<html><body><p id=\"status\">loading</p><script src=\"app.js\"></script></body></html>", &base_url)
+            .await
+            .unwrap();
+
+        assert_eq!(core.get_element_text("#status").await.unwrap(), "ready");
+        assert_eq!(core.requests_started(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_url_policy_refuses_navigation_before_any_request() {
+        let base = serve(&[("/", "<html><body><h1>Kiosk home</h1></body></html>")]);
+        let mut core = BrowserCore::new();
+        core.set_url_policy(UrlPolicy::new().allow_host("127.0.0.1"));
+
+        core.load_url(&format!("{}/", base)).await.expect("allowed host should load");
+        let blocked = core.load_url("http://localhost:9/").await;
+        assert!(matches!(blocked, Err(AluminumError::BlockedByPolicy(message)) if message.contains("localhost")));
+        assert_eq!(core.requests_started(), 1);
+        assert_eq!(core.get_element_text("h1").await.unwrap(), "Kiosk home");
+    }
+
+    #[tokio::test]
+    async fn test_is_element_enabled_follows_disabled_fieldsets() {
+        let core = core_with(
+            "<html><body><form>\
+             <input id=\"name\"><button id=\"save\" disabled>Save</button>\
+             <fieldset id=\"billing\" disabled><input id=\"card\"><p id=\"note\">Locked</p></fieldset>\
+             </form><a id=\"help\" href=\"/help\" disabled>Help</a></body></html>",
+        )
+        .await;
+
+        assert!(core.is_element_enabled("#name").await.unwrap());
+        assert!(!core.is_element_enabled("#save").await.unwrap());
+        assert!(!core.is_element_enabled("#billing").await.unwrap());
+        assert!(!core.is_element_enabled("#card").await.unwrap());
+        assert!(core.is_element_enabled("#note").await.unwrap());
+        assert!(core.is_element_enabled("#help").await.unwrap());
+        assert!(matches!(core.is_element_enabled("#missing").await, Err(AluminumError::ElementNotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_query_selector_all_sees_script_changes() {
+        let core = core_with(
+            "<html><body><ul><li class=\"item\">One</li><li class=\"item done\">Two</li></ul>\
+             <script>setTimeout(function () {\
+               var li = document.querySelector('.item');\
+               li.setAttribute('class', 'item done');\
+             }, 0);</script></body></html>",
+        )
+        .await;
+
+        let done = core.query_selector_all("li.done").await.unwrap();
+        assert_eq!(done.len(), 2);
+        assert!(done[0].node_id() < done[1].node_id());
+        assert_eq!(core.query_selector_all("ul > .item").await.unwrap(), core.query_selector_all("li").await.unwrap());
+        assert!(core.query_selector_all("table").await.unwrap().is_empty());
+        assert!(matches!(core.query_selector_all("li[").await, Err(AluminumError::InvalidParameter(_))));
+    }
+
+    #[tokio::test]
+    async fn test_take_console_errors_collects_errors_and_uncaught_exceptions() {
+        let mut core = core_with(
+            "<html><body><script>console.warn('deprecated API'); console.error('payment failed', 402);</script>\
+             <script>missingFunction();</script></body></html>",
+        )
+        .await;
+
+        let errors = core.take_console_errors();
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+        assert_eq!(errors[0], "payment failed 402");
+        assert!(errors[1].starts_with("Uncaught ReferenceError"), "{}", errors[1]);
+        assert!(core.take_console_errors().is_empty());
+
+        core.evaluate_script("setTimeout(() => { throw new Error('late failure'); }, 0)").await.unwrap();
+        assert!(core.take_console_errors()[0].contains("late failure"));
+
+        let old_page = Url::parse("https://aluminum.test/old").unwrap();
+        core.open_document("<html><body><script>console.error('old page');</script></body></html>", old_page)
+            .await
+            .unwrap();
+        let new_page = Url::parse("https://aluminum.test/new").unwrap();
+        core.open_document("<html><body></body></html>", new_page).await.unwrap();
+        assert!(core.take_console_errors().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_click_at_hits_the_topmost_box() {
+        let mut core = core_with(
+            "<html><body style=\"margin:0\"><div id=\"card\" style=\"height:100px\">Card</div>\
+             <button id=\"close\" style=\"position:absolute;left:10px;top:10px;width:100px;height:50px\" \
+             onclick=\"document.getElementById('card').textContent = 'Closed'\">x</button></body></html>",
+        )
+        .await;
+        let close = core.query_selector_all("#close").await.unwrap()[0];
+        let card = core.query_selector_all("#card").await.unwrap()[0];
+
+        assert_eq!(core.element_from_point(60.0, 35.0), Some(close));
+        assert_eq!(core.element_from_point(200.0, 35.0), Some(card));
+        assert_eq!(core.element_from_point(600.0, 400.0), None);
+
+        core.click_at(Point { x: 200.0, y: 80.0 }).await.unwrap();
+        assert_eq!(core.get_element_text("#card").await.unwrap(), "Card");
+        core.click_at(Point { x: 60.0, y: 35.0 }).await.unwrap();
+        assert_eq!(core.get_element_text("#card").await.unwrap(), "Closed");
+    }
+
+    #[tokio::test]
+    async fn test_debug_snapshot_leaves_console_errors_in_place() {
+        let mut core = core_with(
+            "<html><head><title>\n  Order   summary\n</title></head>\
+             <body><p>Total</p><script>console.error('tax lookup failed');</script></body></html>",
+        )
+        .await;
+
+        let snapshot = core.debug_snapshot();
+        assert_eq!(snapshot.url, "https://aluminum.test/");
+        assert_eq!(snapshot.title, "Order summary");
+        // document, html, head, title, its text, body, p, its text, script, its text
+        assert_eq!(snapshot.dom_node_count, 10);
+        assert_eq!(snapshot.console_errors, vec!["tax lookup failed".to_string()]);
+        assert_eq!(core.take_console_errors(), snapshot.console_errors);
+
+        let json = serde_json::to_string(&snapshot).unwrap();
+        assert_eq!(serde_json::from_str::<CoreSnapshot>(&json).unwrap(), snapshot);
+    }
+
+    #[tokio::test]
+    async fn test_headless_backend_still_lays_out_pages() {
+        assert_eq!(BrowserCore::new().backend(), EngineBackend::Full);
+
+        let mut core = BrowserCore::new_with_backend(EngineBackend::Headless);
+        assert_eq!(core.backend(), EngineBackend::Headless);
+        core.load_html("<html><body><h1>Report</h1></body></html>", &Url::parse("https://aluminum.test/").unwrap())
+            .await
+            .unwrap();
+        let heading = core.get_element_rect("h1").await.unwrap();
+        assert_eq!((heading.x, heading.y), (DEFAULT_BODY_MARGIN, DEFAULT_BODY_MARGIN));
+        assert_eq!(heading.height, LINE_HEIGHT);
+    }
+
+    #[tokio::test]
+    async fn test_capture_screenshot_paints_on_full_backend_only() {
+        let html = "<html><body style=\"margin:0\"><button style=\"width:100px;height:50px\">Buy</button></body></html>";
+        let base_url = Url::parse("https://aluminum.test/").unwrap();
+
+        let mut full = BrowserCore::new();
+        full.load_html(html, &base_url).await.unwrap();
+        let png = full.capture_screenshot().await.unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+        let image = image::load_from_memory(&png).unwrap().to_rgba8();
+        assert_eq!((image.width(), image.height()), (VIEWPORT_WIDTH as u32, VIEWPORT_HEIGHT as u32));
+        assert_eq!(image.get_pixel(0, 0), &Rgba([160, 160, 160, 255]));
+        assert_eq!(image.get_pixel(50, 25), &Rgba([225, 225, 225, 255]));
+        assert_eq!(image.get_pixel(600, 400), &Rgba([255, 255, 255, 255]));
+
+        let mut headless = BrowserCore::new_with_backend(EngineBackend::Headless);
+        headless.load_html(html, &base_url).await.unwrap();
+        assert!(matches!(headless.capture_screenshot().await, Err(AluminumError::Unsupported(_))));
+    }
+
+    #[tokio::test]
+    async fn test_reset_returns_to_a_blank_page() {
+        let mut core = core_with(
+            "<html><head><title>Cart</title></head>\
+             <body><script>window.items = 3; console.error('stale');</script></body></html>",
+        )
+        .await;
+        core.set_user_agent("Custom/1.0");
+        core.set_url_policy(UrlPolicy::new().allow_host("aluminum.test"));
+        core.set_request_interceptor(|_| RequestAction::Block);
+
+        core.reset();
+
+        let snapshot = core.debug_snapshot();
+        assert_eq!(snapshot.url, "about:blank");
+        assert_eq!(snapshot.title, "");
+        assert!(snapshot.console_errors.is_empty());
+        assert_eq!(core.evaluate_script("typeof items").await.unwrap(), "undefined");
+        assert_eq!(core.user_agent, DEFAULT_USER_AGENT);
+        assert!(core.interceptor.is_none() && core.url_policy.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_extract_readable_text_skips_scripts_and_styles() {
+        let core = core_with(
+            "<html><head><title>Docs</title><style>p { color: red; }</style></head><body>\
+             <script>var hidden = 'script text';</script>\
+             <p>Visible <b>bold</b> text</p>\
+             <div hidden>Hidden attribute</div><div style=\"display:none\">Hidden style</div>\
+             <span aria-hidden=\"true\">Icon</span></body></html>",
+        )
+        .await;
+
+        assert_eq!(core.extract_readable_text(), "Visible bold text");
+    }
+
+    #[tokio::test]
+    async fn test_extract_readable_text_breaks_lines_at_blocks() {
+        let core = core_with(
+            "<html><body><h1>Aluminum\n   Browser</h1>Intro <em>with</em> inline\
+             <ul><li>First</li><li>Second</li></ul><p>Line one<br>Line two</p>\
+             <p><b>Al</b>uminum</p></body></html>",
+        )
+        .await;
+
+        assert_eq!(
+            core.extract_readable_text(),
+            "Aluminum Browser\nIntro with inline\nFirst\nSecond\nLine one\nLine two\nAluminum"
+        );
+    }
+}
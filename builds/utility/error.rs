@@ -0,0 +1,93 @@
+// Error Type for Aluminum Web Browser
+// This module defines the error shared by the browser core, the test runner and
+// the utilities built on them, so callers can tell failures apart by variant
+// instead of by parsing messages.
+
+use std::fmt;
+use std::io;
+
+use similar::{ChangeTag, TextDiff};
+
+/// Errors reported by the browser core and the test runner
+#[derive(Debug)]
+pub enum AluminumError {
+    /// A page or resource couldn't be loaded
+    NetworkError(String),
+    /// A test step names an action that doesn't exist
+    UnknownTestStep(String),
+    /// A step parameter is missing or has a value the action can't use
+    InvalidParameter(String),
+    /// A check against the page didn't hold
+    AssertionFailed(String),
+    /// An element's text didn't match; reports render a diff of the two values
+    AssertionMismatch {
+        selector: String,
+        expected: String,
+        actual: String,
+    },
+    /// No element matches the selector
+    ElementNotFound(String),
+    /// A navigation or subresource request was blocked by a request interceptor
+    BlockedByInterceptor(String),
+    /// A navigation was refused by the URL policy
+    BlockedByPolicy(String),
+    /// A script threw or couldn't be parsed; holds the JavaScript error message
+    ScriptError(String),
+    /// The browser core was built without the feature the operation needs, e.g. a headless screenshot
+    Unsupported(String),
+    /// Reading or writing a local file failed
+    Io(io::Error),
+}
+
+impl AluminumError {
+    /// Whether trying the same operation again might succeed
+    ///
+    /// Network failures (including timeouts) are transient; assertion, parse and
+    /// unknown-step errors fail the same way on every attempt.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, AluminumError::NetworkError(_))
+    }
+}
+
+impl fmt::Display for AluminumError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AluminumError::NetworkError(message) => write!(f, "Network error: {}", message),
+            AluminumError::UnknownTestStep(action) => write!(f, "Unknown test step: {}", action),
+            AluminumError::InvalidParameter(message) => write!(f, "Invalid step parameter: {}", message),
+            AluminumError::AssertionFailed(message) => write!(f, "Assertion failed: {}", message),
+            AluminumError::AssertionMismatch { selector, expected, actual } => {
+                // Stay on one line for logs; multi-line values only report how many lines differ
+                if !expected.contains('\n') && !actual.contains('\n') {
+                    return write!(f, "Expected text '{}' but found '{}'", expected, actual);
+                }
+                let changed = TextDiff::from_lines(expected, actual)
+                    .iter_all_changes()
+                    .filter(|change| change.tag() == ChangeTag::Delete)
+                    .count();
+                write!(f, "Text of '{}' differs from expected on {} line(s)", selector, changed)
+            }
+            AluminumError::ElementNotFound(selector) => write!(f, "No element matches '{}'", selector),
+            AluminumError::BlockedByInterceptor(url) => write!(f, "Blocked by request interceptor: {}", url),
+            AluminumError::BlockedByPolicy(message) => write!(f, "Blocked by URL policy: {}", message),
+            AluminumError::ScriptError(message) => write!(f, "Script error: {}", message),
+            AluminumError::Unsupported(message) => write!(f, "Unsupported: {}", message),
+            AluminumError::Io(error) => write!(f, "I/O error: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for AluminumError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AluminumError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for AluminumError {
+    fn from(error: io::Error) -> Self {
+        AluminumError::Io(error)
+    }
+}
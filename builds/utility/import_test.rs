@@ -1,430 +1,1917 @@
-
-// Import Test for Aluminum Web Browser
-// This comprehensive test suite ensures the proper functionality of the import system
-// in the Aluminum web browser. It covers various scenarios and edge cases to maintain
-// a robust and reliable import mechanism.
-
-use std::fs::{self, File};
-use std::io::{self, Read, Write};
-use std::path::{Path, PathBuf};
-use std::collections::HashMap;
-use std::time::{Duration, Instant};
-use std::sync::{Arc, Mutex};
-use std::thread;
-
-use serde::{Serialize, Deserialize};
-use reqwest::blocking::Client;
-use tempfile::TempDir;
-use log::{info, warn, error};
-use chrono::{DateTime, Utc};
-use rand::{thread_rng, Rng};
-use sha2::{Sha256, Digest};
-use zip::ZipArchive;
-
-// Constants for test configuration
-const MAX_IMPORT_SIZE: usize = 1024 * 1024 * 100; // 100 MB
-const IMPORT_TIMEOUT: Duration = Duration::from_secs(300); // 5 minutes
-const CONCURRENT_IMPORTS: usize = 5;
-
-// Struct to represent an import item
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct ImportItem {
-    url: String,
-    filename: String,
-    size: usize,
-    checksum: String,
-}
-
-// Enum to represent import status
-#[derive(Debug, Clone, PartialEq)]
-enum ImportStatus {
-    Pending,
-    InProgress,
-    Completed,
-    Failed(String),
-}
-
-// Struct to manage import operations
-struct ImportManager {
-    client: Client,
-    temp_dir: TempDir,
-    import_queue: Arc<Mutex<Vec<ImportItem>>>,
-    import_status: Arc<Mutex<HashMap<String, ImportStatus>>>,
-}
-
-impl ImportManager {
-    // Initialize a new ImportManager
-    fn new() -> io::Result<Self> {
-        Ok(Self {
-            client: Client::new(),
-            temp_dir: TempDir::new()?,
-            import_queue: Arc::new(Mutex::new(Vec::new())),
-            import_status: Arc::new(Mutex::new(HashMap::new())),
-        })
-    }
-
-    // Add an item to the import queue
-    fn queue_import(&self, item: ImportItem) {
-        let mut queue = self.import_queue.lock().unwrap();
-        queue.push(item.clone());
-        let mut status = self.import_status.lock().unwrap();
-        status.insert(item.filename.clone(), ImportStatus::Pending);
-    }
-
-    // Process the import queue
-    fn process_queue(&self) {
-        let queue = Arc::clone(&self.import_queue);
-        let status = Arc::clone(&self.import_status);
-
-        for _ in 0..CONCURRENT_IMPORTS {
-            let queue = Arc::clone(&queue);
-            let status = Arc::clone(&status);
-            let client = self.client.clone();
-            let temp_dir = self.temp_dir.path().to_owned();
-
-            thread::spawn(move || {
-                loop {
-                    let item = {
-                        let mut queue = queue.lock().unwrap();
-                        queue.pop()
-                    };
-
-                    match item {
-                        Some(import_item) => {
-                            let result = Self::process_import(&client, &temp_dir, &import_item);
-                            let mut status = status.lock().unwrap();
-                            status.insert(
-                                import_item.filename.clone(),
-                                match result {
-                                    Ok(_) => ImportStatus::Completed,
-                                    Err(e) => ImportStatus::Failed(e.to_string()),
-                                },
-                            );
-                        }
-                        None => break,
-                    }
-                }
-            });
-        }
-    }
-
-    // Process a single import item
-    fn process_import(
-        client: &Client,
-        temp_dir: &Path,
-        item: &ImportItem,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        info!("Starting import for: {}", item.filename);
-
-        // Download the file
-        let mut response = client
-            .get(&item.url)
-            .timeout(IMPORT_TIMEOUT)
-            .send()?
-            .error_for_status()?;
-
-        let mut buffer = Vec::new();
-        response.read_to_end(&mut buffer)?;
-
-        // Verify file size
-        if buffer.len() > MAX_IMPORT_SIZE {
-            return Err(format!("File size exceeds maximum allowed size of {} bytes", MAX_IMPORT_SIZE).into());
-        }
-
-        // Verify checksum
-        let calculated_checksum = format!("{:x}", Sha256::digest(&buffer));
-        if calculated_checksum != item.checksum {
-            return Err("Checksum verification failed".into());
-        }
-
-        // Save the file
-        let file_path = temp_dir.join(&item.filename);
-        let mut file = File::create(file_path)?;
-        file.write_all(&buffer)?;
-
-        info!("Import completed successfully for: {}", item.filename);
-        Ok(())
-    }
-
-    // Generate a detailed report of the import process
-    fn generate_report(&self) -> String {
-        let status = self.import_status.lock().unwrap();
-        let mut report = String::new();
-
-        report.push_str("Import Test Report for Aluminum Web Browser\n");
-        report.push_str("===========================================\n\n");
-
-        let now: DateTime<Utc> = Utc::now();
-        report.push_str(&format!("Generated on: {}\n\n", now.format("%Y-%m-%d %H:%M:%S UTC")));
-
-        for (filename, status) in status.iter() {
-            report.push_str(&format!("File: {}\n", filename));
-            report.push_str(&format!("Status: {:?}\n", status));
-            report.push_str("\n");
-        }
-
-        report
-    }
-}
-
-// Main test function
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_import_system() {
-        // Initialize logging
-        env_logger::init();
-
-        // Create an ImportManager instance
-        let import_manager = ImportManager::new().expect("Failed to create ImportManager");
-
-        // Generate test import items
-        let test_items = generate_test_import_items();
-
-        // Queue import items
-        for item in test_items {
-            import_manager.queue_import(item);
-        }
-
-        // Process the import queue
-        import_manager.process_queue();
-
-        // Wait for all imports to complete
-        thread::sleep(Duration::from_secs(10));
-
-        // Generate and print the report
-        let report = import_manager.generate_report();
-        println!("{}", report);
-
-        // Verify import results
-        let status = import_manager.import_status.lock().unwrap();
-        for (filename, import_status) in status.iter() {
-            assert!(
-                matches!(import_status, ImportStatus::Completed),
-                "Import failed for file: {}",
-                filename
-            );
-        }
-    }
-
-    // Helper function to generate test import items
-    fn generate_test_import_items() -> Vec<ImportItem> {
-        let mut items = Vec::new();
-        let mut rng = thread_rng();
-
-        for i in 1..=10 {
-            let size = rng.gen_range(1024..MAX_IMPORT_SIZE);
-            let mut hasher = Sha256::new();
-            hasher.update(&size.to_le_bytes());
-            let checksum = format!("{:x}", hasher.finalize());
-
-            items.push(ImportItem {
-                url: format!("https://www.Aluminum.com/test_file_{}.zip", i),
-                filename: format!("test_file_{}.zip", i),
-                size,
-                checksum,
-            });
-        }
-
-        items
-    }
-}
-
-// Additional helper functions for the import system
-
-// Function to validate the structure of imported ZIP files
-fn validate_zip_structure(zip_path: &Path) -> io::Result<bool> {
-    let file = File::open(zip_path)?;
-    let mut archive = ZipArchive::new(file)?;
-
-    // Check for required files and directories
-    let required_entries = vec!["manifest.json", "content/", "resources/"];
-
-    for entry in required_entries {
-        if archive.by_name(entry).is_err() {
-            return Ok(false);
-        }
-    }
-
-    Ok(true)
-}
-
-// Function to extract and process imported ZIP files
-fn process_imported_zip(zip_path: &Path, output_dir: &Path) -> io::Result<()> {
-    let file = File::open(zip_path)?;
-    let mut archive = ZipArchive::new(file)?;
-
-    for i in 0..archive.len() {
-        let mut file = archive.by_index(i)?;
-        let outpath = output_dir.join(file.name());
-
-        if file.name().ends_with('/') {
-            fs::create_dir_all(&outpath)?;
-        } else {
-            if let Some(p) = outpath.parent() {
-                if !p.exists() {
-                    fs::create_dir_all(p)?;
-                }
-            }
-            let mut outfile = File::create(&outpath)?;
-            io::copy(&mut file, &mut outfile)?;
-        }
-    }
-
-    Ok(())
-}
-
-// Function to clean up temporary files after import
-fn cleanup_temp_files(temp_dir: &Path) -> io::Result<()> {
-    for entry in fs::read_dir(temp_dir)? {
-        let entry = entry?;
-        let path = entry.path();
-        if path.is_file() {
-            fs::remove_file(path)?;
-        } else if path.is_dir() {
-            fs::remove_dir_all(path)?;
-        }
-    }
-    Ok(())
-}
-
-// Function to log import activities
-fn log_import_activity(activity: &str, item: &ImportItem) {
-    let timestamp = Utc::now().format("%Y-%m-%d %H:%M:%S%.3f");
-    info!("[{}] {}: {}", timestamp, activity, item.filename);
-}
-
-// Function to calculate the overall progress of imports
-fn calculate_import_progress(status: &HashMap<String, ImportStatus>) -> f64 {
-    let total = status.len() as f64;
-    let completed = status.values().filter(|&s| *s == ImportStatus::Completed).count() as f64;
-    (completed / total) * 100.0
-}
-
-// Enum to represent different types of import sources
-enum ImportSource {
-    LocalFile(PathBuf),
-    RemoteUrl(String),
-    CloudStorage(String, String), // (provider, identifier)
-}
-
-// Trait for import plugins
-trait ImportPlugin {
-    fn name(&self) -> &str;
-    fn version(&self) -> &str;
-    fn supports_source(&self, source: &ImportSource) -> bool;
-    fn process_import(&self, source: &ImportSource, destination: &Path) -> io::Result<()>;
-}
-
-// Example implementation of an import plugin
-struct ZipImportPlugin;
-
-impl ImportPlugin for ZipImportPlugin {
-    fn name(&self) -> &str {
-        "ZIP Import Plugin"
-    }
-
-    fn version(&self) -> &str {
-        "1.0.0"
-    }
-
-    fn supports_source(&self, source: &ImportSource) -> bool {
-        match source {
-            ImportSource::LocalFile(path) => path.extension().map_or(false, |ext| ext == "zip"),
-            ImportSource::RemoteUrl(url) => url.ends_with(".zip"),
-            ImportSource::CloudStorage(_, identifier) => identifier.ends_with(".zip"),
-        }
-    }
-
-    fn process_import(&self, source: &ImportSource, destination: &Path) -> io::Result<()> {
-        // Implementation for processing ZIP imports
-        // This is a placeholder and should be replaced with actual ZIP processing logic
-        Ok(())
-    }
-}
-
-// Function to register import plugins
-fn register_import_plugins() -> Vec<Box<dyn ImportPlugin>> {
-    vec![Box::new(ZipImportPlugin)]
-}
-
-// Main function to run the import test suite
-fn main() {
-    println!("Running Aluminum Web Browser Import Test Suite");
-    println!("==============================================");
-
-    // Initialize logging
-    env_logger::init();
-
-    // Register import plugins
-    let plugins = register_import_plugins();
-
-    // Create an ImportManager instance
-    let import_manager = match ImportManager::new() {
-        Ok(manager) => manager,
-        Err(e) => {
-            error!("Failed to create ImportManager: {}", e);
-            return;
-        }
-    };
-
-    // Generate test import items
-    let test_items = generate_test_import_items();
-
-    // Queue import items
-    for item in test_items {
-        import_manager.queue_import(item);
-    }
-
-    // Process the import queue
-    import_manager.process_queue();
-
-    // Wait for all imports to complete
-    let start_time = Instant::now();
-    loop {
-        thread::sleep(Duration::from_secs(1));
-        let status = import_manager.import_status.lock().unwrap();
-        let progress = calculate_import_progress(&status);
-        println!("Import progress: {:.2}%", progress);
-
-        if progress == 100.0 || start_time.elapsed() > Duration::from_secs(600) {
-            break;
-        }
-    }
-
-    // Generate and print the final report
-    let report = import_manager.generate_report();
-    println!("\nFinal Import Test Report:");
-    println!("{}", report);
-
-    // Cleanup temporary files
-    if let Err(e) = cleanup_temp_files(import_manager.temp_dir.path()) {
-        error!("Failed to clean up temporary files: {}", e);
-    }
-
-    println!("Import Test Suite completed.");
-}
-
-// Helper function to generate test import items (moved outside of the test module)
-fn generate_test_import_items() -> Vec<ImportItem> {
-    let mut items = Vec::new();
-    let mut rng = thread_rng();
-
-    for i in 1..=10 {
-        let size = rng.gen_range(1024..MAX_IMPORT_SIZE);
-        let mut hasher = Sha256::new();
-        hasher.update(&size.to_le_bytes());
-        let checksum = format!("{:x}", hasher.finalize());
-
-        items.push(ImportItem {
-            url: format!("https://example.com/test_file_{}.zip", i),
-            filename: format!("test_file_{}.zip", i),
-            size,
-            checksum,
-        });
-    }
-
-    items
-}
+
+// Import Test for Aluminum Web Browser
+// This comprehensive test suite ensures the proper functionality of the import system
+// in the Aluminum web browser. It covers various scenarios and edge cases to maintain
+// a robust and reliable import mechanism.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use serde::{Serialize, Deserialize};
+use reqwest::blocking::Client;
+use tempfile::TempDir;
+use log::{info, warn, error};
+use chrono::{DateTime, Utc};
+use rand::{thread_rng, Rng};
+use sha2::{Sha256, Digest};
+use zip::ZipArchive;
+
+mod metrics;
+use metrics::Metrics;
+mod proxy;
+use proxy::ProxyConfig;
+mod redirect;
+use redirect::{into_redirect_error, RedirectPolicy};
+
+// Constants for test configuration
+const MAX_IMPORT_SIZE: usize = 1024 * 1024 * 100; // 100 MB
+const IMPORT_TIMEOUT: Duration = Duration::from_secs(300); // 5 minutes
+const CONCURRENT_IMPORTS: usize = 5;
+const COMPLETION_DEADLINE: Duration = Duration::from_secs(600); // 10 minutes
+const PROGRESS_POLL_INTERVAL: Duration = Duration::from_secs(1);
+const TIMED_OUT_MESSAGE: &str = "timed out waiting";
+const CANCELLED_MESSAGE: &str = "cancelled before it started";
+const PARTIAL_FILE_SUFFIX: &str = ".part";
+const CONTENT_LENGTH_TOLERANCE: f64 = 0.01; // 1% of the expected size
+const ADAPTIVE_INITIAL_CONCURRENCY: usize = 2;
+const ADAPTIVE_ERROR_RATE_THRESHOLD: f64 = 0.25;
+const ADAPTIVE_SLOT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+// File extensions with a verifiable content type, and the MIME type their bytes must sniff as
+const KNOWN_IMPORT_TYPES: &[(&str, &str)] = &[
+    ("zip", "application/zip"),
+    ("gz", "application/gzip"),
+    ("pdf", "application/pdf"),
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+];
+
+// Struct to represent an import item
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ImportItem {
+    url: String,
+    filename: String,
+    size: usize,
+    checksum: String,
+}
+
+// One entry of an import manifest file, before validation
+#[derive(Debug, Deserialize)]
+struct ManifestEntry {
+    url: String,
+    filename: String,
+    size: usize,
+    checksum: String,
+    // Only SHA-256 checksums are verified; omitted means sha256
+    #[serde(default)]
+    algorithm: Option<String>,
+}
+
+// Import state written to disk so an interrupted run can be resumed
+#[derive(Debug, Serialize, Deserialize)]
+struct PersistedImportState {
+    output_dir: PathBuf,
+    items: Vec<PersistedImportItem>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PersistedImportItem {
+    item: ImportItem,
+    status: ImportStatus,
+}
+
+// Enum to represent import status
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+enum ImportStatus {
+    Pending,
+    InProgress,
+    Completed,
+    Failed(String),
+}
+
+// Struct to describe how far the import queue has got
+#[derive(Debug, Clone, PartialEq)]
+struct ImportProgress {
+    // Share of items that have finished, successfully or not, from 0 to 100
+    percent: f64,
+    completed: usize,
+    total: usize,
+    failed: usize,
+    // Estimated time until every item finishes, based on the average rate since processing started
+    eta: Option<Duration>,
+    // Number of imports allowed to run at once
+    concurrency: usize,
+}
+
+// Struct to adjust the number of concurrent imports with additive-increase/multiplicative-decrease.
+// After each round of `limit` finished items the limit halves if too many failed, grows by one if
+// throughput held up, and otherwise stays put.
+#[derive(Debug)]
+struct ConcurrencyController {
+    limit: usize,
+    max: usize,
+    active: usize,
+    round_succeeded: usize,
+    round_failed: usize,
+    round_started: Instant,
+    last_throughput: Option<f64>,
+}
+
+impl ConcurrencyController {
+    fn new(max: usize) -> Self {
+        let max = max.max(1);
+        ConcurrencyController {
+            limit: ADAPTIVE_INITIAL_CONCURRENCY.min(max),
+            max,
+            active: 0,
+            round_succeeded: 0,
+            round_failed: 0,
+            round_started: Instant::now(),
+            last_throughput: None,
+        }
+    }
+
+    // Claim a slot for one import if fewer than `limit` are running
+    fn try_acquire(&mut self) -> bool {
+        if self.active < self.limit {
+            self.active += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn release(&mut self) {
+        self.active = self.active.saturating_sub(1);
+    }
+
+    // Record the outcome of one import and adjust the limit at the end of each round
+    fn record(&mut self, succeeded: bool) {
+        if succeeded {
+            self.round_succeeded += 1;
+        } else {
+            self.round_failed += 1;
+        }
+
+        let finished = self.round_succeeded + self.round_failed;
+        if finished < self.limit {
+            return;
+        }
+
+        let error_rate = self.round_failed as f64 / finished as f64;
+        let throughput = finished as f64 / self.round_started.elapsed().as_secs_f64().max(f64::EPSILON);
+        if error_rate > ADAPTIVE_ERROR_RATE_THRESHOLD {
+            self.limit = (self.limit / 2).max(1);
+        } else if self.last_throughput.map_or(true, |previous| throughput >= previous * 0.9) {
+            self.limit = (self.limit + 1).min(self.max);
+        }
+
+        self.last_throughput = Some(throughput);
+        self.round_succeeded = 0;
+        self.round_failed = 0;
+        self.round_started = Instant::now();
+    }
+}
+
+// Directory downloaded files are written to
+enum OutputDir {
+    // Created for this manager and deleted, with everything in it, when the manager is dropped
+    Temporary(TempDir),
+    // Supplied by the caller and left in place when the manager is dropped
+    Persistent(PathBuf),
+}
+
+impl OutputDir {
+    fn path(&self) -> &Path {
+        match self {
+            OutputDir::Temporary(dir) => dir.path(),
+            OutputDir::Persistent(path) => path,
+        }
+    }
+}
+
+// Struct to manage import operations
+struct ImportManager {
+    client: Client,
+    proxy: Option<ProxyConfig>,
+    redirect_policy: RedirectPolicy,
+    output_dir: OutputDir,
+    keep_on_success: bool,
+    import_queue: Arc<Mutex<Vec<ImportItem>>>,
+    import_status: Arc<Mutex<HashMap<String, ImportStatus>>>,
+    verify_content_type: bool,
+    started_at: Arc<Mutex<Option<Instant>>>,
+    cancelled: Arc<AtomicBool>,
+    workers: Mutex<Vec<JoinHandle<()>>>,
+    concurrency: Option<Arc<Mutex<ConcurrencyController>>>,
+    plugins: Arc<PluginRegistry>,
+    // Every item ever queued, so persisted state can rebuild the queue
+    items: Arc<Mutex<HashMap<String, ImportItem>>>,
+    state_path: Option<PathBuf>,
+}
+
+impl ImportManager {
+    // Initialize a new ImportManager that downloads into a temporary directory
+    fn new() -> io::Result<Self> {
+        Self::with_output(OutputDir::Temporary(TempDir::new()?))
+    }
+
+    // Initialize an ImportManager that downloads into `dir`, creating it if needed.
+    // The directory outlives the manager; with `keep_on_success` cleanup leaves
+    // completed downloads in it.
+    fn with_output_dir(dir: impl Into<PathBuf>, keep_on_success: bool) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        let mut manager = Self::with_output(OutputDir::Persistent(dir))?;
+        manager.keep_on_success = keep_on_success;
+        Ok(manager)
+    }
+
+    fn with_output(output_dir: OutputDir) -> io::Result<Self> {
+        let redirect_policy = RedirectPolicy::default();
+        let client = build_import_client(None, redirect_policy)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+
+        Ok(Self {
+            client,
+            proxy: None,
+            redirect_policy,
+            output_dir,
+            keep_on_success: false,
+            import_queue: Arc::new(Mutex::new(Vec::new())),
+            import_status: Arc::new(Mutex::new(HashMap::new())),
+            verify_content_type: false,
+            started_at: Arc::new(Mutex::new(None)),
+            cancelled: Arc::new(AtomicBool::new(false)),
+            workers: Mutex::new(Vec::new()),
+            concurrency: None,
+            plugins: Arc::new(register_import_plugins()),
+            items: Arc::new(Mutex::new(HashMap::new())),
+            state_path: None,
+        })
+    }
+
+    // Rebuild a manager from state persisted by `persist_state_to`. Completed items whose
+    // files are still on disk are skipped; everything else is queued again, and partial
+    // files left in the output directory are resumed with Range requests. The resumed
+    // manager keeps completed downloads on cleanup and keeps persisting to `path`.
+    fn resume_from(path: &Path) -> io::Result<Self> {
+        let data = fs::read_to_string(path)?;
+        let state: PersistedImportState = serde_json::from_str(&data).map_err(|e| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("Import state {} is malformed: {}", path.display(), e))
+        })?;
+
+        let mut manager = Self::with_output_dir(&state.output_dir, true)?;
+        manager.state_path = Some(path.to_owned());
+
+        let mut resumed = 0;
+        for PersistedImportItem { item, status } in state.items {
+            if status == ImportStatus::Completed && state.output_dir.join(&item.filename).is_file() {
+                manager.import_status.lock().unwrap().insert(item.filename.clone(), ImportStatus::Completed);
+                manager.items.lock().unwrap().insert(item.filename.clone(), item);
+            } else {
+                manager.queue_import(item);
+                resumed += 1;
+            }
+        }
+        info!("Resumed import state from {} with {} item(s) left to import", path.display(), resumed);
+        Ok(manager)
+    }
+
+    // Write the import state to `path` now and after every finished item, so a crashed
+    // run can be picked up with `resume_from`. Requires a persistent output directory,
+    // since a temporary one is deleted along with its partial files.
+    fn persist_state_to(&mut self, path: impl Into<PathBuf>) -> io::Result<()> {
+        if let OutputDir::Temporary(_) = self.output_dir {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "import state can only be persisted for a persistent output directory",
+            ));
+        }
+        self.state_path = Some(path.into());
+        self.save_state()
+    }
+
+    // Write the current import state, if persistence is enabled
+    fn save_state(&self) -> io::Result<()> {
+        match &self.state_path {
+            Some(path) => {
+                let status = self.import_status.lock().unwrap();
+                save_import_state(path, self.output_dir(), &self.items, &status)
+            }
+            None => Ok(()),
+        }
+    }
+
+    // Directory downloaded files are written to
+    fn output_dir(&self) -> &Path {
+        self.output_dir.path()
+    }
+
+    // Keep completed downloads when cleaning up; partial and failed files are always removed
+    fn set_keep_on_success(&mut self, keep_on_success: bool) {
+        self.keep_on_success = keep_on_success;
+    }
+
+    // Remove downloaded files according to the cleanup policy
+    fn cleanup(&self) -> io::Result<()> {
+        let keep: Vec<String> = if self.keep_on_success {
+            self.import_status
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(_, status)| **status == ImportStatus::Completed)
+                .map(|(filename, _)| filename.clone())
+                .collect()
+        } else {
+            Vec::new()
+        };
+        cleanup_temp_files(self.output_dir(), &keep)
+    }
+
+    // Replace the plugins that post-process downloaded files; takes effect for the next process_queue
+    fn set_plugins(&mut self, plugins: PluginRegistry) {
+        self.plugins = Arc::new(plugins);
+    }
+
+    // Start with a couple of concurrent imports and let throughput and errors decide how
+    // many run at once, up to `max`, instead of always running CONCURRENT_IMPORTS
+    fn enable_adaptive_concurrency(&mut self, max: usize) {
+        self.concurrency = Some(Arc::new(Mutex::new(ConcurrencyController::new(max))));
+    }
+
+    // Number of imports currently allowed to run at once
+    fn effective_concurrency(&self) -> usize {
+        match &self.concurrency {
+            Some(controller) => controller.lock().unwrap().limit,
+            None => CONCURRENT_IMPORTS,
+        }
+    }
+
+    // Get the flag that stops workers from picking up new items, e.g. for a signal handler
+    fn cancellation_flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.cancelled)
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    // Stop the import: workers finish the item they are on and exit, items still
+    // queued are marked failed, and the output directory is cleaned up
+    fn shutdown(&self) -> io::Result<()> {
+        self.cancelled.store(true, Ordering::SeqCst);
+
+        let workers: Vec<JoinHandle<()>> = self.workers.lock().unwrap().drain(..).collect();
+        for worker in workers {
+            if worker.join().is_err() {
+                warn!("Import worker panicked during shutdown");
+            }
+        }
+
+        let remaining: Vec<ImportItem> = self.import_queue.lock().unwrap().drain(..).collect();
+        let mut status = self.import_status.lock().unwrap();
+        for item in remaining {
+            status.insert(item.filename, ImportStatus::Failed(CANCELLED_MESSAGE.to_string()));
+        }
+        drop(status);
+
+        self.cleanup()
+    }
+
+    // Reject downloads whose content doesn't match their file extension
+    fn set_verify_content_type(&mut self, enabled: bool) {
+        self.verify_content_type = enabled;
+    }
+
+    // Route downloads through a proxy, or directly when `proxy` is None
+    fn set_proxy(&mut self, proxy: Option<&ProxyConfig>) -> Result<(), Box<dyn std::error::Error>> {
+        self.client = build_import_client(proxy, self.redirect_policy)?;
+        self.proxy = proxy.cloned();
+        Ok(())
+    }
+
+    // Limit how many redirects downloads follow and whether they may leave the origin
+    fn set_redirect_policy(&mut self, policy: RedirectPolicy) -> Result<(), Box<dyn std::error::Error>> {
+        self.client = build_import_client(self.proxy.as_ref(), policy)?;
+        self.redirect_policy = policy;
+        Ok(())
+    }
+
+    // Add an item to the import queue
+    fn queue_import(&self, item: ImportItem) {
+        self.items.lock().unwrap().insert(item.filename.clone(), item.clone());
+        let mut queue = self.import_queue.lock().unwrap();
+        queue.push(item.clone());
+        let mut status = self.import_status.lock().unwrap();
+        status.insert(item.filename.clone(), ImportStatus::Pending);
+    }
+
+    // Read a JSON array of {url, filename, size, checksum, algorithm?} entries to import.
+    // Every entry is validated, and the first bad one is reported by its index.
+    fn load_manifest(path: &Path) -> io::Result<Vec<ImportItem>> {
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+        let data = fs::read_to_string(path)?;
+        let entries: Vec<serde_json::Value> = serde_json::from_str(&data)
+            .map_err(|e| invalid(format!("Manifest {} is not a JSON array: {}", path.display(), e)))?;
+
+        let mut filenames = HashSet::new();
+        let mut items = Vec::with_capacity(entries.len());
+        for (index, entry) in entries.into_iter().enumerate() {
+            let entry: ManifestEntry = serde_json::from_value(entry)
+                .map_err(|e| invalid(format!("Manifest entry {} is malformed: {}", index, e)))?;
+            validate_manifest_entry(&entry)
+                .map_err(|reason| invalid(format!("Manifest entry {} is invalid: {}", index, reason)))?;
+            if !filenames.insert(entry.filename.clone()) {
+                return Err(invalid(format!(
+                    "Manifest entry {} is invalid: filename {} is already listed",
+                    index, entry.filename
+                )));
+            }
+            items.push(ImportItem {
+                url: entry.url,
+                filename: entry.filename,
+                size: entry.size,
+                checksum: entry.checksum.to_lowercase(),
+            });
+        }
+        Ok(items)
+    }
+
+    // Load a manifest and queue every item in it, returning how many were queued.
+    // Nothing is queued if any entry is invalid.
+    fn queue_manifest(&self, path: &Path) -> io::Result<usize> {
+        let items = Self::load_manifest(path)?;
+        let count = items.len();
+        for item in items {
+            self.queue_import(item);
+        }
+        info!("Queued {} item(s) from manifest {}", count, path.display());
+        Ok(count)
+    }
+
+    // Process the import queue
+    fn process_queue(&self) {
+        self.started_at.lock().unwrap().get_or_insert_with(Instant::now);
+        if let Err(e) = self.save_state() {
+            warn!("Failed to persist import state: {}", e);
+        }
+
+        let queue = Arc::clone(&self.import_queue);
+        let status = Arc::clone(&self.import_status);
+
+        // In adaptive mode spawn enough workers for the maximum and let the controller gate them
+        let worker_count = match &self.concurrency {
+            Some(controller) => controller.lock().unwrap().max,
+            None => CONCURRENT_IMPORTS,
+        };
+
+        for _ in 0..worker_count {
+            let queue = Arc::clone(&queue);
+            let status = Arc::clone(&status);
+            let client = self.client.clone();
+            let temp_dir = self.output_dir().to_owned();
+            let verify_content_type = self.verify_content_type;
+            let cancelled = Arc::clone(&self.cancelled);
+            let controller = self.concurrency.clone();
+            let plugins = Arc::clone(&self.plugins);
+            let items = Arc::clone(&self.items);
+            let state_path = self.state_path.clone();
+
+            let worker = thread::spawn(move || {
+                'work: loop {
+                    if cancelled.load(Ordering::SeqCst) {
+                        break;
+                    }
+
+                    if let Some(controller) = &controller {
+                        while !controller.lock().unwrap().try_acquire() {
+                            if cancelled.load(Ordering::SeqCst) || queue.lock().unwrap().is_empty() {
+                                break 'work;
+                            }
+                            thread::sleep(ADAPTIVE_SLOT_POLL_INTERVAL);
+                        }
+                    }
+
+                    // Mark the item in progress before releasing the queue so it is never unaccounted for
+                    let item = {
+                        let mut queue = queue.lock().unwrap();
+                        let item = queue.pop();
+                        if let Some(item) = &item {
+                            status.lock().unwrap().insert(item.filename.clone(), ImportStatus::InProgress);
+                        }
+                        item
+                    };
+
+                    match item {
+                        Some(import_item) => {
+                            let import_started = Instant::now();
+                            let result = Self::process_import(&client, &temp_dir, &import_item, verify_content_type)
+                                .and_then(|_| Self::run_import_plugin(&plugins, &temp_dir, &import_item));
+                            let metrics = Metrics::global();
+                            metrics.increment_counter("import_requests_total", 1);
+                            metrics.observe("import_duration_seconds", import_started.elapsed().as_secs_f64());
+                            if result.is_err() {
+                                metrics.increment_counter("import_failures_total", 1);
+                            }
+                            if let Some(controller) = &controller {
+                                let mut controller = controller.lock().unwrap();
+                                controller.record(result.is_ok());
+                                controller.release();
+                            }
+                            let mut status = status.lock().unwrap();
+                            status.insert(
+                                import_item.filename.clone(),
+                                match result {
+                                    Ok(_) => ImportStatus::Completed,
+                                    Err(e) => ImportStatus::Failed(e.to_string()),
+                                },
+                            );
+                            if let Some(path) = &state_path {
+                                if let Err(e) = save_import_state(path, &temp_dir, &items, &status) {
+                                    warn!("Failed to persist import state: {}", e);
+                                }
+                            }
+                        }
+                        None => {
+                            if let Some(controller) = &controller {
+                                controller.lock().unwrap().release();
+                            }
+                            break;
+                        }
+                    }
+                }
+            });
+            self.workers.lock().unwrap().push(worker);
+        }
+    }
+
+    // Process a single import item
+    fn process_import(
+        client: &Client,
+        temp_dir: &Path,
+        item: &ImportItem,
+        verify_content_type: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        info!("Starting import for: {}", item.filename);
+
+        // Download under a partial name first so an interrupted write never looks complete
+        let file_path = temp_dir.join(&item.filename);
+        let partial_path = temp_dir.join(format!("{}{}", item.filename, PARTIAL_FILE_SUFFIX));
+
+        // Ask for the rest of a partial file left by an interrupted run
+        let resume_offset = fs::metadata(&partial_path).map(|metadata| metadata.len()).unwrap_or(0);
+        let mut request = client.get(&item.url).timeout(IMPORT_TIMEOUT);
+        if resume_offset > 0 {
+            request = request.header(reqwest::header::RANGE, format!("bytes={}-", resume_offset));
+        }
+
+        // Download the file
+        let response = request
+            .send()
+            .map_err(into_redirect_error)?
+            .error_for_status()?;
+
+        // Servers that ignore the Range header send the whole file, so start over
+        let resumed = resume_offset > 0 && response.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+        let already_downloaded = if resumed { resume_offset as usize } else { 0 };
+        if resumed {
+            info!("Resuming import for {} from byte {}", item.filename, resume_offset);
+        }
+
+        // Abort before reading the body if the server disagrees with the manifest.
+        // Chunked responses have no Content-Length and rely on the checks below.
+        check_content_length(item.size.saturating_sub(already_downloaded), response.content_length())?;
+
+        let mut file = if resumed {
+            OpenOptions::new().append(true).open(&partial_path)?
+        } else {
+            File::create(&partial_path)?
+        };
+        let received = io::copy(&mut response.take((MAX_IMPORT_SIZE + 1) as u64), &mut file)?;
+        drop(file);
+        Metrics::global().increment_counter("import_bytes_total", received);
+
+        // Files that fail verification are discarded rather than resumed later
+        let reject = |message: String| -> Box<dyn std::error::Error> {
+            let _ = fs::remove_file(&partial_path);
+            message.into()
+        };
+
+        // Verify file size
+        if already_downloaded + received as usize > MAX_IMPORT_SIZE {
+            return Err(reject(format!("File size exceeds maximum allowed size of {} bytes", MAX_IMPORT_SIZE)));
+        }
+
+        let buffer = fs::read(&partial_path)?;
+
+        // Verify the content matches the expected file type
+        if verify_content_type {
+            check_content_type(&item.filename, &buffer).map_err(|e| reject(e.to_string()))?;
+        }
+
+        // Verify checksum over the whole file, including any resumed part
+        let calculated_checksum = format!("{:x}", Sha256::digest(&buffer));
+        if calculated_checksum != item.checksum {
+            return Err(reject("Checksum verification failed".to_string()));
+        }
+
+        fs::rename(&partial_path, &file_path)?;
+
+        info!("Import completed successfully for: {}", item.filename);
+        Ok(())
+    }
+
+    // Hand a downloaded file to the first registered plugin that supports it.
+    // Files no plugin supports are left as downloaded.
+    fn run_import_plugin(
+        plugins: &PluginRegistry,
+        temp_dir: &Path,
+        item: &ImportItem,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let source = ImportSource::LocalFile(temp_dir.join(&item.filename));
+        if let Some(plugin) = plugins.find_for(&source) {
+            info!("Processing {} with {} {}", item.filename, plugin.name(), plugin.version());
+            plugin.process_import(&source, temp_dir)?;
+        }
+        Ok(())
+    }
+
+    // Get the current progress, with an ETA once at least one item has finished
+    fn progress_snapshot(&self) -> ImportProgress {
+        let elapsed = self.started_at.lock().unwrap().map(|started| started.elapsed());
+        let status = self.import_status.lock().unwrap();
+        progress_from_status(&status, elapsed, self.effective_concurrency())
+    }
+
+    // Get the status of a single item, if it has been queued
+    fn status_of(&self, filename: &str) -> Option<ImportStatus> {
+        self.import_status.lock().unwrap().get(filename).cloned()
+    }
+
+    // Filenames of items that have not started yet
+    fn pending(&self) -> Vec<String> {
+        self.filenames_where(|s| *s == ImportStatus::Pending)
+    }
+
+    // Filenames of items currently being downloaded
+    fn in_progress(&self) -> Vec<String> {
+        self.filenames_where(|s| *s == ImportStatus::InProgress)
+    }
+
+    // Filenames of items that finished successfully
+    fn completed(&self) -> Vec<String> {
+        self.filenames_where(|s| *s == ImportStatus::Completed)
+    }
+
+    // Filenames of items that failed, for any reason
+    fn failed(&self) -> Vec<String> {
+        self.filenames_where(|s| matches!(s, ImportStatus::Failed(_)))
+    }
+
+    // Sorted filenames whose status matches `predicate`, copied out so the lock
+    // is released before the caller iterates
+    fn filenames_where(&self, predicate: impl Fn(&ImportStatus) -> bool) -> Vec<String> {
+        let mut filenames: Vec<String> = {
+            let status = self.import_status.lock().unwrap();
+            status
+                .iter()
+                .filter(|(_, s)| predicate(s))
+                .map(|(filename, _)| filename.clone())
+                .collect()
+        };
+        filenames.sort();
+        filenames
+    }
+
+    // Poll until every item has finished, the import is cancelled, or `deadline` passes.
+    // Items still pending at the deadline are marked failed and their filenames returned.
+    fn wait_for_completion(&self, deadline: Duration, poll_interval: Duration) -> Vec<String> {
+        let start_time = Instant::now();
+        loop {
+            let progress = self.progress_snapshot();
+            info!("Import progress: {:.2}%", progress.percent);
+
+            if progress.completed + progress.failed == progress.total || self.is_cancelled() {
+                return Vec::new();
+            }
+            if start_time.elapsed() >= deadline {
+                break;
+            }
+            thread::sleep(poll_interval);
+        }
+
+        let mut status = self.import_status.lock().unwrap();
+        let mut stragglers: Vec<String> = status
+            .iter()
+            .filter(|(_, s)| matches!(s, ImportStatus::Pending | ImportStatus::InProgress))
+            .map(|(filename, _)| filename.clone())
+            .collect();
+        stragglers.sort();
+
+        for filename in &stragglers {
+            warn!("Import of {} did not finish before the deadline", filename);
+            status.insert(filename.clone(), ImportStatus::Failed(TIMED_OUT_MESSAGE.to_string()));
+        }
+        stragglers
+    }
+
+    // Generate a detailed report of the import process
+    fn generate_report(&self) -> String {
+        let status = self.import_status.lock().unwrap();
+        let mut report = String::new();
+
+        report.push_str("Import Test Report for Aluminum Web Browser\n");
+        report.push_str("===========================================\n\n");
+
+        let now: DateTime<Utc> = Utc::now();
+        report.push_str(&format!("Generated on: {}\n\n", now.format("%Y-%m-%d %H:%M:%S UTC")));
+
+        for (filename, status) in status.iter() {
+            report.push_str(&format!("File: {}\n", filename));
+            report.push_str(&format!("Status: {:?}\n", status));
+            report.push_str("\n");
+        }
+
+        let mut timed_out: Vec<&String> = status
+            .iter()
+            .filter(|(_, s)| **s == ImportStatus::Failed(TIMED_OUT_MESSAGE.to_string()))
+            .map(|(filename, _)| filename)
+            .collect();
+        if !timed_out.is_empty() {
+            timed_out.sort();
+            report.push_str(&format!("Timed out ({} file(s) never finished):\n", timed_out.len()));
+            for filename in timed_out {
+                report.push_str(&format!("  {}\n", filename));
+            }
+        }
+
+        report
+    }
+}
+
+// Main test function
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use redirect::RedirectError;
+
+    #[test]
+    fn test_import_system() {
+        // Initialize logging
+        env_logger::init();
+
+        // Create an ImportManager instance
+        let import_manager = ImportManager::new().expect("Failed to create ImportManager");
+
+        // Generate test import items
+        let test_items = generate_test_import_items();
+
+        // Queue import items
+        for item in test_items {
+            import_manager.queue_import(item);
+        }
+
+        // Process the import queue
+        import_manager.process_queue();
+
+        // Wait for all imports to complete
+        thread::sleep(Duration::from_secs(10));
+
+        // Generate and print the report
+        let report = import_manager.generate_report();
+        println!("{}", report);
+
+        // Verify import results
+        let status = import_manager.import_status.lock().unwrap();
+        for (filename, import_status) in status.iter() {
+            assert!(
+                matches!(import_status, ImportStatus::Completed),
+                "Import failed for file: {}",
+                filename
+            );
+        }
+    }
+
+    #[test]
+    fn test_content_type_accepts_real_zip() {
+        let mut buffer = io::Cursor::new(Vec::new());
+        {
+            let mut writer = zip::ZipWriter::new(&mut buffer);
+            writer
+                .start_file("manifest.json", zip::write::FileOptions::default())
+                .unwrap();
+            writer.write_all(b"{}").unwrap();
+            writer.finish().unwrap();
+        }
+
+        assert!(check_content_type("bundle.zip", buffer.get_ref()).is_ok());
+    }
+
+    #[test]
+    fn test_content_type_rejects_html_masquerading_as_zip() {
+        let html = b"<!DOCTYPE html><html><body>404 Not Found</body></html>";
+
+        let error = check_content_type("bundle.zip", html).unwrap_err();
+        assert!(error.contains("application/zip"));
+        assert!(error.contains("text/html"));
+    }
+
+    #[test]
+    fn test_progress_snapshot_eta() {
+        let import_manager = ImportManager::new().expect("Failed to create ImportManager");
+        {
+            let mut status = import_manager.import_status.lock().unwrap();
+            for i in 0..10 {
+                status.insert(format!("file_{}.zip", i), ImportStatus::Pending);
+            }
+        }
+
+        // No ETA before processing starts or before anything finishes
+        assert_eq!(import_manager.progress_snapshot().eta, None);
+        *import_manager.started_at.lock().unwrap() = Some(Instant::now() - Duration::from_secs(4));
+        assert_eq!(import_manager.progress_snapshot().eta, None);
+
+        // Two items in four seconds leaves eight items at two seconds each
+        {
+            let mut status = import_manager.import_status.lock().unwrap();
+            status.insert("file_0.zip".to_string(), ImportStatus::Completed);
+            status.insert("file_1.zip".to_string(), ImportStatus::Failed("Checksum verification failed".to_string()));
+        }
+        let progress = import_manager.progress_snapshot();
+
+        assert_eq!(progress.total, 10);
+        assert_eq!(progress.completed, 1);
+        assert_eq!(progress.failed, 1);
+        assert!((progress.percent - 20.0).abs() < f64::EPSILON);
+        let eta = progress.eta.expect("ETA should be available once items finish").as_secs_f64();
+        assert!((eta - 16.0).abs() < 0.5, "ETA was {}s", eta);
+    }
+
+    #[test]
+    fn test_wait_for_completion_fails_stragglers() {
+        let import_manager = ImportManager::new().expect("Failed to create ImportManager");
+        import_manager.queue_import(ImportItem {
+            url: "https://www.Aluminum.com/never_served.zip".to_string(),
+            filename: "never_served.zip".to_string(),
+            size: 1024,
+            checksum: String::new(),
+        });
+        import_manager
+            .import_status
+            .lock()
+            .unwrap()
+            .insert("done.zip".to_string(), ImportStatus::Completed);
+
+        // The queue is never processed, so the item stays pending until the deadline
+        let started = Instant::now();
+        let stragglers = import_manager.wait_for_completion(Duration::from_millis(200), Duration::from_millis(20));
+
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert_eq!(stragglers, vec!["never_served.zip".to_string()]);
+        assert_eq!(
+            import_manager.import_status.lock().unwrap()["never_served.zip"],
+            ImportStatus::Failed(TIMED_OUT_MESSAGE.to_string())
+        );
+        let report = import_manager.generate_report();
+        assert!(report.contains("Timed out (1 file(s) never finished):\n  never_served.zip\n"));
+        assert!(!report.contains("  done.zip\n"));
+    }
+
+    #[test]
+    fn test_downloads_traverse_configured_proxy() {
+        use std::io::{BufRead, BufReader};
+        use std::net::TcpListener;
+
+        let body = b"proxied import payload";
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind mock proxy");
+        let proxy_addr = listener.local_addr().unwrap();
+
+        // Mock proxy: record the request line and answer it directly
+        let proxy_thread = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                if header == "\r\n" || header.is_empty() {
+                    break;
+                }
+            }
+            let mut stream = reader.into_inner();
+            write!(stream, "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", body.len()).unwrap();
+            stream.write_all(body).unwrap();
+            request_line
+        });
+
+        let mut import_manager = ImportManager::new().expect("Failed to create ImportManager");
+        let proxy = ProxyConfig::new(&format!("http://{}", proxy_addr), None).unwrap();
+        import_manager.set_proxy(Some(&proxy)).unwrap();
+
+        let item = ImportItem {
+            url: "http://imports.aluminum.test/bookmarks.zip".to_string(),
+            filename: "bookmarks.zip".to_string(),
+            size: body.len(),
+            checksum: format!("{:x}", Sha256::digest(body)),
+        };
+        ImportManager::process_import(&import_manager.client, import_manager.output_dir(), &item, false)
+            .expect("import through proxy failed");
+
+        let request_line = proxy_thread.join().unwrap();
+        assert_eq!(request_line.trim_end(), "GET http://imports.aluminum.test/bookmarks.zip HTTP/1.1");
+        assert_eq!(fs::read(import_manager.output_dir().join("bookmarks.zip")).unwrap(), body);
+    }
+
+    // Serve a single raw HTTP response on a local port and return the URL to fetch
+    fn serve_once(response: Vec<u8>) -> (String, JoinHandle<()>) {
+        serve_once_gated(response, None)
+    }
+
+    // Like serve_once, but hold the response back until `release` fires
+    fn serve_once_gated(response: Vec<u8>, release: Option<std::sync::mpsc::Receiver<()>>) -> (String, JoinHandle<()>) {
+        use std::io::{BufRead, BufReader};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind mock server");
+        let url = format!("http://{}/payload.zip", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                if header == "\r\n" || header.is_empty() {
+                    break;
+                }
+            }
+            if let Some(release) = release {
+                let _ = release.recv();
+            }
+            // The client may hang up early on a mismatch, so write errors are expected
+            let _ = reader.into_inner().write_all(&response);
+        });
+        (url, server)
+    }
+
+    fn content_length_item(url: String, size: usize, body: &[u8]) -> ImportItem {
+        ImportItem {
+            url,
+            filename: "payload.zip".to_string(),
+            size,
+            checksum: format!("{:x}", Sha256::digest(body)),
+        }
+    }
+
+    #[test]
+    fn test_content_length_matching_manifest_is_accepted() {
+        let body = vec![b'x'; 1000];
+        let mut response = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", body.len()).into_bytes();
+        response.extend_from_slice(&body);
+        let (url, server) = serve_once(response);
+
+        let import_manager = ImportManager::new().expect("Failed to create ImportManager");
+        // 1005 is within 1% of the 1000 bytes served
+        let item = content_length_item(url, 1005, &body);
+        ImportManager::process_import(&import_manager.client, import_manager.output_dir(), &item, false)
+            .expect("matching Content-Length was rejected");
+        server.join().unwrap();
+
+        assert_eq!(fs::read(import_manager.output_dir().join("payload.zip")).unwrap(), body);
+    }
+
+    #[test]
+    fn test_content_length_mismatch_aborts_before_download() {
+        let body = vec![b'x'; 1000];
+        let mut response = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", body.len()).into_bytes();
+        response.extend_from_slice(&body);
+        let (url, server) = serve_once(response);
+
+        let import_manager = ImportManager::new().expect("Failed to create ImportManager");
+        let item = content_length_item(url, 4096, &body);
+        let error = ImportManager::process_import(&import_manager.client, import_manager.output_dir(), &item, false)
+            .unwrap_err();
+        server.join().unwrap();
+
+        assert!(error.to_string().contains("Content-Length mismatch"), "{}", error);
+        assert!(error.to_string().contains("1000") && error.to_string().contains("4096"));
+        assert!(!import_manager.output_dir().join("payload.zip").exists());
+    }
+
+    #[test]
+    fn test_missing_content_length_falls_back_to_post_download_checks() {
+        let body = vec![b'x'; 1000];
+        let mut response = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n".to_vec();
+        response.extend_from_slice(format!("{:x}\r\n", body.len()).as_bytes());
+        response.extend_from_slice(&body);
+        response.extend_from_slice(b"\r\n0\r\n\r\n");
+        let (url, server) = serve_once(response);
+
+        // The manifest size is wrong, but without a Content-Length only the checksum decides
+        let import_manager = ImportManager::new().expect("Failed to create ImportManager");
+        let item = content_length_item(url, 4096, &body);
+        ImportManager::process_import(&import_manager.client, import_manager.output_dir(), &item, false)
+            .expect("chunked response was rejected");
+        server.join().unwrap();
+
+        assert_eq!(fs::read(import_manager.output_dir().join("payload.zip")).unwrap(), body);
+        assert_eq!(check_content_length(4096, None), Ok(()));
+    }
+
+    // Serve `/hop/N` as a redirect to `/hop/N-1` and `/hop/0` as `body`, until the test ends.
+    // `/away` redirects to `away_base`. Returns the server's base URL.
+    fn serve_redirect_chain(body: &'static [u8], away_base: Option<String>) -> String {
+        use std::io::{BufRead, BufReader};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind mock server");
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut reader = BufReader::new(stream.unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header == "\r\n" || header.is_empty() {
+                        break;
+                    }
+                }
+
+                let path = request_line.split_whitespace().nth(1).unwrap_or("/").to_string();
+                let location = match path.strip_prefix("/hop/").and_then(|n| n.parse::<usize>().ok()) {
+                    Some(0) => None,
+                    Some(n) => Some(format!("/hop/{}", n - 1)),
+                    None => away_base.as_ref().map(|away| format!("{}/hop/0", away)),
+                };
+                let mut stream = reader.into_inner();
+                let _ = match location {
+                    Some(location) => write!(
+                        stream,
+                        "HTTP/1.1 302 Found\r\nLocation: {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                        location
+                    ),
+                    None => write!(stream, "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", body.len())
+                        .and_then(|_| stream.write_all(body)),
+                };
+            }
+        });
+        base
+    }
+
+    #[test]
+    fn test_redirect_policy_limits_hops_and_origins() {
+        let body: &'static [u8] = b"redirected payload";
+        let base = serve_redirect_chain(body, None);
+        let item = |url: String| ImportItem {
+            url,
+            filename: "payload.zip".to_string(),
+            size: body.len(),
+            checksum: format!("{:x}", Sha256::digest(body)),
+        };
+        let mut import_manager = ImportManager::new().expect("Failed to create ImportManager");
+        let import = |manager: &ImportManager, url: String| {
+            ImportManager::process_import(&manager.client, manager.output_dir(), &item(url), false)
+        };
+
+        // Three hops are allowed by a three-hop limit but not by a two-hop one
+        import_manager.set_redirect_policy(RedirectPolicy { max_hops: 3, same_origin_only: false }).unwrap();
+        import(&import_manager, format!("{}/hop/3", base)).expect("three redirects within the limit failed");
+        import_manager.set_redirect_policy(RedirectPolicy { max_hops: 2, same_origin_only: false }).unwrap();
+        let error = import(&import_manager, format!("{}/hop/3", base)).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<RedirectError>(),
+            Some(&RedirectError::TooManyRedirects { max_hops: 2 })
+        );
+
+        // A different port is a different origin
+        let cross_origin = serve_redirect_chain(body, Some(base.clone()));
+        import_manager.set_redirect_policy(RedirectPolicy { max_hops: 5, same_origin_only: true }).unwrap();
+        import(&import_manager, format!("{}/hop/2", cross_origin)).expect("same-origin redirects were refused");
+        let error = import(&import_manager, format!("{}/away", cross_origin)).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<RedirectError>(),
+            Some(&RedirectError::CrossOriginRedirect {
+                from: format!("{}/away", cross_origin),
+                to: format!("{}/hop/0", base),
+            })
+        );
+
+        import_manager.set_redirect_policy(RedirectPolicy::default()).unwrap();
+        import(&import_manager, format!("{}/away", cross_origin)).expect("default policy refused a cross-origin redirect");
+    }
+
+    // Leave one completed, one failed and one partial download in the manager's output directory
+    fn seed_output_dir(import_manager: &ImportManager) {
+        let dir = import_manager.output_dir();
+        fs::write(dir.join("done.zip"), b"complete").unwrap();
+        fs::write(dir.join("broken.zip"), b"bad checksum").unwrap();
+        fs::write(dir.join("next.zip.part"), b"half").unwrap();
+        let mut status = import_manager.import_status.lock().unwrap();
+        status.insert("done.zip".to_string(), ImportStatus::Completed);
+        status.insert("broken.zip".to_string(), ImportStatus::Failed("Checksum verification failed".to_string()));
+        status.insert("next.zip".to_string(), ImportStatus::InProgress);
+    }
+
+    #[test]
+    fn test_persistent_output_dir_keeps_completed_files() {
+        let base = TempDir::new().unwrap();
+        let output = base.path().join("imports");
+        let import_manager = ImportManager::with_output_dir(&output, true).expect("Failed to create ImportManager");
+        assert_eq!(import_manager.output_dir(), output.as_path());
+        seed_output_dir(&import_manager);
+
+        import_manager.cleanup().unwrap();
+        drop(import_manager);
+
+        let mut remaining: Vec<String> = fs::read_dir(&output)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        remaining.sort();
+        assert_eq!(remaining, vec!["done.zip".to_string()]);
+    }
+
+    #[test]
+    fn test_temporary_output_dir_is_removed() {
+        let mut import_manager = ImportManager::new().expect("Failed to create ImportManager");
+        seed_output_dir(&import_manager);
+
+        // Without keep_on_success cleanup empties the directory
+        import_manager.cleanup().unwrap();
+        assert_eq!(fs::read_dir(import_manager.output_dir()).unwrap().count(), 0);
+
+        // Even completed files kept by cleanup go with the temporary directory
+        import_manager.set_keep_on_success(true);
+        seed_output_dir(&import_manager);
+        import_manager.cleanup().unwrap();
+        assert!(import_manager.output_dir().join("done.zip").exists());
+        let output = import_manager.output_dir().to_path_buf();
+        drop(import_manager);
+        assert!(!output.exists());
+    }
+
+    #[test]
+    fn test_status_queries_group_items_by_state() {
+        let import_manager = ImportManager::new().expect("Failed to create ImportManager");
+        for filename in ["a.zip", "b.zip", "c.zip", "d.zip", "e.zip", "f.zip"] {
+            import_manager.queue_import(ImportItem {
+                url: format!("https://example.com/{}", filename),
+                filename: filename.to_string(),
+                size: 0,
+                checksum: String::new(),
+            });
+        }
+        {
+            let mut status = import_manager.import_status.lock().unwrap();
+            status.insert("b.zip".to_string(), ImportStatus::InProgress);
+            status.insert("c.zip".to_string(), ImportStatus::Completed);
+            status.insert("d.zip".to_string(), ImportStatus::Completed);
+            status.insert("e.zip".to_string(), ImportStatus::Failed("Checksum verification failed".to_string()));
+        }
+
+        assert_eq!(import_manager.pending(), vec!["a.zip", "f.zip"]);
+        assert_eq!(import_manager.in_progress(), vec!["b.zip"]);
+        assert_eq!(import_manager.completed(), vec!["c.zip", "d.zip"]);
+        assert_eq!(import_manager.failed(), vec!["e.zip"]);
+        assert_eq!(import_manager.status_of("c.zip"), Some(ImportStatus::Completed));
+        assert_eq!(
+            import_manager.status_of("e.zip"),
+            Some(ImportStatus::Failed("Checksum verification failed".to_string()))
+        );
+        assert_eq!(import_manager.status_of("missing.zip"), None);
+    }
+
+    #[test]
+    fn test_worker_marks_item_in_progress_while_downloading() {
+        let body = vec![b'x'; 100];
+        let mut response = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", body.len()).into_bytes();
+        response.extend_from_slice(&body);
+        let (release, released) = std::sync::mpsc::channel();
+        let (url, server) = serve_once_gated(response, Some(released));
+
+        let import_manager = ImportManager::new().expect("Failed to create ImportManager");
+        import_manager.queue_import(content_length_item(url, body.len(), &body));
+        import_manager.process_queue();
+
+        // The server is holding the response, so the worker is stuck mid-download
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while import_manager.in_progress().is_empty() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(import_manager.in_progress(), vec!["payload.zip"]);
+        assert!(import_manager.pending().is_empty());
+
+        release.send(()).unwrap();
+        import_manager.wait_for_completion(Duration::from_secs(30), Duration::from_millis(10));
+        server.join().unwrap();
+        assert!(import_manager.in_progress().is_empty());
+        assert_eq!(import_manager.completed(), vec!["payload.zip"]);
+    }
+
+    #[test]
+    fn test_status_queries_on_empty_manager() {
+        let import_manager = ImportManager::new().expect("Failed to create ImportManager");
+
+        assert!(import_manager.pending().is_empty());
+        assert!(import_manager.in_progress().is_empty());
+        assert!(import_manager.completed().is_empty());
+        assert!(import_manager.failed().is_empty());
+        assert_eq!(import_manager.status_of("a.zip"), None);
+    }
+
+    #[test]
+    fn test_queue_manifest_loads_valid_entries() {
+        let dir = TempDir::new().expect("Failed to create temp dir");
+        let manifest = dir.path().join("manifest.json");
+        let checksum = format!("{:x}", Sha256::digest(b"bookmarks"));
+        fs::write(
+            &manifest,
+            serde_json::json!([
+                { "url": "https://example.com/bookmarks.zip", "filename": "bookmarks.zip", "size": 9, "checksum": checksum },
+                {
+                    "url": "https://example.com/history.gz",
+                    "filename": "history.gz",
+                    "size": 2048,
+                    "checksum": checksum.to_uppercase(),
+                    "algorithm": "SHA256"
+                }
+            ])
+            .to_string(),
+        )
+        .unwrap();
+
+        let items = ImportManager::load_manifest(&manifest).expect("valid manifest should load");
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].filename, "bookmarks.zip");
+        assert_eq!(items[1].size, 2048);
+        assert_eq!(items[1].checksum, checksum);
+
+        let import_manager = ImportManager::new().expect("Failed to create ImportManager");
+        assert_eq!(import_manager.queue_manifest(&manifest).unwrap(), 2);
+        assert_eq!(import_manager.pending(), vec!["bookmarks.zip", "history.gz"]);
+    }
+
+    #[test]
+    fn test_load_manifest_reports_offending_entry() {
+        let dir = TempDir::new().expect("Failed to create temp dir");
+        let checksum = format!("{:x}", Sha256::digest(b"bookmarks"));
+        let valid = serde_json::json!({
+            "url": "https://example.com/bookmarks.zip",
+            "filename": "bookmarks.zip",
+            "size": 9,
+            "checksum": checksum
+        });
+        let cases = [
+            (serde_json::json!({ "url": "", "filename": "a.zip", "size": 9, "checksum": checksum }), "url is empty"),
+            (serde_json::json!({ "url": "https://example.com/a.zip", "filename": "a.zip", "size": 0, "checksum": checksum }), "size 0"),
+            (serde_json::json!({ "url": "https://example.com/a.zip", "filename": "a.zip", "size": "big" }), "malformed"),
+            (serde_json::json!({ "url": "https://example.com/a.zip", "filename": "../a.zip", "size": 9, "checksum": checksum }), "plain file name"),
+        ];
+
+        let import_manager = ImportManager::new().expect("Failed to create ImportManager");
+        for (entry, reason) in cases {
+            let manifest = dir.path().join("manifest.json");
+            fs::write(&manifest, serde_json::json!([valid, entry]).to_string()).unwrap();
+
+            let error = import_manager.queue_manifest(&manifest).expect_err("bad entry should be rejected");
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+            let message = error.to_string();
+            assert!(message.contains("entry 1"), "{}", message);
+            assert!(message.contains(reason), "{}", message);
+        }
+        assert!(import_manager.pending().is_empty());
+    }
+
+    #[test]
+    fn test_resume_skips_completed_items() {
+        let body = vec![b'x'; 1000];
+        let mut response = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", body.len()).into_bytes();
+        response.extend_from_slice(&body);
+        let (url, server) = serve_once(response);
+
+        let base = TempDir::new().unwrap();
+        let state_path = base.path().join("import_state.json");
+        let mut import_manager = ImportManager::with_output_dir(base.path().join("imports"), true)
+            .expect("Failed to create ImportManager");
+        import_manager.persist_state_to(&state_path).unwrap();
+        import_manager.queue_import(content_length_item(url, body.len(), &body));
+        // Nothing listens on port 9, so this item fails and is retried after the restart
+        import_manager.queue_import(ImportItem {
+            url: "http://127.0.0.1:9/missing.zip".to_string(),
+            filename: "missing.zip".to_string(),
+            size: 10,
+            checksum: String::new(),
+        });
+        import_manager.process_queue();
+        import_manager.wait_for_completion(Duration::from_secs(30), Duration::from_millis(10));
+        server.join().unwrap();
+        assert_eq!(import_manager.completed(), vec!["payload.zip".to_string()]);
+        drop(import_manager);
+
+        // The mock server has shut down, so re-downloading payload.zip would fail
+        let resumed = ImportManager::resume_from(&state_path).expect("Failed to resume import state");
+        assert_eq!(resumed.completed(), vec!["payload.zip".to_string()]);
+        assert_eq!(resumed.pending(), vec!["missing.zip".to_string()]);
+        resumed.process_queue();
+        resumed.wait_for_completion(Duration::from_secs(30), Duration::from_millis(10));
+        assert_eq!(resumed.completed(), vec!["payload.zip".to_string()]);
+        assert_eq!(resumed.failed(), vec!["missing.zip".to_string()]);
+        assert_eq!(fs::read(resumed.output_dir().join("payload.zip")).unwrap(), body);
+    }
+
+    #[test]
+    fn test_partial_download_is_resumed_and_verified() {
+        let body: Vec<u8> = (0..1000u32).map(|i| (i % 251) as u8).collect();
+        let (head, tail) = body.split_at(400);
+        let mut response = format!(
+            "HTTP/1.1 206 Partial Content\r\nContent-Length: {}\r\nContent-Range: bytes 400-999/1000\r\nConnection: close\r\n\r\n",
+            tail.len()
+        )
+        .into_bytes();
+        response.extend_from_slice(tail);
+        let (url, server) = serve_once(response);
+
+        let base = TempDir::new().unwrap();
+        let import_manager = ImportManager::with_output_dir(base.path(), true).expect("Failed to create ImportManager");
+        fs::write(base.path().join("payload.zip.part"), head).unwrap();
+
+        let item = content_length_item(url, body.len(), &body);
+        ImportManager::process_import(&import_manager.client, import_manager.output_dir(), &item, false)
+            .expect("resumed download was rejected");
+        server.join().unwrap();
+
+        assert_eq!(fs::read(base.path().join("payload.zip")).unwrap(), body);
+        assert!(!base.path().join("payload.zip.part").exists());
+    }
+
+    #[test]
+    fn test_shutdown_stops_workers_and_removes_partial_files() {
+        // Nothing listens on this port, so every download fails quickly
+        let closed_port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let import_manager = ImportManager::new().expect("Failed to create ImportManager");
+        for i in 1..=50 {
+            import_manager.queue_import(ImportItem {
+                url: format!("http://127.0.0.1:{}/file_{}.zip", closed_port, i),
+                filename: format!("file_{}.zip", i),
+                size: 1024,
+                checksum: String::new(),
+            });
+        }
+        // Leftovers of a download that was interrupted mid-write
+        fs::write(import_manager.output_dir().join("file_0.zip.part"), b"half a zip").unwrap();
+
+        import_manager.process_queue();
+        import_manager.cancellation_flag().store(true, Ordering::SeqCst);
+        import_manager.shutdown().expect("shutdown failed");
+
+        assert!(import_manager.workers.lock().unwrap().is_empty());
+        assert!(import_manager.import_queue.lock().unwrap().is_empty());
+        assert_eq!(fs::read_dir(import_manager.output_dir()).unwrap().count(), 0);
+        let status = import_manager.import_status.lock().unwrap();
+        assert_eq!(status.len(), 50);
+        assert!(status.values().all(|s| matches!(s, ImportStatus::Failed(_))));
+    }
+
+    #[test]
+    fn test_adaptive_concurrency_backs_off_as_errors_rise() {
+        let mut import_manager = ImportManager::new().expect("Failed to create ImportManager");
+        assert_eq!(import_manager.progress_snapshot().concurrency, CONCURRENT_IMPORTS);
+        import_manager.enable_adaptive_concurrency(8);
+        assert_eq!(import_manager.progress_snapshot().concurrency, ADAPTIVE_INITIAL_CONCURRENCY);
+
+        let controller = Arc::clone(import_manager.concurrency.as_ref().unwrap());
+        let run_round = |failures: usize| {
+            let mut controller = controller.lock().unwrap();
+            let limit = controller.limit;
+            for i in 0..limit {
+                controller.record(i >= failures);
+            }
+            controller.limit
+        };
+
+        // A clean first round has no earlier throughput to compare against, so it grows
+        assert_eq!(run_round(0), ADAPTIVE_INITIAL_CONCURRENCY + 1);
+        let mut limit = run_round(0);
+        while limit < 8 {
+            let next = run_round(0);
+            if next == limit {
+                break;
+            }
+            limit = next;
+        }
+        assert!(limit > ADAPTIVE_INITIAL_CONCURRENCY);
+
+        // Error rates at or under the threshold never shrink the limit
+        let failures_under_threshold = (limit as f64 * ADAPTIVE_ERROR_RATE_THRESHOLD) as usize;
+        assert!(run_round(failures_under_threshold) >= limit);
+
+        // Rising error rates halve the limit every round down to a single import
+        let mut limits = vec![import_manager.effective_concurrency()];
+        for _ in 0..4 {
+            let current = *limits.last().unwrap();
+            limits.push(run_round(current / 2 + 1));
+        }
+        assert!(limits.windows(2).all(|pair| pair[1] == (pair[0] / 2).max(1)), "{:?}", limits);
+        assert_eq!(*limits.last().unwrap(), 1);
+        assert_eq!(import_manager.progress_snapshot().concurrency, 1);
+    }
+
+    #[test]
+    fn test_invalid_proxy_url_rejected() {
+        assert!(ProxyConfig::new("not a proxy url", None).is_err());
+        assert!(ProxyConfig::new("ftp://proxy.example.com:21", None).is_err());
+        assert!(ProxyConfig::new("socks5://127.0.0.1:1080", Some(("user".into(), "pass".into()))).is_ok());
+    }
+
+    // Plugin that claims every ZIP source and records the files it was given
+    struct RecordingZipPlugin {
+        seen: Arc<Mutex<Vec<PathBuf>>>,
+    }
+
+    impl ImportPlugin for RecordingZipPlugin {
+        fn name(&self) -> &str {
+            "Recording ZIP Plugin"
+        }
+
+        fn version(&self) -> &str {
+            "0.1.0"
+        }
+
+        fn supports_source(&self, source: &ImportSource) -> bool {
+            match source {
+                ImportSource::LocalFile(path) => path.extension().map_or(false, |ext| ext == "zip"),
+                ImportSource::RemoteUrl(url) => url.ends_with(".zip"),
+                ImportSource::CloudStorage(..) => false,
+            }
+        }
+
+        fn process_import(&self, source: &ImportSource, _destination: &Path) -> io::Result<()> {
+            if let ImportSource::LocalFile(path) = source {
+                self.seen.lock().unwrap().push(path.clone());
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_plugin_registry_selects_first_matching_plugin() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let zip_source = ImportSource::RemoteUrl("https://example.com/bookmarks.zip".to_string());
+
+        // Registered before the built-in, the custom plugin wins
+        let mut registry = PluginRegistry::new();
+        registry.register(Box::new(RecordingZipPlugin { seen: Arc::clone(&seen) }));
+        registry.register(Box::new(ZipImportPlugin));
+        assert_eq!(registry.find_for(&zip_source).unwrap().name(), "Recording ZIP Plugin");
+
+        // Registered after it, the built-in wins
+        let mut registry = register_import_plugins();
+        registry.register(Box::new(RecordingZipPlugin { seen }));
+        assert_eq!(registry.find_for(&zip_source).unwrap().name(), "ZIP Import Plugin");
+
+        let html_source = ImportSource::RemoteUrl("https://example.com/bookmarks.html".to_string());
+        assert!(registry.find_for(&html_source).is_none());
+    }
+
+    #[test]
+    fn test_import_manager_runs_registered_plugin() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mut registry = PluginRegistry::new();
+        registry.register(Box::new(RecordingZipPlugin { seen: Arc::clone(&seen) }));
+        let mut import_manager = ImportManager::new().expect("Failed to create ImportManager");
+        import_manager.set_plugins(registry);
+
+        let item = |filename: &str| ImportItem {
+            url: format!("https://example.com/{}", filename),
+            filename: filename.to_string(),
+            size: 0,
+            checksum: String::new(),
+        };
+        let temp_dir = import_manager.output_dir();
+        ImportManager::run_import_plugin(&import_manager.plugins, temp_dir, &item("bookmarks.zip")).unwrap();
+        ImportManager::run_import_plugin(&import_manager.plugins, temp_dir, &item("notes.txt")).unwrap();
+
+        assert_eq!(*seen.lock().unwrap(), vec![temp_dir.join("bookmarks.zip")]);
+    }
+
+    // Helper function to generate test import items
+    fn generate_test_import_items() -> Vec<ImportItem> {
+        let mut items = Vec::new();
+        let mut rng = thread_rng();
+
+        for i in 1..=10 {
+            let size = rng.gen_range(1024..MAX_IMPORT_SIZE);
+            let mut hasher = Sha256::new();
+            hasher.update(&size.to_le_bytes());
+            let checksum = format!("{:x}", hasher.finalize());
+
+            items.push(ImportItem {
+                url: format!("https://www.Aluminum.com/test_file_{}.zip", i),
+                filename: format!("test_file_{}.zip", i),
+                size,
+                checksum,
+            });
+        }
+
+        items
+    }
+}
+
+// Additional helper functions for the import system
+
+// Function to validate the structure of imported ZIP files
+fn validate_zip_structure(zip_path: &Path) -> io::Result<bool> {
+    let file = File::open(zip_path)?;
+    let mut archive = ZipArchive::new(file)?;
+
+    // Check for required files and directories
+    let required_entries = vec!["manifest.json", "content/", "resources/"];
+
+    for entry in required_entries {
+        if archive.by_name(entry).is_err() {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+// Function to extract and process imported ZIP files
+fn process_imported_zip(zip_path: &Path, output_dir: &Path) -> io::Result<()> {
+    let file = File::open(zip_path)?;
+    let mut archive = ZipArchive::new(file)?;
+
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        let outpath = output_dir.join(file.name());
+
+        if file.name().ends_with('/') {
+            fs::create_dir_all(&outpath)?;
+        } else {
+            if let Some(p) = outpath.parent() {
+                if !p.exists() {
+                    fs::create_dir_all(p)?;
+                }
+            }
+            let mut outfile = File::create(&outpath)?;
+            io::copy(&mut file, &mut outfile)?;
+        }
+    }
+
+    Ok(())
+}
+
+// Function to clean up downloaded files after import, leaving the files named in `keep`
+fn cleanup_temp_files(temp_dir: &Path, keep: &[String]) -> io::Result<()> {
+    for entry in fs::read_dir(temp_dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if keep.iter().any(|name| entry.file_name() == name.as_str()) {
+            continue;
+        }
+        if path.is_file() {
+            fs::remove_file(path)?;
+        } else if path.is_dir() {
+            fs::remove_dir_all(path)?;
+        }
+    }
+    Ok(())
+}
+
+// Function to build the download client with an optional proxy and a redirect policy
+fn build_import_client(
+    proxy: Option<&ProxyConfig>,
+    redirect_policy: RedirectPolicy,
+) -> Result<Client, Box<dyn std::error::Error>> {
+    let mut builder = Client::builder().redirect(redirect_policy.to_reqwest());
+    if let Some(proxy) = proxy {
+        builder = builder.proxy(proxy.to_reqwest()?);
+    }
+    Ok(builder.build()?)
+}
+
+// Function to compare a response's Content-Length with the size an import item expects
+fn check_content_length(expected: usize, content_length: Option<u64>) -> Result<(), String> {
+    let actual = match content_length {
+        Some(actual) => actual,
+        None => return Ok(()),
+    };
+
+    let tolerance = (expected as f64 * CONTENT_LENGTH_TOLERANCE).ceil() as u64;
+    if actual.abs_diff(expected as u64) > tolerance {
+        return Err(format!(
+            "Content-Length mismatch: server reported {} bytes but the manifest expects {} bytes",
+            actual, expected
+        ));
+    }
+    Ok(())
+}
+
+// Function to write every known item and its status to `path`. The state is written
+// to a temporary file and renamed into place so a crash never leaves it half written.
+fn save_import_state(
+    path: &Path,
+    output_dir: &Path,
+    items: &Mutex<HashMap<String, ImportItem>>,
+    status: &HashMap<String, ImportStatus>,
+) -> io::Result<()> {
+    let mut persisted: Vec<PersistedImportItem> = items
+        .lock()
+        .unwrap()
+        .values()
+        .map(|item| PersistedImportItem {
+            item: item.clone(),
+            status: status.get(&item.filename).cloned().unwrap_or(ImportStatus::Pending),
+        })
+        .collect();
+    persisted.sort_by(|a, b| a.item.filename.cmp(&b.item.filename));
+
+    let state = PersistedImportState { output_dir: output_dir.to_owned(), items: persisted };
+    let json = serde_json::to_string_pretty(&state).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+    let temp_path = path.with_extension("tmp");
+    fs::write(&temp_path, json)?;
+    fs::rename(&temp_path, path)
+}
+
+// Function to check a manifest entry describes something we can download and verify
+fn validate_manifest_entry(entry: &ManifestEntry) -> Result<(), String> {
+    if entry.url.trim().is_empty() {
+        return Err("url is empty".to_string());
+    }
+    match reqwest::Url::parse(&entry.url) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") => {}
+        _ => return Err(format!("url {} is not an http(s) URL", entry.url)),
+    }
+    if entry.filename.trim().is_empty() {
+        return Err("filename is empty".to_string());
+    }
+    if entry.filename.contains(['/', '\\']) || entry.filename == "." || entry.filename == ".." {
+        return Err(format!("filename {} must be a plain file name", entry.filename));
+    }
+    if entry.size == 0 || entry.size > MAX_IMPORT_SIZE {
+        return Err(format!("size {} is outside 1..={} bytes", entry.size, MAX_IMPORT_SIZE));
+    }
+    let algorithm = entry.algorithm.as_deref().unwrap_or("sha256");
+    if !algorithm.eq_ignore_ascii_case("sha256") {
+        return Err(format!("checksum algorithm {} is not supported (expected sha256)", algorithm));
+    }
+    if entry.checksum.len() != 64 || !entry.checksum.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err("checksum is not a 64-character hex SHA-256 digest".to_string());
+    }
+    Ok(())
+}
+
+// Function to check that downloaded bytes match the type implied by the filename
+fn check_content_type(filename: &str, buffer: &[u8]) -> Result<(), String> {
+    let extension = Path::new(filename)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase());
+
+    let expected_mime = match extension.as_deref().and_then(|ext| {
+        KNOWN_IMPORT_TYPES.iter().find(|(known, _)| *known == ext)
+    }) {
+        Some((_, mime)) => *mime,
+        // Only known import types are checked
+        None => return Ok(()),
+    };
+
+    let detected_mime = match infer::get(buffer) {
+        Some(kind) => kind.mime_type(),
+        None if looks_like_html(buffer) => "text/html",
+        None => "unknown",
+    };
+
+    if detected_mime != expected_mime {
+        return Err(format!(
+            "Content type mismatch for {}: expected {} but content looks like {}",
+            filename, expected_mime, detected_mime
+        ));
+    }
+
+    Ok(())
+}
+
+// Function to detect HTML (such as an error page) by its leading markup
+fn looks_like_html(buffer: &[u8]) -> bool {
+    let head = String::from_utf8_lossy(&buffer[..buffer.len().min(512)]).to_lowercase();
+    let head = head.trim_start();
+    head.starts_with("<!doctype html") || head.starts_with("<html") || head.starts_with("<head")
+}
+
+// Function to log import activities
+fn log_import_activity(activity: &str, item: &ImportItem) {
+    let timestamp = Utc::now().format("%Y-%m-%d %H:%M:%S%.3f");
+    info!("[{}] {}: {}", timestamp, activity, item.filename);
+}
+
+// Function to calculate the overall progress of imports
+fn calculate_import_progress(status: &HashMap<String, ImportStatus>) -> f64 {
+    let total = status.len() as f64;
+    let completed = status.values().filter(|&s| *s == ImportStatus::Completed).count() as f64;
+    (completed / total) * 100.0
+}
+
+// Function to summarize import statuses, estimating the time remaining from the
+// average rate at which items have finished over `elapsed`
+fn progress_from_status(
+    status: &HashMap<String, ImportStatus>,
+    elapsed: Option<Duration>,
+    concurrency: usize,
+) -> ImportProgress {
+    let total = status.len();
+    let completed = status.values().filter(|&s| *s == ImportStatus::Completed).count();
+    let failed = status.values().filter(|s| matches!(s, ImportStatus::Failed(_))).count();
+    let finished = completed + failed;
+
+    let percent = if total == 0 {
+        0.0
+    } else {
+        finished as f64 / total as f64 * 100.0
+    };
+
+    let eta = match elapsed {
+        Some(elapsed) if finished > 0 => {
+            let per_item = elapsed.as_secs_f64() / finished as f64;
+            Some(Duration::from_secs_f64(per_item * (total - finished) as f64))
+        }
+        _ => None,
+    };
+
+    ImportProgress {
+        percent,
+        completed,
+        total,
+        failed,
+        eta,
+        concurrency,
+    }
+}
+
+// Enum to represent different types of import sources
+enum ImportSource {
+    LocalFile(PathBuf),
+    RemoteUrl(String),
+    CloudStorage(String, String), // (provider, identifier)
+}
+
+// Trait for import plugins
+trait ImportPlugin: Send + Sync {
+    fn name(&self) -> &str;
+    fn version(&self) -> &str;
+    fn supports_source(&self, source: &ImportSource) -> bool;
+    fn process_import(&self, source: &ImportSource, destination: &Path) -> io::Result<()>;
+}
+
+// Example implementation of an import plugin
+struct ZipImportPlugin;
+
+impl ImportPlugin for ZipImportPlugin {
+    fn name(&self) -> &str {
+        "ZIP Import Plugin"
+    }
+
+    fn version(&self) -> &str {
+        "1.0.0"
+    }
+
+    fn supports_source(&self, source: &ImportSource) -> bool {
+        match source {
+            ImportSource::LocalFile(path) => path.extension().map_or(false, |ext| ext == "zip"),
+            ImportSource::RemoteUrl(url) => url.ends_with(".zip"),
+            ImportSource::CloudStorage(_, identifier) => identifier.ends_with(".zip"),
+        }
+    }
+
+    fn process_import(&self, source: &ImportSource, destination: &Path) -> io::Result<()> {
+        // Implementation for processing ZIP imports
+        // This is a placeholder and should be replaced with actual ZIP processing logic
+        Ok(())
+    }
+}
+
+// Ordered collection of import plugins; earlier registrations take precedence
+#[derive(Default)]
+struct PluginRegistry {
+    plugins: Vec<Box<dyn ImportPlugin>>,
+}
+
+impl PluginRegistry {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    // Add a plugin after the ones already registered
+    fn register(&mut self, plugin: Box<dyn ImportPlugin>) {
+        self.plugins.push(plugin);
+    }
+
+    // Find the first registered plugin that supports `source`
+    fn find_for(&self, source: &ImportSource) -> Option<&dyn ImportPlugin> {
+        self.plugins
+            .iter()
+            .find(|plugin| plugin.supports_source(source))
+            .map(|plugin| plugin.as_ref())
+    }
+}
+
+// Function to register the built-in import plugins
+fn register_import_plugins() -> PluginRegistry {
+    let mut registry = PluginRegistry::new();
+    registry.register(Box::new(ZipImportPlugin));
+    registry
+}
+
+// Main function to run the import test suite
+fn main() {
+    println!("Running Aluminum Web Browser Import Test Suite");
+    println!("==============================================");
+
+    // Initialize logging
+    env_logger::init();
+
+    // Create an ImportManager instance with the built-in import plugins
+    let import_manager = match ImportManager::new() {
+        Ok(manager) => manager,
+        Err(e) => {
+            error!("Failed to create ImportManager: {}", e);
+            return;
+        }
+    };
+
+    // Generate test import items
+    let test_items = generate_test_import_items();
+
+    // Queue import items
+    for item in test_items {
+        import_manager.queue_import(item);
+    }
+
+    // Stop picking up new items on Ctrl-C; the main thread does the actual shutdown
+    let cancelled = import_manager.cancellation_flag();
+    if let Err(e) = ctrlc::set_handler(move || cancelled.store(true, Ordering::SeqCst)) {
+        warn!("Failed to install Ctrl-C handler: {}", e);
+    }
+
+    // Process the import queue
+    import_manager.process_queue();
+
+    // Wait for all imports to complete, failing any that miss the deadline
+    let stragglers = import_manager.wait_for_completion(COMPLETION_DEADLINE, PROGRESS_POLL_INTERVAL);
+    if !stragglers.is_empty() {
+        error!("{} import(s) timed out: {}", stragglers.len(), stragglers.join(", "));
+    }
+
+    if import_manager.is_cancelled() {
+        println!("Interrupted, waiting for running imports to stop...");
+        if let Err(e) = import_manager.shutdown() {
+            error!("Failed to clean up temporary files: {}", e);
+        }
+        println!("{}", import_manager.generate_report());
+        println!("Import Test Suite cancelled.");
+        return;
+    }
+
+    // Generate and print the final report
+    let report = import_manager.generate_report();
+    println!("\nFinal Import Test Report:");
+    println!("{}", report);
+
+    // Cleanup temporary files
+    if let Err(e) = import_manager.cleanup() {
+        error!("Failed to clean up temporary files: {}", e);
+    }
+
+    println!("Import Test Suite completed.");
+}
+
+// Helper function to generate test import items (moved outside of the test module)
+fn generate_test_import_items() -> Vec<ImportItem> {
+    let mut items = Vec::new();
+    let mut rng = thread_rng();
+
+    for i in 1..=10 {
+        let size = rng.gen_range(1024..MAX_IMPORT_SIZE);
+        let mut hasher = Sha256::new();
+        hasher.update(&size.to_le_bytes());
+        let checksum = format!("{:x}", hasher.finalize());
+
+        items.push(ImportItem {
+            url: format!("https://example.com/test_file_{}.zip", i),
+            filename: format!("test_file_{}.zip", i),
+            size,
+            checksum,
+        });
+    }
+
+    items
+}
@@ -1,450 +1,3892 @@
-
-// Import Test Library for Aluminum Web Browser
-// This module provides comprehensive testing utilities and frameworks
-// specifically designed for the Aluminum web browser project.
-
-// Standard library imports
-use std::collections::{HashMap, HashSet};
-use std::sync::{Arc, Mutex};
-use std::time::{Duration, Instant};
-
-// External crate imports
-use chrono::{DateTime, Utc};
-use futures::future::{self, Future};
-use log::{debug, error, info, warn};
-use rand::prelude::*;
-use reqwest::Client as HttpClient;
-use serde::{Deserialize, Serialize};
-use tokio::runtime::Runtime;
-
-// Internal module imports
-use crate::browser::core::{BrowserCore, RenderingEngine};
-use crate::network::protocol::{Http, Https, WebSocket};
-use crate::ui::components::{Button, InputField, TabBar};
-use crate::utils::{config::Config, error::AluminumError};
-
-/// Represents a test case for the Aluminum browser
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AluminumTestCase {
-    id: String,
-    name: String,
-    description: String,
-    steps: Vec<TestStep>,
-    expected_result: String,
-    timeout: Duration,
-}
-
-/// Represents a single step in a test case
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TestStep {
-    action: String,
-    params: HashMap<String, String>,
-}
-
-/// Test runner for executing Aluminum browser test cases
-pub struct AluminumTestRunner {
-    browser_core: Arc<Mutex<BrowserCore>>,
-    http_client: HttpClient,
-    runtime: Runtime,
-    results: HashMap<String, TestResult>,
-}
-
-/// Represents the result of a test case execution
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TestResult {
-    test_case_id: String,
-    status: TestStatus,
-    start_time: DateTime<Utc>,
-    end_time: DateTime<Utc>,
-    error_message: Option<String>,
-}
-
-/// Enum representing the possible statuses of a test case
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum TestStatus {
-    Passed,
-    Failed,
-    Skipped,
-    Timeout,
-}
-
-impl AluminumTestRunner {
-    /// Creates a new instance of the AluminumTestRunner
-    pub fn new(browser_core: BrowserCore) -> Self {
-        AluminumTestRunner {
-            browser_core: Arc::new(Mutex::new(browser_core)),
-            http_client: HttpClient::new(),
-            runtime: Runtime::new().expect("Failed to create Tokio runtime"),
-            results: HashMap::new(),
-        }
-    }
-
-    /// Runs a single test case
-    pub async fn run_test_case(&mut self, test_case: AluminumTestCase) -> TestResult {
-        let start_time = Utc::now();
-        let mut status = TestStatus::Passed;
-        let mut error_message = None;
-
-        for step in test_case.steps {
-            match self.execute_step(step).await {
-                Ok(_) => continue,
-                Err(e) => {
-                    status = TestStatus::Failed;
-                    error_message = Some(e.to_string());
-                    break;
-                }
-            }
-        }
-
-        let end_time = Utc::now();
-        let duration = end_time.signed_duration_since(start_time);
-
-        if duration > test_case.timeout {
-            status = TestStatus::Timeout;
-            error_message = Some(format!("Test case timed out after {:?}", duration));
-        }
-
-        TestResult {
-            test_case_id: test_case.id,
-            status,
-            start_time,
-            end_time,
-            error_message,
-        }
-    }
-
-    /// Executes a single test step
-    async fn execute_step(&self, step: TestStep) -> Result<(), AluminumError> {
-        match step.action.as_str() {
-            "navigate" => self.navigate(step.params.get("url").unwrap()).await,
-            "click" => self.click(step.params.get("selector").unwrap()).await,
-            "input" => {
-                self.input(
-                    step.params.get("selector").unwrap(),
-                    step.params.get("value").unwrap(),
-                )
-                .await
-            }
-            "assert_text" => {
-                self.assert_text(
-                    step.params.get("selector").unwrap(),
-                    step.params.get("expected").unwrap(),
-                )
-                .await
-            }
-            "wait" => {
-                tokio::time::sleep(Duration::from_secs(
-                    step.params.get("seconds").unwrap().parse().unwrap(),
-                ))
-                .await;
-                Ok(())
-            }
-            _ => Err(AluminumError::UnknownTestStep(step.action)),
-        }
-    }
-
-    /// Simulates navigating to a URL in the browser
-    async fn navigate(&self, url: &str) -> Result<(), AluminumError> {
-        let mut core = self.browser_core.lock().unwrap();
-        core.load_url(url).await?;
-        Ok(())
-    }
-
-    /// Simulates clicking an element in the browser
-    async fn click(&self, selector: &str) -> Result<(), AluminumError> {
-        let mut core = self.browser_core.lock().unwrap();
-        core.click_element(selector).await?;
-        Ok(())
-    }
-
-    /// Simulates inputting text into an element in the browser
-    async fn input(&self, selector: &str, value: &str) -> Result<(), AluminumError> {
-        let mut core = self.browser_core.lock().unwrap();
-        core.input_text(selector, value).await?;
-        Ok(())
-    }
-
-    /// Asserts that the text content of an element matches the expected value
-    async fn assert_text(&self, selector: &str, expected: &str) -> Result<(), AluminumError> {
-        let core = self.browser_core.lock().unwrap();
-        let actual = core.get_element_text(selector).await?;
-        if actual != expected {
-            return Err(AluminumError::AssertionFailed(format!(
-                "Expected text '{}' but found '{}'",
-                expected, actual
-            )));
-        }
-        Ok(())
-    }
-
-    /// Runs a batch of test cases concurrently
-    pub async fn run_test_suite(&mut self, test_cases: Vec<AluminumTestCase>) -> HashMap<String, TestResult> {
-        let mut handles = Vec::new();
-
-        for test_case in test_cases {
-            let test_case_id = test_case.id.clone();
-            let handle = tokio::spawn(async move {
-                let mut runner = AluminumTestRunner::new(BrowserCore::new());
-                runner.run_test_case(test_case).await
-            });
-            handles.push((test_case_id, handle));
-        }
-
-        for (test_case_id, handle) in handles {
-            let result = handle.await.expect("Failed to join test case task");
-            self.results.insert(test_case_id, result);
-        }
-
-        self.results.clone()
-    }
-
-    /// Generates a detailed report of the test suite execution
-    pub fn generate_report(&self) -> String {
-        let mut report = String::new();
-        report.push_str("Aluminum Browser Test Suite Report\n");
-        report.push_str("===================================\n\n");
-
-        let mut passed = 0;
-        let mut failed = 0;
-        let mut skipped = 0;
-        let mut timed_out = 0;
-
-        for (test_case_id, result) in &self.results {
-            report.push_str(&format!("Test Case: {}\n", test_case_id));
-            report.push_str(&format!("Status: {:?}\n", result.status));
-            report.push_str(&format!("Start Time: {}\n", result.start_time));
-            report.push_str(&format!("End Time: {}\n", result.end_time));
-            if let Some(error) = &result.error_message {
-                report.push_str(&format!("Error: {}\n", error));
-            }
-            report.push_str("\n");
-
-            match result.status {
-                TestStatus::Passed => passed += 1,
-                TestStatus::Failed => failed += 1,
-                TestStatus::Skipped => skipped += 1,
-                TestStatus::Timeout => timed_out += 1,
-            }
-        }
-
-        report.push_str("Summary:\n");
-        report.push_str(&format!("Total Tests: {}\n", self.results.len()));
-        report.push_str(&format!("Passed: {}\n", passed));
-        report.push_str(&format!("Failed: {}\n", failed));
-        report.push_str(&format!("Skipped: {}\n", skipped));
-        report.push_str(&format!("Timed Out: {}\n", timed_out));
-
-        report
-    }
-}
-
-// Helper functions for creating test cases and steps
-
-/// Creates a new test case with the given parameters
-pub fn create_test_case(
-    id: &str,
-    name: &str,
-    description: &str,
-    steps: Vec<TestStep>,
-    expected_result: &str,
-    timeout: Duration,
-) -> AluminumTestCase {
-    AluminumTestCase {
-        id: id.to_string(),
-        name: name.to_string(),
-        description: description.to_string(),
-        steps,
-        expected_result: expected_result.to_string(),
-        timeout,
-    }
-}
-
-/// Creates a new test step with the given action and parameters
-pub fn create_test_step(action: &str, params: HashMap<String, String>) -> TestStep {
-    TestStep {
-        action: action.to_string(),
-        params,
-    }
-}
-
-// Example usage of the test library
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[tokio::test]
-    async fn test_aluminum_browser() {
-        let mut test_runner = AluminumTestRunner::new(BrowserCore::new());
-
-        let test_cases = vec![
-            create_test_case(
-                "TC001",
-                "Homepage Load Test",
-                "Verifies that the Aluminum browser homepage loads correctly",
-                vec![
-                    create_test_step(
-                        "navigate",
-                        [("url".to_string(), "https://aluminum.browser.com".to_string())]
-                            .iter()
-                            .cloned()
-                            .collect(),
-                    ),
-                    create_test_step(
-                        "assert_text",
-                        [
-                            ("selector".to_string(), "h1".to_string()),
-                            ("expected".to_string(), "Welcome to Aluminum".to_string()),
-                        ]
-                        .iter()
-                        .cloned()
-                        .collect(),
-                    ),
-                ],
-                "Homepage loads with correct title",
-                Duration::from_secs(10),
-            ),
-            // Add more test cases here...
-        ];
-
-        let results = test_runner.run_test_suite(test_cases).await;
-        let report = test_runner.generate_report();
-
-        println!("{}", report);
-
-        // Assert that all tests passed
-        for (_, result) in results {
-            assert!(matches!(result.status, TestStatus::Passed));
-        }
-    }
-}
-
-// Additional utility functions for the test library
-
-/// Generates a random test data string
-pub fn generate_random_test_data(length: usize) -> String {
-    const CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ\
-                            abcdefghijklmnopqrstuvwxyz\
-                            0123456789)(*&^%$#@!~";
-    let mut rng = rand::thread_rng();
-    (0..length)
-        .map(|_| {
-            let idx = rng.gen_range(0..CHARSET.len());
-            CHARSET[idx] as char
-        })
-        .collect()
-}
-
-/// Measures the performance of a given operation
-pub async fn measure_performance<F, Fut, T>(operation: F) -> (T, Duration)
-where
-    F: FnOnce() -> Fut,
-    Fut: Future<Output = T>,
-{
-    let start = Instant::now();
-    let result = operation().await;
-    let duration = start.elapsed();
-    (result, duration)
-}
-
-/// Retries an asynchronous operation with exponential backoff
-pub async fn retry_with_backoff<F, Fut, T, E>(
-    operation: F,
-    max_retries: u32,
-    initial_delay: Duration,
-) -> Result<T, E>
-where
-    F: Fn() -> Fut,
-    Fut: Future<Output = Result<T, E>>,
-    E: std::fmt::Debug,
-{
-    let mut retries = 0;
-    let mut delay = initial_delay;
-
-    loop {
-        match operation().await {
-            Ok(value) => return Ok(value),
-            Err(e) => {
-                if retries >= max_retries {
-                    return Err(e);
-                }
-                warn!("Operation failed, retrying in {:?}: {:?}", delay, e);
-                tokio::time::sleep(delay).await;
-                retries += 1;
-                delay *= 2;
-            }
-        }
-    }
-}
-
-/// Simulates network conditions for testing
-pub struct NetworkSimulator {
-    latency: Duration,
-    packet_loss_rate: f64,
-}
-
-impl NetworkSimulator {
-    pub fn new(latency: Duration, packet_loss_rate: f64) -> Self {
-        NetworkSimulator {
-            latency,
-            packet_loss_rate,
-        }
-    }
-
-    pub async fn simulate<F, Fut, T>(&self, operation: F) -> Result<T, AluminumError>
-    where
-        F: FnOnce() -> Fut,
-        Fut: Future<Output = Result<T, AluminumError>>,
-    {
-        // Simulate latency
-        tokio::time::sleep(self.latency).await;
-
-        // Simulate packet loss
-        let mut rng = rand::thread_rng();
-        if rng.gen::<f64>() < self.packet_loss_rate {
-            return Err(AluminumError::NetworkError("Simulated packet loss".to_string()));
-        }
-
-        // Execute the operation
-        operation().await
-    }
-}
-
-// Constants for common test configurations
-pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
-pub const MAX_RETRIES: u32 = 3;
-pub const INITIAL_RETRY_DELAY: Duration = Duration::from_millis(100);
-
-// Macros for simplifying test case creation
-
-#[macro_export]
-macro_rules! aluminum_test_case {
-    ($id:expr, $name:expr, $description:expr, $steps:expr, $expected:expr) => {
-        create_test_case(
-            $id,
-            $name,
-            $description,
-            $steps,
-            $expected,
-            DEFAULT_TIMEOUT,
-        )
-    };
-}
-
-#[macro_export]
-macro_rules! aluminum_test_step {
-    ($action:expr, $($key:expr => $value:expr),*) => {
-        create_test_step(
-            $action,
-            vec![$(($key.to_string(), $value.to_string())),*].into_iter().collect()
-        )
-    };
-}
-
-// Example of how to use the macros:
-// 
-// let test_case = aluminum_test_case!(
-//     "TC002",
-//     "Login Test",
-//     "Verifies that a user can log in successfully",
-//
+
+// Import Test Library for Aluminum Web Browser
+// This module provides comprehensive testing utilities and frameworks
+// specifically designed for the Aluminum web browser project.
+
+// Standard library imports
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+// External crate imports
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::future::{self, Future};
+use log::{debug, error, info, warn};
+use rand::prelude::*;
+use reqwest::Client as HttpClient;
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+use tokio::runtime::Runtime;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+// Internal module imports
+use crate::network::protocol::{Http, Https, WebSocket};
+use crate::ui::components::{Button, InputField, TabBar};
+use crate::utils::browser_core::{
+    BrowserCore, CoreSnapshot, ElementHandle, EngineBackend, Point, Rect, RequestAction,
+};
+use crate::utils::{config::Config, error::AluminumError, metrics::Metrics};
+
+pub use crate::utils::browser_core::UrlPolicy;
+
+/// Represents a test case for the Aluminum browser
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AluminumTestCase {
+    id: String,
+    name: String,
+    description: String,
+    steps: Vec<TestStep>,
+    expected_result: String,
+    timeout: Duration,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/// Represents a single step in a test case
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestStep {
+    action: String,
+    params: HashMap<String, String>,
+}
+
+/// The actions a `TestStep` can perform, parsed from its `action` string
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestAction {
+    Navigate,
+    Click,
+    Input,
+    AssertText,
+    AssertCount,
+    SetUserAgent,
+    AssertVisible,
+    AssertNoConsoleErrors,
+    ClickAt,
+    PressKey,
+    Store,
+    EvalJs,
+    Screenshot,
+    Wait,
+    WaitFor,
+    WaitNetworkIdle,
+    AssertAll,
+    Breakpoint,
+    AssertEnabled,
+    AssertDisabled,
+}
+
+/// Test runner for executing Aluminum browser test cases
+pub struct AluminumTestRunner {
+    browser_core: Arc<Mutex<BrowserCore>>,
+    http_client: HttpClient,
+    /// Owned runtime for runners built outside async code; `None` inside one,
+    /// where dropping a runtime would panic
+    runtime: Option<Runtime>,
+    results: HashMap<String, TestResult>,
+    core_pool: Arc<BrowserCorePool>,
+    screenshot_dir: PathBuf,
+    custom_assertions: HashMap<String, Arc<dyn CustomAssertion>>,
+    url_policy: Option<UrlPolicy>,
+    /// Whether `breakpoint` steps pause for the user; off for CI runs
+    interactive: bool,
+    /// How long text assertions keep retrying before failing; zero checks once
+    assertion_timeout: Duration,
+}
+
+/// A project-specific step action, registered with `AluminumTestRunner::register_assertion`
+#[async_trait]
+pub trait CustomAssertion: Send + Sync {
+    /// The step action that invokes this assertion
+    fn name(&self) -> &str;
+
+    /// Checks the page, returning `AluminumError::AssertionFailed` when it doesn't hold
+    async fn check(&self, core: &BrowserCore, params: &HashMap<String, String>) -> Result<(), AluminumError>;
+}
+
+/// Bounded pool of reusable browser cores shared by concurrently running test cases
+pub struct BrowserCorePool {
+    idle: Mutex<Vec<Arc<Mutex<BrowserCore>>>>,
+    permits: Arc<Semaphore>,
+    created: AtomicUsize,
+    size: usize,
+    backend: EngineBackend,
+}
+
+/// A browser core checked out of a `BrowserCorePool`, returned to it on drop
+pub struct PooledCore {
+    core: Arc<Mutex<BrowserCore>>,
+    pool: Arc<BrowserCorePool>,
+    _permit: OwnedSemaphorePermit,
+}
+
+/// Represents the result of a test case execution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestResult {
+    test_case_id: String,
+    status: TestStatus,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+    error_message: Option<String>,
+    #[serde(default)]
+    assertion_details: Option<AssertionDetails>,
+    #[serde(default)]
+    artifact_paths: Vec<PathBuf>,
+    #[serde(default)]
+    step_timings: Vec<StepTiming>,
+    /// State of the page when the test case failed
+    #[serde(default)]
+    page_snapshot: Option<CoreSnapshot>,
+}
+
+/// How long a single step of a test case took to run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StepTiming {
+    action: String,
+    elapsed_ms: u64,
+}
+
+/// Expected and actual values of a failed assertion, kept for rendering diffs in reports
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssertionDetails {
+    selector: String,
+    expected: String,
+    actual: String,
+}
+
+/// Per-test-case state shared between steps
+#[derive(Debug, Default)]
+struct StepContext {
+    test_case_id: String,
+    variables: HashMap<String, String>,
+    artifacts: Vec<PathBuf>,
+    step_timings: Vec<StepTiming>,
+}
+
+/// Enum representing the possible statuses of a test case
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum TestStatus {
+    Passed,
+    Failed,
+    Skipped,
+    Timeout,
+}
+
+impl AluminumTestRunner {
+    /// Creates a new instance of the AluminumTestRunner
+    pub fn new(browser_core: BrowserCore) -> Self {
+        AluminumTestRunner {
+            browser_core: Arc::new(Mutex::new(browser_core)),
+            http_client: HttpClient::new(),
+            runtime: tokio::runtime::Handle::try_current()
+                .is_err()
+                .then(|| Runtime::new().expect("Failed to create Tokio runtime")),
+            results: HashMap::new(),
+            core_pool: Arc::new(BrowserCorePool::new(DEFAULT_SUITE_CONCURRENCY)),
+            screenshot_dir: PathBuf::from(DEFAULT_SCREENSHOT_DIR),
+            custom_assertions: HashMap::new(),
+            url_policy: None,
+            interactive: false,
+            assertion_timeout: DEFAULT_ASSERTION_TIMEOUT,
+        }
+    }
+
+    /// Creates a runner whose own core and pooled suite cores all use `backend`
+    ///
+    /// `EngineBackend::Headless` builds the DOM and layout without painting, so
+    /// assertions run faster in CI but screenshot steps fail.
+    pub fn with_backend(backend: EngineBackend) -> Self {
+        let mut runner = Self::new(BrowserCore::new_with_backend(backend));
+        runner.core_pool = Arc::new(BrowserCorePool::with_backend(DEFAULT_SUITE_CONCURRENCY, backend));
+        runner
+    }
+
+    /// Creates a runner driving an already shared browser core
+    ///
+    /// Only used for pooled suite cases, which always run inside the suite's
+    /// runtime, so the runner never owns one.
+    fn with_shared_core(browser_core: Arc<Mutex<BrowserCore>>) -> Self {
+        AluminumTestRunner {
+            browser_core,
+            http_client: HttpClient::new(),
+            runtime: None,
+            results: HashMap::new(),
+            core_pool: Arc::new(BrowserCorePool::new(1)),
+            screenshot_dir: PathBuf::from(DEFAULT_SCREENSHOT_DIR),
+            custom_assertions: HashMap::new(),
+            url_policy: None,
+            interactive: false,
+            assertion_timeout: DEFAULT_ASSERTION_TIMEOUT,
+        }
+    }
+
+    /// Sets the directory used for screenshot steps that don't name a path
+    pub fn set_screenshot_dir(&mut self, dir: impl Into<PathBuf>) {
+        self.screenshot_dir = dir.into();
+    }
+
+    /// Registers a custom assertion that steps can invoke by its name
+    ///
+    /// Built-in actions can't be overridden, and each name can only be registered once.
+    pub fn register_assertion<A>(&mut self, assertion: A) -> Result<(), String>
+    where
+        A: CustomAssertion + 'static,
+    {
+        let name = assertion.name().to_string();
+        if name.parse::<TestAction>().is_ok() {
+            return Err(format!("'{}' is a built-in action and can't be overridden", name));
+        }
+        if self.custom_assertions.contains_key(&name) {
+            return Err(format!("A custom assertion named '{}' is already registered", name));
+        }
+        self.custom_assertions.insert(name, Arc::new(assertion));
+        Ok(())
+    }
+
+    /// Loads a suite like `load_suite_from_path`, also accepting the actions of
+    /// assertions registered with this runner
+    pub fn load_suite(&self, path: impl AsRef<Path>) -> Result<Vec<AluminumTestCase>, Box<dyn std::error::Error>> {
+        load_suite_checked(path.as_ref(), |action| self.custom_assertions.contains_key(action))
+    }
+
+    /// Restricts navigation to URLs permitted by `policy`
+    ///
+    /// The policy is checked by the `navigate` step, including for local files,
+    /// and handed to the browser core so `load_url` enforces it as well.
+    pub fn set_url_policy(&mut self, policy: UrlPolicy) {
+        self.browser_core.lock().unwrap().set_url_policy(policy.clone());
+        self.url_policy = Some(policy);
+    }
+
+    /// Installs a hook consulted before every navigation and subresource request
+    ///
+    /// Requests the hook blocks fail with `AluminumError::BlockedByInterceptor`.
+    pub fn set_request_interceptor<F>(&self, interceptor: F)
+    where
+        F: Fn(&url::Url) -> RequestAction + Send + Sync + 'static,
+    {
+        let mut core = self.browser_core.lock().unwrap();
+        core.set_request_interceptor(interceptor);
+    }
+
+    /// Makes `breakpoint` steps pause until Enter is pressed, for debugging
+    /// against a headed browser. Runners start non-interactive.
+    pub fn set_interactive(&mut self, interactive: bool) {
+        self.interactive = interactive;
+    }
+
+    /// Makes `assert_text` steps retry until the text matches or `timeout` passes,
+    /// for content that settles after the element appears. This is separate from
+    /// the test case timeout; zero, the default, fails on the first mismatch.
+    pub fn set_assertion_timeout(&mut self, timeout: Duration) {
+        self.assertion_timeout = timeout;
+    }
+
+    /// Sets how many test cases `run_test_suite` runs at once, which is also
+    /// the number of browser cores it keeps around
+    pub fn set_concurrency(&mut self, concurrency: usize) {
+        self.core_pool = Arc::new(BrowserCorePool::with_backend(concurrency, self.core_pool.backend()));
+    }
+
+    /// Runs a single test case
+    pub async fn run_test_case(&mut self, test_case: AluminumTestCase) -> TestResult {
+        let start_time = Utc::now();
+        let mut status = TestStatus::Passed;
+        let mut error_message = None;
+        let mut assertion_details = None;
+        let mut context = StepContext {
+            test_case_id: test_case.id.clone(),
+            ..StepContext::default()
+        };
+
+        for step in test_case.steps {
+            let step = match interpolate_step(step, &context.variables) {
+                Ok(step) => step,
+                Err(message) => {
+                    status = TestStatus::Failed;
+                    error_message = Some(message);
+                    break;
+                }
+            };
+            let action = step.action.clone();
+            let step_started = Instant::now();
+            let outcome = self.execute_step(step, &mut context).await;
+            context.step_timings.push(StepTiming {
+                action,
+                elapsed_ms: step_started.elapsed().as_millis() as u64,
+            });
+            match outcome {
+                Ok(_) => continue,
+                Err(e) => {
+                    status = TestStatus::Failed;
+                    error_message = Some(e.to_string());
+                    assertion_details = AssertionDetails::from_error(&e);
+                    break;
+                }
+            }
+        }
+
+        let end_time = Utc::now();
+        let duration = end_time.signed_duration_since(start_time);
+
+        if duration > test_case.timeout {
+            status = TestStatus::Timeout;
+            error_message = Some(format!("Test case timed out after {:?}", duration));
+        }
+
+        let page_snapshot = match status {
+            TestStatus::Passed => None,
+            _ => Some(self.page_snapshot()),
+        };
+
+        let metrics = Metrics::global();
+        metrics.increment_counter("test_cases_total", 1);
+        if !matches!(status, TestStatus::Passed) {
+            metrics.increment_counter("test_case_failures_total", 1);
+        }
+        if let Ok(elapsed) = duration.to_std() {
+            metrics.observe("test_case_duration_seconds", elapsed.as_secs_f64());
+        }
+
+        TestResult {
+            test_case_id: test_case.id,
+            status,
+            start_time,
+            end_time,
+            error_message,
+            assertion_details,
+            artifact_paths: context.artifacts,
+            step_timings: context.step_timings,
+            page_snapshot,
+        }
+    }
+
+    /// Captures the current page state for failure reports
+    ///
+    /// The snapshot is taken synchronously, so the core lock is never held across an await.
+    fn page_snapshot(&self) -> CoreSnapshot {
+        self.browser_core.lock().unwrap().debug_snapshot()
+    }
+
+    /// Executes a single test step, recording captured values and failures in `context`
+    async fn execute_step(&self, step: TestStep, context: &mut StepContext) -> Result<(), AluminumError> {
+        if let Some(assertion) = self.custom_assertions.get(&step.action) {
+            let core = self.browser_core.lock().unwrap();
+            return assertion.check(&core, &step.params).await;
+        }
+
+        let action = step.action.parse::<TestAction>()?;
+        let param = |name: &str| required_param(&step.params, action.as_str(), name);
+        match action {
+            TestAction::Navigate => self.navigate(param("url")?).await,
+            TestAction::Click => self.click(param("selector")?).await,
+            TestAction::Input => {
+                self.input(param("selector")?, param("value")?).await
+            }
+            TestAction::AssertText => {
+                self.assert_text(param("selector")?, param("expected")?).await
+            }
+            TestAction::AssertCount => {
+                self.assert_count(
+                    param("selector")?,
+                    param("expected")?,
+                    step.params.get("comparator").map(String::as_str),
+                )
+                .await
+            }
+            TestAction::SetUserAgent => self.set_user_agent(param("user_agent")?).await,
+            TestAction::AssertVisible => self.assert_visible(param("selector")?).await,
+            TestAction::AssertNoConsoleErrors => {
+                let ignore: Vec<&str> = step
+                    .params
+                    .get("ignore")
+                    .map(|patterns| patterns.split('|').map(str::trim).filter(|p| !p.is_empty()).collect())
+                    .unwrap_or_default();
+                self.assert_no_console_errors(&ignore).await
+            }
+            TestAction::ClickAt => {
+                let point = Point {
+                    x: parse_coordinate(&step.params, "x")?,
+                    y: parse_coordinate(&step.params, "y")?,
+                };
+                self.click_at(point).await
+            }
+            TestAction::PressKey => {
+                self.press_key(step.params.get("selector").map(String::as_str), param("key")?).await
+            }
+            TestAction::Store => {
+                context.variables.insert(param("name")?.clone(), param("value")?.clone());
+                Ok(())
+            }
+            TestAction::EvalJs => {
+                let value = self.eval_js(param("script")?).await?;
+                if let Some(name) = step.params.get("store_as") {
+                    context.variables.insert(name.clone(), value);
+                }
+                Ok(())
+            }
+            TestAction::Screenshot => {
+                let path = match step.params.get("path") {
+                    Some(path) => PathBuf::from(path),
+                    None => self.screenshot_dir.join(format!(
+                        "{}_{}.png",
+                        context.test_case_id,
+                        context.artifacts.len() + 1
+                    )),
+                };
+                self.screenshot(&path).await?;
+                context.artifacts.push(path);
+                Ok(())
+            }
+            TestAction::Wait => {
+                let raw = param("seconds")?;
+                let seconds = raw.parse().map_err(|_| {
+                    AluminumError::InvalidParameter(format!("wait: 'seconds' must be a number, got '{}'", raw))
+                })?;
+                tokio::time::sleep(Duration::from_secs(seconds)).await;
+                Ok(())
+            }
+            TestAction::WaitFor => {
+                let timeout = parse_millis(&step.params, "wait_for", "timeout_ms", WAIT_FOR_TIMEOUT)?;
+                self.wait_for(param("selector")?, timeout).await
+            }
+            TestAction::WaitNetworkIdle => {
+                let idle = parse_millis(&step.params, "wait_network_idle", "idle_ms", NETWORK_IDLE_DURATION)?;
+                let timeout = parse_millis(&step.params, "wait_network_idle", "timeout_ms", WAIT_FOR_TIMEOUT)?;
+                let core = Arc::clone(&self.browser_core);
+                wait_for_network_idle(
+                    move || {
+                        let core = core.lock().unwrap();
+                        NetworkActivity {
+                            in_flight: core.in_flight_requests(),
+                            started: core.requests_started(),
+                        }
+                    },
+                    idle,
+                    timeout,
+                )
+                .await
+            }
+            TestAction::AssertAll => self.assert_all(&parse_sub_assertions(&step.params)?).await,
+            TestAction::Breakpoint => {
+                let timeout = parse_millis(&step.params, "breakpoint", "timeout_ms", BREAKPOINT_TIMEOUT)?;
+                self.breakpoint(&context.test_case_id, step.params.get("message").map(String::as_str), timeout)
+                    .await;
+                Ok(())
+            }
+            TestAction::AssertEnabled => self.assert_enabled(param("selector")?, true).await,
+            TestAction::AssertDisabled => self.assert_enabled(param("selector")?, false).await,
+        }
+    }
+
+    /// Simulates navigating to a URL in the browser
+    ///
+    /// `file://` URLs and plain filesystem paths are read from disk and handed
+    /// to the rendering pipeline directly, so local fixtures need no server.
+    async fn navigate(&self, url: &str) -> Result<(), AluminumError> {
+        // Console errors are tracked per page, so drop any left over from the previous one
+        self.browser_core.lock().unwrap().take_console_errors();
+
+        if let Some(path) = local_navigation_path(url) {
+            if let Some(policy) = &self.url_policy {
+                // Check before touching the disk so refused paths aren't probed
+                let requested = std::path::absolute(&path)?;
+                let file_url = url::Url::from_file_path(&requested).map_err(|_| {
+                    AluminumError::NetworkError(format!("Cannot build a file URL for '{}'", requested.display()))
+                })?;
+                policy.check(&file_url)?;
+            }
+            let html = std::fs::read_to_string(&path).map_err(|e| {
+                AluminumError::NetworkError(format!("Failed to load local file '{}': {}", path.display(), e))
+            })?;
+            let absolute = std::fs::canonicalize(&path)?;
+            let base_url = url::Url::from_file_path(&absolute).map_err(|_| {
+                AluminumError::NetworkError(format!("Cannot build a file URL for '{}'", absolute.display()))
+            })?;
+            let mut core = self.browser_core.lock().unwrap();
+            core.load_html(&html, &base_url).await?;
+            return Ok(());
+        }
+
+        if let (Some(policy), Ok(parsed)) = (&self.url_policy, url::Url::parse(url)) {
+            policy.check(&parsed)?;
+        }
+
+        let mut core = self.browser_core.lock().unwrap();
+        core.load_url(url).await?;
+        Ok(())
+    }
+
+    /// Simulates clicking an element in the browser
+    async fn click(&self, selector: &str) -> Result<(), AluminumError> {
+        let mut core = self.browser_core.lock().unwrap();
+        core.click_element(selector).await?;
+        Ok(())
+    }
+
+    /// Simulates inputting text into an element in the browser
+    async fn input(&self, selector: &str, value: &str) -> Result<(), AluminumError> {
+        let mut core = self.browser_core.lock().unwrap();
+        core.input_text(selector, value).await?;
+        Ok(())
+    }
+
+    /// Overrides the user agent sent by subsequent navigations in this core
+    async fn set_user_agent(&self, user_agent: &str) -> Result<(), AluminumError> {
+        let mut core = self.browser_core.lock().unwrap();
+        core.set_user_agent(user_agent);
+        Ok(())
+    }
+
+    /// Asserts that an element has a non-empty laid-out box inside the viewport
+    async fn assert_visible(&self, selector: &str) -> Result<(), AluminumError> {
+        let core = self.browser_core.lock().unwrap();
+        let rect = core.get_element_rect(selector).await?;
+        let viewport = core.viewport_rect();
+
+        if rect.width <= 0.0 || rect.height <= 0.0 {
+            return Err(AluminumError::AssertionFailed(format!(
+                "Expected '{}' to be visible but its box is {}x{}",
+                selector, rect.width, rect.height
+            )));
+        }
+        if !rects_intersect(&rect, &viewport) {
+            return Err(AluminumError::AssertionFailed(format!(
+                "Expected '{}' to be visible but it is off-screen at ({}, {})",
+                selector, rect.x, rect.y
+            )));
+        }
+        Ok(())
+    }
+
+    /// Asserts that a form control is enabled, or disabled when `expected` is false
+    ///
+    /// A selector matching nothing fails with `ElementNotFound` rather than
+    /// `AssertionFailed`, so a typo isn't mistaken for a control in the wrong state.
+    async fn assert_enabled(&self, selector: &str, expected: bool) -> Result<(), AluminumError> {
+        let core = self.browser_core.lock().unwrap();
+        // The core reports a missing element as ElementNotFound itself
+        let enabled = core.is_element_enabled(selector).await?;
+        if enabled != expected {
+            let describe = |enabled: bool| if enabled { "enabled" } else { "disabled" };
+            return Err(AluminumError::AssertionFailed(format!(
+                "Expected '{}' to be {} but it is {}",
+                selector,
+                describe(expected),
+                describe(enabled)
+            )));
+        }
+        Ok(())
+    }
+
+    /// Waits until an element matching `selector` exists, polling with a capped backoff
+    ///
+    /// Fails with `AssertionFailed` once `timeout` has passed without a match.
+    async fn wait_for(&self, selector: &str, timeout: Duration) -> Result<(), AluminumError> {
+        let started = Instant::now();
+        let mut backoff = Backoff::capped(WAIT_FOR_INITIAL_POLL, WAIT_FOR_MAX_POLL);
+
+        loop {
+            let found = {
+                let core = self.browser_core.lock().unwrap();
+                !core.query_selector_all(selector).await?.is_empty()
+            };
+            if found {
+                return Ok(());
+            }
+
+            let remaining = timeout.saturating_sub(started.elapsed());
+            if remaining.is_zero() {
+                return Err(AluminumError::AssertionFailed(format!(
+                    "Timed out after {}ms waiting for '{}'",
+                    timeout.as_millis(),
+                    selector
+                )));
+            }
+            tokio::time::sleep(backoff.next_delay().min(remaining)).await;
+        }
+    }
+
+    /// Fails if the page logged console errors since the last navigation
+    ///
+    /// Errors containing any of the `ignore` substrings are not counted.
+    async fn assert_no_console_errors(&self, ignore: &[&str]) -> Result<(), AluminumError> {
+        let errors: Vec<String> = {
+            let mut core = self.browser_core.lock().unwrap();
+            core.take_console_errors()
+        }
+        .into_iter()
+        .filter(|error| !ignore.iter().any(|pattern| error.contains(pattern)))
+        .collect();
+
+        if !errors.is_empty() {
+            return Err(AluminumError::AssertionFailed(format!(
+                "Page logged {} console error(s):\n{}",
+                errors.len(),
+                errors.join("\n")
+            )));
+        }
+        Ok(())
+    }
+
+    /// Clicks whatever element is laid out at `point`, in viewport coordinates
+    ///
+    /// A point inside the viewport with no element under it is a no-op; a
+    /// point outside the viewport is an error.
+    async fn click_at(&self, point: Point) -> Result<(), AluminumError> {
+        let mut core = self.browser_core.lock().unwrap();
+        let viewport = core.viewport_rect();
+        if !viewport.contains(point) {
+            return Err(AluminumError::AssertionFailed(format!(
+                "click_at: ({}, {}) is outside the {}x{} viewport",
+                point.x, point.y, viewport.width, viewport.height
+            )));
+        }
+
+        match core.element_from_point(point.x, point.y) {
+            Some(_element) => core.click_at(point).await,
+            None => {
+                debug!("click_at: no element at ({}, {}), nothing to click", point.x, point.y);
+                Ok(())
+            }
+        }
+    }
+
+    /// Simulates pressing a key, optionally focusing an element first
+    async fn press_key(&self, selector: Option<&str>, key: &str) -> Result<(), AluminumError> {
+        if !SUPPORTED_KEYS.contains(&key) {
+            return Err(AluminumError::InvalidParameter(format!(
+                "press_key: unsupported key '{}' (expected one of {:?})",
+                key, SUPPORTED_KEYS
+            )));
+        }
+        let mut core = self.browser_core.lock().unwrap();
+        core.press_key(selector, key).await?;
+        Ok(())
+    }
+
+    /// Captures the current page as a PNG and writes it to `path`
+    async fn screenshot(&self, path: &Path) -> Result<(), AluminumError> {
+        let png = {
+            let core = self.browser_core.lock().unwrap();
+            core.capture_screenshot().await?
+        };
+
+        if path.exists() {
+            warn!("Overwriting existing screenshot at {}", path.display());
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, png)?;
+        info!("Saved screenshot to {}", path.display());
+        Ok(())
+    }
+
+    /// Pauses until the user presses Enter or `timeout` passes, so the page can be inspected
+    ///
+    /// Does nothing unless the runner is interactive, so suites can keep their
+    /// breakpoints when run in CI.
+    async fn breakpoint(&self, test_case_id: &str, message: Option<&str>, timeout: Duration) {
+        if !self.interactive {
+            debug!("Skipping breakpoint in test case '{}' (runner is not interactive)", test_case_id);
+            return;
+        }
+
+        println!(
+            "Paused at breakpoint in test case '{}'{}. Press Enter to continue...",
+            test_case_id,
+            message.map(|message| format!(": {}", message)).unwrap_or_default()
+        );
+        let read_line = tokio::task::spawn_blocking(|| {
+            let mut line = String::new();
+            std::io::stdin().read_line(&mut line).map(|_| ())
+        });
+        if tokio::time::timeout(timeout, read_line).await.is_err() {
+            info!("Breakpoint in test case '{}' timed out after {:?}, resuming", test_case_id, timeout);
+        }
+    }
+
+    /// Evaluates a script in the page and returns its stringified result
+    async fn eval_js(&self, script: &str) -> Result<String, AluminumError> {
+        let mut core = self.browser_core.lock().unwrap();
+        core.evaluate_script(script).await
+    }
+
+    /// Asserts that the text content of an element matches the expected value
+    ///
+    /// With an assertion timeout set, mismatches and unreadable elements are
+    /// retried with a capped backoff and only the last attempt is reported.
+    async fn assert_text(&self, selector: &str, expected: &str) -> Result<(), AluminumError> {
+        let started = Instant::now();
+        let mut backoff = Backoff::capped(WAIT_FOR_INITIAL_POLL, WAIT_FOR_MAX_POLL);
+
+        loop {
+            let text = {
+                let core = self.browser_core.lock().unwrap();
+                core.get_element_text(selector).await
+            };
+            let remaining = self.assertion_timeout.saturating_sub(started.elapsed());
+            match text {
+                Ok(actual) if actual == expected => return Ok(()),
+                Ok(actual) if remaining.is_zero() => {
+                    return Err(AluminumError::AssertionMismatch {
+                        selector: selector.to_string(),
+                        expected: expected.to_string(),
+                        actual,
+                    });
+                }
+                Err(e) if remaining.is_zero() => return Err(e),
+                _ => tokio::time::sleep(backoff.next_delay().min(remaining)).await,
+            }
+        }
+    }
+
+    /// Checks the text of several elements, reporting every mismatch in one failure
+    ///
+    /// Unlike a run of `assert_text` steps, a failing check doesn't stop the
+    /// ones after it. Elements that can't be read count as failures too.
+    async fn assert_all(&self, checks: &[(String, String)]) -> Result<(), AluminumError> {
+        let core = self.browser_core.lock().unwrap();
+        let mut failures = Vec::new();
+        for (selector, expected) in checks {
+            match core.get_element_text(selector).await {
+                Ok(actual) if actual == *expected => {}
+                Ok(actual) => {
+                    let details = AssertionDetails {
+                        selector: selector.clone(),
+                        expected: expected.clone(),
+                        actual,
+                    };
+                    failures.push(format!("'{}': {}", selector, details));
+                }
+                Err(e) => failures.push(format!("'{}': {}", selector, e)),
+            }
+        }
+
+        if failures.is_empty() {
+            return Ok(());
+        }
+        Err(AluminumError::AssertionFailed(format!(
+            "{} of {} assertion(s) failed:\n  {}",
+            failures.len(),
+            checks.len(),
+            failures.join("\n  ")
+        )))
+    }
+
+    /// Asserts how many elements match a selector
+    ///
+    /// `comparator` is `eq` (the default), `gte` or `lte`.
+    async fn assert_count(
+        &self,
+        selector: &str,
+        expected: &str,
+        comparator: Option<&str>,
+    ) -> Result<(), AluminumError> {
+        let expected: usize = expected.trim().parse().map_err(|_| {
+            AluminumError::InvalidParameter(format!(
+                "assert_count: expected must be a non-negative number, got '{}'",
+                expected
+            ))
+        })?;
+        let comparator = comparator.unwrap_or("eq");
+        let (matches, relation) = match comparator {
+            "eq" => (usize::eq as fn(&usize, &usize) -> bool, "exactly"),
+            "gte" => (usize::ge as fn(&usize, &usize) -> bool, "at least"),
+            "lte" => (usize::le as fn(&usize, &usize) -> bool, "at most"),
+            other => {
+                return Err(AluminumError::InvalidParameter(format!(
+                    "assert_count: unsupported comparator '{}' (expected eq, gte or lte)",
+                    other
+                )))
+            }
+        };
+
+        let core = self.browser_core.lock().unwrap();
+        let elements: Vec<ElementHandle> = core.query_selector_all(selector).await?;
+        if !matches(&elements.len(), &expected) {
+            return Err(AluminumError::AssertionFailed(format!(
+                "Expected {} {} element(s) matching '{}' but found {}",
+                relation,
+                expected,
+                selector,
+                elements.len()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Runs a batch of test cases concurrently
+    ///
+    /// Results are added to those of earlier runs on this runner; call
+    /// `clear_results` first to report on this suite alone.
+    pub async fn run_test_suite(&mut self, test_cases: Vec<AluminumTestCase>) -> HashMap<String, TestResult> {
+        self.run_test_suite_with_observer(test_cases, |_| {}).await
+    }
+
+    /// Runs a batch of test cases concurrently, calling `observer` as each case completes
+    pub async fn run_test_suite_with_observer<O>(
+        &mut self,
+        test_cases: Vec<AluminumTestCase>,
+        observer: O,
+    ) -> HashMap<String, TestResult>
+    where
+        O: Fn(&TestResult) + Send + Sync + 'static,
+    {
+        let observer = Arc::new(observer);
+        let mut handles = Vec::new();
+
+        for test_case in test_cases {
+            let test_case_id = test_case.id.clone();
+            handles.push((test_case_id, self.spawn_pooled_test_case(test_case, Arc::clone(&observer))));
+        }
+
+        for (test_case_id, handle) in handles {
+            let result = handle.await.expect("Failed to join test case task");
+            self.results.insert(test_case_id, result);
+        }
+
+        self.results.clone()
+    }
+
+    /// Runs every test case `runs` times and reports how often each one passed
+    ///
+    /// Runs share the runner's core pool, so at most `set_concurrency` cases
+    /// execute at once. Results are not added to the runner's recorded results.
+    pub async fn run_flakiness_analysis(
+        &self,
+        test_cases: Vec<AluminumTestCase>,
+        runs: usize,
+    ) -> HashMap<String, FlakinessReport> {
+        let observer = Arc::new(|_: &TestResult| {});
+        let mut handles = Vec::new();
+
+        for test_case in &test_cases {
+            for _ in 0..runs {
+                handles.push(self.spawn_pooled_test_case(test_case.clone(), Arc::clone(&observer)));
+            }
+        }
+
+        let mut results_by_case: HashMap<String, Vec<TestResult>> = test_cases
+            .iter()
+            .map(|test_case| (test_case.id.clone(), Vec::new()))
+            .collect();
+        for handle in handles {
+            let result = handle.await.expect("Failed to join test case task");
+            results_by_case
+                .entry(result.test_case_id.clone())
+                .or_default()
+                .push(result);
+        }
+
+        results_by_case
+            .into_iter()
+            .map(|(test_case_id, results)| (test_case_id, FlakinessReport::from_results(&results)))
+            .collect()
+    }
+
+    /// Runs a test case on a core checked out of the pool, calling `observer` with its result
+    fn spawn_pooled_test_case<O>(
+        &self,
+        test_case: AluminumTestCase,
+        observer: Arc<O>,
+    ) -> tokio::task::JoinHandle<TestResult>
+    where
+        O: Fn(&TestResult) + Send + Sync + 'static,
+    {
+        let core_pool = Arc::clone(&self.core_pool);
+        let screenshot_dir = self.screenshot_dir.clone();
+        let custom_assertions = self.custom_assertions.clone();
+        let url_policy = self.url_policy.clone();
+        let interactive = self.interactive;
+        let assertion_timeout = self.assertion_timeout;
+        tokio::spawn(async move {
+            let pooled = core_pool.checkout().await;
+            let mut runner = AluminumTestRunner::with_shared_core(pooled.core());
+            runner.set_screenshot_dir(screenshot_dir);
+            runner.custom_assertions = custom_assertions;
+            runner.set_interactive(interactive);
+            runner.set_assertion_timeout(assertion_timeout);
+            if let Some(policy) = url_policy {
+                runner.set_url_policy(policy);
+            }
+            let result = runner.run_test_case(test_case).await;
+            observer(&result);
+            result
+        })
+    }
+
+    /// Folds the results of other shards of a suite into this runner's results
+    ///
+    /// Fails without merging anything if a test case id appears in more than
+    /// one shard or is already recorded here.
+    pub fn merge_results(&mut self, others: Vec<HashMap<String, TestResult>>) -> Result<(), String> {
+        let mut seen: HashSet<&String> = self.results.keys().collect();
+        let mut duplicates: Vec<&String> = others
+            .iter()
+            .flat_map(|shard| shard.keys())
+            .filter(|test_case_id| !seen.insert(*test_case_id))
+            .collect();
+        if !duplicates.is_empty() {
+            duplicates.sort();
+            duplicates.dedup();
+            return Err(format!(
+                "Test case id(s) reported by more than one shard: {}",
+                duplicates.iter().map(|id| id.as_str()).collect::<Vec<_>>().join(", ")
+            ));
+        }
+
+        for shard in others {
+            self.results.extend(shard);
+        }
+        Ok(())
+    }
+
+    /// Discards all recorded results so the runner can be reused for a fresh suite
+    pub fn clear_results(&mut self) {
+        self.results.clear();
+    }
+
+    /// Generates a detailed report of the test suite execution
+    pub fn generate_report(&self) -> String {
+        self.generate_report_with(ReportOptions::default())
+    }
+
+    /// Generates a report with the detail chosen by `opts`
+    ///
+    /// The summary always counts every recorded result, including cases left
+    /// out of the per-case sections.
+    pub fn generate_report_with(&self, opts: ReportOptions) -> String {
+        let mut report = String::new();
+        report.push_str("Aluminum Browser Test Suite Report\n");
+        report.push_str("===================================\n\n");
+
+        let cases = if opts.summary_only { Vec::new() } else { self.sorted_results() };
+        for (test_case_id, result) in cases {
+            if !opts.include_passed && matches!(result.status, TestStatus::Passed) {
+                continue;
+            }
+            report.push_str(&format!("Test Case: {}\n", test_case_id));
+            report.push_str(&format!("Status: {:?}\n", result.status));
+            report.push_str(&format!("Start Time: {}\n", result.start_time));
+            report.push_str(&format!("End Time: {}\n", result.end_time));
+            if let Some(error) = &result.error_message {
+                report.push_str(&format!("Error: {}\n", error));
+            }
+            if let Some(snapshot) = &result.page_snapshot {
+                report.push_str(&format!(
+                    "Page: {} ({:?}, {} DOM nodes)\n",
+                    snapshot.url, snapshot.title, snapshot.dom_node_count
+                ));
+                for console_error in &snapshot.console_errors {
+                    report.push_str(&format!("Console error: {}\n", console_error));
+                }
+            }
+            if let Some(details) = &result.assertion_details {
+                report.push_str("Diff (- expected, + actual):\n");
+                for line in details.line_diff().lines() {
+                    report.push_str(&format!("  {}\n", line));
+                }
+            }
+            report.push_str("\n");
+        }
+
+        let summary = self.summarize();
+        report.push_str("Summary:\n");
+        report.push_str(&format!("Total Tests: {}\n", summary.total));
+        report.push_str(&format!("Passed: {}\n", summary.passed));
+        report.push_str(&format!("Failed: {}\n", summary.failed));
+        report.push_str(&format!("Skipped: {}\n", summary.skipped));
+        report.push_str(&format!("Timed Out: {}\n", summary.timed_out));
+
+        report
+    }
+
+    /// Generates a JSON report of the test suite execution
+    ///
+    /// Each result is emitted with an extra `duration_ms` field, and the
+    /// summary totals durations in milliseconds as well.
+    pub fn generate_json_report(&self) -> String {
+        let results: serde_json::Map<String, serde_json::Value> = self
+            .sorted_results()
+            .into_iter()
+            .map(|(test_case_id, result)| {
+                let mut value = serde_json::to_value(result).expect("TestResult is serializable");
+                value["duration_ms"] = serde_json::json!(result.duration_ms());
+                (test_case_id.clone(), value)
+            })
+            .collect();
+
+        let report = serde_json::json!({
+            "results": results,
+            "summary": self.summarize(),
+        });
+
+        serde_json::to_string_pretty(&report).expect("report is serializable")
+    }
+
+    /// Generates a compact Markdown report for pull request comments
+    ///
+    /// Status counts are shown as a table, followed by a collapsible
+    /// `<details>` block per failed or timed-out case holding its error.
+    /// Case ids and errors are HTML-escaped so they can't break the markup.
+    pub fn generate_markdown_report(&self) -> String {
+        let summary = self.summarize();
+        let mut report = String::new();
+        report.push_str("## Aluminum Browser Test Suite Report\n\n");
+        report.push_str("| Status | Count |\n");
+        report.push_str("| --- | ---: |\n");
+        for (label, count) in [
+            ("Passed", summary.passed),
+            ("Failed", summary.failed),
+            ("Skipped", summary.skipped),
+            ("Timed Out", summary.timed_out),
+        ] {
+            report.push_str(&format!("| {} | {} |\n", label, count));
+        }
+        report.push_str(&format!("| **Total** | {} |\n", summary.total));
+
+        for (test_case_id, result) in self.sorted_results() {
+            if !matches!(result.status, TestStatus::Failed | TestStatus::Timeout) {
+                continue;
+            }
+            let error = result.error_message.as_deref().unwrap_or("No error message recorded");
+            report.push_str(&format!(
+                "\n<details>\n<summary>{} ({:?})</summary>\n\n<pre>{}</pre>\n\n</details>\n",
+                escape_html(test_case_id),
+                result.status,
+                escape_html(error)
+            ));
+        }
+
+        report
+    }
+
+    /// Recorded results ordered by test case id, so reports are stable between runs
+    fn sorted_results(&self) -> Vec<(&String, &TestResult)> {
+        let mut results: Vec<_> = self.results.iter().collect();
+        results.sort_by(|(a, _), (b, _)| a.cmp(b));
+        results
+    }
+
+    /// Tallies the recorded results per status
+    fn summarize(&self) -> ReportSummary {
+        let mut summary = ReportSummary {
+            total: self.results.len(),
+            ..ReportSummary::default()
+        };
+
+        for result in self.results.values() {
+            match result.status {
+                TestStatus::Passed => summary.passed += 1,
+                TestStatus::Failed => summary.failed += 1,
+                TestStatus::Skipped => summary.skipped += 1,
+                TestStatus::Timeout => summary.timed_out += 1,
+            }
+            summary.total_duration_ms += result.duration_ms();
+        }
+
+        summary
+    }
+}
+
+impl TestAction {
+    /// Every action, in the order they are documented
+    pub const ALL: &'static [TestAction] = &[
+        TestAction::Navigate,
+        TestAction::Click,
+        TestAction::Input,
+        TestAction::AssertText,
+        TestAction::AssertCount,
+        TestAction::SetUserAgent,
+        TestAction::AssertVisible,
+        TestAction::AssertNoConsoleErrors,
+        TestAction::ClickAt,
+        TestAction::PressKey,
+        TestAction::Store,
+        TestAction::EvalJs,
+        TestAction::Screenshot,
+        TestAction::Wait,
+        TestAction::WaitFor,
+        TestAction::WaitNetworkIdle,
+        TestAction::AssertAll,
+        TestAction::Breakpoint,
+        TestAction::AssertEnabled,
+        TestAction::AssertDisabled,
+    ];
+
+    /// The action string used in test steps and suite files
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TestAction::Navigate => "navigate",
+            TestAction::Click => "click",
+            TestAction::Input => "input",
+            TestAction::AssertText => "assert_text",
+            TestAction::AssertCount => "assert_count",
+            TestAction::SetUserAgent => "set_user_agent",
+            TestAction::AssertVisible => "assert_visible",
+            TestAction::AssertNoConsoleErrors => "assert_no_console_errors",
+            TestAction::ClickAt => "click_at",
+            TestAction::PressKey => "press_key",
+            TestAction::Store => "store",
+            TestAction::EvalJs => "eval_js",
+            TestAction::Screenshot => "screenshot",
+            TestAction::Wait => "wait",
+            TestAction::WaitFor => "wait_for",
+            TestAction::WaitNetworkIdle => "wait_network_idle",
+            TestAction::AssertAll => "assert_all",
+            TestAction::Breakpoint => "breakpoint",
+            TestAction::AssertEnabled => "assert_enabled",
+            TestAction::AssertDisabled => "assert_disabled",
+        }
+    }
+}
+
+impl FromStr for TestAction {
+    type Err = AluminumError;
+
+    fn from_str(action: &str) -> Result<Self, Self::Err> {
+        TestAction::ALL
+            .iter()
+            .copied()
+            .find(|candidate| candidate.as_str() == action)
+            .ok_or_else(|| AluminumError::UnknownTestStep(action.to_string()))
+    }
+}
+
+impl TryFrom<&str> for TestAction {
+    type Error = AluminumError;
+
+    fn try_from(action: &str) -> Result<Self, Self::Error> {
+        action.parse()
+    }
+}
+
+impl std::fmt::Display for TestAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl BrowserCorePool {
+    /// Creates an empty pool that will hold at most `size` cores
+    pub fn new(size: usize) -> Self {
+        Self::with_backend(size, EngineBackend::Full)
+    }
+
+    /// Creates an empty pool whose cores render with `backend`
+    pub fn with_backend(size: usize, backend: EngineBackend) -> Self {
+        let size = size.max(1);
+        BrowserCorePool {
+            idle: Mutex::new(Vec::with_capacity(size)),
+            permits: Arc::new(Semaphore::new(size)),
+            created: AtomicUsize::new(0),
+            size,
+            backend,
+        }
+    }
+
+    /// Waits for a free slot and hands out an idle core, creating one only if none is idle
+    pub async fn checkout(self: &Arc<Self>) -> PooledCore {
+        let permit = Arc::clone(&self.permits)
+            .acquire_owned()
+            .await
+            .expect("core pool semaphore closed");
+
+        let idle = self.idle.lock().unwrap().pop();
+        let core = idle.unwrap_or_else(|| {
+            self.created.fetch_add(1, Ordering::SeqCst);
+            Arc::new(Mutex::new(BrowserCore::new_with_backend(self.backend)))
+        });
+
+        PooledCore {
+            core,
+            pool: Arc::clone(self),
+            _permit: permit,
+        }
+    }
+
+    /// Number of cores this pool has constructed so far
+    pub fn created(&self) -> usize {
+        self.created.load(Ordering::SeqCst)
+    }
+
+    /// Maximum number of cores the pool holds
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Rendering backend of the cores this pool creates
+    pub fn backend(&self) -> EngineBackend {
+        self.backend
+    }
+}
+
+impl PooledCore {
+    /// Shared handle to the checked-out core
+    pub fn core(&self) -> Arc<Mutex<BrowserCore>> {
+        Arc::clone(&self.core)
+    }
+}
+
+impl Drop for PooledCore {
+    fn drop(&mut self) {
+        // Reset page state so the next test case starts clean. A case that panicked
+        // mid-step poisons the lock, but reset() discards whatever it left behind.
+        self.core.lock().unwrap_or_else(PoisonError::into_inner).reset();
+        self.core.clear_poison();
+        self.pool
+            .idle
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(Arc::clone(&self.core));
+    }
+}
+
+impl AssertionDetails {
+    /// Extracts the values compared by a failed text assertion
+    fn from_error(error: &AluminumError) -> Option<Self> {
+        match error {
+            AluminumError::AssertionMismatch { selector, expected, actual } => Some(AssertionDetails {
+                selector: selector.clone(),
+                expected: expected.clone(),
+                actual: actual.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Renders a line-level diff between the expected and actual values
+    ///
+    /// Removed lines are prefixed with `-`, added lines with `+`, and
+    /// unchanged lines with a space.
+    pub fn line_diff(&self) -> String {
+        let diff = TextDiff::from_lines(&self.expected, &self.actual);
+        let mut output = String::new();
+
+        for change in diff.iter_all_changes() {
+            let sign = match change.tag() {
+                ChangeTag::Delete => '-',
+                ChangeTag::Insert => '+',
+                ChangeTag::Equal => ' ',
+            };
+            output.push(sign);
+            output.push_str(change.value().trim_end_matches('\n'));
+            output.push('\n');
+        }
+
+        output
+    }
+}
+
+impl std::fmt::Display for AssertionDetails {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", AluminumError::from(self.clone()))
+    }
+}
+
+impl From<AssertionDetails> for AluminumError {
+    fn from(details: AssertionDetails) -> Self {
+        AluminumError::AssertionMismatch {
+            selector: details.selector,
+            expected: details.expected,
+            actual: details.actual,
+        }
+    }
+}
+
+impl TestResult {
+    /// Wall-clock duration of the test case in milliseconds
+    pub fn duration_ms(&self) -> i64 {
+        self.end_time
+            .signed_duration_since(self.start_time)
+            .num_milliseconds()
+    }
+}
+
+/// How consistently a test case passed over repeated runs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlakinessReport {
+    pub runs: usize,
+    pub passes: usize,
+    pub failures: usize,
+    /// Fraction of runs that passed, 0.0 when the case was never run
+    pub pass_ratio: f64,
+    /// Each distinct error message seen, sorted
+    pub distinct_errors: Vec<String>,
+}
+
+impl FlakinessReport {
+    /// Summarizes the results of repeated runs of one test case
+    ///
+    /// Any status other than `Passed`, including timeouts, counts as a failure.
+    pub fn from_results(results: &[TestResult]) -> Self {
+        let runs = results.len();
+        let passes = results
+            .iter()
+            .filter(|result| matches!(result.status, TestStatus::Passed))
+            .count();
+        let mut distinct_errors: Vec<String> = results
+            .iter()
+            .filter_map(|result| result.error_message.clone())
+            .collect();
+        distinct_errors.sort();
+        distinct_errors.dedup();
+
+        FlakinessReport {
+            runs,
+            passes,
+            failures: runs - passes,
+            pass_ratio: if runs == 0 { 0.0 } else { passes as f64 / runs as f64 },
+            distinct_errors,
+        }
+    }
+}
+
+/// How much detail `AluminumTestRunner::generate_report_with` includes
+///
+/// The default includes every case, matching `generate_report`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReportOptions {
+    /// Whether passing cases get their own section
+    pub include_passed: bool,
+    /// Whether to leave out every per-case section and print only the summary
+    pub summary_only: bool,
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        ReportOptions {
+            include_passed: true,
+            summary_only: false,
+        }
+    }
+}
+
+/// Comparisons between sets of recorded test results
+pub struct TestReport;
+
+/// How a run's results changed relative to a baseline run, by test case id
+///
+/// `Failed` and `Timeout` count as failing; skipped cases are neither failing
+/// nor passing. Every list is sorted.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReportDiff {
+    /// Cases that didn't fail in the baseline but fail now
+    pub newly_failed: Vec<String>,
+    /// Cases that failed in the baseline and pass now
+    pub newly_passed: Vec<String>,
+    /// Cases that fail in both runs
+    pub still_failing: Vec<String>,
+    /// Cases only present in the current run
+    pub new_cases: Vec<String>,
+    /// Cases only present in the baseline
+    pub removed_cases: Vec<String>,
+}
+
+impl TestReport {
+    /// Compares `current` against `baseline`, e.g. a pull request's run against the main branch's
+    pub fn diff(baseline: &HashMap<String, TestResult>, current: &HashMap<String, TestResult>) -> ReportDiff {
+        let is_failing = |result: &TestResult| matches!(result.status, TestStatus::Failed | TestStatus::Timeout);
+        let mut diff = ReportDiff::default();
+
+        for (test_case_id, result) in current {
+            let Some(previous) = baseline.get(test_case_id) else {
+                diff.new_cases.push(test_case_id.clone());
+                continue;
+            };
+            match (is_failing(previous), is_failing(result)) {
+                (false, true) => diff.newly_failed.push(test_case_id.clone()),
+                (true, true) => diff.still_failing.push(test_case_id.clone()),
+                (true, false) if matches!(result.status, TestStatus::Passed) => {
+                    diff.newly_passed.push(test_case_id.clone())
+                }
+                _ => {}
+            }
+        }
+        diff.removed_cases = baseline
+            .keys()
+            .filter(|test_case_id| !current.contains_key(*test_case_id))
+            .cloned()
+            .collect();
+
+        for bucket in [
+            &mut diff.newly_failed,
+            &mut diff.newly_passed,
+            &mut diff.still_failing,
+            &mut diff.new_cases,
+            &mut diff.removed_cases,
+        ] {
+            bucket.sort();
+        }
+        diff
+    }
+}
+
+impl ReportDiff {
+    /// True when a case that existed in the baseline has started failing
+    pub fn has_new_failures(&self) -> bool {
+        !self.newly_failed.is_empty()
+    }
+}
+
+/// Aggregate counts for a test suite report
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReportSummary {
+    total: usize,
+    passed: usize,
+    failed: usize,
+    skipped: usize,
+    timed_out: usize,
+    total_duration_ms: i64,
+}
+
+// Helper functions for creating test cases and steps
+
+/// Loads a JSON array of test cases from `path`, rejecting the suite if any
+/// step names an unknown action
+///
+/// Validation happens before anything runs, so a typo such as `"navgate"` is
+/// reported with the offending test case id and step number up front instead
+/// of failing midway through the suite.
+///
+/// `${NAME}` and `${NAME:-default}` in step parameters are replaced with
+/// environment variables, so secrets and per-machine URLs stay out of suite files.
+///
+/// Only built-in actions are accepted; suites using custom assertions are
+/// loaded with `AluminumTestRunner::load_suite` instead.
+pub fn load_suite_from_path(path: impl AsRef<Path>) -> Result<Vec<AluminumTestCase>, Box<dyn std::error::Error>> {
+    load_suite_checked(path.as_ref(), |_| false)
+}
+
+/// Loads a suite, accepting built-in actions and those `is_registered` allows
+fn load_suite_checked(
+    path: &Path,
+    is_registered: impl Fn(&str) -> bool,
+) -> Result<Vec<AluminumTestCase>, Box<dyn std::error::Error>> {
+    let data = std::fs::read_to_string(path)?;
+    let mut test_cases: Vec<AluminumTestCase> = serde_json::from_str(&data)?;
+    validate_suite(&test_cases, is_registered)?;
+    resolve_env_references(&mut test_cases, |name| std::env::var(name).ok())?;
+    Ok(test_cases)
+}
+
+/// Replaces `${NAME}` references in every step parameter using `lookup`
+///
+/// Fails with a single error naming every variable that is unset and has no default.
+fn resolve_env_references(
+    test_cases: &mut [AluminumTestCase],
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<(), String> {
+    let mut missing = Vec::new();
+    for test_case in test_cases.iter_mut() {
+        for step in test_case.steps.iter_mut() {
+            for value in step.params.values_mut() {
+                match interpolate_env(value, &lookup) {
+                    Ok(resolved) => *value = resolved,
+                    Err(names) => missing.extend(names),
+                }
+            }
+        }
+    }
+
+    if missing.is_empty() {
+        return Ok(());
+    }
+    missing.sort();
+    missing.dedup();
+    Err(format!("Suite references unset environment variable(s): {}", missing.join(", ")))
+}
+
+/// Replaces `${NAME}` with `lookup(NAME)` and `${NAME:-default}` with the value,
+/// or `default` when the variable is unset or empty
+///
+/// Returns the names of unset variables without defaults as the error.
+fn interpolate_env(input: &str, lookup: impl Fn(&str) -> Option<String>) -> Result<String, Vec<String>> {
+    let mut output = String::with_capacity(input.len());
+    let mut missing = Vec::new();
+    let mut rest = input;
+
+    while let Some(start) = rest.find("${") {
+        output.push_str(&rest[..start]);
+        let after_open = &rest[start + 2..];
+        let Some(end) = after_open.find('}') else {
+            output.push_str(&rest[start..]);
+            rest = "";
+            break;
+        };
+        let reference = &after_open[..end];
+        let (name, default) = match reference.split_once(":-") {
+            Some((name, default)) => (name.trim(), Some(default)),
+            None => (reference.trim(), None),
+        };
+        match (lookup(name), default) {
+            (Some(value), Some(default)) if value.is_empty() => output.push_str(default),
+            (Some(value), _) => output.push_str(&value),
+            (None, Some(default)) => output.push_str(default),
+            (None, None) => missing.push(name.to_string()),
+        }
+        rest = &after_open[end + 1..];
+    }
+    output.push_str(rest);
+
+    if missing.is_empty() {
+        Ok(output)
+    } else {
+        Err(missing)
+    }
+}
+
+/// Checks that every step in the suite names a built-in or registered action
+fn validate_suite(test_cases: &[AluminumTestCase], is_registered: impl Fn(&str) -> bool) -> Result<(), AluminumError> {
+    for test_case in test_cases {
+        for (index, step) in test_case.steps.iter().enumerate() {
+            if step.action.parse::<TestAction>().is_err() && !is_registered(&step.action) {
+                return Err(AluminumError::UnknownTestStep(format!(
+                    "'{}' in step {} of test case '{}'",
+                    step.action,
+                    index + 1,
+                    test_case.id
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Creates a new test case with the given parameters
+pub fn create_test_case(
+    id: &str,
+    name: &str,
+    description: &str,
+    steps: Vec<TestStep>,
+    expected_result: &str,
+    timeout: Duration,
+) -> AluminumTestCase {
+    TestCaseBuilder::new()
+        .id(id)
+        .name(name)
+        .description(description)
+        .steps(steps)
+        .expected_result(expected_result)
+        .timeout(timeout)
+        .build()
+}
+
+/// Fluent builder for `AluminumTestCase`
+#[derive(Debug, Clone, Default)]
+pub struct TestCaseBuilder {
+    id: Option<String>,
+    name: Option<String>,
+    description: String,
+    steps: Vec<TestStep>,
+    expected_result: String,
+    timeout: Option<Duration>,
+    tags: Vec<String>,
+}
+
+impl TestCaseBuilder {
+    /// Creates an empty builder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the test case id
+    pub fn id(mut self, id: &str) -> Self {
+        self.id = Some(id.to_string());
+        self
+    }
+
+    /// Sets the test case name
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    /// Sets the test case description
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = description.to_string();
+        self
+    }
+
+    /// Appends a single step
+    pub fn step(mut self, step: TestStep) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    /// Appends several steps
+    pub fn steps(mut self, steps: Vec<TestStep>) -> Self {
+        self.steps.extend(steps);
+        self
+    }
+
+    /// Sets the expected result description
+    pub fn expected_result(mut self, expected_result: &str) -> Self {
+        self.expected_result = expected_result.to_string();
+        self
+    }
+
+    /// Sets the test case timeout
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Adds a tag to the test case
+    pub fn tag(mut self, tag: &str) -> Self {
+        self.tags.push(tag.to_string());
+        self
+    }
+
+    /// Builds the test case, falling back to defaults for unset fields
+    ///
+    /// A missing id is derived from the name and a missing name from the id.
+    pub fn build(self) -> AluminumTestCase {
+        let name = self.name.or_else(|| self.id.clone()).unwrap_or_default();
+        let id = self
+            .id
+            .unwrap_or_else(|| name.to_lowercase().replace(' ', "_"));
+
+        AluminumTestCase {
+            id,
+            name,
+            description: self.description,
+            steps: self.steps,
+            expected_result: self.expected_result,
+            timeout: self.timeout.unwrap_or(DEFAULT_TIMEOUT),
+            tags: self.tags,
+        }
+    }
+}
+
+/// Returns true if the two boxes overlap by a non-zero area
+fn rects_intersect(a: &Rect, b: &Rect) -> bool {
+    a.x < b.x + b.width && b.x < a.x + a.width && a.y < b.y + b.height && b.y < a.y + a.height
+}
+
+/// Reads an optional millisecond duration parameter, falling back to `default`
+fn parse_millis(
+    params: &HashMap<String, String>,
+    action: &str,
+    name: &str,
+    default: Duration,
+) -> Result<Duration, AluminumError> {
+    match params.get(name) {
+        Some(raw) => raw.parse().map(Duration::from_millis).map_err(|_| {
+            AluminumError::InvalidParameter(format!("{}: '{}' must be a number, got '{}'", action, name, raw))
+        }),
+        None => Ok(default),
+    }
+}
+
+/// Reads a parameter the step's action can't run without
+fn required_param<'a>(params: &'a HashMap<String, String>, action: &str, name: &str) -> Result<&'a String, AluminumError> {
+    params
+        .get(name)
+        .ok_or_else(|| AluminumError::InvalidParameter(format!("{}: missing '{}' parameter", action, name)))
+}
+
+/// Reads a numeric coordinate parameter of a `click_at` step
+fn parse_coordinate(params: &HashMap<String, String>, name: &str) -> Result<f64, AluminumError> {
+    let raw = params.get(name).ok_or_else(|| {
+        AluminumError::InvalidParameter(format!("click_at: missing '{}' coordinate", name))
+    })?;
+    raw.trim().parse().map_err(|_| {
+        AluminumError::InvalidParameter(format!("click_at: '{}' must be a number, got '{}'", name, raw))
+    })
+}
+
+/// Escapes text for inclusion in HTML, which also keeps Markdown from interpreting it
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            '|' => escaped.push_str("&#124;"),
+            '`' => escaped.push_str("&#96;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Reads the checks of an `assert_all` step, ordered by index
+///
+/// Each check is a `selector.N` / `expected.N` pair of parameters, e.g.
+/// `selector.1: h1` and `expected.1: Welcome`.
+fn parse_sub_assertions(params: &HashMap<String, String>) -> Result<Vec<(String, String)>, AluminumError> {
+    let invalid = |message: String| AluminumError::InvalidParameter(format!("assert_all: {}", message));
+    let mut checks = Vec::new();
+    for (key, selector) in params {
+        let Some(index) = key.strip_prefix("selector.") else {
+            if !key.starts_with("expected.") {
+                return Err(invalid(format!("unexpected parameter '{}'", key)));
+            }
+            continue;
+        };
+        let number: usize = index
+            .parse()
+            .map_err(|_| invalid(format!("'{}' must end in a number", key)))?;
+        let expected = params
+            .get(&format!("expected.{}", index))
+            .ok_or_else(|| invalid(format!("'{}' has no matching 'expected.{}'", key, index)))?;
+        checks.push((number, selector.clone(), expected.clone()));
+    }
+    for key in params.keys().filter(|key| key.starts_with("expected.")) {
+        let index = &key["expected.".len()..];
+        if !params.contains_key(&format!("selector.{}", index)) {
+            return Err(invalid(format!("'{}' has no matching 'selector.{}'", key, index)));
+        }
+    }
+    if checks.is_empty() {
+        return Err(invalid("at least one 'selector.N' / 'expected.N' pair is required".to_string()));
+    }
+
+    checks.sort_by_key(|(number, _, _)| *number);
+    Ok(checks.into_iter().map(|(_, selector, expected)| (selector, expected)).collect())
+}
+
+/// Returns the filesystem path a navigation target refers to, if it is local
+///
+/// `file://` URLs are converted to paths; anything that doesn't parse as a URL
+/// (or only parses as one because of a Windows drive letter) is taken as a path.
+fn local_navigation_path(target: &str) -> Option<PathBuf> {
+    match url::Url::parse(target) {
+        Ok(url) if url.scheme() == "file" => url.to_file_path().ok(),
+        Ok(url) if url.scheme().len() == 1 => Some(PathBuf::from(target)),
+        Ok(_) => None,
+        Err(_) => Some(PathBuf::from(target)),
+    }
+}
+
+/// Replaces `{{name}}` placeholders in every parameter of a step
+fn interpolate_step(mut step: TestStep, variables: &HashMap<String, String>) -> Result<TestStep, String> {
+    for value in step.params.values_mut() {
+        *value = interpolate_variables(value, variables).map_err(|name| {
+            format!("Unresolved variable '{{{{{}}}}}' in '{}' step", name, step.action)
+        })?;
+    }
+    Ok(step)
+}
+
+/// Replaces `{{name}}` placeholders with values from `variables`
+///
+/// Returns the name of the first placeholder with no matching variable as the error.
+pub fn interpolate_variables(input: &str, variables: &HashMap<String, String>) -> Result<String, String> {
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let after_open = &rest[start + 2..];
+        match after_open.find("}}") {
+            Some(end) => {
+                let name = after_open[..end].trim();
+                let value = variables.get(name).ok_or_else(|| name.to_string())?;
+                output.push_str(value);
+                rest = &after_open[end + 2..];
+            }
+            None => {
+                output.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    output.push_str(rest);
+
+    Ok(output)
+}
+
+/// Creates a new test step with the given action and parameters
+pub fn create_test_step(action: &str, params: HashMap<String, String>) -> TestStep {
+    TestStep {
+        action: action.to_string(),
+        params,
+    }
+}
+
+// Example usage of the test library
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Builds a test step from borrowed key/value pairs
+    fn step(action: &str, params: &[(&str, &str)]) -> TestStep {
+        create_test_step(
+            action,
+            params
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_aluminum_browser() {
+        let mut test_runner = AluminumTestRunner::new(BrowserCore::new());
+
+        let test_cases = vec![
+            create_test_case(
+                "TC001",
+                "Homepage Load Test",
+                "Verifies that the Aluminum browser homepage loads correctly",
+                vec![
+                    create_test_step(
+                        "navigate",
+                        [("url".to_string(), "https://aluminum.browser.com".to_string())]
+                            .iter()
+                            .cloned()
+                            .collect(),
+                    ),
+                    create_test_step(
+                        "assert_text",
+                        [
+                            ("selector".to_string(), "h1".to_string()),
+                            ("expected".to_string(), "Welcome to Aluminum".to_string()),
+                        ]
+                        .iter()
+                        .cloned()
+                        .collect(),
+                    ),
+                ],
+                "Homepage loads with correct title",
+                Duration::from_secs(10),
+            ),
+            // Add more test cases here...
+        ];
+
+        let results = test_runner.run_test_suite(test_cases).await;
+        let report = test_runner.generate_report();
+
+        println!("{}", report);
+
+        // Assert that all tests passed
+        for (_, result) in results {
+            assert!(matches!(result.status, TestStatus::Passed));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_press_key_enter_on_focused_input() {
+        let fixture_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let fixture = fixture_dir.path().join("search.html");
+        // The submit handler records the query and cancels the navigation
+        std::fs::write(
+            &fixture,
+            "<html><body><form onsubmit=\"document.getElementById('status').textContent = \
+             'Searching for ' + this.querySelector('input').value; return false\">\
+             <input name=\"q\"></form><p id=\"status\">Idle</p></body></html>",
+        )
+        .unwrap();
+        let runner = AluminumTestRunner::new(BrowserCore::new());
+
+        let steps = vec![
+            step("navigate", &[("url", fixture.to_str().unwrap())]),
+            step("input", &[("selector", "input[name=q]"), ("value", "aluminum")]),
+            step("press_key", &[("selector", "input[name=q]"), ("key", "Enter")]),
+            step("assert_text", &[("selector", "#status"), ("expected", "Searching for aluminum")]),
+        ];
+
+        for test_step in steps {
+            runner
+                .execute_step(test_step, &mut StepContext::default())
+                .await
+                .expect("step failed");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_press_key_rejects_unknown_key() {
+        let runner = AluminumTestRunner::new(BrowserCore::new());
+
+        let result = runner
+            .execute_step(step("press_key", &[("key", "Hyperspace")]), &mut StepContext::default())
+            .await;
+
+        match result {
+            Err(AluminumError::InvalidParameter(message)) => assert!(message.contains("Hyperspace")),
+            other => panic!("expected an unsupported key error, got {:?}", other),
+        }
+    }
+
+    // Builds a finished test result with the given status
+    fn result(test_case_id: &str, status: TestStatus) -> TestResult {
+        let start_time = Utc::now();
+        TestResult {
+            test_case_id: test_case_id.to_string(),
+            status,
+            start_time,
+            end_time: start_time + chrono::Duration::milliseconds(250),
+            error_message: None,
+            assertion_details: None,
+            artifact_paths: Vec::new(),
+            step_timings: Vec::new(),
+            page_snapshot: None,
+        }
+    }
+
+    // Pulls the count for a summary label out of a text report
+    fn text_count(report: &str, label: &str) -> u64 {
+        report
+            .lines()
+            .find_map(|line| line.strip_prefix(&format!("{}: ", label)))
+            .and_then(|count| count.parse().ok())
+            .expect("summary line missing from text report")
+    }
+
+    #[test]
+    fn test_json_report_matches_text_report() {
+        let mut runner = AluminumTestRunner::new(BrowserCore::new());
+        for (id, status) in [
+            ("TC001", TestStatus::Passed),
+            ("TC002", TestStatus::Passed),
+            ("TC003", TestStatus::Failed),
+            ("TC004", TestStatus::Skipped),
+            ("TC005", TestStatus::Timeout),
+        ] {
+            runner.results.insert(id.to_string(), result(id, status));
+        }
+
+        let text = runner.generate_report();
+        let json: serde_json::Value =
+            serde_json::from_str(&runner.generate_json_report()).expect("report is valid JSON");
+        let summary = &json["summary"];
+
+        assert_eq!(summary["total"].as_u64().unwrap(), text_count(&text, "Total Tests"));
+        assert_eq!(summary["passed"].as_u64().unwrap(), text_count(&text, "Passed"));
+        assert_eq!(summary["failed"].as_u64().unwrap(), text_count(&text, "Failed"));
+        assert_eq!(summary["skipped"].as_u64().unwrap(), text_count(&text, "Skipped"));
+        assert_eq!(summary["timed_out"].as_u64().unwrap(), text_count(&text, "Timed Out"));
+        assert_eq!(summary["total_duration_ms"].as_i64().unwrap(), 5 * 250);
+        assert_eq!(json["results"]["TC003"]["duration_ms"].as_i64().unwrap(), 250);
+    }
+
+    #[test]
+    fn test_markdown_report_tables_statuses_and_details_failures() {
+        let mut runner = AluminumTestRunner::new(BrowserCore::new());
+        for (id, status) in [
+            ("TC001", TestStatus::Passed),
+            ("TC004", TestStatus::Skipped),
+            ("TC005", TestStatus::Timeout),
+            ("TC002", TestStatus::Passed),
+        ] {
+            runner.results.insert(id.to_string(), result(id, status));
+        }
+        let mut failed = result("TC003", TestStatus::Failed);
+        failed.error_message = Some("Expected text '<b>a|b</b>' but found ''".to_string());
+        runner.results.insert("TC003".to_string(), failed);
+
+        let markdown = runner.generate_markdown_report();
+
+        for row in ["| Passed | 2 |", "| Failed | 1 |", "| Skipped | 1 |", "| Timed Out | 1 |", "| **Total** | 5 |"] {
+            assert!(markdown.contains(row), "missing {:?} in\n{}", row, markdown);
+        }
+        assert_eq!(markdown.matches("<details>").count(), 2);
+        assert_eq!(markdown.matches("</details>").count(), 2);
+        let tc003 = markdown.find("<summary>TC003 (Failed)</summary>").expect("TC003 details missing");
+        let tc005 = markdown.find("<summary>TC005 (Timeout)</summary>").expect("TC005 details missing");
+        assert!(tc003 < tc005);
+        assert!(markdown.contains(
+            "<pre>Expected text &#39;&lt;b&gt;a&#124;b&lt;/b&gt;&#39; but found &#39;&#39;</pre>"
+        ));
+        assert!(!markdown.contains("TC001 ("));
+    }
+
+    #[test]
+    fn test_reports_list_cases_sorted_by_id() {
+        let mut runner = AluminumTestRunner::new(BrowserCore::new());
+        for id in ["TC007", "TC002", "TC010", "TC001", "TC005"] {
+            runner.results.insert(id.to_string(), result(id, TestStatus::Passed));
+        }
+        let sorted = ["TC001", "TC002", "TC005", "TC007", "TC010"];
+
+        let text = runner.generate_report();
+        let listed: Vec<&str> = text
+            .lines()
+            .filter_map(|line| line.strip_prefix("Test Case: "))
+            .collect();
+        assert_eq!(listed, sorted);
+
+        let json = runner.generate_json_report();
+        let positions: Vec<usize> = sorted
+            .iter()
+            .map(|id| json.find(&format!("\"{}\": {{", id)).expect("case missing from JSON report"))
+            .collect();
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]), "{}", json);
+    }
+
+    #[tokio::test]
+    async fn test_clear_results_between_suites() {
+        let mut runner = AluminumTestRunner::new(BrowserCore::new());
+
+        let first_suite = vec![TestCaseBuilder::new().id("SUITE1_TC001").build()];
+        runner.run_test_suite(first_suite).await;
+        assert!(runner.generate_report().contains("SUITE1_TC001"));
+
+        runner.clear_results();
+
+        let second_suite = vec![TestCaseBuilder::new().id("SUITE2_TC001").build()];
+        runner.run_test_suite(second_suite).await;
+        let report = runner.generate_report();
+
+        assert!(report.contains("SUITE2_TC001"));
+        assert!(!report.contains("SUITE1_TC001"));
+        assert!(report.contains("Total Tests: 1"));
+    }
+
+    #[tokio::test]
+    async fn test_suite_reuses_pooled_cores() {
+        let mut runner = AluminumTestRunner::new(BrowserCore::new());
+        runner.set_concurrency(2);
+
+        let test_cases = (1..=8)
+            .map(|i| TestCaseBuilder::new().id(&format!("POOL_TC{:03}", i)).build())
+            .collect();
+        let results = runner.run_test_suite(test_cases).await;
+
+        assert_eq!(results.len(), 8);
+        assert!(runner.core_pool.created() >= 1);
+        assert!(runner.core_pool.created() <= runner.core_pool.size());
+    }
+
+    #[tokio::test]
+    async fn test_pooled_core_returns_to_pool_after_poisoning() {
+        let pool = Arc::new(BrowserCorePool::new(1));
+        let pooled = pool.checkout().await;
+        let core = pooled.core();
+        core.lock().unwrap().set_user_agent("Poisoned/1.0");
+        let poisoner = Arc::clone(&core);
+        let _ = std::thread::spawn(move || {
+            let _guard = poisoner.lock().unwrap();
+            panic!("test case panicked while holding the core");
+        })
+        .join();
+        assert!(core.is_poisoned());
+
+        drop(pooled);
+        let reused = pool.checkout().await;
+        assert!(Arc::ptr_eq(&reused.core(), &core));
+        assert!(!core.is_poisoned());
+        assert_eq!(pool.created(), 1);
+    }
+
+    #[test]
+    fn test_runner_built_outside_async_code_owns_a_runtime() {
+        assert!(AluminumTestRunner::new(BrowserCore::new()).runtime.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_runner_built_inside_a_runtime_does_not_own_one() {
+        let runner = AluminumTestRunner::new(BrowserCore::new());
+        assert!(runner.runtime.is_none());
+        // Dropping a runner here must not drop a runtime from async code
+        drop(runner);
+    }
+
+    #[tokio::test]
+    async fn test_flakiness_analysis_of_stable_case() {
+        let mut runner = AluminumTestRunner::new(BrowserCore::new());
+        runner.set_concurrency(2);
+
+        let test_cases = vec![TestCaseBuilder::new().id("STABLE_TC001").build()];
+        let reports = runner.run_flakiness_analysis(test_cases, 5).await;
+
+        let report = &reports["STABLE_TC001"];
+        assert_eq!(report.runs, 5);
+        assert_eq!(report.passes, 5);
+        assert_eq!(report.pass_ratio, 1.0);
+        assert!(report.distinct_errors.is_empty());
+        assert!(runner.generate_report().contains("Total Tests: 0"));
+    }
+
+    #[test]
+    fn test_flakiness_report_matches_injected_failure_rate() {
+        // Every fourth run of the stub case fails, alternating between two errors
+        let results: Vec<TestResult> = (0..20)
+            .map(|run| {
+                if run % 4 == 3 {
+                    let mut failed = result("FLAKY_TC001", TestStatus::Failed);
+                    failed.error_message = Some(format!("Element '#toast-{}' not found", run % 8 / 4));
+                    failed
+                } else {
+                    result("FLAKY_TC001", TestStatus::Passed)
+                }
+            })
+            .collect();
+
+        let report = FlakinessReport::from_results(&results);
+
+        assert_eq!(report.runs, 20);
+        assert_eq!(report.passes, 15);
+        assert_eq!(report.failures, 5);
+        assert_eq!(report.pass_ratio, 0.75);
+        assert_eq!(
+            report.distinct_errors,
+            vec!["Element '#toast-0' not found", "Element '#toast-1' not found"]
+        );
+    }
+
+    // Fails every `every`-th time it's checked, counting checks across every runner sharing it
+    struct EveryNthFails {
+        checks: Arc<AtomicUsize>,
+        every: usize,
+    }
+
+    #[async_trait]
+    impl CustomAssertion for EveryNthFails {
+        fn name(&self) -> &str {
+            "assert_every_nth_fails"
+        }
+
+        async fn check(&self, _core: &BrowserCore, _params: &HashMap<String, String>) -> Result<(), AluminumError> {
+            if self.checks.fetch_add(1, Ordering::SeqCst) % self.every == self.every - 1 {
+                return Err(AluminumError::AssertionFailed("toast never appeared".to_string()));
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_flakiness_analysis_of_case_failing_every_fourth_run() {
+        let checks = Arc::new(AtomicUsize::new(0));
+        let mut runner = AluminumTestRunner::new(BrowserCore::new());
+        runner.set_concurrency(3);
+        runner.register_assertion(EveryNthFails { checks: Arc::clone(&checks), every: 4 }).unwrap();
+
+        let test_cases = vec![
+            TestCaseBuilder::new().id("FLAKY_TC002").step(step("assert_every_nth_fails", &[])).build(),
+            TestCaseBuilder::new().id("STABLE_TC002").build(),
+        ];
+        let reports = runner.run_flakiness_analysis(test_cases, 20).await;
+
+        // Runs overlap, but exactly one check in four fails whichever runs they land in
+        assert_eq!(checks.load(Ordering::SeqCst), 20);
+        let flaky = &reports["FLAKY_TC002"];
+        assert_eq!((flaky.runs, flaky.passes, flaky.failures), (20, 15, 5));
+        assert_eq!(flaky.pass_ratio, 0.75);
+        assert_eq!(
+            flaky.distinct_errors,
+            vec![AluminumError::AssertionFailed("toast never appeared".to_string()).to_string()]
+        );
+        assert_eq!(reports["STABLE_TC002"].pass_ratio, 1.0);
+    }
+
+    #[test]
+    fn test_report_diff_buckets_cases() {
+        let results = |cases: &[(&str, TestStatus)]| -> HashMap<String, TestResult> {
+            cases
+                .iter()
+                .map(|(id, status)| (id.to_string(), result(id, status.clone())))
+                .collect()
+        };
+        let baseline = results(&[
+            ("TC_STABLE", TestStatus::Passed),
+            ("TC_BROKE", TestStatus::Passed),
+            ("TC_HUNG", TestStatus::Skipped),
+            ("TC_FIXED", TestStatus::Failed),
+            ("TC_STILL", TestStatus::Timeout),
+            ("TC_DROPPED", TestStatus::Failed),
+        ]);
+        let current = results(&[
+            ("TC_STABLE", TestStatus::Passed),
+            ("TC_BROKE", TestStatus::Failed),
+            ("TC_HUNG", TestStatus::Timeout),
+            ("TC_FIXED", TestStatus::Passed),
+            ("TC_STILL", TestStatus::Failed),
+            ("TC_ADDED", TestStatus::Failed),
+        ]);
+
+        let diff = TestReport::diff(&baseline, &current);
+
+        assert_eq!(diff.newly_failed, vec!["TC_BROKE", "TC_HUNG"]);
+        assert_eq!(diff.newly_passed, vec!["TC_FIXED"]);
+        assert_eq!(diff.still_failing, vec!["TC_STILL"]);
+        assert_eq!(diff.new_cases, vec!["TC_ADDED"]);
+        assert_eq!(diff.removed_cases, vec!["TC_DROPPED"]);
+        assert!(diff.has_new_failures());
+        assert!(!TestReport::diff(&current, &current).has_new_failures());
+    }
+
+    #[tokio::test]
+    async fn test_eval_js_result_used_in_later_assert() {
+        let mut runner = AluminumTestRunner::new(BrowserCore::new());
+
+        let test_case = TestCaseBuilder::new()
+            .id("TC_EVAL")
+            .step(step("navigate", &[("url", "https://aluminum.browser.com")]))
+            .step(step(
+                "eval_js",
+                &[("script", "document.querySelector('h1').textContent"), ("store_as", "heading")],
+            ))
+            .step(step("assert_text", &[("selector", "h1"), ("expected", "{{heading}}")]))
+            .build();
+
+        let result = runner.run_test_case(test_case).await;
+        assert!(matches!(result.status, TestStatus::Passed), "{:?}", result.error_message);
+    }
+
+    #[tokio::test]
+    async fn test_eval_js_stores_expression_result() {
+        let runner = AluminumTestRunner::new(BrowserCore::new());
+        let mut context = StepContext::default();
+
+        runner
+            .execute_step(step("eval_js", &[("script", "6 * 7"), ("store_as", "answer")]), &mut context)
+            .await
+            .expect("script failed");
+
+        assert_eq!(context.variables.get("answer").map(String::as_str), Some("42"));
+    }
+
+    #[tokio::test]
+    async fn test_missing_step_params_are_invalid_not_a_panic() {
+        let runner = AluminumTestRunner::new(BrowserCore::new());
+        let mut context = StepContext::default();
+
+        for (action, params, expected) in [
+            ("store", &[("name", "user")][..], "store: missing 'value' parameter"),
+            ("eval_js", &[("store_as", "answer")][..], "eval_js: missing 'script' parameter"),
+            ("navigate", &[][..], "navigate: missing 'url' parameter"),
+            ("input", &[("selector", "#user")][..], "input: missing 'value' parameter"),
+            ("wait", &[("seconds", "soon")][..], "wait: 'seconds' must be a number, got 'soon'"),
+        ] {
+            match runner.execute_step(step(action, params), &mut context).await {
+                Err(AluminumError::InvalidParameter(message)) => assert_eq!(message, expected),
+                other => panic!("{}: expected an invalid parameter error, got {:?}", action, other),
+            }
+        }
+        // Nothing was stored by the rejected steps
+        assert!(context.variables.is_empty());
+    }
+
+    #[test]
+    fn test_interpolate_variables() {
+        let variables: HashMap<String, String> =
+            [("id".to_string(), "42".to_string())].into_iter().collect();
+
+        assert_eq!(interpolate_variables("/items/{{id}}/edit", &variables).unwrap(), "/items/42/edit");
+        assert_eq!(interpolate_variables("{{ id }}", &variables).unwrap(), "42");
+        assert_eq!(interpolate_variables("no placeholders", &variables).unwrap(), "no placeholders");
+        assert_eq!(interpolate_variables("{{missing}}", &variables), Err("missing".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_stored_value_chained_into_navigate_and_assert() {
+        let mut runner = AluminumTestRunner::new(BrowserCore::new());
+
+        let test_case = TestCaseBuilder::new()
+            .id("TC_STORE")
+            .step(step("store", &[("name", "host"), ("value", "https://aluminum.browser.com")]))
+            .step(step("store", &[("name", "greeting"), ("value", "Welcome to Aluminum")]))
+            .step(step("navigate", &[("url", "{{host}}/")]))
+            .step(step("assert_text", &[("selector", "h1"), ("expected", "{{greeting}}")]))
+            .build();
+
+        let result = runner.run_test_case(test_case).await;
+        assert!(matches!(result.status, TestStatus::Passed), "{:?}", result.error_message);
+    }
+
+    #[tokio::test]
+    async fn test_unresolved_variable_fails_step() {
+        let mut runner = AluminumTestRunner::new(BrowserCore::new());
+
+        let test_case = TestCaseBuilder::new()
+            .id("TC_UNRESOLVED")
+            .step(step("navigate", &[("url", "{{base_url}}/login")]))
+            .build();
+
+        let result = runner.run_test_case(test_case).await;
+        assert!(matches!(result.status, TestStatus::Failed));
+        assert!(result.error_message.unwrap().contains("{{base_url}}"));
+    }
+
+    #[tokio::test]
+    async fn test_observer_sees_every_case_once() {
+        let mut runner = AluminumTestRunner::new(BrowserCore::new());
+        let observed = Arc::new(Mutex::new(Vec::new()));
+
+        let test_cases = (1..=5)
+            .map(|i| TestCaseBuilder::new().id(&format!("OBS_TC{:03}", i)).build())
+            .collect();
+        let sink = Arc::clone(&observed);
+        runner
+            .run_test_suite_with_observer(test_cases, move |result| {
+                sink.lock().unwrap().push(result.test_case_id.clone());
+            })
+            .await;
+
+        let mut observed = observed.lock().unwrap().clone();
+        observed.sort();
+        let expected: Vec<String> = (1..=5).map(|i| format!("OBS_TC{:03}", i)).collect();
+        assert_eq!(observed, expected);
+    }
+
+    #[test]
+    fn test_assertion_diff_identifies_changed_line() {
+        let details = AssertionDetails {
+            selector: "#terms".to_string(),
+            expected: "line one\nline two\nline three\n".to_string(),
+            actual: "line one\nline 2\nline three\n".to_string(),
+        };
+
+        let diff = details.line_diff();
+        let removed: Vec<&str> = diff.lines().filter(|line| line.starts_with('-')).collect();
+        let added: Vec<&str> = diff.lines().filter(|line| line.starts_with('+')).collect();
+
+        assert_eq!(removed, vec!["-line two"]);
+        assert_eq!(added, vec!["+line 2"]);
+        assert_eq!(
+            details.to_string(),
+            "Text of '#terms' differs from expected on 1 line(s)"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_screenshot_step_writes_file() {
+        let screenshot_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let mut runner = AluminumTestRunner::new(BrowserCore::new());
+        runner.set_screenshot_dir(screenshot_dir.path());
+
+        let named_path = screenshot_dir.path().join("baseline/home.png");
+        let test_case = TestCaseBuilder::new()
+            .id("TC_SHOT")
+            .step(step("navigate", &[("url", "https://aluminum.browser.com")]))
+            .step(step("screenshot", &[("path", named_path.to_str().unwrap())]))
+            .step(step("screenshot", &[]))
+            .build();
+
+        let result = runner.run_test_case(test_case).await;
+
+        assert!(matches!(result.status, TestStatus::Passed), "{:?}", result.error_message);
+        assert_eq!(
+            result.artifact_paths,
+            vec![named_path.clone(), screenshot_dir.path().join("TC_SHOT_2.png")]
+        );
+        for path in &result.artifact_paths {
+            let bytes = std::fs::read(path).expect("screenshot was not written");
+            assert!(bytes.starts_with(b"\x89PNG"));
+        }
+    }
+
+    // Navigates to a small fixture and checks its text, element count and layout
+    fn backend_parity_case(dir: &Path) -> AluminumTestCase {
+        let fixture = dir.join("parity.html");
+        std::fs::write(
+            &fixture,
+            "<html><body><h1 id=\"title\">Parity</h1><ul><li>a</li><li>b</li></ul></body></html>",
+        )
+        .unwrap();
+        TestCaseBuilder::new()
+            .id("TC_PARITY")
+            .step(step("navigate", &[("url", fixture.to_str().unwrap())]))
+            .step(step("assert_text", &[("selector", "#title"), ("expected", "Parity")]))
+            .step(step("assert_count", &[("selector", "li"), ("expected", "2")]))
+            .step(step("assert_visible", &[("selector", "h1")]))
+            .step(step(
+                "assert_all",
+                &[("selector.1", "h1"), ("expected.1", "Parity"), ("selector.2", "li"), ("expected.2", "a")],
+            ))
+            .build()
+    }
+
+    #[tokio::test]
+    async fn test_assertions_pass_identically_on_both_backends() {
+        let fixture_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+
+        for backend in [EngineBackend::Full, EngineBackend::Headless] {
+            let mut runner = AluminumTestRunner::with_backend(backend);
+            assert_eq!(runner.core_pool.backend(), backend);
+
+            let result = runner.run_test_case(backend_parity_case(fixture_dir.path())).await;
+            assert!(
+                matches!(result.status, TestStatus::Passed),
+                "{:?}: {:?}",
+                backend,
+                result.error_message
+            );
+
+            let suite = runner.run_test_suite(vec![backend_parity_case(fixture_dir.path())]).await;
+            assert!(matches!(suite["TC_PARITY"].status, TestStatus::Passed), "{:?}", backend);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_headless_backend_rejects_screenshots() {
+        let screenshot_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let path = screenshot_dir.path().join("headless.png");
+        let runner = AluminumTestRunner::with_backend(EngineBackend::Headless);
+        let mut context = StepContext::default();
+
+        runner
+            .execute_step(step("navigate", &[("url", "https://aluminum.browser.com")]), &mut context)
+            .await
+            .expect("headless backend should still load pages");
+        let result = runner
+            .execute_step(step("screenshot", &[("path", path.to_str().unwrap())]), &mut context)
+            .await;
+
+        assert!(result.is_err());
+        assert!(!path.exists());
+        assert!(context.artifacts.is_empty());
+    }
+
+    // Navigates to a page with a visible heading, a hidden banner and a box placed below the viewport
+    async fn navigate_to_layout_fixture(runner: &AluminumTestRunner, dir: &Path, context: &mut StepContext) {
+        let fixture = dir.join("layout.html");
+        std::fs::write(
+            &fixture,
+            "<html><body><h1>Welcome</h1><div id=\"banner\" style=\"display:none\">Sale</div>\
+             <div id=\"footer\" style=\"position:absolute;top:5000px\">Fine print</div></body></html>",
+        )
+        .unwrap();
+        runner
+            .execute_step(step("navigate", &[("url", fixture.to_str().unwrap())]), context)
+            .await
+            .expect("navigation failed");
+    }
+
+    #[tokio::test]
+    async fn test_assert_visible_on_laid_out_element() {
+        let fixture_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let runner = AluminumTestRunner::new(BrowserCore::new());
+        let mut context = StepContext::default();
+        navigate_to_layout_fixture(&runner, fixture_dir.path(), &mut context).await;
+
+        runner
+            .execute_step(step("assert_visible", &[("selector", "h1")]), &mut context)
+            .await
+            .expect("heading should be visible");
+    }
+
+    #[tokio::test]
+    async fn test_assert_visible_fails_for_hidden_element() {
+        let fixture_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let runner = AluminumTestRunner::new(BrowserCore::new());
+        let mut context = StepContext::default();
+        navigate_to_layout_fixture(&runner, fixture_dir.path(), &mut context).await;
+
+        let result = runner
+            .execute_step(step("assert_visible", &[("selector", "[style*='display:none']")]), &mut context)
+            .await;
+        assert!(matches!(result, Err(AluminumError::AssertionFailed(message)) if message.contains("0x0")));
+
+        let result = runner
+            .execute_step(step("assert_visible", &[("selector", "#footer")]), &mut context)
+            .await;
+        assert!(matches!(result, Err(AluminumError::AssertionFailed(message)) if message.contains("off-screen")));
+    }
+
+    // Navigates to a form with an enabled #save button and a disabled #submit button
+    async fn navigate_to_form(runner: &AluminumTestRunner, dir: &Path, context: &mut StepContext) {
+        let fixture = dir.join("form.html");
+        std::fs::write(
+            &fixture,
+            "<html><body><form><button id=\"save\">Save</button><button id=\"submit\" disabled>Submit</button></form></body></html>",
+        )
+        .unwrap();
+        runner
+            .execute_step(step("navigate", &[("url", fixture.to_str().unwrap())]), context)
+            .await
+            .expect("navigation failed");
+    }
+
+    #[tokio::test]
+    async fn test_assert_enabled_on_enabled_button() {
+        let fixture_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let runner = AluminumTestRunner::new(BrowserCore::new());
+        let mut context = StepContext::default();
+        navigate_to_form(&runner, fixture_dir.path(), &mut context).await;
+
+        runner
+            .execute_step(step("assert_enabled", &[("selector", "#save")]), &mut context)
+            .await
+            .expect("#save should be enabled");
+        let result = runner
+            .execute_step(step("assert_disabled", &[("selector", "#save")]), &mut context)
+            .await;
+        match result {
+            Err(AluminumError::AssertionFailed(message)) => {
+                assert_eq!(message, "Expected '#save' to be disabled but it is enabled")
+            }
+            other => panic!("expected an assertion failure, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_assert_disabled_on_disabled_button() {
+        let fixture_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let runner = AluminumTestRunner::new(BrowserCore::new());
+        let mut context = StepContext::default();
+        navigate_to_form(&runner, fixture_dir.path(), &mut context).await;
+
+        runner
+            .execute_step(step("assert_disabled", &[("selector", "#submit")]), &mut context)
+            .await
+            .expect("#submit should be disabled");
+        let result = runner
+            .execute_step(step("assert_enabled", &[("selector", "#submit")]), &mut context)
+            .await;
+        assert!(matches!(result, Err(AluminumError::AssertionFailed(_))), "{:?}", result);
+    }
+
+    #[tokio::test]
+    async fn test_assert_enabled_on_missing_element() {
+        let fixture_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let runner = AluminumTestRunner::new(BrowserCore::new());
+        let mut context = StepContext::default();
+        navigate_to_form(&runner, fixture_dir.path(), &mut context).await;
+
+        for action in ["assert_enabled", "assert_disabled"] {
+            let result = runner.execute_step(step(action, &[("selector", "#missing")]), &mut context).await;
+            match result {
+                Err(AluminumError::ElementNotFound(selector)) => assert_eq!(selector, "#missing"),
+                other => panic!("expected ElementNotFound from {}, got {:?}", action, other),
+            }
+        }
+    }
+
+    // Serves `/user-agent` like httpbin, echoing the request's User-Agent header as JSON in a page body
+    fn serve_user_agent_echo() -> String {
+        use std::io::{BufRead, BufReader, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind mock server");
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut reader = BufReader::new(stream.unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut user_agent = String::new();
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header == "\r\n" || header.is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':') {
+                        if name.eq_ignore_ascii_case("user-agent") {
+                            user_agent = value.trim().to_string();
+                        }
+                    }
+                }
+
+                let body = format!(
+                    "<html><body>{}</body></html>",
+                    serde_json::json!({ "user-agent": user_agent })
+                );
+                let mut stream = reader.into_inner();
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+            }
+        });
+        base
+    }
+
+    #[tokio::test]
+    async fn test_set_user_agent_applies_to_navigation() {
+        const MOBILE_UA: &str =
+            "Mozilla/5.0 (iPhone; CPU iPhone OS 17_0 like Mac OS X) Aluminum/1.0 Mobile";
+        let echo_url = format!("{}/user-agent", serve_user_agent_echo());
+        let mut runner = AluminumTestRunner::new(BrowserCore::new());
+
+        let test_case = TestCaseBuilder::new()
+            .id("TC_UA")
+            .step(step("set_user_agent", &[("user_agent", MOBILE_UA)]))
+            .step(step("navigate", &[("url", echo_url.as_str())]))
+            .step(step("eval_js", &[("script", "JSON.parse(document.body.innerText)['user-agent']"), ("store_as", "sent_ua")]))
+            .step(step("store", &[("name", "expected_ua"), ("value", MOBILE_UA)]))
+            .step(step("eval_js", &[("script", "if ('{{sent_ua}}' !== '{{expected_ua}}') throw new Error('wrong UA: {{sent_ua}}')")]))
+            .build();
+
+        let result = runner.run_test_case(test_case).await;
+        assert!(matches!(result.status, TestStatus::Passed), "{:?}", result.error_message);
+    }
+
+    #[tokio::test]
+    async fn test_request_interceptor_blocks_matching_urls() {
+        let runner = AluminumTestRunner::new(BrowserCore::new());
+        runner.set_request_interceptor(|url| {
+            if url.host_str().map_or(false, |host| host.ends_with("tracker.example")) {
+                RequestAction::Block
+            } else {
+                RequestAction::Allow
+            }
+        });
+        let mut context = StepContext::default();
+
+        let blocked = runner
+            .execute_step(step("navigate", &[("url", "https://ads.tracker.example/pixel")]), &mut context)
+            .await;
+        assert!(matches!(blocked, Err(AluminumError::BlockedByInterceptor(_))));
+
+        runner
+            .execute_step(step("navigate", &[("url", "https://aluminum.browser.com")]), &mut context)
+            .await
+            .expect("allowed navigation should proceed");
+    }
+
+    #[test]
+    fn test_test_action_round_trips_through_strings() {
+        for action in TestAction::ALL {
+            assert_eq!(action.as_str().parse::<TestAction>().unwrap(), *action);
+            assert_eq!(TestAction::try_from(action.as_str()).unwrap(), *action);
+        }
+        assert!(matches!(
+            "navgate".parse::<TestAction>(),
+            Err(AluminumError::UnknownTestStep(action)) if action == "navgate"
+        ));
+    }
+
+    #[test]
+    fn test_load_suite_from_path_accepts_valid_suite() {
+        let dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let path = dir.path().join("suite.json");
+        let suite = vec![TestCaseBuilder::new()
+            .id("TC_LOAD")
+            .step(step("navigate", &[("url", "https://aluminum.browser.com")]))
+            .step(step("assert_text", &[("selector", "h1"), ("expected", "Welcome")]))
+            .build()];
+        std::fs::write(&path, serde_json::to_string(&suite).unwrap()).unwrap();
+
+        let loaded = load_suite_from_path(&path).expect("valid suite should load");
+
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].id, "TC_LOAD");
+        assert_eq!(loaded[0].steps.len(), 2);
+    }
+
+    #[test]
+    fn test_load_suite_from_path_resolves_env_references() {
+        std::env::set_var("ALUMINUM_TEST_SUITE_BASE_URL", "https://staging.browser.com");
+        std::env::remove_var("ALUMINUM_TEST_SUITE_UNSET_USER");
+        let dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let path = dir.path().join("suite.json");
+        let suite = vec![TestCaseBuilder::new()
+            .id("TC_ENV")
+            .step(step("navigate", &[("url", "${ALUMINUM_TEST_SUITE_BASE_URL}/login")]))
+            .step(step("input", &[("selector", "#user"), ("value", "${ALUMINUM_TEST_SUITE_UNSET_USER:-guest}")]))
+            .build()];
+        std::fs::write(&path, serde_json::to_string(&suite).unwrap()).unwrap();
+
+        let loaded = load_suite_from_path(&path).expect("suite should load");
+
+        assert_eq!(loaded[0].steps[0].params["url"], "https://staging.browser.com/login");
+        assert_eq!(loaded[0].steps[1].params["value"], "guest");
+    }
+
+    #[test]
+    fn test_load_suite_from_path_lists_missing_env_vars() {
+        std::env::remove_var("ALUMINUM_TEST_SUITE_MISSING_PASSWORD");
+        std::env::remove_var("ALUMINUM_TEST_SUITE_MISSING_HOST");
+        let dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let path = dir.path().join("suite.json");
+        let suite = vec![TestCaseBuilder::new()
+            .id("TC_ENV_MISSING")
+            .step(step("navigate", &[("url", "https://${ALUMINUM_TEST_SUITE_MISSING_HOST}/")]))
+            .step(step("input", &[("selector", "#pw"), ("value", "${ALUMINUM_TEST_SUITE_MISSING_PASSWORD}")]))
+            .build()];
+        std::fs::write(&path, serde_json::to_string(&suite).unwrap()).unwrap();
+
+        let message = load_suite_from_path(&path).expect_err("unset variables should be rejected").to_string();
+
+        assert!(message.contains("ALUMINUM_TEST_SUITE_MISSING_HOST"), "{}", message);
+        assert!(message.contains("ALUMINUM_TEST_SUITE_MISSING_PASSWORD"), "{}", message);
+    }
+
+    #[test]
+    fn test_interpolate_env_defaults() {
+        let lookup = |name: &str| match name {
+            "SET" => Some("value".to_string()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        };
+
+        assert_eq!(interpolate_env("${SET:-fallback}", lookup).unwrap(), "value");
+        assert_eq!(interpolate_env("${EMPTY:-fallback}", lookup).unwrap(), "fallback");
+        assert_eq!(interpolate_env("${UNSET:-}", lookup).unwrap(), "");
+        assert_eq!(interpolate_env("{{name}} ${unterminated", lookup).unwrap(), "{{name}} ${unterminated");
+        assert_eq!(interpolate_env("${A}/${B}", lookup).unwrap_err(), vec!["A", "B"]);
+    }
+
+    #[test]
+    fn test_load_suite_from_path_rejects_misspelled_action() {
+        let dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let path = dir.path().join("suite.json");
+        let suite = vec![
+            TestCaseBuilder::new()
+                .id("TC_OK")
+                .step(step("navigate", &[("url", "https://aluminum.browser.com")]))
+                .build(),
+            TestCaseBuilder::new()
+                .id("TC_TYPO")
+                .step(step("click", &[("selector", "#go")]))
+                .step(step("navgate", &[("url", "https://aluminum.browser.com")]))
+                .build(),
+        ];
+        std::fs::write(&path, serde_json::to_string(&suite).unwrap()).unwrap();
+
+        let error = load_suite_from_path(&path).expect_err("misspelled action should be rejected");
+        let message = error.to_string();
+
+        assert!(message.contains("navgate"), "{}", message);
+        assert!(message.contains("TC_TYPO"), "{}", message);
+        assert!(message.contains("step 2"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn test_navigate_loads_local_fixture() {
+        let fixture_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let fixture = fixture_dir.path().join("fixture.html");
+        std::fs::write(&fixture, "<html><body><h1>Local fixture</h1></body></html>").unwrap();
+        let file_url = url::Url::from_file_path(&fixture).unwrap().to_string();
+        let mut runner = AluminumTestRunner::new(BrowserCore::new());
+
+        for target in [file_url.as_str(), fixture.to_str().unwrap()] {
+            let test_case = TestCaseBuilder::new()
+                .id("TC_LOCAL")
+                .step(step("navigate", &[("url", target)]))
+                .step(step("assert_text", &[("selector", "h1"), ("expected", "Local fixture")]))
+                .build();
+
+            let result = runner.run_test_case(test_case).await;
+            assert!(matches!(result.status, TestStatus::Passed), "{}: {:?}", target, result.error_message);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_navigate_to_missing_local_file_fails() {
+        let fixture_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let missing = fixture_dir.path().join("missing.html");
+        let mut runner = AluminumTestRunner::new(BrowserCore::new());
+
+        let test_case = TestCaseBuilder::new()
+            .id("TC_MISSING")
+            .step(step("navigate", &[("url", missing.to_str().unwrap())]))
+            .build();
+
+        let result = runner.run_test_case(test_case).await;
+        assert!(matches!(result.status, TestStatus::Failed));
+        let message = result.error_message.unwrap();
+        assert!(message.contains("missing.html"), "{}", message);
+    }
+
+    // Writes a page with `rows` result rows and returns a case that opens it
+    fn result_rows_case(dir: &Path, rows: usize, assertion: TestStep) -> AluminumTestCase {
+        let fixture = dir.join("results.html");
+        let items: String = (1..=rows).map(|i| format!("<li class=\"result\">Result {}</li>", i)).collect();
+        std::fs::write(&fixture, format!("<html><body><ul>{}</ul></body></html>", items)).unwrap();
+        TestCaseBuilder::new()
+            .id("TC_COUNT")
+            .step(step("navigate", &[("url", fixture.to_str().unwrap())]))
+            .step(assertion)
+            .build()
+    }
+
+    #[tokio::test]
+    async fn test_assert_count_exact_and_gte() {
+        let fixture_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let mut runner = AluminumTestRunner::new(BrowserCore::new());
+
+        for assertion in [
+            step("assert_count", &[("selector", "li.result"), ("expected", "5")]),
+            step("assert_count", &[("selector", "li.result"), ("expected", "3"), ("comparator", "gte")]),
+            step("assert_count", &[("selector", "li.result"), ("expected", "5"), ("comparator", "lte")]),
+        ] {
+            let result = runner.run_test_case(result_rows_case(fixture_dir.path(), 5, assertion)).await;
+            assert!(matches!(result.status, TestStatus::Passed), "{:?}", result.error_message);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_assert_count_fails_on_wrong_count() {
+        let fixture_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let mut runner = AluminumTestRunner::new(BrowserCore::new());
+
+        let assertion = step("assert_count", &[("selector", "li.result"), ("expected", "5")]);
+        let result = runner.run_test_case(result_rows_case(fixture_dir.path(), 4, assertion)).await;
+
+        assert!(matches!(result.status, TestStatus::Failed));
+        assert!(result.error_message.unwrap().contains("exactly 5 element(s) matching 'li.result' but found 4"));
+    }
+
+    #[tokio::test]
+    async fn test_assert_count_rejects_non_numeric_expected() {
+        let fixture_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let mut runner = AluminumTestRunner::new(BrowserCore::new());
+
+        let assertion = step("assert_count", &[("selector", "li.result"), ("expected", "five")]);
+        let result = runner.run_test_case(result_rows_case(fixture_dir.path(), 5, assertion)).await;
+
+        assert!(matches!(result.status, TestStatus::Failed));
+        let message = result.error_message.unwrap();
+        assert!(message.starts_with("Invalid step parameter"), "{}", message);
+        assert!(message.contains("'five'"), "{}", message);
+    }
+
+    // Writes a page with a 100x50 button at (10, 10) that renames itself when clicked
+    fn positioned_button_fixture(dir: &Path) -> PathBuf {
+        let fixture = dir.join("button.html");
+        std::fs::write(
+            &fixture,
+            "<html><body style=\"margin:0\">\
+             <button id=\"target\" style=\"position:absolute;left:10px;top:10px;width:100px;height:50px\" \
+             onclick=\"this.textContent='Clicked'\">Click me</button></body></html>",
+        )
+        .unwrap();
+        fixture
+    }
+
+    #[tokio::test]
+    async fn test_click_at_hits_laid_out_element() {
+        let fixture_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let fixture = positioned_button_fixture(fixture_dir.path());
+        let runner = AluminumTestRunner::new(BrowserCore::new());
+        let mut context = StepContext::default();
+
+        runner
+            .execute_step(step("navigate", &[("url", fixture.to_str().unwrap())]), &mut context)
+            .await
+            .expect("fixture should load");
+        assert!(runner.browser_core.lock().unwrap().element_from_point(60.0, 35.0).is_some());
+
+        runner
+            .execute_step(step("click_at", &[("x", "60"), ("y", "35")]), &mut context)
+            .await
+            .expect("click inside the button should succeed");
+        runner
+            .execute_step(step("assert_text", &[("selector", "#target"), ("expected", "Clicked")]), &mut context)
+            .await
+            .expect("button should have handled the click");
+    }
+
+    #[tokio::test]
+    async fn test_click_at_empty_region_and_outside_viewport() {
+        let fixture_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let fixture = positioned_button_fixture(fixture_dir.path());
+        let runner = AluminumTestRunner::new(BrowserCore::new());
+        let mut context = StepContext::default();
+
+        runner
+            .execute_step(step("navigate", &[("url", fixture.to_str().unwrap())]), &mut context)
+            .await
+            .expect("fixture should load");
+        assert!(runner.browser_core.lock().unwrap().element_from_point(400.0, 300.0).is_none());
+
+        runner
+            .execute_step(step("click_at", &[("x", "400"), ("y", "300")]), &mut context)
+            .await
+            .expect("clicking empty space is a no-op");
+        runner
+            .execute_step(step("assert_text", &[("selector", "#target"), ("expected", "Click me")]), &mut context)
+            .await
+            .expect("button should not have been clicked");
+
+        let error = runner
+            .execute_step(step("click_at", &[("x", "-5"), ("y", "10")]), &mut context)
+            .await
+            .expect_err("clicking outside the viewport should fail");
+        assert!(error.to_string().contains("outside"));
+    }
+
+    #[test]
+    fn test_seeded_test_data_is_reproducible() {
+        let first = generate_random_test_data_seeded(64, 42);
+        let second = generate_random_test_data_seeded(64, 42);
+
+        assert_eq!(first.len(), 64);
+        assert_eq!(first, second);
+        assert_ne!(first, generate_random_test_data_seeded(64, 43));
+
+        let url_safe = generate_random_test_data_with_charset(64, ALPHANUMERIC_CHARSET, &mut StdRng::seed_from_u64(42));
+        assert!(url_safe.chars().all(|c| c.is_ascii_alphanumeric()));
+    }
+
+    // Writes a page that runs `script` on load and returns a case that checks its console
+    fn console_case(dir: &Path, script: &str, check: TestStep) -> AluminumTestCase {
+        let fixture = dir.join("console.html");
+        std::fs::write(&fixture, format!("<html><body><p>Console</p><script>{}</script></body></html>", script)).unwrap();
+        TestCaseBuilder::new()
+            .id("TC_CONSOLE")
+            .step(step("navigate", &[("url", fixture.to_str().unwrap())]))
+            .step(check)
+            .build()
+    }
+
+    #[tokio::test]
+    async fn test_assert_no_console_errors_on_clean_page() {
+        let fixture_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let mut runner = AluminumTestRunner::new(BrowserCore::new());
+
+        let test_case = console_case(fixture_dir.path(), "console.log('ready')", step("assert_no_console_errors", &[]));
+        let result = runner.run_test_case(test_case).await;
+
+        assert!(matches!(result.status, TestStatus::Passed), "{:?}", result.error_message);
+    }
+
+    #[tokio::test]
+    async fn test_assert_no_console_errors_reports_logged_error() {
+        let fixture_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let mut runner = AluminumTestRunner::new(BrowserCore::new());
+        let script = "console.error('widget failed to render'); console.error('favicon.ico 404')";
+
+        let test_case = console_case(fixture_dir.path(), script, step("assert_no_console_errors", &[]));
+        let result = runner.run_test_case(test_case).await;
+        assert!(matches!(result.status, TestStatus::Failed));
+        let message = result.error_message.unwrap();
+        assert!(message.contains("2 console error(s)"), "{}", message);
+        assert!(message.contains("widget failed to render"), "{}", message);
+
+        let test_case = console_case(
+            fixture_dir.path(),
+            script,
+            step("assert_no_console_errors", &[("ignore", "favicon.ico | widget failed")]),
+        );
+        let result = runner.run_test_case(test_case).await;
+        assert!(matches!(result.status, TestStatus::Passed), "{:?}", result.error_message);
+    }
+
+    #[tokio::test]
+    async fn test_retry_with_backoff_skips_non_retryable_errors() {
+        let attempts = AtomicUsize::new(0);
+        let result: Result<(), AluminumError> = retry_with_backoff_if(
+            || async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err(AluminumError::AssertionFailed("title mismatch".to_string()))
+            },
+            MAX_RETRIES,
+            Duration::from_millis(1),
+            AluminumError::is_retryable,
+        )
+        .await;
+
+        assert!(matches!(result, Err(AluminumError::AssertionFailed(_))));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_retry_with_backoff_retries_network_errors() {
+        let attempts = AtomicUsize::new(0);
+        let result = retry_with_backoff_if(
+            || async {
+                if attempts.fetch_add(1, Ordering::SeqCst) < 2 {
+                    Err(AluminumError::NetworkError("connection reset".to_string()))
+                } else {
+                    Ok("loaded")
+                }
+            },
+            MAX_RETRIES,
+            Duration::from_millis(1),
+            AluminumError::is_retryable,
+        )
+        .await;
+
+        assert_eq!(result.unwrap(), "loaded");
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_with_backoff_retries_any_error() {
+        let attempts = AtomicUsize::new(0);
+        let result: Result<(), String> = retry_with_backoff(
+            || async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err("still failing".to_string())
+            },
+            2,
+            Duration::from_millis(1),
+        )
+        .await;
+
+        assert_eq!(result.unwrap_err(), "still failing");
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_cap() {
+        let mut backoff = Backoff::capped(Duration::from_millis(10), Duration::from_millis(50));
+        let delays: Vec<u128> = (0..5).map(|_| backoff.next_delay().as_millis()).collect();
+        assert_eq!(delays, vec![10, 20, 40, 50, 50]);
+
+        let mut uncapped = Backoff::new(Duration::from_millis(100));
+        uncapped.next_delay();
+        assert_eq!(uncapped.next_delay(), Duration::from_millis(200));
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_returns_once_element_exists() {
+        let fixture_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let fixture = fixture_dir.path().join("ready.html");
+        std::fs::write(&fixture, "<html><body><div id=\"ready\">Loaded</div></body></html>").unwrap();
+        let mut runner = AluminumTestRunner::new(BrowserCore::new());
+
+        let test_case = TestCaseBuilder::new()
+            .id("TC_WAIT_FOR")
+            .step(step("navigate", &[("url", fixture.to_str().unwrap())]))
+            .step(step("wait_for", &[("selector", "#ready")]))
+            .build();
+        let result = runner.run_test_case(test_case).await;
+
+        assert!(matches!(result.status, TestStatus::Passed), "{:?}", result.error_message);
+        let wait = &result.step_timings[1];
+        assert_eq!(wait.action, "wait_for");
+        // Found on the first poll, so no backoff delay was slept
+        assert!(wait.elapsed_ms < WAIT_FOR_INITIAL_POLL.as_millis() as u64, "waited {}ms", wait.elapsed_ms);
+    }
+
+    // Writes a page whose #status text changes from "Loading" to "Ready" after `delay_ms`
+    fn settling_text_fixture(dir: &Path, delay_ms: u64) -> PathBuf {
+        let fixture = dir.join("settling.html");
+        let script = format!(
+            "setTimeout(function () {{ document.getElementById('status').textContent = 'Ready'; }}, {});",
+            delay_ms
+        );
+        std::fs::write(
+            &fixture,
+            format!("<html><body><p id=\"status\">Loading</p><script>{}</script></body></html>", script),
+        )
+        .unwrap();
+        fixture
+    }
+
+    #[tokio::test]
+    async fn test_assert_text_retries_until_text_settles() {
+        let fixture_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let fixture = settling_text_fixture(fixture_dir.path(), 200);
+        let settling_case = |id: &str| {
+            TestCaseBuilder::new()
+                .id(id)
+                .step(step("navigate", &[("url", fixture.to_str().unwrap())]))
+                .step(step("assert_text", &[("selector", "#status"), ("expected", "Ready")]))
+                .build()
+        };
+
+        // Without an assertion timeout the text is checked once, before it settles
+        let mut runner = AluminumTestRunner::new(BrowserCore::new());
+        assert_eq!(runner.assertion_timeout, Duration::ZERO);
+        let result = runner.run_test_case(settling_case("TC_ASSERT_NO_WAIT")).await;
+        assert!(matches!(result.status, TestStatus::Failed));
+
+        let mut runner = AluminumTestRunner::new(BrowserCore::new());
+        runner.set_assertion_timeout(Duration::from_secs(2));
+        let result = runner.run_test_case(settling_case("TC_ASSERT_WAIT")).await;
+        assert!(matches!(result.status, TestStatus::Passed), "{:?}", result.error_message);
+    }
+
+    #[tokio::test]
+    async fn test_assert_text_fails_after_assertion_timeout() {
+        let fixture_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let fixture = settling_text_fixture(fixture_dir.path(), 60_000);
+        let mut runner = AluminumTestRunner::new(BrowserCore::new());
+        runner.set_assertion_timeout(Duration::from_millis(300));
+        let mut context = StepContext::default();
+        runner
+            .execute_step(step("navigate", &[("url", fixture.to_str().unwrap())]), &mut context)
+            .await
+            .expect("navigation failed");
+
+        let started = Instant::now();
+        let outcome = runner
+            .execute_step(step("assert_text", &[("selector", "#status"), ("expected", "Ready")]), &mut context)
+            .await;
+
+        let elapsed = started.elapsed();
+        match outcome {
+            Err(AluminumError::AssertionMismatch { actual, .. }) => assert_eq!(actual, "Loading"),
+            other => panic!("expected a text mismatch, got {:?}", other),
+        }
+        assert!(elapsed >= Duration::from_millis(300));
+        assert!(elapsed < Duration::from_millis(300) + WAIT_FOR_MAX_POLL * 2, "took {:?}", elapsed);
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_times_out_when_element_never_appears() {
+        let runner = AluminumTestRunner::new(BrowserCore::new());
+        let started = Instant::now();
+
+        let outcome = runner
+            .execute_step(
+                step("wait_for", &[("selector", "#never"), ("timeout_ms", "300")]),
+                &mut StepContext::default(),
+            )
+            .await;
+
+        let elapsed = started.elapsed();
+        match outcome {
+            Err(AluminumError::AssertionFailed(message)) => {
+                assert_eq!(message, "Timed out after 300ms waiting for '#never'")
+            }
+            other => panic!("expected a timeout, got {:?}", other),
+        }
+        assert!(elapsed >= Duration::from_millis(300));
+        assert!(elapsed < Duration::from_millis(300) + WAIT_FOR_MAX_POLL * 2, "took {:?}", elapsed);
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_network_idle_once_requests_finish() {
+        // Stub core: three requests in flight that finish one every 30ms
+        let in_flight = Arc::new(AtomicUsize::new(3));
+        let finisher = {
+            let in_flight = Arc::clone(&in_flight);
+            tokio::spawn(async move {
+                for _ in 0..3 {
+                    tokio::time::sleep(Duration::from_millis(30)).await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                }
+            })
+        };
+        let started = Instant::now();
+
+        let outcome = wait_for_network_idle(
+            || NetworkActivity { in_flight: in_flight.load(Ordering::SeqCst), started: 3 },
+            Duration::from_millis(100),
+            Duration::from_secs(2),
+        )
+        .await;
+
+        finisher.await.unwrap();
+        assert!(outcome.is_ok(), "{:?}", outcome);
+        // The last request finishes after ~90ms and the network must then stay idle for 100ms
+        assert!(started.elapsed() >= Duration::from_millis(190), "idle after {:?}", started.elapsed());
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_network_idle_times_out_while_requests_keep_starting() {
+        // Stub core: nothing is in flight at any poll, but a new request starts between every poll
+        let mut started_requests = 0;
+        let started = Instant::now();
+
+        let outcome = wait_for_network_idle(
+            || {
+                started_requests += 1;
+                NetworkActivity { in_flight: 0, started: started_requests }
+            },
+            Duration::from_millis(100),
+            Duration::from_millis(300),
+        )
+        .await;
+
+        match outcome {
+            Err(AluminumError::AssertionFailed(message)) => {
+                assert!(message.starts_with("Network was not idle for 100ms within 300ms"), "{}", message)
+            }
+            other => panic!("expected a timeout, got {:?}", other),
+        }
+        assert!(started.elapsed() >= Duration::from_millis(300));
+    }
+
+    #[test]
+    fn test_wait_network_idle_rejects_non_numeric_durations() {
+        let params = HashMap::from([("idle_ms".to_string(), "soon".to_string())]);
+        assert!(matches!(
+            parse_millis(&params, "wait_network_idle", "idle_ms", NETWORK_IDLE_DURATION),
+            Err(AluminumError::InvalidParameter(_))
+        ));
+        assert_eq!(
+            parse_millis(&params, "wait_network_idle", "timeout_ms", WAIT_FOR_TIMEOUT).unwrap(),
+            WAIT_FOR_TIMEOUT
+        );
+        assert_eq!("wait_network_idle".parse::<TestAction>().unwrap(), TestAction::WaitNetworkIdle);
+    }
+
+    #[tokio::test]
+    async fn test_assert_all_reports_every_failure() {
+        let fixture_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let fixture = fixture_dir.path().join("profile.html");
+        std::fs::write(
+            &fixture,
+            "<html><body><h1>Profile</h1><p id=\"name\">Ada</p><p id=\"role\">Admin</p></body></html>",
+        )
+        .unwrap();
+        let runner = AluminumTestRunner::new(BrowserCore::new());
+        let mut context = StepContext::default();
+        runner
+            .execute_step(step("navigate", &[("url", fixture.to_str().unwrap())]), &mut context)
+            .await
+            .expect("fixture should load");
+
+        let result = runner
+            .execute_step(
+                step(
+                    "assert_all",
+                    &[
+                        ("selector.1", "h1"),
+                        ("expected.1", "Profile"),
+                        ("selector.2", "#name"),
+                        ("expected.2", "Grace"),
+                        ("selector.3", "#role"),
+                        ("expected.3", "Viewer"),
+                    ],
+                ),
+                &mut context,
+            )
+            .await;
+
+        match result {
+            Err(AluminumError::AssertionFailed(message)) => {
+                assert!(message.starts_with("2 of 3 assertion(s) failed"), "{}", message);
+                assert!(message.contains("'#name': Expected text 'Grace' but found 'Ada'"), "{}", message);
+                assert!(message.contains("'#role': Expected text 'Viewer' but found 'Admin'"), "{}", message);
+                assert!(!message.contains("'h1'"), "{}", message);
+            }
+            other => panic!("expected a combined assertion failure, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_assert_all_rejects_unpaired_checks() {
+        let params = |pairs: &[(&str, &str)]| step("assert_all", pairs).params;
+
+        let checks = parse_sub_assertions(&params(&[
+            ("selector.10", "p"),
+            ("expected.10", "b"),
+            ("selector.2", "h1"),
+            ("expected.2", "a"),
+        ]))
+        .unwrap();
+        assert_eq!(checks, vec![("h1".to_string(), "a".to_string()), ("p".to_string(), "b".to_string())]);
+
+        assert!(matches!(
+            parse_sub_assertions(&params(&[("selector.1", "h1")])),
+            Err(AluminumError::InvalidParameter(_))
+        ));
+        assert!(parse_sub_assertions(&params(&[("expected.1", "Welcome")])).is_err());
+        assert!(parse_sub_assertions(&params(&[])).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_breakpoint_is_a_no_op_when_not_interactive() {
+        let runner = AluminumTestRunner::new(BrowserCore::new());
+        assert!(!runner.interactive);
+        let mut context = StepContext::default();
+
+        let result = tokio::time::timeout(
+            Duration::from_secs(1),
+            runner.execute_step(step("breakpoint", &[("message", "inspect the cart")]), &mut context),
+        )
+        .await
+        .expect("breakpoint should not block a non-interactive runner");
+
+        assert!(result.is_ok());
+    }
+
+    struct EvenCountAssertion;
+
+    #[async_trait]
+    impl CustomAssertion for EvenCountAssertion {
+        fn name(&self) -> &str {
+            "assert_even_count"
+        }
+
+        async fn check(&self, core: &BrowserCore, params: &HashMap<String, String>) -> Result<(), AluminumError> {
+            let selector = &params["selector"];
+            let count = core.query_selector_all(selector).await?.len();
+            if count % 2 != 0 {
+                return Err(AluminumError::AssertionFailed(format!(
+                    "Expected an even number of '{}' elements but found {}",
+                    selector, count
+                )));
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_custom_assertion_driven_from_step() {
+        let fixture_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let fixture = fixture_dir.path().join("list.html");
+        std::fs::write(&fixture, "<html><body><ul><li>a</li><li>b</li><li>c</li></ul></body></html>").unwrap();
+        let mut runner = AluminumTestRunner::new(BrowserCore::new());
+        runner.register_assertion(EvenCountAssertion).unwrap();
+
+        let case = |selector: &str| {
+            TestCaseBuilder::new()
+                .id("TC_EVEN")
+                .step(step("navigate", &[("url", fixture.to_str().unwrap())]))
+                .step(step("assert_even_count", &[("selector", selector)]))
+                .build()
+        };
+
+        let result = runner.run_test_case(case("ul")).await;
+        assert!(matches!(result.status, TestStatus::Passed), "{:?}", result.error_message);
+
+        let result = runner.run_test_case(case("li")).await;
+        assert!(matches!(result.status, TestStatus::Failed));
+        assert_eq!(
+            result.error_message.unwrap(),
+            AluminumError::AssertionFailed("Expected an even number of 'li' elements but found 3".to_string()).to_string()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_custom_assertion_loaded_from_suite_file() {
+        let fixture_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let fixture = fixture_dir.path().join("list.html");
+        std::fs::write(&fixture, "<html><body><ul><li>a</li><li>b</li></ul></body></html>").unwrap();
+        let path = fixture_dir.path().join("suite.json");
+        let suite = vec![TestCaseBuilder::new()
+            .id("TC_EVEN_FILE")
+            .step(step("navigate", &[("url", fixture.to_str().unwrap())]))
+            .step(step("assert_even_count", &[("selector", "li")]))
+            .build()];
+        std::fs::write(&path, serde_json::to_string(&suite).unwrap()).unwrap();
+
+        // Without the runner's registrations the custom action is unknown
+        let message = load_suite_from_path(&path).expect_err("unregistered action should be rejected").to_string();
+        assert!(message.contains("assert_even_count"), "{}", message);
+
+        let mut runner = AluminumTestRunner::new(BrowserCore::new());
+        runner.register_assertion(EvenCountAssertion).unwrap();
+        let loaded = runner.load_suite(&path).expect("registered action should be accepted");
+        let result = runner.run_test_case(loaded.into_iter().next().unwrap()).await;
+        assert!(matches!(result.status, TestStatus::Passed), "{:?}", result.error_message);
+    }
+
+    #[test]
+    fn test_custom_assertion_cannot_override_builtin() {
+        struct FakeNavigate;
+
+        #[async_trait]
+        impl CustomAssertion for FakeNavigate {
+            fn name(&self) -> &str {
+                "navigate"
+            }
+
+            async fn check(&self, _core: &BrowserCore, _params: &HashMap<String, String>) -> Result<(), AluminumError> {
+                Ok(())
+            }
+        }
+
+        let mut runner = AluminumTestRunner::new(BrowserCore::new());
+        let error = runner.register_assertion(FakeNavigate).unwrap_err();
+        assert!(error.contains("built-in"), "{}", error);
+        runner.register_assertion(EvenCountAssertion).unwrap();
+        assert!(runner.register_assertion(EvenCountAssertion).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_failed_result_includes_page_snapshot() {
+        let fixture_dir = tempfile::TempDir::new().expect("Failed to create temp dir");
+        let fixture = fixture_dir.path().join("checkout.html");
+        std::fs::write(
+            &fixture,
+            "<html><head><title>Checkout</title></head><body><h1>Your cart</h1></body></html>",
+        )
+        .unwrap();
+        let mut runner = AluminumTestRunner::new(BrowserCore::new());
+
+        let test_case = TestCaseBuilder::new()
+            .id("TC_SNAPSHOT")
+            .step(step("navigate", &[("url", fixture.to_str().unwrap())]))
+            .step(step("assert_text", &[("selector", "h1"), ("expected", "Order placed")]))
+            .build();
+        let result = runner.run_test_case(test_case).await;
+
+        assert!(matches!(result.status, TestStatus::Failed));
+        let snapshot = result.page_snapshot.clone().expect("failed result should carry a snapshot");
+        assert!(snapshot.url.starts_with("file://"), "{}", snapshot.url);
+        assert!(snapshot.url.ends_with("checkout.html"), "{}", snapshot.url);
+        assert_eq!(snapshot.title, "Checkout");
+        assert!(snapshot.dom_node_count > 0);
+
+        runner.results.insert(result.test_case_id.clone(), result);
+        assert!(runner.generate_report().contains("(\"Checkout\", "));
+    }
+
+    #[tokio::test]
+    async fn test_url_policy_gates_navigation() {
+        let mut runner = AluminumTestRunner::new(BrowserCore::new());
+        runner.set_url_policy(
+            UrlPolicy::new()
+                .allow_scheme("https")
+                .allow_host("*.browser.com")
+                .block_host("ads.browser.com"),
+        );
+        let mut context = StepContext::default();
+
+        runner
+            .execute_step(step("navigate", &[("url", "https://aluminum.browser.com")]), &mut context)
+            .await
+            .expect("allowed host should load");
+
+        let blocked = runner
+            .execute_step(step("navigate", &[("url", "https://ads.browser.com/banner")]), &mut context)
+            .await;
+        assert!(matches!(blocked, Err(AluminumError::BlockedByPolicy(message)) if message.contains("ads.browser.com")));
+
+        let insecure = runner
+            .execute_step(step("navigate", &[("url", "http://aluminum.browser.com")]), &mut context)
+            .await;
+        assert!(matches!(insecure, Err(AluminumError::BlockedByPolicy(message)) if message.contains("'http'")));
+    }
+
+    #[test]
+    fn test_url_policy_host_patterns() {
+        let policy = UrlPolicy::new().allow_host("*.example.com").allow_host("kiosk.local");
+        let check = |url: &str| policy.check(&url::Url::parse(url).unwrap()).is_ok();
+
+        assert!(check("https://example.com/"));
+        assert!(check("https://docs.EXAMPLE.com/guide"));
+        assert!(check("http://kiosk.local:8080/"));
+        assert!(!check("https://notexample.com/"));
+        assert!(!check("https://sub.kiosk.local/"));
+        assert!(!check("file:///etc/passwd"));
+    }
+
+    #[test]
+    fn test_report_options_omit_passed_cases_but_count_them() {
+        let mut runner = AluminumTestRunner::new(BrowserCore::new());
+        for (id, status) in [
+            ("TC001", TestStatus::Passed),
+            ("TC002", TestStatus::Failed),
+            ("TC003", TestStatus::Passed),
+            ("TC004", TestStatus::Timeout),
+        ] {
+            runner.results.insert(id.to_string(), result(id, status));
+        }
+
+        assert_eq!(runner.generate_report_with(ReportOptions::default()), runner.generate_report());
+
+        let failures_only = runner.generate_report_with(ReportOptions {
+            include_passed: false,
+            ..ReportOptions::default()
+        });
+        assert!(!failures_only.contains("Test Case: TC001"));
+        assert!(!failures_only.contains("Test Case: TC003"));
+        assert!(failures_only.contains("Test Case: TC002"));
+        assert!(failures_only.contains("Test Case: TC004"));
+        assert_eq!(text_count(&failures_only, "Total Tests"), 4);
+        assert_eq!(text_count(&failures_only, "Passed"), 2);
+
+        let summary_only = runner.generate_report_with(ReportOptions {
+            summary_only: true,
+            ..ReportOptions::default()
+        });
+        assert!(!summary_only.contains("Test Case:"));
+        assert_eq!(text_count(&summary_only, "Total Tests"), 4);
+        assert_eq!(text_count(&summary_only, "Failed"), 1);
+        assert_eq!(text_count(&summary_only, "Timed Out"), 1);
+    }
+
+    #[test]
+    fn test_merge_results_combines_disjoint_shards() {
+        let mut runner = AluminumTestRunner::new(BrowserCore::new());
+        runner.results.insert("SHARD0_TC001".to_string(), result("SHARD0_TC001", TestStatus::Passed));
+
+        let shard = |ids: &[&str], status: fn() -> TestStatus| -> HashMap<String, TestResult> {
+            ids.iter().map(|id| (id.to_string(), result(id, status()))).collect()
+        };
+        runner
+            .merge_results(vec![
+                shard(&["SHARD1_TC001", "SHARD1_TC002"], || TestStatus::Passed),
+                shard(&["SHARD2_TC001"], || TestStatus::Failed),
+            ])
+            .expect("disjoint shards should merge");
+
+        let report = runner.generate_report();
+        assert!(report.contains("Total Tests: 4"));
+        assert!(report.contains("Passed: 3"));
+        assert!(report.contains("Failed: 1"));
+        assert!(report.contains("Test Case: SHARD2_TC001"));
+
+        let error = runner
+            .merge_results(vec![
+                shard(&["SHARD3_TC001", "SHARD3_TC002"], || TestStatus::Passed),
+                shard(&["SHARD3_TC002"], || TestStatus::Failed),
+            ])
+            .unwrap_err();
+        assert_eq!(error, "Test case id(s) reported by more than one shard: SHARD3_TC002");
+        assert!(!runner.results.contains_key("SHARD3_TC001"), "a failed merge must not be applied partially");
+    }
+
+    // Runs `calls` simulated requests and records which ones were dropped
+    async fn loss_pattern(simulator: &NetworkSimulator, calls: usize) -> Vec<bool> {
+        let mut pattern = Vec::with_capacity(calls);
+        for _ in 0..calls {
+            let outcome = simulator.simulate(|| async { Ok(()) }).await;
+            pattern.push(outcome.is_err());
+        }
+        pattern
+    }
+
+    #[tokio::test]
+    async fn test_seeded_network_simulator_replays_loss_sequence() {
+        let first = loss_pattern(&NetworkSimulator::with_seed(Duration::ZERO, 0.3, 42), 200).await;
+        let second = loss_pattern(&NetworkSimulator::with_seed(Duration::ZERO, 0.3, 42), 200).await;
+        let other_seed = loss_pattern(&NetworkSimulator::with_seed(Duration::ZERO, 0.3, 7), 200).await;
+
+        assert_eq!(first, second);
+        assert_ne!(first, other_seed);
+        let dropped = first.iter().filter(|&&dropped| dropped).count();
+        assert!((30..=90).contains(&dropped), "dropped {} of 200", dropped);
+    }
+
+    #[tokio::test]
+    async fn test_scripted_network_simulator_follows_script() {
+        let simulator = NetworkSimulator::scripted(Duration::ZERO, vec![false, true, true, false]);
+
+        let pattern = loss_pattern(&simulator, 6).await;
+
+        assert_eq!(pattern, vec![false, true, true, false, false, false]);
+    }
+
+    #[test]
+    fn test_builder_fills_defaults() {
+        let test_case = TestCaseBuilder::new().id("TC100").build();
+
+        assert_eq!(test_case.id, "TC100");
+        assert_eq!(test_case.name, "TC100");
+        assert!(test_case.description.is_empty());
+        assert!(test_case.steps.is_empty());
+        assert!(test_case.tags.is_empty());
+        assert_eq!(test_case.timeout, DEFAULT_TIMEOUT);
+    }
+
+    #[test]
+    fn test_builder_with_all_fields() {
+        let test_case = TestCaseBuilder::new()
+            .id("TC101")
+            .name("Search Test")
+            .description("Verifies that search returns results")
+            .step(create_test_step(
+                "navigate",
+                [("url".to_string(), "https://aluminum.browser.com".to_string())]
+                    .iter()
+                    .cloned()
+                    .collect(),
+            ))
+            .step(create_test_step(
+                "click",
+                [("selector".to_string(), "#search".to_string())]
+                    .iter()
+                    .cloned()
+                    .collect(),
+            ))
+            .expected_result("Search results are shown")
+            .tag("smoke")
+            .tag("search")
+            .timeout(Duration::from_secs(5))
+            .build();
+
+        assert_eq!(test_case.id, "TC101");
+        assert_eq!(test_case.name, "Search Test");
+        assert_eq!(test_case.description, "Verifies that search returns results");
+        assert_eq!(test_case.steps.len(), 2);
+        assert_eq!(test_case.steps[1].action, "click");
+        assert_eq!(test_case.expected_result, "Search results are shown");
+        assert_eq!(test_case.tags, vec!["smoke", "search"]);
+        assert_eq!(test_case.timeout, Duration::from_secs(5));
+    }
+}
+
+// Additional utility functions for the test library
+
+/// Generates a random test data string
+pub fn generate_random_test_data(length: usize) -> String {
+    generate_random_test_data_with_charset(length, TEST_DATA_CHARSET, &mut rand::thread_rng())
+}
+
+/// Generates a reproducible test data string; the same seed always yields the same output
+pub fn generate_random_test_data_seeded(length: usize, seed: u64) -> String {
+    generate_random_test_data_with_charset(length, TEST_DATA_CHARSET, &mut StdRng::seed_from_u64(seed))
+}
+
+/// Generates a test data string drawn from `charset` using the given generator
+///
+/// Pass `ALPHANUMERIC_CHARSET` for data that is safe to put in URLs.
+pub fn generate_random_test_data_with_charset<R: Rng>(length: usize, charset: &[u8], rng: &mut R) -> String {
+    assert!(!charset.is_empty(), "charset must not be empty");
+    (0..length)
+        .map(|_| {
+            let idx = rng.gen_range(0..charset.len());
+            charset[idx] as char
+        })
+        .collect()
+}
+
+/// Measures the performance of a given operation
+pub async fn measure_performance<F, Fut, T>(operation: F) -> (T, Duration)
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = T>,
+{
+    let start = Instant::now();
+    let result = operation().await;
+    let duration = start.elapsed();
+    (result, duration)
+}
+
+/// Exponentially growing delays between attempts, optionally capped
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    next: Duration,
+    max: Option<Duration>,
+}
+
+impl Backoff {
+    /// Delays that start at `initial` and double without limit
+    pub fn new(initial: Duration) -> Self {
+        Backoff { next: initial, max: None }
+    }
+
+    /// Delays that start at `initial` and double until they reach `max`
+    pub fn capped(initial: Duration, max: Duration) -> Self {
+        Backoff { next: initial.min(max), max: Some(max) }
+    }
+
+    /// Returns the delay to wait now and advances to the next one
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self.next;
+        self.next = match self.max {
+            Some(max) => (delay * 2).min(max),
+            None => delay * 2,
+        };
+        delay
+    }
+}
+
+/// Retries an asynchronous operation with exponential backoff
+pub async fn retry_with_backoff<F, Fut, T, E>(
+    operation: F,
+    max_retries: u32,
+    initial_delay: Duration,
+) -> Result<T, E>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: std::fmt::Debug,
+{
+    retry_with_backoff_if(operation, max_retries, initial_delay, |_| true).await
+}
+
+/// Retries an asynchronous operation with exponential backoff while `should_retry` accepts the error
+///
+/// Errors it rejects are returned immediately, e.g. pass `AluminumError::is_retryable`.
+pub async fn retry_with_backoff_if<F, Fut, T, E, P>(
+    operation: F,
+    max_retries: u32,
+    initial_delay: Duration,
+    should_retry: P,
+) -> Result<T, E>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: std::fmt::Debug,
+    P: Fn(&E) -> bool,
+{
+    let mut retries = 0;
+    let mut backoff = Backoff::new(initial_delay);
+
+    loop {
+        match operation().await {
+            Ok(value) => return Ok(value),
+            Err(e) => {
+                if retries >= max_retries || !should_retry(&e) {
+                    return Err(e);
+                }
+                let delay = backoff.next_delay();
+                warn!("Operation failed, retrying in {:?}: {:?}", delay, e);
+                tokio::time::sleep(delay).await;
+                retries += 1;
+            }
+        }
+    }
+}
+
+/// Request counters of a browser core at one point in time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NetworkActivity {
+    /// Requests sent but not yet finished
+    pub in_flight: usize,
+    /// Requests sent since the core was created
+    pub started: u64,
+}
+
+/// Waits until no request has been in flight or started for `idle`
+///
+/// `activity` is polled every `NETWORK_IDLE_POLL`; a change in the started
+/// count restarts the idle period even if the request finished between polls.
+/// Fails with `AssertionFailed` if the network has not been idle for `idle`
+/// by the time `timeout` passes.
+pub async fn wait_for_network_idle<F>(mut activity: F, idle: Duration, timeout: Duration) -> Result<(), AluminumError>
+where
+    F: FnMut() -> NetworkActivity,
+{
+    let started = Instant::now();
+    let mut last = activity();
+    let mut idle_since = (last.in_flight == 0).then(Instant::now);
+
+    loop {
+        if let Some(since) = idle_since {
+            if since.elapsed() >= idle {
+                return Ok(());
+            }
+        }
+        if started.elapsed() >= timeout {
+            return Err(AluminumError::AssertionFailed(format!(
+                "Network was not idle for {}ms within {}ms ({} request(s) in flight)",
+                idle.as_millis(),
+                timeout.as_millis(),
+                last.in_flight
+            )));
+        }
+
+        tokio::time::sleep(NETWORK_IDLE_POLL).await;
+        let current = activity();
+        if current.in_flight > 0 || current.started != last.started {
+            idle_since = None;
+        } else if idle_since.is_none() {
+            idle_since = Some(Instant::now());
+        }
+        last = current;
+    }
+}
+
+/// Simulates network conditions for testing
+pub struct NetworkSimulator {
+    latency: Duration,
+    packet_loss_rate: f64,
+    loss: Mutex<LossSource>,
+}
+
+/// Where a `NetworkSimulator` gets its drop/keep decisions from
+enum LossSource {
+    /// A fresh thread RNG per call, so runs differ
+    Random,
+    /// A seeded RNG, so runs with the same seed drop the same calls
+    Seeded(StdRng),
+    /// Explicit decisions, `true` meaning drop; calls past the end are kept
+    Scripted(VecDeque<bool>),
+}
+
+impl NetworkSimulator {
+    pub fn new(latency: Duration, packet_loss_rate: f64) -> Self {
+        NetworkSimulator {
+            latency,
+            packet_loss_rate,
+            loss: Mutex::new(LossSource::Random),
+        }
+    }
+
+    /// Creates a simulator whose packet loss sequence is determined by `seed`
+    pub fn with_seed(latency: Duration, packet_loss_rate: f64, seed: u64) -> Self {
+        NetworkSimulator {
+            latency,
+            packet_loss_rate,
+            loss: Mutex::new(LossSource::Seeded(StdRng::seed_from_u64(seed))),
+        }
+    }
+
+    /// Creates a simulator that drops exactly the calls marked `true` in `drops`, in order
+    pub fn scripted(latency: Duration, drops: Vec<bool>) -> Self {
+        NetworkSimulator {
+            latency,
+            packet_loss_rate: 0.0,
+            loss: Mutex::new(LossSource::Scripted(drops.into())),
+        }
+    }
+
+    /// Decides whether the next call is dropped
+    fn next_call_dropped(&self) -> bool {
+        let mut loss = self.loss.lock().unwrap();
+        match &mut *loss {
+            LossSource::Random => rand::thread_rng().gen::<f64>() < self.packet_loss_rate,
+            LossSource::Seeded(rng) => rng.gen::<f64>() < self.packet_loss_rate,
+            LossSource::Scripted(drops) => drops.pop_front().unwrap_or(false),
+        }
+    }
+
+    pub async fn simulate<F, Fut, T>(&self, operation: F) -> Result<T, AluminumError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, AluminumError>>,
+    {
+        // Simulate latency
+        tokio::time::sleep(self.latency).await;
+
+        // Simulate packet loss
+        if self.next_call_dropped() {
+            return Err(AluminumError::NetworkError("Simulated packet loss".to_string()));
+        }
+
+        // Execute the operation
+        operation().await
+    }
+}
+
+// Constants for common test configurations
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+pub const MAX_RETRIES: u32 = 3;
+pub const INITIAL_RETRY_DELAY: Duration = Duration::from_millis(100);
+pub const DEFAULT_SUITE_CONCURRENCY: usize = 4;
+pub const DEFAULT_SCREENSHOT_DIR: &str = "./screenshots";
+pub const WAIT_FOR_TIMEOUT: Duration = Duration::from_secs(10);
+pub const WAIT_FOR_INITIAL_POLL: Duration = Duration::from_millis(10);
+pub const WAIT_FOR_MAX_POLL: Duration = Duration::from_millis(500);
+pub const NETWORK_IDLE_DURATION: Duration = Duration::from_millis(500);
+pub const NETWORK_IDLE_POLL: Duration = Duration::from_millis(10);
+pub const BREAKPOINT_TIMEOUT: Duration = Duration::from_secs(600);
+pub const DEFAULT_ASSERTION_TIMEOUT: Duration = Duration::ZERO;
+
+/// Characters used by `generate_random_test_data`
+pub const TEST_DATA_CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ\
+                                       abcdefghijklmnopqrstuvwxyz\
+                                       0123456789)(*&^%$#@!~";
+
+/// Letters and digits only, for test data that must be URL-safe
+pub const ALPHANUMERIC_CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ\
+                                          abcdefghijklmnopqrstuvwxyz\
+                                          0123456789";
+
+/// Key names accepted by the `press_key` step
+pub const SUPPORTED_KEYS: &[&str] = &[
+    "Enter", "Tab", "Escape", "Backspace", "Delete", "Space",
+    "ArrowUp", "ArrowDown", "ArrowLeft", "ArrowRight", "Home", "End", "PageUp", "PageDown",
+];
+
+// Macros for simplifying test case creation
+
+#[macro_export]
+macro_rules! aluminum_test_case {
+    ($id:expr, $name:expr, $description:expr, $steps:expr, $expected:expr) => {
+        create_test_case(
+            $id,
+            $name,
+            $description,
+            $steps,
+            $expected,
+            DEFAULT_TIMEOUT,
+        )
+    };
+}
+
+#[macro_export]
+macro_rules! aluminum_test_step {
+    ($action:expr, $($key:expr => $value:expr),*) => {
+        create_test_step(
+            $action,
+            vec![$(($key.to_string(), $value.to_string())),*].into_iter().collect()
+        )
+    };
+}
+
+// Example of how to use the macros:
+// 
+// let test_case = aluminum_test_case!(
+//     "TC002",
+//     "Login Test",
+//     "Verifies that a user can log in successfully",
+//
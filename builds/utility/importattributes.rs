@@ -1,284 +1,946 @@
-
-use std::collections::HashMap;
-use std::fs::{self, File};
-use std::io::{self, BufRead, BufReader, Write};
-use std::path::{Path, PathBuf};
-use serde::{Deserialize, Serialize};
-use regex::Regex;
-use chrono::{DateTime, Utc};
-use log::{info, warn, error};
-use rayon::prelude::*;
-use indicatif::{ProgressBar, ProgressStyle};
-
-// Define a struct to hold attribute information
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct Attribute {
-    name: String,
-    value: String,
-    category: String,
-    last_modified: DateTime<Utc>,
-}
-
-// Define a struct to hold import configuration
-#[derive(Debug, Serialize, Deserialize)]
-struct ImportConfig {
-    source_path: PathBuf,
-    destination_path: PathBuf,
-    file_patterns: Vec<String>,
-    attribute_regex: String,
-    max_file_size: usize,
-    parallel_processing: bool,
-}
-
-/// Import attributes for the Aluminum web browser
-///
-/// This function reads attributes from various source files, processes them,
-/// and imports them into the Aluminum web browser's attribute system.
-///
-/// # Arguments
-///
-/// * `config_path` - A string slice that holds the path to the import configuration file
-///
-/// # Returns
-///
-/// * `io::Result<()>` - Ok(()) if the import was successful, or an error if something went wrong
-pub fn import_attributes(config_path: &str) -> io::Result<()> {
-    // Load the import configuration
-    let config = load_import_config(config_path)?;
-
-    // Create a progress bar for the import process
-    let progress_bar = ProgressBar::new_spinner();
-    progress_bar.set_style(ProgressStyle::default_spinner()
-        .template("{spinner:.green} [{elapsed_precise}] {msg}")
-        .unwrap());
-    progress_bar.set_message("Importing attributes...");
-
-    // Collect all files matching the specified patterns
-    let files_to_process = collect_files_to_process(&config)?;
-
-    // Process files and extract attributes
-    let attributes = if config.parallel_processing {
-        process_files_parallel(&config, &files_to_process, &progress_bar)?
-    } else {
-        process_files_sequential(&config, &files_to_process, &progress_bar)?
-    };
-
-    // Import attributes into the Aluminum attribute system
-    import_attributes_to_aluminum(&config, &attributes, &progress_bar)?;
-
-    progress_bar.finish_with_message("Attribute import completed successfully!");
-
-    Ok(())
-}
-
-/// Load the import configuration from a file
-fn load_import_config(config_path: &str) -> io::Result<ImportConfig> {
-    let config_file = File::open(config_path)?;
-    let config: ImportConfig = serde_json::from_reader(config_file)?;
-    Ok(config)
-}
-
-/// Collect all files matching the specified patterns in the configuration
-fn collect_files_to_process(config: &ImportConfig) -> io::Result<Vec<PathBuf>> {
-    let mut files = Vec::new();
-
-    for entry in fs::read_dir(&config.source_path)? {
-        let entry = entry?;
-        let path = entry.path();
-
-        if path.is_file() {
-            for pattern in &config.file_patterns {
-                if path.to_str().unwrap().contains(pattern) {
-                    files.push(path.clone());
-                    break;
-                }
-            }
-        }
-    }
-
-    Ok(files)
-}
-
-/// Process files in parallel to extract attributes
-fn process_files_parallel(
-    config: &ImportConfig,
-    files: &[PathBuf],
-    progress_bar: &ProgressBar,
-) -> io::Result<Vec<Attribute>> {
-    let regex = Regex::new(&config.attribute_regex).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
-
-    let attributes: Vec<Attribute> = files
-        .par_iter()
-        .flat_map(|file| {
-            let result = process_single_file(file, config, ®ex);
-            progress_bar.inc(1);
-            result
-        })
-        .collect();
-
-    Ok(attributes)
-}
-
-/// Process files sequentially to extract attributes
-fn process_files_sequential(
-    config: &ImportConfig,
-    files: &[PathBuf],
-    progress_bar: &ProgressBar,
-) -> io::Result<Vec<Attribute>> {
-    let regex = Regex::new(&config.attribute_regex).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
-
-    let mut attributes = Vec::new();
-
-    for file in files {
-        attributes.extend(process_single_file(file, config, ®ex)?);
-        progress_bar.inc(1);
-    }
-
-    Ok(attributes)
-}
-
-/// Process a single file to extract attributes
-fn process_single_file(
-    file: &Path,
-    config: &ImportConfig,
-    regex: &Regex,
-) -> io::Result<Vec<Attribute>> {
-    let file = File::open(file)?;
-    let metadata = file.metadata()?;
-
-    if metadata.len() as usize > config.max_file_size {
-        warn!("Skipping file {:?} due to size limit", file);
-        return Ok(Vec::new());
-    }
-
-    let reader = BufReader::new(file);
-    let mut attributes = Vec::new();
-
-    for line in reader.lines() {
-        let line = line?;
-        if let Some(captures) = regex.captures(&line) {
-            if captures.len() >= 4 {
-                attributes.push(Attribute {
-                    name: captures[1].to_string(),
-                    value: captures[2].to_string(),
-                    category: captures[3].to_string(),
-                    last_modified: Utc::now(),
-                });
-            }
-        }
-    }
-
-    Ok(attributes)
-}
-
-/// Import extracted attributes into the Aluminum attribute system
-fn import_attributes_to_aluminum(
-    config: &ImportConfig,
-    attributes: &[Attribute],
-    progress_bar: &ProgressBar,
-) -> io::Result<()> {
-    let mut attribute_map: HashMap<String, Attribute> = HashMap::new();
-
-    // Merge attributes with the same name, keeping the most recent one
-    for attr in attributes {
-        attribute_map
-            .entry(attr.name.clone())
-            .and_modify(|existing| {
-                if attr.last_modified > existing.last_modified {
-                    *existing = attr.clone();
-                }
-            })
-            .or_insert_with(|| attr.clone());
-    }
-
-    // Write attributes to the destination file
-    let mut dest_file = File::create(&config.destination_path)?;
-    for (_, attr) in attribute_map {
-        writeln!(
-            dest_file,
-            "{}|{}|{}|{}",
-            attr.name,
-            attr.value,
-            attr.category,
-            attr.last_modified.to_rfc3339()
-        )?;
-        progress_bar.inc(1);
-    }
-
-    info!(
-        "Imported {} attributes to {}",
-        attribute_map.len(),
-        config.destination_path.display()
-    );
-
-    Ok(())
-}
-
-/// Validate the imported attributes against a schema
-fn validate_imported_attributes(config: &ImportConfig) -> io::Result<()> {
-    
-    Ok(())
-}
-
-/// Generate a report of the import process
-fn generate_import_report(config: &ImportConfig, attributes: &[Attribute]) -> io::Result<()> {
-    let report_path = config.destination_path.with_file_name("import_report.txt");
-    let mut report_file = File::create(report_path)?;
-
-    writeln!(report_file, "Aluminum Attribute Import Report")?;
-    writeln!(report_file, "===============================")?;
-    writeln!(report_file, "Import Date: {}", Utc::now().to_rfc3339())?;
-    writeln!(report_file, "Source Path: {}", config.source_path.display())?;
-    writeln!(
-        report_file,
-        "Destination Path: {}",
-        config.destination_path.display()
-    )?;
-    writeln!(report_file, "Total Attributes Imported: {}", attributes.len())?;
-
-    // Generate category statistics
-    let mut category_stats: HashMap<String, usize> = HashMap::new();
-    for attr in attributes {
-        *category_stats.entry(attr.category.clone()).or_insert(0) += 1;
-    }
-
-    writeln!(report_file, "\nCategory Statistics:")?;
-    for (category, count) in category_stats {
-        writeln!(report_file, "  {}: {}", category, count)?;
-    }
-
-    Ok(())
-}
-
-/// Clean up temporary files and resources after the import process
-fn cleanup_import_resources(config: &ImportConfig) -> io::Result<()> {
-   
-    Ok(())
-}
-
-/// Main function to orchestrate the attribute import process
-pub fn run_attribute_import(config_path: &str) -> io::Result<()> {
-    // Initialize logging
-    env_logger::init();
-
-    info!("Starting Aluminum attribute import process");
-
-    // Load configuration and import attributes
-    let config = load_import_config(config_path)?;
-    let attributes = import_attributes(config_path)?;
-
-    // Validate imported attributes
-    validate_imported_attributes(&config)?;
-
-    // Generate import report
-    generate_import_report(&config, &attributes)?;
-
-    // Clean up resources
-    cleanup_import_resources(&config)?;
-
-    info!("Aluminum attribute import process completed successfully");
-
-    Ok(())
-}
-
-// Add any additional helper functions or utilities below this line
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use regex::Regex;
+use chrono::{DateTime, Utc};
+use log::{info, warn, error};
+use rayon::prelude::*;
+use indicatif::{ProgressBar, ProgressStyle};
+
+// Define a struct to hold attribute information
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attribute {
+    name: String,
+    value: String,
+    category: String,
+    last_modified: DateTime<Utc>,
+}
+
+// Define a struct to hold import configuration
+#[derive(Debug, Serialize, Deserialize)]
+struct ImportConfig {
+    source_path: PathBuf,
+    destination_path: PathBuf,
+    file_patterns: Vec<String>,
+    attribute_regex: String,
+    max_file_size: usize,
+    parallel_processing: bool,
+    // Report what would change instead of writing the destination file
+    #[serde(default)]
+    dry_run: bool,
+    // Undo the backslash escapes written by `export_attributes`; only set this
+    // for sources produced by an export, since other files may contain literal `\.`
+    #[serde(default)]
+    unescape_fields: bool,
+}
+
+// Define a struct to hold the changes an import would make to the destination file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportPreview {
+    pub added: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    // Attributes only in the destination, which the import would delete
+    pub removed: usize,
+}
+
+// Define an enum to select how source files are processed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessingMode {
+    Parallel,
+    Sequential,
+}
+
+// Number of attributes handed to the merge step at a time when streaming
+const STREAM_BATCH_SIZE: usize = 1024;
+
+// Number of files listed in the "Slowest Files" section of the import report
+const SLOWEST_FILES_IN_REPORT: usize = 5;
+
+// Define a struct to hold how long a source file took to process and what it yielded
+#[derive(Debug, Clone)]
+pub struct FileTiming {
+    path: PathBuf,
+    duration: Duration,
+    attribute_count: usize,
+    category_counts: HashMap<String, usize>,
+}
+
+// Define a struct to hold the imported attributes and per-file timings of an import run
+#[derive(Debug, Clone, Default)]
+pub struct ImportSummary {
+    // The merged attributes, one per name, as written to the destination
+    pub attributes: Vec<Attribute>,
+    pub file_timings: Vec<FileTiming>,
+    // What a dry run would have changed; `None` when the destination was written
+    pub preview: Option<ImportPreview>,
+}
+
+/// Import attributes for the Aluminum web browser
+///
+/// This function reads attributes from various source files, processes them,
+/// and imports them into the Aluminum web browser's attribute system.
+///
+/// # Arguments
+///
+/// * `config_path` - A string slice that holds the path to the import configuration file
+/// * `mode_override` - Processing mode to use instead of the config's `parallel_processing`;
+///   `None` keeps the configured mode
+///
+/// # Returns
+///
+/// * `io::Result<ImportSummary>` - The merged attributes, how long each file took and,
+///   for a dry run, the changes it would have made, or an error if something went wrong
+pub fn import_attributes(
+    config_path: &str,
+    mode_override: Option<ProcessingMode>,
+) -> io::Result<ImportSummary> {
+    // Load the import configuration
+    let config = load_import_config(config_path)?;
+
+    // Create a progress bar for the import process
+    let progress_bar = ProgressBar::new_spinner();
+    progress_bar.set_style(ProgressStyle::default_spinner()
+        .template("{spinner:.green} [{elapsed_precise}] {msg}")
+        .unwrap());
+    progress_bar.set_message("Importing attributes...");
+
+    // Collect all files matching the specified patterns
+    let files_to_process = collect_files_to_process(&config)?;
+
+    // Process files and extract attributes
+    let mut summary = match resolve_processing_mode(&config, mode_override) {
+        ProcessingMode::Parallel => process_files_parallel(&config, &files_to_process, &progress_bar)?,
+        ProcessingMode::Sequential => process_files_sequential(&config, &files_to_process, &progress_bar)?,
+    };
+
+    // Import attributes into the Aluminum attribute system
+    let attribute_map = merge_attributes(&summary.attributes);
+    summary.preview = import_attributes_to_aluminum(&config, &attribute_map, &progress_bar)?;
+    summary.attributes = attribute_map.into_values().collect();
+
+    progress_bar.finish_with_message("Attribute import completed successfully!");
+
+    Ok(summary)
+}
+
+/// Import attributes without holding every extracted attribute in memory
+///
+/// Files are read sequentially and each batch of at most `batch_size`
+/// attributes is merged as soon as it is extracted, so peak memory is bounded
+/// by the merged attribute map rather than by the size of the source files.
+/// The returned summary is the same as `import_attributes` would return.
+pub fn import_attributes_streaming(config_path: &str, batch_size: usize) -> io::Result<ImportSummary> {
+    let config = load_import_config(config_path)?;
+    let regex = Regex::new(&config.attribute_regex).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+    let progress_bar = ProgressBar::new_spinner();
+    progress_bar.set_style(ProgressStyle::default_spinner()
+        .template("{spinner:.green} [{elapsed_precise}] {msg}")
+        .unwrap());
+    progress_bar.set_message("Streaming attributes...");
+
+    let mut attribute_map = HashMap::new();
+    let mut file_timings = Vec::new();
+
+    for file in collect_files_to_process(&config)? {
+        let timing = extract_attribute_batches(&file, &config, &regex, batch_size, |batch| {
+            merge_into(&mut attribute_map, batch);
+            Ok(())
+        })?;
+        file_timings.push(timing);
+        progress_bar.inc(1);
+    }
+
+    let preview = import_attributes_to_aluminum(&config, &attribute_map, &progress_bar)?;
+
+    progress_bar.finish_with_message("Attribute import completed successfully!");
+
+    Ok(ImportSummary {
+        attributes: attribute_map.into_values().collect(),
+        file_timings,
+        preview,
+    })
+}
+
+/// Pick the processing mode, letting an explicit override win over the config file
+fn resolve_processing_mode(config: &ImportConfig, mode_override: Option<ProcessingMode>) -> ProcessingMode {
+    mode_override.unwrap_or(if config.parallel_processing {
+        ProcessingMode::Parallel
+    } else {
+        ProcessingMode::Sequential
+    })
+}
+
+/// Load the import configuration from a file
+fn load_import_config(config_path: &str) -> io::Result<ImportConfig> {
+    let config_file = File::open(config_path)?;
+    let config: ImportConfig = serde_json::from_reader(config_file)?;
+    Ok(config)
+}
+
+/// Collect all files matching the specified patterns in the configuration
+fn collect_files_to_process(config: &ImportConfig) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+
+    for entry in fs::read_dir(&config.source_path)? {
+        let entry = entry?;
+        let path = entry.path();
+
+        if path.is_file() {
+            for pattern in &config.file_patterns {
+                if path.to_str().unwrap().contains(pattern) {
+                    files.push(path.clone());
+                    break;
+                }
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+/// Process files in parallel to extract attributes
+///
+/// Files that fail to read are logged and skipped.
+fn process_files_parallel(
+    config: &ImportConfig,
+    files: &[PathBuf],
+    progress_bar: &ProgressBar,
+) -> io::Result<ImportSummary> {
+    let regex = Regex::new(&config.attribute_regex).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+    let results: Vec<(Vec<Attribute>, FileTiming)> = files
+        .par_iter()
+        .filter_map(|file| {
+            let result = process_single_file(file, config, &regex);
+            progress_bar.inc(1);
+            result.map_err(|e| warn!("Skipping file {:?}: {}", file, e)).ok()
+        })
+        .collect();
+
+    let mut summary = ImportSummary::default();
+    for (attributes, timing) in results {
+        summary.attributes.extend(attributes);
+        summary.file_timings.push(timing);
+    }
+
+    Ok(summary)
+}
+
+/// Process files sequentially to extract attributes
+fn process_files_sequential(
+    config: &ImportConfig,
+    files: &[PathBuf],
+    progress_bar: &ProgressBar,
+) -> io::Result<ImportSummary> {
+    let regex = Regex::new(&config.attribute_regex).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+    let mut summary = ImportSummary::default();
+
+    for file in files {
+        let (attributes, timing) = process_single_file(file, config, &regex)?;
+        summary.attributes.extend(attributes);
+        summary.file_timings.push(timing);
+        progress_bar.inc(1);
+    }
+
+    Ok(summary)
+}
+
+/// Process a single file to extract attributes, timing how long it takes
+fn process_single_file(
+    path: &Path,
+    config: &ImportConfig,
+    regex: &Regex,
+) -> io::Result<(Vec<Attribute>, FileTiming)> {
+    let mut attributes = Vec::new();
+    let timing = extract_attribute_batches(path, config, regex, STREAM_BATCH_SIZE, |batch| {
+        attributes.extend(batch);
+        Ok(())
+    })?;
+    Ok((attributes, timing))
+}
+
+/// Extract attributes from a single file, handing them to `on_batch` in batches
+///
+/// Lines are read into one reused buffer and at most `batch_size` attributes
+/// are held before `on_batch` is called, so memory use does not grow with the
+/// file. An error from `on_batch` stops the extraction.
+fn extract_attribute_batches<F>(
+    path: &Path,
+    config: &ImportConfig,
+    regex: &Regex,
+    batch_size: usize,
+    mut on_batch: F,
+) -> io::Result<FileTiming>
+where
+    F: FnMut(Vec<Attribute>) -> io::Result<()>,
+{
+    let started = Instant::now();
+    let mut timing = FileTiming {
+        path: path.to_path_buf(),
+        duration: Duration::ZERO,
+        attribute_count: 0,
+        category_counts: HashMap::new(),
+    };
+
+    let file = File::open(path)?;
+    let metadata = file.metadata()?;
+
+    if metadata.len() as usize > config.max_file_size {
+        warn!("Skipping file {:?} due to size limit", file);
+        timing.duration = started.elapsed();
+        return Ok(timing);
+    }
+
+    let batch_size = batch_size.max(1);
+    let field = |raw: &str| {
+        if config.unescape_fields {
+            unescape_attribute_field(raw)
+        } else {
+            raw.to_string()
+        }
+    };
+    let mut reader = BufReader::new(file);
+    let mut line = String::new();
+    let mut batch = Vec::with_capacity(batch_size);
+
+    while reader.read_line(&mut line)? > 0 {
+        if let Some(captures) = regex.captures(line.trim_end_matches(&['\r', '\n'][..])) {
+            if captures.len() >= 4 {
+                let attr = Attribute {
+                    name: field(&captures[1]),
+                    value: field(&captures[2]),
+                    category: field(&captures[3]),
+                    last_modified: Utc::now(),
+                };
+                *timing.category_counts.entry(attr.category.clone()).or_insert(0) += 1;
+                timing.attribute_count += 1;
+                batch.push(attr);
+
+                if batch.len() == batch_size {
+                    on_batch(std::mem::replace(&mut batch, Vec::with_capacity(batch_size)))?;
+                }
+            }
+        }
+        line.clear();
+    }
+
+    if !batch.is_empty() {
+        on_batch(batch)?;
+    }
+
+    timing.duration = started.elapsed();
+    Ok(timing)
+}
+
+/// Import extracted attributes into the Aluminum attribute system
+///
+/// A dry run leaves the destination alone and returns the changes the import would make.
+fn import_attributes_to_aluminum(
+    config: &ImportConfig,
+    attribute_map: &HashMap<String, Attribute>,
+    progress_bar: &ProgressBar,
+) -> io::Result<Option<ImportPreview>> {
+    if config.dry_run {
+        let existing = read_destination_attributes(&config.destination_path)?;
+        let preview = preview_changes(&existing, attribute_map);
+        let summary = format!(
+            "Dry run: {} added, {} updated, {} unchanged, {} removed; {} was not modified",
+            preview.added,
+            preview.updated,
+            preview.unchanged,
+            preview.removed,
+            config.destination_path.display()
+        );
+        info!("{}", summary);
+        progress_bar.set_message(summary);
+        return Ok(Some(preview));
+    }
+
+    // Write attributes to the destination file
+    write_atomically(&config.destination_path, |dest_file| {
+        for attr in attribute_map.values() {
+            writeln!(
+                dest_file,
+                "{}|{}|{}|{}",
+                attr.name,
+                attr.value,
+                attr.category,
+                attr.last_modified.to_rfc3339()
+            )?;
+            progress_bar.inc(1);
+        }
+        Ok(())
+    })?;
+
+    info!(
+        "Imported {} attributes to {}",
+        attribute_map.len(),
+        config.destination_path.display()
+    );
+
+    Ok(None)
+}
+
+/// Replace `dest` with the output of `write` without ever leaving it half-written
+///
+/// The content goes to a temporary file next to `dest`, which is renamed over
+/// `dest` only once `write` succeeds and the data is synced. If anything fails
+/// the temporary file is removed and `dest` keeps its previous contents.
+fn write_atomically<F>(dest: &Path, write: F) -> io::Result<()>
+where
+    F: FnOnce(&mut dyn Write) -> io::Result<()>,
+{
+    let file_name = dest.file_name().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Destination {} is not a file path", dest.display()),
+        )
+    })?;
+    let temp_path = dest.with_file_name(format!(".{}.tmp", file_name.to_string_lossy()));
+
+    let result = (|| {
+        let mut writer = BufWriter::new(File::create(&temp_path)?);
+        write(&mut writer)?;
+        writer.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+        fs::rename(&temp_path, dest)
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// Merge attributes with the same name, keeping the most recent one
+fn merge_attributes(attributes: &[Attribute]) -> HashMap<String, Attribute> {
+    let mut attribute_map: HashMap<String, Attribute> = HashMap::new();
+    merge_into(&mut attribute_map, attributes.iter().cloned());
+    attribute_map
+}
+
+/// Merge a batch of attributes into an existing map, keeping the most recent one per name
+fn merge_into<I>(attribute_map: &mut HashMap<String, Attribute>, attributes: I)
+where
+    I: IntoIterator<Item = Attribute>,
+{
+    for attr in attributes {
+        match attribute_map.get_mut(&attr.name) {
+            Some(existing) => {
+                if attr.last_modified > existing.last_modified {
+                    *existing = attr;
+                }
+            }
+            None => {
+                attribute_map.insert(attr.name.clone(), attr);
+            }
+        }
+    }
+}
+
+/// Read the attributes already written to a pipe-delimited destination file
+///
+/// A missing destination is treated as empty. Malformed lines are skipped
+/// with a warning.
+fn read_destination_attributes(path: &Path) -> io::Result<HashMap<String, Attribute>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(e) => return Err(e),
+    };
+
+    let mut attributes = HashMap::new();
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match parse_destination_line(&line) {
+            Some(attr) => {
+                attributes.insert(attr.name.clone(), attr);
+            }
+            None => warn!("Skipping malformed line {} in {}", index + 1, path.display()),
+        }
+    }
+
+    Ok(attributes)
+}
+
+/// Parse a `name|value|category|last_modified` line as written by `import_attributes_to_aluminum`
+fn parse_destination_line(line: &str) -> Option<Attribute> {
+    // Values aren't escaped, so split the fixed trailing fields off from the right
+    let mut fields = line.rsplitn(3, '|');
+    let last_modified = DateTime::parse_from_rfc3339(fields.next()?).ok()?.with_timezone(&Utc);
+    let category = fields.next()?;
+    let (name, value) = fields.next()?.split_once('|')?;
+
+    Some(Attribute {
+        name: name.to_string(),
+        value: value.to_string(),
+        category: category.to_string(),
+        last_modified,
+    })
+}
+
+/// Count how the merged attributes differ from those already in the destination
+///
+/// Timestamps are ignored, since every import stamps attributes with the current time.
+fn preview_changes(
+    existing: &HashMap<String, Attribute>,
+    merged: &HashMap<String, Attribute>,
+) -> ImportPreview {
+    let mut preview = ImportPreview::default();
+
+    for (name, attr) in merged {
+        match existing.get(name) {
+            None => preview.added += 1,
+            Some(current) if current.value != attr.value || current.category != attr.category => {
+                preview.updated += 1
+            }
+            Some(_) => preview.unchanged += 1,
+        }
+    }
+    // The destination is rewritten from the merged attributes, so anything else in it goes
+    preview.removed = existing.keys().filter(|name| !merged.contains_key(*name)).count();
+
+    preview
+}
+
+/// Validate the imported attributes against a schema
+fn validate_imported_attributes(config: &ImportConfig) -> io::Result<()> {
+    
+    Ok(())
+}
+
+/// Generate a report of the import process
+///
+/// Per-category processing time splits each file's duration between the
+/// categories it contained, in proportion to their attribute counts.
+fn generate_import_report(config: &ImportConfig, summary: &ImportSummary) -> io::Result<()> {
+    let attributes = &summary.attributes;
+    let report_path = config.destination_path.with_file_name("import_report.txt");
+    let mut report_file = File::create(report_path)?;
+
+    writeln!(report_file, "Aluminum Attribute Import Report")?;
+    writeln!(report_file, "===============================")?;
+    writeln!(report_file, "Import Date: {}", Utc::now().to_rfc3339())?;
+    writeln!(report_file, "Source Path: {}", config.source_path.display())?;
+    writeln!(
+        report_file,
+        "Destination Path: {}",
+        config.destination_path.display()
+    )?;
+    writeln!(report_file, "Total Attributes Imported: {}", attributes.len())?;
+
+    // Generate category statistics: attribute count, value bytes and processing time
+    let mut category_stats: HashMap<String, (usize, usize, Duration)> = HashMap::new();
+    for attr in attributes {
+        let stats = category_stats.entry(attr.category.clone()).or_default();
+        stats.0 += 1;
+        stats.1 += attr.value.len();
+    }
+    for timing in &summary.file_timings {
+        for (category, count) in &timing.category_counts {
+            let share = timing.duration.mul_f64(*count as f64 / timing.attribute_count as f64);
+            category_stats.entry(category.clone()).or_default().2 += share;
+        }
+    }
+
+    let mut categories: Vec<_> = category_stats.into_iter().collect();
+    categories.sort_by(|a, b| a.0.cmp(&b.0));
+
+    writeln!(report_file, "\nCategory Statistics:")?;
+    for (category, (count, value_bytes, duration)) in categories {
+        writeln!(
+            report_file,
+            "  {}: {} attributes, {} value bytes, {:.1} ms",
+            category,
+            count,
+            value_bytes,
+            duration.as_secs_f64() * 1000.0
+        )?;
+    }
+
+    // List the files that took longest to process
+    let mut slowest: Vec<&FileTiming> = summary.file_timings.iter().collect();
+    slowest.sort_by(|a, b| b.duration.cmp(&a.duration));
+
+    writeln!(report_file, "\nSlowest Files:")?;
+    for timing in slowest.into_iter().take(SLOWEST_FILES_IN_REPORT) {
+        writeln!(
+            report_file,
+            "  {}: {:.1} ms, {} attributes",
+            timing.path.display(),
+            timing.duration.as_secs_f64() * 1000.0,
+            timing.attribute_count
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Clean up temporary files and resources after the import process
+fn cleanup_import_resources(config: &ImportConfig) -> io::Result<()> {
+   
+    Ok(())
+}
+
+/// Main function to orchestrate the attribute import process
+///
+/// `mode_override` (e.g. from a `--parallel` or `--sequential` flag) takes
+/// precedence over `parallel_processing` in the config file; pass `None` to
+/// use the configured mode. Returns the import's summary, whose `preview`
+/// holds the would-be changes of a dry run.
+pub fn run_attribute_import(config_path: &str, mode_override: Option<ProcessingMode>) -> io::Result<ImportSummary> {
+    // Initialize logging
+    env_logger::init();
+
+    info!("Starting Aluminum attribute import process");
+
+    // Load configuration and import attributes
+    let config = load_import_config(config_path)?;
+    let summary = import_attributes(config_path, mode_override)?;
+
+    // Validate imported attributes
+    validate_imported_attributes(&config)?;
+
+    // Generate import report; a dry run imported nothing, so it leaves any earlier report alone
+    if config.dry_run {
+        info!("Dry run: skipping the import report");
+    } else {
+        generate_import_report(&config, &summary)?;
+    }
+
+    // Clean up resources
+    cleanup_import_resources(&config)?;
+
+    info!("Aluminum attribute import process completed successfully");
+
+    Ok(summary)
+}
+
+// Add any additional helper functions or utilities below this line
+
+/// Placeholders recognised by `export_attributes` templates
+const TEMPLATE_PLACEHOLDERS: [&str; 4] = ["{name}", "{value}", "{category}", "{last_modified}"];
+
+/// Export attributes back to a source-style file
+///
+/// Each attribute is written on its own line by substituting `{name}`,
+/// `{value}`, `{category}` and `{last_modified}` in `template`, for example
+/// `{name}={value} # {category}`. Backslashes and any punctuation character
+/// that appears literally in the template are escaped with a backslash, so an
+/// import config with `unescape_fields` set and an `attribute_regex` that
+/// accepts `\\.` escapes reads the file back unchanged.
+///
+/// # Arguments
+///
+/// * `attributes` - The attributes to write
+/// * `template` - The line template, which must contain at least one placeholder
+/// * `dest` - The file to create or overwrite
+///
+/// # Returns
+///
+/// * `io::Result<()>` - An `InvalidInput` error if the template has no placeholder
+///   or a field contains a line break
+pub fn export_attributes(attributes: &[Attribute], template: &str, dest: &Path) -> io::Result<()> {
+    let mut delimiters = template.to_string();
+    for placeholder in TEMPLATE_PLACEHOLDERS.iter() {
+        delimiters = delimiters.replace(placeholder, "");
+    }
+    if delimiters.len() == template.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Export template '{}' has no placeholders", template),
+        ));
+    }
+
+    let mut dest_file = File::create(dest)?;
+    for attr in attributes {
+        let line = template
+            .replace("{name}", &escape_attribute_field(&attr.name, &delimiters)?)
+            .replace("{value}", &escape_attribute_field(&attr.value, &delimiters)?)
+            .replace("{category}", &escape_attribute_field(&attr.category, &delimiters)?)
+            .replace("{last_modified}", &attr.last_modified.to_rfc3339());
+        writeln!(dest_file, "{}", line)?;
+    }
+
+    info!("Exported {} attributes to {}", attributes.len(), dest.display());
+
+    Ok(())
+}
+
+/// Escape backslashes and template delimiter characters in a single field
+fn escape_attribute_field(field: &str, delimiters: &str) -> io::Result<String> {
+    if field.contains('\n') || field.contains('\r') {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Attribute field {:?} contains a line break and cannot be exported", field),
+        ));
+    }
+
+    let mut escaped = String::with_capacity(field.len());
+    for c in field.chars() {
+        if c == '\\' || (c.is_ascii_punctuation() && delimiters.contains(c)) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    Ok(escaped)
+}
+
+/// Reverse `escape_attribute_field`
+///
+/// Only a backslash followed by ASCII punctuation is treated as an escape, so
+/// values such as `C:\Program Files` import unchanged.
+fn unescape_attribute_field(field: &str) -> String {
+    let mut unescaped = String::with_capacity(field.len());
+    let mut chars = field.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            if let Some(&next) = chars.peek() {
+                if next.is_ascii_punctuation() {
+                    unescaped.push(next);
+                    chars.next();
+                    continue;
+                }
+            }
+        }
+        unescaped.push(c);
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    // Matches `{name}={value} # {category}` lines, allowing backslash escapes in each field
+    const ESCAPED_LINE_REGEX: &str = r"^((?:\\.|[^\\=])*)=((?:\\.|[^\\#])*) # ((?:\\.|[^\\])*)$";
+    const EXPORT_TEMPLATE: &str = "{name}={value} # {category}";
+
+    fn test_config(source: &Path, destination: &Path) -> ImportConfig {
+        ImportConfig {
+            source_path: source.to_path_buf(),
+            destination_path: destination.to_path_buf(),
+            file_patterns: vec![".txt".to_string()],
+            attribute_regex: ESCAPED_LINE_REGEX.to_string(),
+            max_file_size: 1 << 20,
+            parallel_processing: false,
+            dry_run: false,
+            unescape_fields: false,
+        }
+    }
+
+    // Write a config next to the source directory and return its path as the import functions expect
+    fn save_config(config: &ImportConfig, path: &Path) -> String {
+        serde_json::to_writer(File::create(path).unwrap(), config).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+
+    fn write_source(dir: &Path, name: &str, lines: &[&str]) {
+        fs::create_dir_all(dir).unwrap();
+        fs::write(dir.join(name), lines.join("\n") + "\n").unwrap();
+    }
+
+    fn find<'a>(attributes: &'a [Attribute], name: &str) -> &'a Attribute {
+        attributes.iter().find(|a| a.name == name).unwrap_or_else(|| panic!("no attribute named {}", name))
+    }
+
+    #[test]
+    fn test_exported_attributes_reimport_unchanged() {
+        let dir = TempDir::new().unwrap();
+        write_source(&dir.path().join("src"), "attrs.txt", &["theme=dark # ui", "home=https://example.com # nav"]);
+        let mut config = test_config(&dir.path().join("src"), &dir.path().join("attributes.db"));
+        let summary = import_attributes(&save_config(&config, &dir.path().join("import.json")), None).unwrap();
+
+        // Edit a value so it contains the template's own delimiters and a backslash
+        let mut attributes = summary.attributes;
+        attributes.iter_mut().find(|a| a.name == "theme").unwrap().value = r"dark = #1e1e1e \ high-contrast".to_string();
+        fs::create_dir_all(dir.path().join("exported")).unwrap();
+        export_attributes(&attributes, EXPORT_TEMPLATE, &dir.path().join("exported").join("attrs.txt")).unwrap();
+
+        config.source_path = dir.path().join("exported");
+        config.unescape_fields = true;
+        let reimported = import_attributes(&save_config(&config, &dir.path().join("reimport.json")), None).unwrap();
+
+        assert_eq!(reimported.attributes.len(), 2);
+        for original in &attributes {
+            let round_tripped = find(&reimported.attributes, &original.name);
+            assert_eq!(round_tripped.value, original.value);
+            assert_eq!(round_tripped.category, original.category);
+        }
+    }
+
+    #[test]
+    fn test_backslashes_in_plain_sources_are_kept() {
+        let dir = TempDir::new().unwrap();
+        write_source(&dir.path().join("src"), "attrs.txt", &[r"pattern=a\.b\|c # regex"]);
+        let config = test_config(&dir.path().join("src"), &dir.path().join("attributes.db"));
+
+        let summary = import_attributes(&save_config(&config, &dir.path().join("import.json")), None).unwrap();
+
+        assert_eq!(find(&summary.attributes, "pattern").value, r"a\.b\|c");
+    }
+
+    #[test]
+    fn test_streaming_and_collecting_imports_summarize_the_same_attributes() {
+        let dir = TempDir::new().unwrap();
+        // zoom is listed twice but imported once
+        write_source(
+            &dir.path().join("src"),
+            "attrs.txt",
+            &["theme=dark # ui", "zoom=100 # ui", "zoom=100 # ui", "home=https://example.com # nav"],
+        );
+        let config = test_config(&dir.path().join("src"), &dir.path().join("attributes.db"));
+        let config_path = save_config(&config, &dir.path().join("import.json"));
+
+        let collected = import_attributes(&config_path, Some(ProcessingMode::Sequential)).unwrap();
+        let streamed = import_attributes_streaming(&config_path, 2).unwrap();
+
+        let values = |summary: &ImportSummary| {
+            let mut values: Vec<(String, String)> =
+                summary.attributes.iter().map(|a| (a.name.clone(), a.value.clone())).collect();
+            values.sort();
+            values
+        };
+        assert_eq!(values(&collected).len(), 3);
+        assert_eq!(values(&collected), values(&streamed));
+    }
+
+    #[test]
+    fn test_dry_run_leaves_destination_and_report_untouched() {
+        let dir = TempDir::new().unwrap();
+        write_source(
+            &dir.path().join("src"),
+            "attrs.txt",
+            &["theme=dark # ui", "home=https://example.org # nav", "zoom=125 # ui"],
+        );
+        let destination = dir.path().join("attributes.db");
+        let existing = "theme|dark|ui|2024-01-01T00:00:00+00:00\n\
+                        home|https://example.com|nav|2024-01-01T00:00:00+00:00\n\
+                        font|serif|ui|2024-01-01T00:00:00+00:00\n";
+        fs::write(&destination, existing).unwrap();
+        let mut config = test_config(&dir.path().join("src"), &destination);
+        config.dry_run = true;
+
+        let summary = run_attribute_import(&save_config(&config, &dir.path().join("import.json")), None).unwrap();
+
+        assert_eq!(fs::read_to_string(&destination).unwrap(), existing);
+        assert!(!dir.path().join("import_report.txt").exists());
+        // zoom is new, home changed, theme is the same and font would be deleted
+        assert_eq!(summary.preview, Some(ImportPreview { added: 1, updated: 1, unchanged: 1, removed: 1 }));
+
+        config.dry_run = false;
+        let written = import_attributes(&save_config(&config, &dir.path().join("import.json")), None).unwrap();
+        assert_eq!(written.preview, None);
+        assert!(!fs::read_to_string(&destination).unwrap().contains("font|"));
+    }
+
+    fn attribute(name: &str, value: &str, category: &str) -> Attribute {
+        Attribute {
+            name: name.to_string(),
+            value: value.to_string(),
+            category: category.to_string(),
+            last_modified: Utc::now(),
+        }
+    }
+
+    fn timing(path: &str, millis: u64, categories: &[(&str, usize)]) -> FileTiming {
+        FileTiming {
+            path: PathBuf::from(path),
+            duration: Duration::from_millis(millis),
+            attribute_count: categories.iter().map(|(_, count)| count).sum(),
+            category_counts: categories.iter().map(|(name, count)| (name.to_string(), *count)).collect(),
+        }
+    }
+
+    #[test]
+    fn test_import_report_splits_time_by_category_and_lists_slowest_files() {
+        let dir = TempDir::new().unwrap();
+        let config = test_config(dir.path(), &dir.path().join("attributes.db"));
+        let mut file_timings = vec![timing("big.txt", 40, &[("ui", 3), ("nav", 1)])];
+        // Six one-attribute nav files taking 1 to 6 ms; only the slowest four make the list
+        file_timings.extend((1..=6).map(|n| timing(&format!("small{}.txt", n), n, &[("nav", 1)])));
+        let summary = ImportSummary {
+            attributes: vec![
+                attribute("theme", "dark", "ui"),
+                attribute("zoom", "125", "ui"),
+                attribute("home", "https://example.com", "nav"),
+            ],
+            file_timings,
+            preview: None,
+        };
+
+        generate_import_report(&config, &summary).unwrap();
+
+        let report = fs::read_to_string(dir.path().join("import_report.txt")).unwrap();
+        // big.txt's 40 ms splits 30/10 between ui and nav; nav also gets the 21 ms of small files
+        assert!(report.contains("  nav: 1 attributes, 19 value bytes, 31.0 ms\n"), "{}", report);
+        assert!(report.contains("  ui: 2 attributes, 7 value bytes, 30.0 ms\n"), "{}", report);
+
+        let slowest: Vec<&str> = report
+            .split("Slowest Files:\n")
+            .nth(1)
+            .expect("report has no Slowest Files section")
+            .lines()
+            .collect();
+        assert_eq!(
+            slowest,
+            vec![
+                "  big.txt: 40.0 ms, 4 attributes",
+                "  small6.txt: 6.0 ms, 1 attributes",
+                "  small5.txt: 5.0 ms, 1 attributes",
+                "  small4.txt: 4.0 ms, 1 attributes",
+                "  small3.txt: 3.0 ms, 1 attributes",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_failed_atomic_write_keeps_original_destination() {
+        let dir = TempDir::new().unwrap();
+        let destination = dir.path().join("attributes.db");
+        fs::write(&destination, "theme|dark|ui|2024-01-01T00:00:00+00:00\n").unwrap();
+
+        let result = write_atomically(&destination, |writer| {
+            // Enough output to spill past the BufWriter into the temporary file
+            for i in 0..10_000 {
+                writeln!(writer, "setting{}|value|misc|2024-01-01T00:00:00+00:00", i)?;
+            }
+            Err(io::Error::new(io::ErrorKind::Other, "disk full"))
+        });
+
+        assert_eq!(result.unwrap_err().to_string(), "disk full");
+        assert_eq!(fs::read_to_string(&destination).unwrap(), "theme|dark|ui|2024-01-01T00:00:00+00:00\n");
+        let leftovers: Vec<_> = fs::read_dir(dir.path()).unwrap().map(|entry| entry.unwrap().file_name()).collect();
+        assert_eq!(leftovers, vec![std::ffi::OsString::from("attributes.db")]);
+
+        write_atomically(&destination, |writer| writer.write_all(b"replaced\n")).unwrap();
+        assert_eq!(fs::read_to_string(&destination).unwrap(), "replaced\n");
+    }
+
+    #[test]
+    fn test_mode_override_wins_over_config() {
+        let dir = TempDir::new().unwrap();
+        let mut config = test_config(dir.path(), &dir.path().join("attributes.db"));
+
+        for parallel_processing in [true, false] {
+            config.parallel_processing = parallel_processing;
+            assert_eq!(resolve_processing_mode(&config, Some(ProcessingMode::Parallel)), ProcessingMode::Parallel);
+            assert_eq!(resolve_processing_mode(&config, Some(ProcessingMode::Sequential)), ProcessingMode::Sequential);
+        }
+
+        config.parallel_processing = true;
+        assert_eq!(resolve_processing_mode(&config, None), ProcessingMode::Parallel);
+        config.parallel_processing = false;
+        assert_eq!(resolve_processing_mode(&config, None), ProcessingMode::Sequential);
+    }
+}
@@ -0,0 +1,223 @@
+// Metrics Registry for Aluminum Web Browser
+// This module provides a lightweight in-process registry of counters, gauges and
+// histograms that the test runner, import manager and incognito mode write to,
+// so runtime activity can be observed in one place.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Mutex, OnceLock};
+
+use serde::Serialize;
+
+/// Upper bounds of the default histogram buckets, in seconds
+pub const DEFAULT_BUCKETS: &[f64] = &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Point-in-time value of a histogram
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HistogramSnapshot {
+    /// Cumulative count of observations at or below each bucket's upper bound
+    pub buckets: Vec<(f64, u64)>,
+    pub count: u64,
+    pub sum: f64,
+}
+
+/// Point-in-time values of every metric in a registry
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MetricsSnapshot {
+    pub counters: BTreeMap<String, u64>,
+    pub gauges: BTreeMap<String, f64>,
+    pub histograms: BTreeMap<String, HistogramSnapshot>,
+}
+
+#[derive(Debug)]
+struct Histogram {
+    bounds: Vec<f64>,
+    counts: Vec<u64>,
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn new(bounds: &[f64]) -> Self {
+        Self {
+            bounds: bounds.to_vec(),
+            counts: vec![0; bounds.len()],
+            count: 0,
+            sum: 0.0,
+        }
+    }
+
+    fn observe(&mut self, value: f64) {
+        if let Some(index) = self.bounds.iter().position(|bound| value <= *bound) {
+            self.counts[index] += 1;
+        }
+        self.count += 1;
+        self.sum += value;
+    }
+
+    fn snapshot(&self) -> HistogramSnapshot {
+        let mut cumulative = 0;
+        let buckets = self
+            .bounds
+            .iter()
+            .zip(&self.counts)
+            .map(|(bound, count)| {
+                cumulative += count;
+                (*bound, cumulative)
+            })
+            .collect();
+
+        HistogramSnapshot {
+            buckets,
+            count: self.count,
+            sum: self.sum,
+        }
+    }
+}
+
+/// Registry of named counters, gauges and histograms
+///
+/// Metrics are created on first use. Use `Metrics::global()` to share one
+/// registry across modules, or `Metrics::new()` for an isolated one.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    counters: Mutex<BTreeMap<String, u64>>,
+    gauges: Mutex<BTreeMap<String, f64>>,
+    histograms: Mutex<BTreeMap<String, Histogram>>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The process-wide registry
+    pub fn global() -> &'static Metrics {
+        static GLOBAL: OnceLock<Metrics> = OnceLock::new();
+        GLOBAL.get_or_init(Metrics::new)
+    }
+
+    /// Add `by` to a counter
+    pub fn increment_counter(&self, name: &str, by: u64) {
+        *self.counters.lock().unwrap().entry(name.to_string()).or_insert(0) += by;
+    }
+
+    /// Set a gauge to `value`, replacing its previous value
+    pub fn set_gauge(&self, name: &str, value: f64) {
+        self.gauges.lock().unwrap().insert(name.to_string(), value);
+    }
+
+    /// Record one observation in a histogram with the default buckets
+    pub fn observe(&self, name: &str, value: f64) {
+        self.histograms
+            .lock()
+            .unwrap()
+            .entry(name.to_string())
+            .or_insert_with(|| Histogram::new(DEFAULT_BUCKETS))
+            .observe(value);
+    }
+
+    /// Current values of every metric
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            counters: self.counters.lock().unwrap().clone(),
+            gauges: self.gauges.lock().unwrap().clone(),
+            histograms: self
+                .histograms
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(name, histogram)| (name.clone(), histogram.snapshot()))
+                .collect(),
+        }
+    }
+
+    /// Render every metric in the Prometheus text exposition format
+    ///
+    /// Characters that are not valid in a Prometheus metric name are replaced
+    /// with `_`.
+    pub fn to_prometheus(&self) -> String {
+        let snapshot = self.snapshot();
+        let mut output = String::new();
+
+        for (name, value) in &snapshot.counters {
+            let name = prometheus_name(name);
+            let _ = writeln!(output, "# TYPE {} counter\n{} {}", name, name, value);
+        }
+        for (name, value) in &snapshot.gauges {
+            let name = prometheus_name(name);
+            let _ = writeln!(output, "# TYPE {} gauge\n{} {}", name, name, value);
+        }
+        for (name, histogram) in &snapshot.histograms {
+            let name = prometheus_name(name);
+            let _ = writeln!(output, "# TYPE {} histogram", name);
+            for (bound, count) in &histogram.buckets {
+                let _ = writeln!(output, "{}_bucket{{le=\"{}\"}} {}", name, bound, count);
+            }
+            let _ = writeln!(output, "{}_bucket{{le=\"+Inf\"}} {}", name, histogram.count);
+            let _ = writeln!(output, "{}_sum {}", name, histogram.sum);
+            let _ = writeln!(output, "{}_count {}", name, histogram.count);
+        }
+
+        output
+    }
+}
+
+fn prometheus_name(name: &str) -> String {
+    name.chars()
+        .enumerate()
+        .map(|(i, c)| {
+            if c.is_ascii_alphabetic() || c == '_' || c == ':' || (i > 0 && c.is_ascii_digit()) {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_and_prometheus_exposition() {
+        let metrics = Metrics::new();
+        metrics.increment_counter("import_requests_total", 2);
+        metrics.increment_counter("import_requests_total", 3);
+        metrics.set_gauge("incognito_cache_bytes", 1024.0);
+        metrics.set_gauge("incognito_cache_bytes", 512.0);
+        metrics.observe("test_case_duration_seconds", 0.02);
+        metrics.observe("test_case_duration_seconds", 0.3);
+        metrics.observe("test_case_duration_seconds", 60.0);
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.counters["import_requests_total"], 5);
+        assert_eq!(snapshot.gauges["incognito_cache_bytes"], 512.0);
+        let histogram = &snapshot.histograms["test_case_duration_seconds"];
+        assert_eq!(histogram.count, 3);
+        assert!((histogram.sum - 60.32).abs() < 1e-9);
+        assert_eq!(histogram.buckets[1], (0.01, 0));
+        assert_eq!(histogram.buckets[2], (0.025, 1));
+        assert_eq!(histogram.buckets[5], (0.25, 1));
+        assert_eq!(histogram.buckets[6], (0.5, 2));
+        assert_eq!(histogram.buckets.last(), Some(&(10.0, 2)));
+
+        let exposition = metrics.to_prometheus();
+        assert!(exposition.contains("# TYPE import_requests_total counter\nimport_requests_total 5\n"));
+        assert!(exposition.contains("# TYPE incognito_cache_bytes gauge\nincognito_cache_bytes 512\n"));
+        assert!(exposition.contains("# TYPE test_case_duration_seconds histogram\n"));
+        assert!(exposition.contains("test_case_duration_seconds_bucket{le=\"0.5\"} 2\n"));
+        assert!(exposition.contains("test_case_duration_seconds_bucket{le=\"+Inf\"} 3\n"));
+        assert!(exposition.contains("test_case_duration_seconds_count 3\n"));
+    }
+
+    #[test]
+    fn test_prometheus_names_are_sanitized() {
+        let metrics = Metrics::new();
+        metrics.increment_counter("2xx.responses-total", 1);
+
+        assert!(metrics.to_prometheus().contains("\n_xx_responses_total 1\n"));
+        assert_eq!(metrics.snapshot().counters["2xx.responses-total"], 1);
+    }
+}
@@ -0,0 +1,46 @@
+// Proxy Configuration for Aluminum Web Browser
+// This module defines the proxy settings shared by the browser prelude, incognito
+// mode and the import tool, so every client validates and applies a proxy the
+// same way.
+
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+/// HTTP(S) or SOCKS proxy that requests are routed through
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProxyConfig {
+    url: String,
+    auth: Option<ProxyAuth>,
+}
+
+/// Basic-auth credentials sent to the proxy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProxyAuth {
+    username: String,
+    password: String,
+}
+
+impl ProxyConfig {
+    /// Creates a proxy config, rejecting URLs reqwest can't route through
+    pub fn new(url: &str, auth: Option<(String, String)>) -> Result<Self, Box<dyn std::error::Error>> {
+        let config = ProxyConfig {
+            url: url.to_string(),
+            auth: auth.map(|(username, password)| ProxyAuth { username, password }),
+        };
+        config.to_reqwest()?;
+        Ok(config)
+    }
+
+    /// Builds the reqwest proxy for every scheme, with the credentials attached
+    pub fn to_reqwest(&self) -> Result<reqwest::Proxy, Box<dyn std::error::Error>> {
+        let parsed = Url::parse(&self.url)?;
+        if !matches!(parsed.scheme(), "http" | "https" | "socks5" | "socks5h") {
+            return Err(format!("Unsupported proxy scheme '{}'", parsed.scheme()).into());
+        }
+        let mut proxy = reqwest::Proxy::all(parsed.as_str())?;
+        if let Some(auth) = &self.auth {
+            proxy = proxy.basic_auth(&auth.username, &auth.password);
+        }
+        Ok(proxy)
+    }
+}
@@ -0,0 +1,78 @@
+// Redirect Policy for Aluminum Web Browser
+// This module defines the redirect limits shared by incognito mode and the import
+// tool, and the error a request fails with when a redirect breaks them.
+
+/// Redirects followed when no policy is configured
+pub const DEFAULT_MAX_REDIRECTS: usize = 10;
+
+/// Limits on the redirects a request may follow
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RedirectPolicy {
+    /// Most redirects followed before the request fails
+    pub max_hops: usize,
+    /// Refuse redirects that change scheme, host or port
+    pub same_origin_only: bool,
+}
+
+impl Default for RedirectPolicy {
+    fn default() -> Self {
+        RedirectPolicy {
+            max_hops: DEFAULT_MAX_REDIRECTS,
+            same_origin_only: false,
+        }
+    }
+}
+
+impl RedirectPolicy {
+    /// Builds the reqwest policy, which fails the request with a `RedirectError`
+    pub fn to_reqwest(self) -> reqwest::redirect::Policy {
+        reqwest::redirect::Policy::custom(move |attempt| {
+            // `previous` holds the original URL plus every redirect already followed
+            if attempt.previous().len() > self.max_hops {
+                return attempt.error(RedirectError::TooManyRedirects { max_hops: self.max_hops });
+            }
+            if self.same_origin_only {
+                let from = attempt.previous().last().cloned();
+                if let Some(from) = from.filter(|from| from.origin() != attempt.url().origin()) {
+                    let to = attempt.url().to_string();
+                    return attempt.error(RedirectError::CrossOriginRedirect { from: from.to_string(), to });
+                }
+            }
+            attempt.follow()
+        })
+    }
+}
+
+/// Error for a redirect that breaks the configured `RedirectPolicy`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RedirectError {
+    TooManyRedirects { max_hops: usize },
+    CrossOriginRedirect { from: String, to: String },
+}
+
+impl std::fmt::Display for RedirectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RedirectError::TooManyRedirects { max_hops } => {
+                write!(f, "Too many redirects: more than {} hop(s)", max_hops)
+            }
+            RedirectError::CrossOriginRedirect { from, to } => {
+                write!(f, "Cross-origin redirect from {} to {} refused", from, to)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RedirectError {}
+
+/// Surfaces a redirect policy violation buried in a reqwest error as the `RedirectError` itself
+pub fn into_redirect_error(error: reqwest::Error) -> Box<dyn std::error::Error> {
+    let mut source = std::error::Error::source(&error);
+    while let Some(cause) = source {
+        if let Some(redirect_error) = cause.downcast_ref::<RedirectError>() {
+            return Box::new(redirect_error.clone());
+        }
+        source = cause.source();
+    }
+    Box::new(error)
+}
@@ -0,0 +1,96 @@
+// Peak Memory of Attribute Imports
+// This test counts heap allocations with its own global allocator, so it lives
+// in a separate test binary where the counting can't slow down or skew any
+// other test.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::fs;
+
+use tempfile::TempDir;
+
+#[allow(dead_code)]
+#[path = "../builds/utility/importattributes.rs"]
+mod importattributes;
+
+use importattributes::{import_attributes, import_attributes_streaming, ProcessingMode};
+
+// Counts live heap bytes on threads inside a `measure_peak` scope, so tests
+// running in parallel don't skew each other
+struct CountingAllocator;
+
+thread_local! {
+    static MEASURING: Cell<bool> = const { Cell::new(false) };
+    static LIVE_BYTES: Cell<usize> = const { Cell::new(0) };
+    static PEAK_BYTES: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() && MEASURING.try_with(Cell::get).unwrap_or(false) {
+            let _ = LIVE_BYTES.try_with(|live| {
+                live.set(live.get() + layout.size());
+                let _ = PEAK_BYTES.try_with(|peak| peak.set(peak.get().max(live.get())));
+            });
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        if MEASURING.try_with(Cell::get).unwrap_or(false) {
+            // Freeing memory allocated before the scope started can take this below zero
+            let _ = LIVE_BYTES.try_with(|live| live.set(live.get().saturating_sub(layout.size())));
+        }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+// Run `f` and return its result with the most heap it held at once
+fn measure_peak<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    LIVE_BYTES.with(|live| live.set(0));
+    PEAK_BYTES.with(|peak| peak.set(0));
+    MEASURING.with(|measuring| measuring.set(true));
+    let result = f();
+    MEASURING.with(|measuring| measuring.set(false));
+    (result, PEAK_BYTES.with(Cell::get))
+}
+
+#[test]
+fn streaming_import_peak_memory_stays_below_collecting_import() {
+    let dir = TempDir::new().unwrap();
+    // 20,000 lines that merge down to 100 attributes
+    let source = dir.path().join("src");
+    fs::create_dir_all(&source).unwrap();
+    let lines: String = (0..20_000)
+        .map(|i| format!("setting{}=value-{:08} # group{}\n", i % 100, i, i % 5))
+        .collect();
+    fs::write(source.join("attrs.txt"), lines).unwrap();
+    let config = serde_json::json!({
+        "source_path": source,
+        "destination_path": dir.path().join("attributes.db"),
+        "file_patterns": [".txt"],
+        "attribute_regex": r"^([^=]*)=([^#]*) # (.*)$",
+        "max_file_size": 1 << 24,
+        "parallel_processing": false,
+    });
+    let config_path = dir.path().join("import.json");
+    fs::write(&config_path, config.to_string()).unwrap();
+    let config_path = config_path.to_str().unwrap();
+
+    let (collected, collected_peak) =
+        measure_peak(|| import_attributes(config_path, Some(ProcessingMode::Sequential)).unwrap());
+    let (streamed, streamed_peak) = measure_peak(|| import_attributes_streaming(config_path, 256).unwrap());
+
+    assert_eq!(collected.attributes.len(), 100);
+    assert_eq!(streamed.attributes.len(), 100);
+    assert!(
+        streamed_peak * 4 < collected_peak,
+        "streaming import peaked at {} bytes, collecting import at {} bytes",
+        streamed_peak,
+        collected_peak
+    );
+}
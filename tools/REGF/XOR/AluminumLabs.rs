@@ -1,405 +1,1330 @@
-
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
-use wasm_bindgen::prelude::*;
-use web_sys::{window, Document, Element, HtmlElement};
-
-// AluminumLabs: A feature-rich laboratory for the Aluminum web browser
-// This module provides an extensive set of tools and experiments for users
-// to enhance their browsing experience and contribute to browser development.
-
-#[wasm_bindgen]
-pub struct AluminumLabs {
-    experiments: Arc<Mutex<HashMap<String, Experiment>>>,
-    active_experiments: Arc<Mutex<Vec<String>>>,
-    user_preferences: Arc<Mutex<UserPreferences>>,
-    telemetry: Arc<Mutex<Telemetry>>,
-}
-
-struct Experiment {
-    name: String,
-    description: String,
-    status: ExperimentStatus,
-    impact: ExperimentImpact,
-    implementation: Box<dyn Fn() -> Result<(), JsValue>>,
-}
-
-enum ExperimentStatus {
-    Active,
-    Inactive,
-    Deprecated,
-}
-
-enum ExperimentImpact {
-    Low,
-    Medium,
-    High,
-}
-
-struct UserPreferences {
-    theme: Theme,
-    font_size: u8,
-    enable_notifications: bool,
-}
-
-enum Theme {
-    Light,
-    Dark,
-    System,
-}
-
-struct Telemetry {
-    data_points: Vec<DataPoint>,
-}
-
-struct DataPoint {
-    timestamp: f64,
-    experiment: String,
-    metric: String,
-    value: f64,
-}
-
-#[wasm_bindgen]
-impl AluminumLabs {
-    #[wasm_bindgen(constructor)]
-    pub fn new() -> Self {
-        console_error_panic_hook::set_once();
-        
-        AluminumLabs {
-            experiments: Arc::new(Mutex::new(HashMap::new())),
-            active_experiments: Arc::new(Mutex::new(Vec::new())),
-            user_preferences: Arc::new(Mutex::new(UserPreferences {
-                theme: Theme::System,
-                font_size: 16,
-                enable_notifications: true,
-            })),
-            telemetry: Arc::new(Mutex::new(Telemetry {
-                data_points: Vec::new(),
-            })),
-        }
-    }
-
-    pub fn initialize(&mut self) -> Result<(), JsValue> {
-        self.register_default_experiments()?;
-        self.create_labs_ui()?;
-        self.load_user_preferences()?;
-        self.setup_telemetry()?;
-        Ok(())
-    }
-
-    fn register_default_experiments(&mut self) -> Result<(), JsValue> {
-        let mut experiments = self.experiments.lock().unwrap();
-
-        // Register various experiments
-        experiments.insert(
-            "super_speed_mode".to_string(),
-            Experiment {
-                name: "Super Speed Mode".to_string(),
-                description: "Optimize browser performance for lightning-fast page loads".to_string(),
-                status: ExperimentStatus::Active,
-                impact: ExperimentImpact::High,
-                implementation: Box::new(|| {
-                    // Implementation for Super Speed Mode
-                    console_log!("Activating Super Speed Mode");
-                    // Add code to optimize browser performance
-                    Ok(())
-                }),
-            },
-        );
-
-        experiments.insert(
-            "ai_content_summarizer".to_string(),
-            Experiment {
-                name: "AI Content Summarizer".to_string(),
-                description: "Use AI to provide concise summaries of web page content".to_string(),
-                status: ExperimentStatus::Active,
-                impact: ExperimentImpact::Medium,
-                implementation: Box::new(|| {
-                    // Implementation for AI Content Summarizer
-                    console_log!("Activating AI Content Summarizer");
-                    // Add code to summarize web page content using AI
-                    Ok(())
-                }),
-            },
-        );
-
-        experiments.insert(
-            "advanced_tab_management".to_string(),
-            Experiment {
-                name: "Advanced Tab Management".to_string(),
-                description: "Intelligent tab grouping and organization based on content and user behavior".to_string(),
-                status: ExperimentStatus::Active,
-                impact: ExperimentImpact::Medium,
-                implementation: Box::new(|| {
-                    // Implementation for Advanced Tab Management
-                    console_log!("Activating Advanced Tab Management");
-                    // Add code to implement intelligent tab management
-                    Ok(())
-                }),
-            },
-        );
-
-        // Add more experiments here dear user...
-
-        Ok(())
-    }
-
-    fn create_labs_ui(&self) -> Result<(), JsValue> {
-        let window = window().unwrap();
-        let document = window.document().unwrap();
-        let body = document.body().unwrap();
-
-        let labs_container = document.create_element("div")?;
-        labs_container.set_id("aluminum-labs-container");
-        labs_container.set_class_name("labs-container");
-
-        let labs_title = document.create_element("h1")?;
-        labs_title.set_text_content(Some("Aluminum Labs"));
-        labs_container.append_child(&labs_title)?;
-
-        let experiments_list = document.create_element("ul")?;
-        experiments_list.set_id("experiments-list");
-
-        let experiments = self.experiments.lock().unwrap();
-        for (id, experiment) in experiments.iter() {
-            let experiment_item = document.create_element("li")?;
-            experiment_item.set_class_name("experiment-item");
-
-            let experiment_name = document.create_element("h3")?;
-            experiment_name.set_text_content(Some(&experiment.name));
-            experiment_item.append_child(&experiment_name)?;
-
-            let experiment_description = document.create_element("p")?;
-            experiment_description.set_text_content(Some(&experiment.description));
-            experiment_item.append_child(&experiment_description)?;
-
-            let toggle_button = document.create_element("button")?;
-            toggle_button.set_text_content(Some("Toggle"));
-            toggle_button.set_attribute("data-experiment-id", id)?;
-            toggle_button.add_event_listener_with_callback("click", &self.toggle_experiment_closure(id.clone()))?;
-            experiment_item.append_child(&toggle_button)?;
-
-            experiments_list.append_child(&experiment_item)?;
-        }
-
-        labs_container.append_child(&experiments_list)?;
-        body.append_child(&labs_container)?;
-
-        Ok(())
-    }
-
-    fn toggle_experiment_closure(&self, experiment_id: String) -> Closure<dyn FnMut()> {
-        let experiments = Arc::clone(&self.experiments);
-        let active_experiments = Arc::clone(&self.active_experiments);
-        let telemetry = Arc::clone(&self.telemetry);
-
-        Closure::wrap(Box::new(move || {
-            let mut experiments = experiments.lock().unwrap();
-            let mut active_experiments = active_experiments.lock().unwrap();
-            let mut telemetry = telemetry.lock().unwrap();
-
-            if let Some(experiment) = experiments.get_mut(&experiment_id) {
-                if active_experiments.contains(&experiment_id) {
-                    // Deactivate the experiment
-                    active_experiments.retain(|id| id != &experiment_id);
-                    console_log!("Deactivated experiment: {}", experiment.name);
-                } else {
-                    // Activate the experiment
-                    active_experiments.push(experiment_id.clone());
-                    if let Err(e) = (experiment.implementation)() {
-                        console_error!("Error activating experiment: {:?}", e);
-                    } else {
-                        console_log!("Activated experiment: {}", experiment.name);
-                    }
-                }
-
-                // Record telemetry
-                telemetry.data_points.push(DataPoint {
-                    timestamp: js_sys::Date::now(),
-                    experiment: experiment_id.clone(),
-                    metric: "toggle".to_string(),
-                    value: if active_experiments.contains(&experiment_id) { 1.0 } else { 0.0 },
-                });
-            }
-        }) as Box<dyn FnMut()>)
-    }
-
-    fn load_user_preferences(&self) -> Result<(), JsValue> {
-        // In a real implementation, this would load preferences from storage
-        console_log!("Loading user preferences");
-        // Simulated loading of preferences
-        let mut preferences = self.user_preferences.lock().unwrap();
-        preferences.theme = Theme::Dark;
-        preferences.font_size = 18;
-        preferences.enable_notifications = true;
-        Ok(())
-    }
-
-    fn setup_telemetry(&self) -> Result<(), JsValue> {
-        console_log!("Setting up telemetry");
-        // In a real implementation, this would set up telemetry reporting
-        Ok(())
-    }
-
-    pub fn get_active_experiments(&self) -> Result<JsValue, JsValue> {
-        let active_experiments = self.active_experiments.lock().unwrap();
-        Ok(serde_wasm_bindgen::to_value(&*active_experiments)?)
-    }
-
-    pub fn update_user_preference(&mut self, key: &str, value: &JsValue) -> Result<(), JsValue> {
-        let mut preferences = self.user_preferences.lock().unwrap();
-        match key {
-            "theme" => {
-                preferences.theme = match value.as_string().unwrap().as_str() {
-                    "light" => Theme::Light,
-                    "dark" => Theme::Dark,
-                    _ => Theme::System,
-                };
-            }
-            "font_size" => {
-                preferences.font_size = value.as_f64().unwrap() as u8;
-            }
-            "enable_notifications" => {
-                preferences.enable_notifications = value.as_bool().unwrap();
-            }
-            _ => return Err(JsValue::from_str("Invalid preference key")),
-        }
-        Ok(())
-    }
-
-    pub fn get_telemetry_report(&self) -> Result<JsValue, JsValue> {
-        let telemetry = self.telemetry.lock().unwrap();
-        Ok(serde_wasm_bindgen::to_value(&telemetry.data_points)?)
-    }
-
-    // Additional methods for managing experiments, user interactions, and browser integration
-
-    pub fn add_custom_experiment(&mut self, name: &str, description: &str, impact: &str) -> Result<(), JsValue> {
-        let mut experiments = self.experiments.lock().unwrap();
-        let impact = match impact {
-            "low" => ExperimentImpact::Low,
-            "medium" => ExperimentImpact::Medium,
-            "high" => ExperimentImpact::High,
-            _ => return Err(JsValue::from_str("Invalid impact level")),
-        };
-
-        let id = name.to_lowercase().replace(" ", "_");
-        experiments.insert(
-            id.clone(),
-            Experiment {
-                name: name.to_string(),
-                description: description.to_string(),
-                status: ExperimentStatus::Active,
-                impact,
-                implementation: Box::new(move || {
-                    console_log!("Activating custom experiment: {}", name);
-                    // Placeholder implementation for custom experiments
-                    Ok(())
-                }),
-            },
-        );
-
-        console_log!("Added custom experiment: {}", name);
-        Ok(())
-    }
-
-    pub fn remove_experiment(&mut self, id: &str) -> Result<(), JsValue> {
-        let mut experiments = self.experiments.lock().unwrap();
-        let mut active_experiments = self.active_experiments.lock().unwrap();
-
-        if experiments.remove(id).is_some() {
-            active_experiments.retain(|exp_id| exp_id != id);
-            console_log!("Removed experiment: {}", id);
-            Ok(())
-        } else {
-            Err(JsValue::from_str("Experiment not found"))
-        }
-    }
-
-    pub fn get_experiment_details(&self, id: &str) -> Result<JsValue, JsValue> {
-        let experiments = self.experiments.lock().unwrap();
-        if let Some(experiment) = experiments.get(id) {
-            Ok(serde_wasm_bindgen::to_value(&experiment)?)
-        } else {
-            Err(JsValue::from_str("Experiment not found"))
-        }
-    }
-
-    pub fn apply_theme(&self) -> Result<(), JsValue> {
-        let preferences = self.user_preferences.lock().unwrap();
-        let theme = match preferences.theme {
-            Theme::Light => "light",
-            Theme::Dark => "dark",
-            Theme::System => {
-                if window().unwrap().match_media("(prefers-color-scheme: dark)")?.unwrap().matches() {
-                    "dark"
-                } else {
-                    "light"
-                }
-            }
-        };
-
-        let document = window().unwrap().document().unwrap();
-        document.document_element().unwrap().set_attribute("data-theme", theme)?;
-        console_log!("Applied theme: {}", theme);
-        Ok(())
-    }
-
-    pub fn collect_performance_metrics(&self) -> Result<(), JsValue> {
-        let window = window().unwrap();
-        let performance = window.performance().unwrap();
-
-        let navigation_timing: web_sys::PerformanceNavigationTiming = js_sys::Reflect::get(
-            &performance.get_entries_by_type("navigation").unwrap(),
-            &JsValue::from(0),
-        )?.dyn_into()?;
-
-        let mut telemetry = self.telemetry.lock().unwrap();
-        telemetry.data_points.push(DataPoint {
-            timestamp: js_sys::Date::now(),
-            experiment: "performance".to_string(),
-            metric: "load_time".to_string(),
-            value: navigation_timing.load_event_end() - navigation_timing.navigation_start(),
-        });
-
-        console_log!("Collected performance metrics");
-        Ok(())
-    }
-
-    pub fn suggest_experiments(&self) -> Result<JsValue, JsValue> {
-        let experiments = self.experiments.lock().unwrap();
-        let active_experiments = self.active_experiments.lock().unwrap();
-
-        let suggestions: Vec<&Experiment> = experiments
-            .values()
-            .filter(|exp| !active_experiments.contains(&exp.name.to_lowercase().replace(" ", "_")))
-            .take(3)
-            .collect();
-
-        Ok(serde_wasm_bindgen::to_value(&suggestions)?)
-    }
-
-    // ... Add more methods as needed for a comprehensive labs feature ...
-
-}
-
-// Helper function to log messages to the console
-#[wasm_bindgen]
-extern "C" {
-    #[wasm_bindgen(js_namespace = console)]
-    fn log(s: &str);
-}
-
-macro_rules! console_log {
-    ($($t:tt)*) => (log(&format!($($t)*)))
-}
-
-#[wasm_bindgen]
-extern "C" {
-    #[wasm_bindgen(js_namespace = console)]
-    fn error(s: &str);
-}
-
-macro_rules! console_error {
-    ($($t:tt)*) => (error(&format!($($t)*)))
-}
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{window, Document, Element, HtmlElement};
+
+// localStorage key holding the ids of active experiments as a JSON array
+const ACTIVE_EXPERIMENTS_STORAGE_KEY: &str = "aluminum_labs_active_experiments";
+// Schema version saved with the active experiment ids. State saved before
+// versioning is a bare JSON array and is treated as version 1.
+const LABS_STATE_SCHEMA_VERSION: u64 = 2;
+// Safe mode skips re-activating persisted experiments on start-up. It is enabled
+// by `?aluminum_safe_mode=1` in the page URL or by this localStorage key set to "true".
+const SAFE_MODE_PARAM: &str = "aluminum_safe_mode";
+const SAFE_MODE_STORAGE_KEY: &str = "aluminum_labs_safe_mode";
+// Number of telemetry points kept before the oldest are dropped
+const DEFAULT_TELEMETRY_CAPACITY: usize = 1000;
+// Seed for telemetry sampling decisions, so the same points are kept on every run
+const DEFAULT_TELEMETRY_SEED: u64 = 0x5EED_A1u64;
+// Experiment that gates `summarize_page`, and the endpoint it posts page text to
+const SUMMARIZER_EXPERIMENT_ID: &str = "ai_content_summarizer";
+const DEFAULT_SUMMARIZER_ENDPOINT: &str = "https://labs.aluminum.browser.org/api/summarize";
+// Activation hooks run on the UI thread; slower ones are logged and recorded in telemetry
+const ACTIVATION_TIME_BUDGET_MS: f64 = 50.0;
+// Elements whose text is never shown to the user
+const NON_VISIBLE_TEXT_TAGS: &[&str] = &["SCRIPT", "STYLE", "NOSCRIPT", "TEMPLATE"];
+// Elements that start and end their own line of readable text
+const BLOCK_TEXT_TAGS: &[&str] = &[
+    "ADDRESS", "ARTICLE", "ASIDE", "BLOCKQUOTE", "DD", "DIV", "DL", "DT", "FIGCAPTION", "FIGURE", "FOOTER",
+    "FORM", "H1", "H2", "H3", "H4", "H5", "H6", "HEADER", "HR", "LI", "MAIN", "NAV", "OL", "P", "PRE",
+    "SECTION", "TABLE", "TR", "UL",
+];
+
+// AluminumLabs: A feature-rich laboratory for the Aluminum web browser
+// This module provides an extensive set of tools and experiments for users
+// to enhance their browsing experience and contribute to browser development.
+
+#[wasm_bindgen]
+pub struct AluminumLabs {
+    experiments: Arc<Mutex<HashMap<String, Experiment>>>,
+    active_experiments: Arc<Mutex<Vec<String>>>,
+    user_preferences: Arc<Mutex<UserPreferences>>,
+    telemetry: Arc<Mutex<Telemetry>>,
+    summarizer_endpoint: String,
+}
+
+// Errors returned to JavaScript by the labs API, so a poisoned lock or a page
+// without a DOM fails the call instead of panicking across the wasm boundary
+#[derive(Debug)]
+pub enum LabsError {
+    // A panic while this lock was held left its data in an unknown state
+    Poisoned(&'static str),
+    // The page has no window, document or other DOM object the call needs
+    MissingDom(&'static str),
+    ExperimentNotFound(String),
+    InvalidArgument(String),
+    // An operation that ran but couldn't complete, e.g. a failed request
+    Failed(String),
+    // An error thrown by a browser API
+    Js(JsValue),
+}
+
+impl fmt::Display for LabsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LabsError::Poisoned(lock) => write!(f, "Labs state '{}' is unavailable after an earlier failure", lock),
+            LabsError::MissingDom(object) => write!(f, "No {} available", object),
+            LabsError::ExperimentNotFound(id) => write!(f, "Experiment not found: {}", id),
+            LabsError::InvalidArgument(message) | LabsError::Failed(message) => f.write_str(message),
+            LabsError::Js(value) => write!(f, "{:?}", value),
+        }
+    }
+}
+
+impl From<LabsError> for JsValue {
+    fn from(error: LabsError) -> Self {
+        match error {
+            LabsError::Js(value) => value,
+            other => js_sys::Error::new(&other.to_string()).into(),
+        }
+    }
+}
+
+impl From<JsValue> for LabsError {
+    fn from(value: JsValue) -> Self {
+        LabsError::Js(value)
+    }
+}
+
+impl From<serde_wasm_bindgen::Error> for LabsError {
+    fn from(error: serde_wasm_bindgen::Error) -> Self {
+        LabsError::Js(error.into())
+    }
+}
+
+// Lock shared labs state, reporting a poisoned lock as an error instead of panicking
+fn lock<'a, T>(mutex: &'a Mutex<T>, name: &'static str) -> Result<MutexGuard<'a, T>, LabsError> {
+    mutex.lock().map_err(|_| LabsError::Poisoned(name))
+}
+
+struct Experiment {
+    name: String,
+    description: String,
+    status: ExperimentStatus,
+    impact: ExperimentImpact,
+    on_activate: Option<ExperimentHook>,
+    on_deactivate: Option<ExperimentHook>,
+}
+
+// Lifecycle hook run when an experiment is switched on or off
+type ExperimentHook = Box<dyn Fn() -> Result<(), JsValue>>;
+
+// Whether an experiment may be switched on. Deprecated is final.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ExperimentStatus {
+    Active,
+    Inactive,
+    Deprecated,
+}
+
+enum ExperimentImpact {
+    Low,
+    Medium,
+    High,
+}
+
+struct UserPreferences {
+    theme: Theme,
+    font_size: u8,
+    enable_notifications: bool,
+}
+
+enum Theme {
+    Light,
+    Dark,
+    System,
+}
+
+// Fixed-capacity ring buffer of telemetry points; the oldest are dropped when full
+struct Telemetry {
+    data_points: VecDeque<DataPoint>,
+    capacity: usize,
+    // Per-metric sampling; metrics without a rule keep every point
+    sampling: HashMap<String, SamplingRule>,
+    // Timestamp of the last point kept for each metric with a min-interval rule
+    last_recorded: HashMap<String, f64>,
+    rng_state: u64,
+}
+
+// How often points of a high-frequency metric are kept
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SamplingRule {
+    // Keep each point with probability 1/n
+    OneIn(u32),
+    // Keep a point only if at least this many milliseconds passed since the last kept one
+    MinInterval(f64),
+}
+
+struct DataPoint {
+    timestamp: f64,
+    experiment: String,
+    metric: String,
+    value: f64,
+}
+
+#[wasm_bindgen]
+impl AluminumLabs {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        console_error_panic_hook::set_once();
+        
+        AluminumLabs {
+            experiments: Arc::new(Mutex::new(HashMap::new())),
+            active_experiments: Arc::new(Mutex::new(Vec::new())),
+            user_preferences: Arc::new(Mutex::new(UserPreferences {
+                theme: Theme::System,
+                font_size: 16,
+                enable_notifications: true,
+            })),
+            telemetry: Arc::new(Mutex::new(Telemetry::new(DEFAULT_TELEMETRY_CAPACITY))),
+            summarizer_endpoint: DEFAULT_SUMMARIZER_ENDPOINT.to_string(),
+        }
+    }
+
+    pub fn initialize(&mut self) -> Result<(), LabsError> {
+        self.register_default_experiments()?;
+        if safe_mode_requested() {
+            console_log!("Safe mode: not re-activating saved experiments");
+        } else {
+            self.restore_active_experiments()?;
+        }
+        self.create_labs_ui()?;
+        self.load_user_preferences()?;
+        self.setup_telemetry()?;
+        Ok(())
+    }
+
+    fn register_default_experiments(&mut self) -> Result<(), LabsError> {
+        let mut experiments = lock(&self.experiments, "experiments")?;
+
+        // Register various experiments
+        experiments.insert(
+            "super_speed_mode".to_string(),
+            Experiment {
+                name: "Super Speed Mode".to_string(),
+                description: "Optimize browser performance for lightning-fast page loads".to_string(),
+                status: ExperimentStatus::Active,
+                impact: ExperimentImpact::High,
+                on_activate: Some(Box::new(|| {
+                    // Implementation for Super Speed Mode
+                    console_log!("Activating Super Speed Mode");
+                    // Add code to optimize browser performance
+                    Ok(())
+                })),
+                on_deactivate: Some(Box::new(|| {
+                    console_log!("Deactivating Super Speed Mode");
+                    // Add code to restore default performance settings
+                    Ok(())
+                })),
+            },
+        );
+
+        experiments.insert(
+            "ai_content_summarizer".to_string(),
+            Experiment {
+                name: "AI Content Summarizer".to_string(),
+                description: "Use AI to provide concise summaries of web page content".to_string(),
+                status: ExperimentStatus::Active,
+                impact: ExperimentImpact::Medium,
+                on_activate: Some(Box::new(|| {
+                    // Implementation for AI Content Summarizer
+                    console_log!("Activating AI Content Summarizer");
+                    // Add code to summarize web page content using AI
+                    Ok(())
+                })),
+                on_deactivate: Some(Box::new(|| {
+                    console_log!("Deactivating AI Content Summarizer");
+                    // Add code to remove page summaries
+                    Ok(())
+                })),
+            },
+        );
+
+        experiments.insert(
+            "advanced_tab_management".to_string(),
+            Experiment {
+                name: "Advanced Tab Management".to_string(),
+                description: "Intelligent tab grouping and organization based on content and user behavior".to_string(),
+                status: ExperimentStatus::Active,
+                impact: ExperimentImpact::Medium,
+                on_activate: Some(Box::new(|| {
+                    // Implementation for Advanced Tab Management
+                    console_log!("Activating Advanced Tab Management");
+                    // Add code to implement intelligent tab management
+                    Ok(())
+                })),
+                on_deactivate: Some(Box::new(|| {
+                    console_log!("Deactivating Advanced Tab Management");
+                    // Add code to ungroup tabs
+                    Ok(())
+                })),
+            },
+        );
+
+        // Add more experiments here dear user...
+
+        Ok(())
+    }
+
+    // Re-activate the experiments that were active when the state was last saved
+    fn restore_active_experiments(&self) -> Result<(), LabsError> {
+        let saved = load_active_experiment_ids()?;
+        let experiments = lock(&self.experiments, "experiments")?;
+        let mut active_experiments = lock(&self.active_experiments, "active_experiments")?;
+        let mut telemetry = lock(&self.telemetry, "telemetry")?;
+
+        for id in saved {
+            if active_experiments.contains(&id) {
+                continue;
+            }
+            if let Some(experiment) = experiments.get(&id) {
+                if experiment.status != ExperimentStatus::Active {
+                    console_log!("Not restoring {:?} experiment: {}", experiment.status, experiment.name);
+                    continue;
+                }
+                if run_activate_hook(&id, experiment, &mut telemetry) {
+                    active_experiments.push(id.clone());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn create_labs_ui(&self) -> Result<(), LabsError> {
+        let window = window().ok_or(LabsError::MissingDom("window"))?;
+        let document = window.document().ok_or(LabsError::MissingDom("document"))?;
+        let body = document.body().ok_or(LabsError::MissingDom("body"))?;
+
+        let labs_container = document.create_element("div")?;
+        labs_container.set_id("aluminum-labs-container");
+        labs_container.set_class_name("labs-container");
+
+        let labs_title = document.create_element("h1")?;
+        labs_title.set_text_content(Some("Aluminum Labs"));
+        labs_container.append_child(&labs_title)?;
+
+        let experiments_list = document.create_element("ul")?;
+        experiments_list.set_id("experiments-list");
+
+        let experiments = lock(&self.experiments, "experiments")?;
+        for (id, experiment) in sorted_experiments(&experiments) {
+            let experiment_item = document.create_element("li")?;
+            experiment_item.set_id(id);
+            experiment_item.set_class_name("experiment-item");
+
+            let experiment_name = document.create_element("h3")?;
+            experiment_name.set_text_content(Some(&experiment.name));
+            experiment_item.append_child(&experiment_name)?;
+
+            let experiment_description = document.create_element("p")?;
+            experiment_description.set_text_content(Some(&experiment.description));
+            experiment_item.append_child(&experiment_description)?;
+
+            let toggle_button = document.create_element("button")?;
+            toggle_button.set_text_content(Some("Toggle"));
+            toggle_button.set_attribute("data-experiment-id", id)?;
+            toggle_button.add_event_listener_with_callback("click", &self.toggle_experiment_closure(id.clone()))?;
+            experiment_item.append_child(&toggle_button)?;
+
+            experiments_list.append_child(&experiment_item)?;
+        }
+
+        labs_container.append_child(&experiments_list)?;
+        body.append_child(&labs_container)?;
+
+        Ok(())
+    }
+
+    fn toggle_experiment_closure(&self, experiment_id: String) -> Closure<dyn FnMut()> {
+        let experiments = Arc::clone(&self.experiments);
+        let active_experiments = Arc::clone(&self.active_experiments);
+        let telemetry = Arc::clone(&self.telemetry);
+
+        Closure::wrap(Box::new(move || {
+            let locked = lock(&experiments, "experiments").and_then(|experiments| {
+                Ok((experiments, lock(&active_experiments, "active_experiments")?, lock(&telemetry, "telemetry")?))
+            });
+            let (mut experiments, mut active_experiments, mut telemetry) = match locked {
+                Ok(guards) => guards,
+                Err(e) => {
+                    console_error!("Cannot toggle experiment {}: {}", experiment_id, e);
+                    return;
+                }
+            };
+
+            if let Some(experiment) = experiments.get_mut(&experiment_id) {
+                if active_experiments.contains(&experiment_id) {
+                    // Deactivate the experiment; it is marked inactive even if its hook fails
+                    active_experiments.retain(|id| id != &experiment_id);
+                    run_deactivate_hook(&experiment_id, experiment, &mut telemetry);
+                } else if experiment.status != ExperimentStatus::Active {
+                    console_error!("Cannot activate {:?} experiment: {}", experiment.status, experiment.name);
+                    return;
+                } else if run_activate_hook(&experiment_id, experiment, &mut telemetry) {
+                    active_experiments.push(experiment_id.clone());
+                }
+
+                if let Err(e) = save_active_experiment_ids(&active_experiments) {
+                    console_error!("Error saving active experiments: {:?}", e);
+                }
+
+                // Record telemetry
+                telemetry.push(DataPoint {
+                    timestamp: js_sys::Date::now(),
+                    experiment: experiment_id.clone(),
+                    metric: "toggle".to_string(),
+                    value: if active_experiments.contains(&experiment_id) { 1.0 } else { 0.0 },
+                });
+            }
+        }) as Box<dyn FnMut()>)
+    }
+
+    fn load_user_preferences(&self) -> Result<(), LabsError> {
+        // In a real implementation, this would load preferences from storage
+        console_log!("Loading user preferences");
+        // Simulated loading of preferences
+        let mut preferences = lock(&self.user_preferences, "user_preferences")?;
+        preferences.theme = Theme::Dark;
+        preferences.font_size = 18;
+        preferences.enable_notifications = true;
+        Ok(())
+    }
+
+    fn setup_telemetry(&self) -> Result<(), LabsError> {
+        console_log!("Setting up telemetry");
+        // In a real implementation, this would set up telemetry reporting
+        Ok(())
+    }
+
+    // Recovery switch: deactivate every active experiment and save the empty state
+    pub fn disable_all_experiments(&mut self) -> Result<(), LabsError> {
+        let experiments = lock(&self.experiments, "experiments")?;
+        let mut active_experiments = lock(&self.active_experiments, "active_experiments")?;
+        let mut telemetry = lock(&self.telemetry, "telemetry")?;
+
+        // Tear down in reverse activation order
+        let disabled: Vec<String> = active_experiments.drain(..).rev().collect();
+        for id in &disabled {
+            if let Some(experiment) = experiments.get(id) {
+                run_deactivate_hook(id, experiment, &mut telemetry);
+            }
+        }
+
+        telemetry.push(DataPoint {
+            timestamp: js_sys::Date::now(),
+            experiment: "labs".to_string(),
+            metric: "disable_all".to_string(),
+            value: disabled.len() as f64,
+        });
+
+        save_active_experiment_ids(&active_experiments)?;
+        console_log!("Disabled {} experiment(s)", disabled.len());
+        Ok(())
+    }
+
+    pub fn get_active_experiments(&self) -> Result<JsValue, LabsError> {
+        let active_experiments = lock(&self.active_experiments, "active_experiments")?;
+        Ok(serde_wasm_bindgen::to_value(&*active_experiments)?)
+    }
+
+    pub fn update_user_preference(&mut self, key: &str, value: &JsValue) -> Result<(), LabsError> {
+        let mut preferences = lock(&self.user_preferences, "user_preferences")?;
+        let invalid = |expected: &str| LabsError::InvalidArgument(format!("Preference {} must be {}", key, expected));
+        match key {
+            "theme" => {
+                preferences.theme = match value.as_string().ok_or_else(|| invalid("a string"))?.as_str() {
+                    "light" => Theme::Light,
+                    "dark" => Theme::Dark,
+                    _ => Theme::System,
+                };
+            }
+            "font_size" => {
+                preferences.font_size = value.as_f64().ok_or_else(|| invalid("a number"))? as u8;
+            }
+            "enable_notifications" => {
+                preferences.enable_notifications = value.as_bool().ok_or_else(|| invalid("a boolean"))?;
+            }
+            _ => return Err(LabsError::InvalidArgument(format!("Invalid preference key: {}", key))),
+        }
+        Ok(())
+    }
+
+    // Change how many telemetry points are kept, dropping the oldest if over the new capacity
+    pub fn set_telemetry_capacity(&mut self, capacity: usize) -> Result<(), LabsError> {
+        if capacity == 0 {
+            return Err(LabsError::InvalidArgument("Telemetry capacity must be at least 1".to_string()));
+        }
+        lock(&self.telemetry, "telemetry")?.set_capacity(capacity);
+        Ok(())
+    }
+
+    // Point the AI Content Summarizer at a different summarization service
+    pub fn set_summarizer_endpoint(&mut self, endpoint: &str) {
+        self.summarizer_endpoint = endpoint.to_string();
+    }
+
+    // Keep roughly one in `n` points of `metric`; 1 keeps every point
+    pub fn set_telemetry_sampling_rate(&mut self, metric: &str, n: u32) -> Result<(), LabsError> {
+        if n == 0 {
+            return Err(LabsError::InvalidArgument("Sampling rate must be at least 1".to_string()));
+        }
+        lock(&self.telemetry, "telemetry")?.set_sampling(metric, SamplingRule::OneIn(n));
+        Ok(())
+    }
+
+    // Keep a point of `metric` only if `interval_ms` has passed since the last one kept
+    pub fn set_telemetry_min_interval(&mut self, metric: &str, interval_ms: f64) -> Result<(), LabsError> {
+        if !(interval_ms >= 0.0) {
+            return Err(LabsError::InvalidArgument("Sampling interval must be a non-negative number".to_string()));
+        }
+        lock(&self.telemetry, "telemetry")?.set_sampling(metric, SamplingRule::MinInterval(interval_ms));
+        Ok(())
+    }
+
+    // Record every point of `metric` again
+    pub fn clear_telemetry_sampling(&mut self, metric: &str) -> Result<(), LabsError> {
+        lock(&self.telemetry, "telemetry")?.clear_sampling(metric);
+        Ok(())
+    }
+
+    // Reseed the sampler, so a given seed always keeps the same points
+    pub fn set_telemetry_seed(&mut self, seed: u64) -> Result<(), LabsError> {
+        lock(&self.telemetry, "telemetry")?.rng_state = seed;
+        Ok(())
+    }
+
+    // Report the telemetry points currently held, oldest first
+    pub fn get_telemetry_report(&self) -> Result<JsValue, LabsError> {
+        let telemetry = lock(&self.telemetry, "telemetry")?;
+        Ok(serde_wasm_bindgen::to_value(&telemetry.data_points)?)
+    }
+
+    // Additional methods for managing experiments, user interactions, and browser integration
+
+    pub fn add_custom_experiment(&mut self, name: &str, description: &str, impact: &str) -> Result<(), LabsError> {
+        let mut experiments = lock(&self.experiments, "experiments")?;
+        let impact = match impact {
+            "low" => ExperimentImpact::Low,
+            "medium" => ExperimentImpact::Medium,
+            "high" => ExperimentImpact::High,
+            _ => return Err(LabsError::InvalidArgument("Invalid impact level".to_string())),
+        };
+
+        let id = name.to_lowercase().replace(" ", "_");
+        let hook_name = name.to_string();
+        experiments.insert(
+            id.clone(),
+            Experiment {
+                name: name.to_string(),
+                description: description.to_string(),
+                status: ExperimentStatus::Active,
+                impact,
+                on_activate: Some(Box::new(move || {
+                    console_log!("Activating custom experiment: {}", hook_name);
+                    // Placeholder implementation for custom experiments
+                    Ok(())
+                })),
+                on_deactivate: None,
+            },
+        );
+
+        console_log!("Added custom experiment: {}", name);
+        Ok(())
+    }
+
+    pub fn remove_experiment(&mut self, id: &str) -> Result<(), LabsError> {
+        let mut experiments = lock(&self.experiments, "experiments")?;
+        let mut active_experiments = lock(&self.active_experiments, "active_experiments")?;
+
+        if experiments.remove(id).is_some() {
+            active_experiments.retain(|exp_id| exp_id != id);
+            console_log!("Removed experiment: {}", id);
+            Ok(())
+        } else {
+            Err(LabsError::ExperimentNotFound(id.to_string()))
+        }
+    }
+
+    // Move an experiment to "active", "inactive" or "deprecated". Deprecated experiments
+    // can't be brought back, and a running experiment is switched off when it stops being active.
+    pub fn set_experiment_status(&mut self, id: &str, status: &str) -> Result<(), LabsError> {
+        let status = match status {
+            "active" => ExperimentStatus::Active,
+            "inactive" => ExperimentStatus::Inactive,
+            "deprecated" => ExperimentStatus::Deprecated,
+            _ => return Err(LabsError::InvalidArgument("Invalid experiment status".to_string())),
+        };
+
+        let mut experiments = lock(&self.experiments, "experiments")?;
+        let mut active_experiments = lock(&self.active_experiments, "active_experiments")?;
+        let mut telemetry = lock(&self.telemetry, "telemetry")?;
+
+        let experiment = experiments
+            .get_mut(id)
+            .ok_or_else(|| LabsError::ExperimentNotFound(id.to_string()))?;
+        if experiment.status == ExperimentStatus::Deprecated && status != ExperimentStatus::Deprecated {
+            return Err(LabsError::InvalidArgument(format!(
+                "Experiment {} is deprecated and can't be made {:?}",
+                id, status
+            )));
+        }
+
+        if status != ExperimentStatus::Active && active_experiments.iter().any(|exp_id| exp_id == id) {
+            active_experiments.retain(|exp_id| exp_id != id);
+            run_deactivate_hook(id, experiment, &mut telemetry);
+            // The experiment is already switched off, so still apply the new status
+            if let Err(e) = save_active_experiment_ids(&active_experiments) {
+                console_error!("Error saving active experiments: {:?}", e);
+            }
+        }
+
+        experiment.status = status;
+        telemetry.push(DataPoint {
+            timestamp: js_sys::Date::now(),
+            experiment: id.to_string(),
+            metric: "status".to_string(),
+            value: match status {
+                ExperimentStatus::Active => 0.0,
+                ExperimentStatus::Inactive => 1.0,
+                ExperimentStatus::Deprecated => 2.0,
+            },
+        });
+
+        console_log!("Experiment {} is now {:?}", id, status);
+        Ok(())
+    }
+
+    pub fn get_experiment_details(&self, id: &str) -> Result<JsValue, LabsError> {
+        let experiments = lock(&self.experiments, "experiments")?;
+        if let Some(experiment) = experiments.get(id) {
+            Ok(serde_wasm_bindgen::to_value(&experiment)?)
+        } else {
+            Err(LabsError::ExperimentNotFound(id.to_string()))
+        }
+    }
+
+    pub fn apply_theme(&self) -> Result<(), LabsError> {
+        let preferences = lock(&self.user_preferences, "user_preferences")?;
+        let window = window().ok_or(LabsError::MissingDom("window"))?;
+        let theme = match preferences.theme {
+            Theme::Light => "light",
+            Theme::Dark => "dark",
+            Theme::System => {
+                let prefers_dark = window
+                    .match_media("(prefers-color-scheme: dark)")?
+                    .map_or(false, |query| query.matches());
+                if prefers_dark {
+                    "dark"
+                } else {
+                    "light"
+                }
+            }
+        };
+
+        let document = window.document().ok_or(LabsError::MissingDom("document"))?;
+        document
+            .document_element()
+            .ok_or(LabsError::MissingDom("document element"))?
+            .set_attribute("data-theme", theme)?;
+        console_log!("Applied theme: {}", theme);
+        Ok(())
+    }
+
+    pub fn collect_performance_metrics(&self) -> Result<(), LabsError> {
+        let window = window().ok_or(LabsError::MissingDom("window"))?;
+        let performance = window.performance().ok_or(LabsError::MissingDom("performance timeline"))?;
+
+        let navigation_timing: web_sys::PerformanceNavigationTiming = js_sys::Reflect::get(
+            &performance.get_entries_by_type("navigation").unwrap(),
+            &JsValue::from(0),
+        )?.dyn_into()?;
+
+        let mut telemetry = lock(&self.telemetry, "telemetry")?;
+        telemetry.push(DataPoint {
+            timestamp: js_sys::Date::now(),
+            experiment: "performance".to_string(),
+            metric: "load_time".to_string(),
+            value: navigation_timing.load_event_end() - navigation_timing.navigation_start(),
+        });
+
+        console_log!("Collected performance metrics");
+        Ok(())
+    }
+
+    pub fn suggest_experiments(&self) -> Result<JsValue, LabsError> {
+        let experiments = lock(&self.experiments, "experiments")?;
+        let active_experiments = lock(&self.active_experiments, "active_experiments")?;
+
+        let suggestions: Vec<&Experiment> = sorted_experiments(&experiments)
+            .into_iter()
+            .filter(|(id, _)| !active_experiments.contains(id))
+            .map(|(_, experiment)| experiment)
+            .take(3)
+            .collect();
+
+        Ok(serde_wasm_bindgen::to_value(&suggestions)?)
+    }
+
+    // ... Add more methods as needed for a comprehensive labs feature ...
+
+}
+
+// Helper function to log messages to the console
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = console)]
+    fn log(s: &str);
+}
+
+macro_rules! console_log {
+    ($($t:tt)*) => (log(&format!($($t)*)))
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = console)]
+    fn error(s: &str);
+}
+
+macro_rules! console_error {
+    ($($t:tt)*) => (error(&format!($($t)*)))
+}
+
+impl AluminumLabs {
+    // Summarize the current page in at most `max_sentences` sentences using the
+    // configured summarization endpoint. Requires the AI Content Summarizer
+    // experiment to be active.
+    pub async fn summarize_page(&self, max_sentences: usize) -> Result<String, LabsError> {
+        if !lock(&self.active_experiments, "active_experiments")?.iter().any(|id| id == SUMMARIZER_EXPERIMENT_ID) {
+            return Err(LabsError::Failed("AI Content Summarizer experiment is not active".to_string()));
+        }
+        if max_sentences == 0 {
+            return Err(LabsError::InvalidArgument("max_sentences must be at least 1".to_string()));
+        }
+
+        let window = window().ok_or(LabsError::MissingDom("window"))?;
+        let document = window.document().ok_or(LabsError::MissingDom("document"))?;
+        let text = extract_visible_text(&document)?;
+        if text.is_empty() {
+            return Err(LabsError::Failed("Page has no visible text to summarize".to_string()));
+        }
+
+        let payload = serde_json::json!({ "text": text, "max_sentences": max_sentences }).to_string();
+        let init = web_sys::RequestInit::new();
+        init.set_method("POST");
+        init.set_body(&JsValue::from_str(&payload));
+        let request = web_sys::Request::new_with_str_and_init(&self.summarizer_endpoint, &init)?;
+        request.headers().set("Content-Type", "application/json")?;
+
+        let response: web_sys::Response = JsFuture::from(window.fetch_with_request(&request))
+            .await
+            .map_err(|e| LabsError::Failed(format!("Summarization request failed: {:?}", e)))?
+            .dyn_into()?;
+        if !response.ok() {
+            return Err(LabsError::Failed(format!(
+                "Summarization endpoint returned HTTP {}",
+                response.status()
+            )));
+        }
+
+        let body = JsFuture::from(response.json()?)
+            .await
+            .map_err(|e| LabsError::Failed(format!("Summarization response was not JSON: {:?}", e)))?;
+        js_sys::Reflect::get(&body, &JsValue::from_str("summary"))?
+            .as_string()
+            .ok_or_else(|| LabsError::Failed("Summarization response has no 'summary' string".to_string()))
+    }
+}
+
+impl Telemetry {
+    fn new(capacity: usize) -> Self {
+        Telemetry {
+            data_points: VecDeque::with_capacity(capacity),
+            capacity,
+            sampling: HashMap::new(),
+            last_recorded: HashMap::new(),
+            rng_state: DEFAULT_TELEMETRY_SEED,
+        }
+    }
+
+    // Record a point, unless its metric's sampling rule drops it, evicting the
+    // oldest one when the buffer is full
+    fn push(&mut self, point: DataPoint) {
+        if !self.should_sample(&point) {
+            return;
+        }
+        if self.data_points.len() == self.capacity {
+            self.data_points.pop_front();
+        }
+        self.data_points.push_back(point);
+    }
+
+    fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.data_points.len() > capacity {
+            self.data_points.pop_front();
+        }
+    }
+
+    fn set_sampling(&mut self, metric: &str, rule: SamplingRule) {
+        self.sampling.insert(metric.to_string(), rule);
+        self.last_recorded.remove(metric);
+    }
+
+    fn clear_sampling(&mut self, metric: &str) {
+        self.sampling.remove(metric);
+        self.last_recorded.remove(metric);
+    }
+
+    // Decide whether a point is kept under its metric's sampling rule
+    fn should_sample(&mut self, point: &DataPoint) -> bool {
+        match self.sampling.get(&point.metric).copied() {
+            None | Some(SamplingRule::OneIn(1)) => true,
+            Some(SamplingRule::OneIn(n)) => self.next_random() % n as u64 == 0,
+            Some(SamplingRule::MinInterval(interval_ms)) => {
+                let due = self
+                    .last_recorded
+                    .get(&point.metric)
+                    .map_or(true, |last| point.timestamp - last >= interval_ms);
+                if due {
+                    self.last_recorded.insert(point.metric.clone(), point.timestamp);
+                }
+                due
+            }
+        }
+    }
+
+    // SplitMix64: small, seedable and good enough for sampling decisions
+    fn next_random(&mut self) -> u64 {
+        self.rng_state = self.rng_state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.rng_state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+// Experiments in display order: by name, case-insensitively, then by id so
+// experiments sharing a name still have a fixed position
+fn sorted_experiments(experiments: &HashMap<String, Experiment>) -> Vec<(&String, &Experiment)> {
+    let mut sorted: Vec<(&String, &Experiment)> = experiments.iter().collect();
+    sorted.sort_by(|(a_id, a), (b_id, b)| {
+        a.name
+            .to_lowercase()
+            .cmp(&b.name.to_lowercase())
+            .then_with(|| a_id.cmp(b_id))
+    });
+    sorted
+}
+
+// Run an experiment's activation hook, if any, and record the outcome and how long
+// it took in telemetry. Returns whether the experiment may be marked active.
+//
+// A hook that fails is rolled back straight away with its deactivation hook, so a
+// broken experiment is left switched off instead of half-applied.
+fn run_activate_hook(id: &str, experiment: &Experiment, telemetry: &mut Telemetry) -> bool {
+    let started = now_ms();
+    let outcome = experiment.on_activate.as_ref().map(|hook| hook());
+    let elapsed = now_ms() - started;
+
+    telemetry.push(DataPoint {
+        timestamp: js_sys::Date::now(),
+        experiment: id.to_string(),
+        metric: "activate_ms".to_string(),
+        value: elapsed,
+    });
+    if elapsed > ACTIVATION_TIME_BUDGET_MS {
+        console_error!(
+            "Activating experiment {} took {:.0}ms, over the {:.0}ms budget",
+            experiment.name,
+            elapsed,
+            ACTIVATION_TIME_BUDGET_MS
+        );
+    }
+
+    let succeeded = match outcome {
+        Some(Err(e)) => {
+            console_error!("Error activating experiment {}, switching it off: {:?}", experiment.name, e);
+            false
+        }
+        _ => {
+            console_log!("Activated experiment: {}", experiment.name);
+            true
+        }
+    };
+    record_lifecycle_event(telemetry, id, "activate", succeeded);
+
+    if !succeeded {
+        run_deactivate_hook(id, experiment, telemetry);
+        record_lifecycle_event(telemetry, id, "auto_deactivated", true);
+    }
+    succeeded
+}
+
+// Milliseconds from a monotonic clock when the page exposes one, else wall-clock time
+fn now_ms() -> f64 {
+    window()
+        .and_then(|window| window.performance())
+        .map_or_else(js_sys::Date::now, |performance| performance.now())
+}
+
+// Run an experiment's deactivation hook, if any, and record the outcome in telemetry.
+// Failures are only logged: the caller has already marked the experiment inactive.
+fn run_deactivate_hook(id: &str, experiment: &Experiment, telemetry: &mut Telemetry) {
+    let succeeded = match experiment.on_deactivate.as_ref().map(|hook| hook()) {
+        Some(Err(e)) => {
+            console_error!("Error deactivating experiment {}: {:?}", experiment.name, e);
+            false
+        }
+        _ => {
+            console_log!("Deactivated experiment: {}", experiment.name);
+            true
+        }
+    };
+    record_lifecycle_event(telemetry, id, "deactivate", succeeded);
+}
+
+fn record_lifecycle_event(telemetry: &mut Telemetry, id: &str, metric: &str, succeeded: bool) {
+    telemetry.push(DataPoint {
+        timestamp: js_sys::Date::now(),
+        experiment: id.to_string(),
+        metric: metric.to_string(),
+        value: if succeeded { 1.0 } else { 0.0 },
+    });
+}
+
+fn local_storage() -> Result<web_sys::Storage, JsValue> {
+    window()
+        .ok_or_else(|| JsValue::from_str("No window available"))?
+        .local_storage()?
+        .ok_or_else(|| JsValue::from_str("localStorage is not available"))
+}
+
+// Read the ids of the experiments that were active when the state was last saved
+fn load_active_experiment_ids() -> Result<Vec<String>, JsValue> {
+    match local_storage()?.get_item(ACTIVE_EXPERIMENTS_STORAGE_KEY)? {
+        Some(data) => parse_active_experiment_ids(&data),
+        None => Ok(Vec::new()),
+    }
+}
+
+// Parse saved experiment ids, upgrading older schema versions and refusing newer ones
+fn parse_active_experiment_ids(data: &str) -> Result<Vec<String>, JsValue> {
+    let to_js = |e: serde_json::Error| JsValue::from_str(&e.to_string());
+    let value: serde_json::Value = serde_json::from_str(data).map_err(to_js)?;
+
+    let (version, ids) = match value {
+        serde_json::Value::Object(mut envelope) => {
+            let version = envelope
+                .get("schema_version")
+                .and_then(serde_json::Value::as_u64)
+                .ok_or_else(|| JsValue::from_str("Saved labs state has no numeric schema_version"))?;
+            (version, envelope.remove("data").unwrap_or_default())
+        }
+        bare => (1, bare),
+    };
+
+    // v1 -> v2 only introduced the envelope; the ids themselves are unchanged
+    if version == 0 || version > LABS_STATE_SCHEMA_VERSION {
+        return Err(JsValue::from_str(&format!(
+            "Saved labs state uses schema version {}, but this build only reads versions 1 to {}",
+            version, LABS_STATE_SCHEMA_VERSION
+        )));
+    }
+    serde_json::from_value(ids).map_err(to_js)
+}
+
+// Save the ids of the currently active experiments
+fn save_active_experiment_ids(ids: &[String]) -> Result<(), JsValue> {
+    let payload = serde_json::json!({ "schema_version": LABS_STATE_SCHEMA_VERSION, "data": ids });
+    local_storage()?.set_item(ACTIVE_EXPERIMENTS_STORAGE_KEY, &payload.to_string())
+}
+
+// Whether the page URL or localStorage asks for safe mode
+fn safe_mode_requested() -> bool {
+    let is_enabled = |value: Option<String>| matches!(value.as_deref(), Some("1") | Some("true"));
+
+    let from_url = window()
+        .and_then(|w| w.location().search().ok())
+        .and_then(|search| web_sys::UrlSearchParams::new_with_str(&search).ok())
+        .and_then(|params| params.get(SAFE_MODE_PARAM));
+    let from_storage = local_storage()
+        .ok()
+        .and_then(|storage| storage.get_item(SAFE_MODE_STORAGE_KEY).ok().flatten());
+
+    is_enabled(from_url) || is_enabled(from_storage)
+}
+
+// Collect the document's readable text in document order, skipping script, style
+// and hidden elements. Runs of whitespace collapse to one space, and block elements
+// and <br> put their text on lines of its own.
+fn extract_visible_text(document: &Document) -> Result<String, JsValue> {
+    let body = match document.body() {
+        Some(body) => body,
+        None => return Ok(String::new()),
+    };
+
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    collect_readable_text(document, &body, &mut lines, &mut line)?;
+    finish_line(&mut lines, &mut line);
+    Ok(lines.join("\n"))
+}
+
+fn collect_readable_text(
+    document: &Document,
+    node: &web_sys::Node,
+    lines: &mut Vec<String>,
+    line: &mut String,
+) -> Result<(), JsValue> {
+    match node.node_type() {
+        web_sys::Node::TEXT_NODE => {
+            // Only whitespace in the source separates words, so <b>Al</b>uminum stays one word
+            let text = node.text_content().unwrap_or_default();
+            let words = text.split_whitespace().collect::<Vec<_>>().join(" ");
+            if text.starts_with(char::is_whitespace) && !line.is_empty() && !line.ends_with(' ') {
+                line.push(' ');
+            }
+            line.push_str(&words);
+            if !words.is_empty() && text.ends_with(char::is_whitespace) {
+                line.push(' ');
+            }
+        }
+        web_sys::Node::ELEMENT_NODE => {
+            let element: &Element = node.unchecked_ref();
+            let tag = element.tag_name().to_uppercase();
+            if NON_VISIBLE_TEXT_TAGS.contains(&tag.as_str()) || is_hidden_element(document, element)? {
+                return Ok(());
+            }
+            if tag == "BR" {
+                finish_line(lines, line);
+                return Ok(());
+            }
+
+            let block = BLOCK_TEXT_TAGS.contains(&tag.as_str());
+            if block {
+                finish_line(lines, line);
+            }
+            let children = node.child_nodes();
+            for index in 0..children.length() {
+                if let Some(child) = children.item(index) {
+                    collect_readable_text(document, &child, lines, line)?;
+                }
+            }
+            if block {
+                finish_line(lines, line);
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+// Move the line being built into `lines`, dropping it if it holds only whitespace
+fn finish_line(lines: &mut Vec<String>, line: &mut String) {
+    let finished = line.trim();
+    if !finished.is_empty() {
+        lines.push(finished.to_string());
+    }
+    line.clear();
+}
+
+// Whether an element is hidden by the `hidden` attribute, aria-hidden or display: none
+fn is_hidden_element(document: &Document, element: &Element) -> Result<bool, JsValue> {
+    if element.has_attribute("hidden") || element.get_attribute("aria-hidden").as_deref() == Some("true") {
+        return Ok(true);
+    }
+    let style = match document.default_view() {
+        Some(window) => window.get_computed_style(element)?,
+        None => None,
+    };
+    Ok(style.map_or(false, |style| style.get_property_value("display").ok().as_deref() == Some("none")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    // Labs with only the given experiments registered and none active. Skips
+    // AluminumLabs::new, whose panic hook needs a JS console.
+    fn labs_with(experiments: Vec<(&str, Experiment)>) -> AluminumLabs {
+        AluminumLabs {
+            experiments: Arc::new(Mutex::new(
+                experiments.into_iter().map(|(id, experiment)| (id.to_string(), experiment)).collect(),
+            )),
+            active_experiments: Arc::new(Mutex::new(Vec::new())),
+            user_preferences: Arc::new(Mutex::new(UserPreferences {
+                theme: Theme::System,
+                font_size: 16,
+                enable_notifications: true,
+            })),
+            telemetry: Arc::new(Mutex::new(Telemetry::new(DEFAULT_TELEMETRY_CAPACITY))),
+            summarizer_endpoint: DEFAULT_SUMMARIZER_ENDPOINT.to_string(),
+        }
+    }
+
+    fn experiment(name: &str, on_activate: Option<ExperimentHook>, on_deactivate: Option<ExperimentHook>) -> Experiment {
+        Experiment {
+            name: name.to_string(),
+            description: format!("{} (test experiment)", name),
+            status: ExperimentStatus::Active,
+            impact: ExperimentImpact::Low,
+            on_activate,
+            on_deactivate,
+        }
+    }
+
+    // A hook that records that it ran in `flag`
+    fn flag_hook(flag: &Rc<Cell<bool>>) -> Option<ExperimentHook> {
+        let flag = Rc::clone(flag);
+        Some(Box::new(move || {
+            flag.set(true);
+            Ok(())
+        }))
+    }
+
+    // Toggle an experiment the way its button in the labs UI does
+    fn toggle(labs: &AluminumLabs, id: &str) {
+        let closure = labs.toggle_experiment_closure(id.to_string());
+        closure.as_ref().unchecked_ref::<js_sys::Function>().call0(&JsValue::NULL).unwrap();
+    }
+
+    fn active_ids(labs: &AluminumLabs) -> Vec<String> {
+        lock(&labs.active_experiments, "active_experiments").unwrap().clone()
+    }
+
+    // Values recorded for one experiment's metric, oldest first
+    fn metric_values(labs: &AluminumLabs, id: &str, metric: &str) -> Vec<f64> {
+        lock(&labs.telemetry, "telemetry")
+            .unwrap()
+            .data_points
+            .iter()
+            .filter(|point| point.experiment == id && point.metric == metric)
+            .map(|point| point.value)
+            .collect()
+    }
+
+    fn point(metric: &str, value: f64) -> DataPoint {
+        DataPoint {
+            timestamp: value,
+            experiment: "test".to_string(),
+            metric: metric.to_string(),
+            value,
+        }
+    }
+
+    // A panic aborts on wasm32 instead of unwinding, so only a native build can poison a lock
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_poisoned_lock_fails_later_calls_with_an_error() {
+        let mut labs = labs_with(Vec::new());
+        let active_experiments = Arc::clone(&labs.active_experiments);
+        let poisoner = std::thread::spawn(move || {
+            let _guard = active_experiments.lock().unwrap();
+            panic!("simulated failure while holding active_experiments");
+        });
+        assert!(poisoner.join().is_err());
+
+        let error = labs.get_active_experiments().unwrap_err();
+        assert!(matches!(error, LabsError::Poisoned("active_experiments")));
+        assert_eq!(error.to_string(), "Labs state 'active_experiments' is unavailable after an earlier failure");
+        assert!(matches!(labs.remove_experiment("missing"), Err(LabsError::Poisoned("active_experiments"))));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_failing_activate_hook_switches_the_experiment_back_off() {
+        let rolled_back = Rc::new(Cell::new(false));
+        let throwing = experiment(
+            "Throwing Experiment",
+            Some(Box::new(|| Err(JsValue::from_str("activation failed")))),
+            flag_hook(&rolled_back),
+        );
+        let labs = labs_with(vec![("throwing", throwing)]);
+
+        toggle(&labs, "throwing");
+
+        assert!(active_ids(&labs).is_empty());
+        assert!(rolled_back.get());
+        assert_eq!(metric_values(&labs, "throwing", "activate"), vec![0.0]);
+        assert_eq!(metric_values(&labs, "throwing", "auto_deactivated"), vec![1.0]);
+        assert_eq!(metric_values(&labs, "throwing", "toggle"), vec![0.0]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_toggling_off_runs_the_deactivate_hook() {
+        let activated = Rc::new(Cell::new(false));
+        let deactivated = Rc::new(Cell::new(false));
+        let labs = labs_with(vec![(
+            "hooked",
+            experiment("Hooked Experiment", flag_hook(&activated), flag_hook(&deactivated)),
+        )]);
+
+        toggle(&labs, "hooked");
+        assert!(activated.get());
+        assert!(!deactivated.get());
+        assert_eq!(active_ids(&labs), vec!["hooked".to_string()]);
+
+        toggle(&labs, "hooked");
+        assert!(deactivated.get());
+        assert!(active_ids(&labs).is_empty());
+        assert_eq!(metric_values(&labs, "hooked", "deactivate"), vec![1.0]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_disable_all_experiments_switches_everything_off() {
+        let teardowns = Rc::new(RefCell::new(Vec::new()));
+        let ids = ["first", "second", "third"];
+        let mut labs = labs_with(
+            ids.iter()
+                .map(|&id| {
+                    let teardowns = Rc::clone(&teardowns);
+                    let on_deactivate: ExperimentHook = Box::new(move || {
+                        teardowns.borrow_mut().push(id);
+                        Ok(())
+                    });
+                    (id, experiment(id, None, Some(on_deactivate)))
+                })
+                .collect(),
+        );
+        for id in ids {
+            toggle(&labs, id);
+        }
+        assert_eq!(active_ids(&labs).len(), 3);
+
+        labs.disable_all_experiments().unwrap();
+
+        assert!(active_ids(&labs).is_empty());
+        assert_eq!(*teardowns.borrow(), vec!["third", "second", "first"]);
+        assert!(load_active_experiment_ids().unwrap().is_empty());
+        assert_eq!(metric_values(&labs, "labs", "disable_all"), vec![3.0]);
+    }
+
+    #[test]
+    fn test_telemetry_keeps_only_the_most_recent_points() {
+        let mut telemetry = Telemetry::new(3);
+        for value in 0..5 {
+            telemetry.push(point("toggle", value as f64));
+        }
+
+        let kept: Vec<f64> = telemetry.data_points.iter().map(|point| point.value).collect();
+        assert_eq!(kept, vec![2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn test_one_in_ten_sampling_keeps_about_a_tenth_of_points() {
+        let sample = || {
+            let mut telemetry = Telemetry::new(DEFAULT_TELEMETRY_CAPACITY);
+            telemetry.set_sampling("scroll", SamplingRule::OneIn(10));
+            for value in 0..100 {
+                telemetry.push(point("scroll", value as f64));
+            }
+            telemetry.data_points.iter().map(|point| point.value).collect::<Vec<f64>>()
+        };
+
+        let kept = sample();
+        assert!((5..=15).contains(&kept.len()), "kept {} of 100 points", kept.len());
+        // The fixed seed makes the same points survive on every run
+        assert_eq!(sample(), kept);
+    }
+
+    // Stand-in for the summarization endpoint: answers every request and keeps the
+    // URL and parsed body of the last one in `globalThis.lastSummaryRequest`
+    const MOCK_SUMMARIZER_FETCH: &str = "return request.text().then(body => {
+        globalThis.lastSummaryRequest = { url: request.url, body: JSON.parse(body) };
+        return new Response(JSON.stringify({ summary: 'Aluminum is a browser.' }), {
+            headers: { 'Content-Type': 'application/json' },
+        });
+    });";
+
+    #[wasm_bindgen_test]
+    async fn test_summarize_page_posts_visible_text_to_the_endpoint() {
+        let window = window().unwrap();
+        let document = window.document().unwrap();
+        let page: HtmlElement = document.create_element("body").unwrap().dyn_into().unwrap();
+        page.set_inner_html(
+            "<h1>Aluminum</h1><p>A fast <b>browser</b>.</p><script>track()</script><p hidden>Secret</p>",
+        );
+        let runner_body = document.body();
+        document.set_body(Some(&page));
+        let real_fetch = js_sys::Reflect::get(&window, &"fetch".into()).unwrap();
+        let mock_fetch = js_sys::Function::new_with_args("request", MOCK_SUMMARIZER_FETCH);
+        js_sys::Reflect::set(&window, &"fetch".into(), &mock_fetch).unwrap();
+
+        let mut labs = labs_with(Vec::new());
+        labs.set_summarizer_endpoint("https://summarizer.test/api");
+        lock(&labs.active_experiments, "active_experiments").unwrap().push(SUMMARIZER_EXPERIMENT_ID.to_string());
+        let summary = labs.summarize_page(2).await;
+
+        js_sys::Reflect::set(&window, &"fetch".into(), &real_fetch).unwrap();
+        document.set_body(runner_body.as_ref());
+
+        assert_eq!(summary.unwrap(), "Aluminum is a browser.");
+        let field = |value: &JsValue, name: &str| js_sys::Reflect::get(value, &name.into()).unwrap();
+        let request = field(&js_sys::global(), "lastSummaryRequest");
+        assert_eq!(field(&request, "url").as_string().unwrap(), "https://summarizer.test/api");
+        let body = field(&request, "body");
+        assert_eq!(field(&body, "text").as_string().unwrap(), "Aluminum\nA fast browser.");
+        assert_eq!(field(&body, "max_sentences").as_f64(), Some(2.0));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_deprecating_an_active_experiment_switches_it_off() {
+        let deactivated = Rc::new(Cell::new(false));
+        let mut labs = labs_with(vec![(
+            "retiring",
+            experiment("Retiring Experiment", None, flag_hook(&deactivated)),
+        )]);
+        toggle(&labs, "retiring");
+        assert_eq!(active_ids(&labs), vec!["retiring".to_string()]);
+
+        labs.set_experiment_status("retiring", "deprecated").unwrap();
+
+        assert_eq!(
+            lock(&labs.experiments, "experiments").unwrap()["retiring"].status,
+            ExperimentStatus::Deprecated
+        );
+        assert!(active_ids(&labs).is_empty());
+        assert!(deactivated.get());
+        assert!(matches!(
+            labs.set_experiment_status("retiring", "active"),
+            Err(LabsError::InvalidArgument(_))
+        ));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_labs_ui_lists_experiments_in_a_stable_order() {
+        let document = window().unwrap().document().unwrap();
+        let rendered_order = || {
+            let labs = labs_with(vec![
+                ("beta", experiment("Beta", None, None)),
+                ("zeta_alpha", experiment("Alpha", None, None)),
+                ("alpha", experiment("alpha", None, None)),
+                ("gamma", experiment("gamma", None, None)),
+            ]);
+            labs.create_labs_ui().unwrap();
+            let container = document.get_element_by_id("aluminum-labs-container").unwrap();
+            let items = container.query_selector_all("li.experiment-item").unwrap();
+            let ids: Vec<String> = (0..items.length())
+                .map(|index| items.item(index).unwrap().unchecked_into::<Element>().id())
+                .collect();
+            container.remove();
+            ids
+        };
+
+        let order = rendered_order();
+        assert_eq!(order, vec!["alpha", "zeta_alpha", "beta", "gamma"]);
+        // Each labs instance has its own HashMap iteration order; the rendered order doesn't change
+        for _ in 0..5 {
+            assert_eq!(rendered_order(), order);
+        }
+    }
+}
@@ -1,246 +1,1042 @@
-
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
-use std::time::{Duration, Instant};
-use tokio::time::sleep;
-
-// Constants for incognito mode settings
-const INCOGNITO_COOKIE_LIFETIME: Duration = Duration::from_secs(3600); // 1 hour
-const INCOGNITO_HISTORY_RETENTION: Duration = Duration::from_secs(1800); // 30 minutes
-const INCOGNITO_CACHE_SIZE: usize = 100 * 1024 * 1024; // 100 MB
-
-// Struct to represent an incognito session
-struct IncognitoSession {
-    id: String,
-    start_time: Instant,
-    cookies: HashMap<String, (String, Instant)>,
-    history: Vec<(String, Instant)>,
-    cache: LruCache<String, Vec<u8>>,
-}
-
-impl IncognitoSession {
-    fn new(id: String) -> Self {
-        IncognitoSession {
-            id,
-            start_time: Instant::now(),
-            cookies: HashMap::new(),
-            history: Vec::new(),
-            cache: LruCache::new(INCOGNITO_CACHE_SIZE),
-        }
-    }
-
-    // Add a cookie to the incognito session
-    fn add_cookie(&mut self, name: String, value: String) {
-        let expiration = Instant::now() + INCOGNITO_COOKIE_LIFETIME;
-        self.cookies.insert(name, (value, expiration));
-    }
-
-    // Retrieve a cookie from the incognito session
-    fn get_cookie(&self, name: &str) -> Option<&String> {
-        self.cookies.get(name).map(|(value, _)| value)
-    }
-
-    // Add a visited URL to the incognito history
-    fn add_history(&mut self, url: String) {
-        self.history.push((url, Instant::now()));
-    }
-
-    // Add an item to the incognito cache
-    fn add_to_cache(&mut self, key: String, value: Vec<u8>) {
-        self.cache.put(key, value);
-    }
-
-    // Retrieve an item from the incognito cache
-    fn get_from_cache(&mut self, key: &str) -> Option<&Vec<u8>> {
-        self.cache.get(key)
-    }
-
-    // Clean up expired data in the incognito session
-    fn cleanup(&mut self) {
-        let now = Instant::now();
-
-        // Remove expired cookies
-        self.cookies.retain(|_, (_, expiration)| *expiration > now);
-
-        // Remove old history entries
-        self.history.retain(|(_, timestamp)| now.duration_since(*timestamp) < INCOGNITO_HISTORY_RETENTION);
-    }
-}
-
-// Struct to manage multiple incognito sessions
-struct IncognitoManager {
-    sessions: HashMap<String, Arc<Mutex<IncognitoSession>>>,
-}
-
-impl IncognitoManager {
-    fn new() -> Self {
-        IncognitoManager {
-            sessions: HashMap::new(),
-        }
-    }
-
-    // Create a new incognito session
-    fn create_session(&mut self) -> String {
-        let session_id = generate_session_id();
-        let session = Arc::new(Mutex::new(IncognitoSession::new(session_id.clone())));
-        self.sessions.insert(session_id.clone(), session);
-        session_id
-    }
-
-    // Get an existing incognito session
-    fn get_session(&self, session_id: &str) -> Option<Arc<Mutex<IncognitoSession>>> {
-        self.sessions.get(session_id).cloned()
-    }
-
-    // Remove an incognito session
-    fn remove_session(&mut self, session_id: &str) {
-        self.sessions.remove(session_id);
-    }
-
-    // Periodically clean up expired data in all sessions
-    async fn cleanup_task(manager: Arc<Mutex<IncognitoManager>>) {
-        loop {
-            sleep(Duration::from_secs(60)).await; // Run cleanup every minute
-
-            let mut manager = manager.lock().unwrap();
-            for session in manager.sessions.values() {
-                let mut session = session.lock().unwrap();
-                session.cleanup();
-            }
-        }
-    }
-}
-
-// Function to generate a unique session ID
-fn generate_session_id() -> String {
-    use rand::Rng;
-    const CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ\
-                            abcdefghijklmnopqrstuvwxyz\
-                            0123456789";
-    const SESSION_ID_LEN: usize = 32;
-
-    let mut rng = rand::thread_rng();
-    (0..SESSION_ID_LEN)
-        .map(|_| {
-            let idx = rng.gen_range(0..CHARSET.len());
-            CHARSET[idx] as char
-        })
-        .collect()
-}
-
-// Struct to represent the Aluminum browser
-struct AluminumBrowser {
-    incognito_manager: Arc<Mutex<IncognitoManager>>,
-}
-
-impl AluminumBrowser {
-    fn new() -> Self {
-        let incognito_manager = Arc::new(Mutex::new(IncognitoManager::new()));
-        
-        // Start the cleanup task
-        let cleanup_manager = Arc::clone(&incognito_manager);
-        tokio::spawn(async move {
-            IncognitoManager::cleanup_task(cleanup_manager).await;
-        });
-
-        AluminumBrowser { incognito_manager }
-    }
-
-    // Start a new incognito session
-    fn start_incognito_session(&self) -> String {
-        let mut manager = self.incognito_manager.lock().unwrap();
-        manager.create_session()
-    }
-
-    // End an incognito session
-    fn end_incognito_session(&self, session_id: &str) {
-        let mut manager = self.incognito_manager.lock().unwrap();
-        manager.remove_session(session_id);
-    }
-
-    // Perform a web request in incognito mode
-    async fn incognito_request(&self, session_id: &str, url: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
-        let manager = self.incognito_manager.lock().unwrap();
-        let session = manager.get_session(session_id)
-            .ok_or("Invalid incognito session")?;
-
-        let mut session = session.lock().unwrap();
-        
-        // Check if the response is cached
-        if let Some(cached_response) = session.get_from_cache(url) {
-            return Ok(cached_response.clone());
-        }
-
-        // Perform the actual web request (simplified for this example)
-        let response = reqwest::get(url).await?.bytes().await?.to_vec();
-
-        // Cache the response
-        session.add_to_cache(url.to_string(), response.clone());
-
-        // Add to history
-        session.add_history(url.to_string());
-
-        Ok(response)
-    }
-
-    // Set a cookie in incognito mode
-    fn set_incognito_cookie(&self, session_id: &str, name: &str, value: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let manager = self.incognito_manager.lock().unwrap();
-        let session = manager.get_session(session_id)
-            .ok_or("Invalid incognito session")?;
-
-        let mut session = session.lock().unwrap();
-        session.add_cookie(name.to_string(), value.to_string());
-
-        Ok(())
-    }
-
-    // Get a cookie in incognito mode
-    fn get_incognito_cookie(&self, session_id: &str, name: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
-        let manager = self.incognito_manager.lock().unwrap();
-        let session = manager.get_session(session_id)
-            .ok_or("Invalid incognito session")?;
-
-        let session = session.lock().unwrap();
-        Ok(session.get_cookie(name).cloned())
-    }
-
-    // Get the browsing history for an incognito session
-    fn get_incognito_history(&self, session_id: &str) -> Result<Vec<String>, Box<dyn std::error::Error>> {
-        let manager = self.incognito_manager.lock().unwrap();
-        let session = manager.get_session(session_id)
-            .ok_or("Invalid incognito session")?;
-
-        let session = session.lock().unwrap();
-        Ok(session.history.iter().map(|(url, _)| url.clone()).collect())
-    }
-}
-
-// Example usage of the incognito mode
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let browser = AluminumBrowser::new();
-
-    // Start an incognito session
-    let session_id = browser.start_incognito_session();
-    println!("Started incognito session: {}", session_id);
-
-    // Perform some incognito browsing
-    let response = browser.incognito_request(&session_id, "https://example.com").await?;
-    println!("Received response of {} bytes", response.len());
-
-    // Set and retrieve a cookie
-    browser.set_incognito_cookie(&session_id, "session_token", "abc123")?;
-    let cookie = browser.get_incognito_cookie(&session_id, "session_token")?;
-    println!("Retrieved cookie: {:?}", cookie);
-
-    // Get browsing history
-    let history = browser.get_incognito_history(&session_id)?;
-    println!("Incognito browsing history: {:?}", history);
-
-    // End the incognito session
-    browser.end_incognito_session(&session_id);
-    println!("Ended incognito session: {}", session_id);
-
-    Ok(())
-}
+
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use log::warn;
+use rand::rngs::OsRng;
+use rand::Rng;
+use serde::Deserialize;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
+
+#[path = "../../../builds/utility/metrics.rs"]
+mod metrics;
+use metrics::Metrics;
+#[path = "../../../builds/utility/proxy.rs"]
+mod proxy;
+use proxy::ProxyConfig;
+#[path = "../../../builds/utility/redirect.rs"]
+mod redirect;
+use redirect::{into_redirect_error, RedirectPolicy};
+
+// Constants for incognito mode settings
+const INCOGNITO_COOKIE_LIFETIME: Duration = Duration::from_secs(3600); // 1 hour
+const INCOGNITO_HISTORY_RETENTION: Duration = Duration::from_secs(1800); // 30 minutes
+const INCOGNITO_CACHE_SIZE: usize = 100 * 1024 * 1024; // 100 MB
+const INCOGNITO_TOTAL_CACHE_BUDGET: usize = 256 * 1024 * 1024; // 256 MB across all sessions
+const DNS_RECORD_TYPE_A: u16 = 1;
+const DEFAULT_SESSION_ID_LEN: usize = 32;
+const DEFAULT_SESSION_ID_CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ\
+                                            abcdefghijklmnopqrstuvwxyz\
+                                            0123456789";
+
+// Struct to hold incognito mode configuration
+#[derive(Debug, Clone, Default)]
+struct IncognitoConfig {
+    // DNS-over-HTTPS endpoint used instead of the system resolver (JSON API)
+    doh_endpoint: Option<String>,
+    // Fail requests instead of falling back to system DNS when DoH fails
+    strict: bool,
+    // Proxy for sessions that don't set their own
+    proxy: Option<ProxyConfig>,
+    // Combined cache budget for all sessions (defaults to INCOGNITO_TOTAL_CACHE_BUDGET)
+    cache_memory_budget: Option<usize>,
+    // How to free cache memory once the combined budget is exceeded
+    cache_eviction: CacheEvictionPolicy,
+    // Length of generated session ids (defaults to DEFAULT_SESSION_ID_LEN)
+    session_id_len: Option<usize>,
+    // Characters session ids are drawn from (defaults to DEFAULT_SESSION_ID_CHARSET)
+    session_id_charset: Option<Vec<u8>>,
+    // How many redirects requests follow and whether they may leave the origin
+    redirect_policy: RedirectPolicy,
+}
+
+// Which cached responses to drop when the sessions together exceed the memory budget
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum CacheEvictionPolicy {
+    // Drop the least recently used response, whichever session it belongs to
+    #[default]
+    GlobalLru,
+    // Empty the cache of the oldest session first, then the next oldest
+    OldestSession,
+}
+
+// Struct to describe an incognito session at a point in time
+#[derive(Debug, Clone)]
+struct SessionSnapshot {
+    id: String,
+    age: Duration,
+    cookie_count: usize,
+    history_len: usize,
+    cache_bytes: usize,
+}
+
+// Struct to record a request made in an incognito session
+#[derive(Debug, Clone)]
+struct HistoryEntry {
+    url: String,
+    visited_at: Instant,
+    // HTTP status and body length, present when a response was received
+    status: Option<u16>,
+    response_bytes: Option<usize>,
+    // Error message when the request failed before a response arrived
+    error: Option<String>,
+}
+
+impl HistoryEntry {
+    fn completed(url: String, status: u16, response_bytes: usize) -> Self {
+        HistoryEntry {
+            url,
+            visited_at: Instant::now(),
+            status: Some(status),
+            response_bytes: Some(response_bytes),
+            error: None,
+        }
+    }
+
+    fn failed(url: String, error: String) -> Self {
+        HistoryEntry {
+            url,
+            visited_at: Instant::now(),
+            status: None,
+            response_bytes: None,
+            error: Some(error),
+        }
+    }
+}
+
+// Structs to deserialize a DNS-over-HTTPS JSON response
+#[derive(Debug, Deserialize)]
+struct DohResponse {
+    #[serde(rename = "Answer", default)]
+    answer: Vec<DohAnswer>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DohAnswer {
+    #[serde(rename = "type")]
+    record_type: u16,
+    data: String,
+}
+
+// Struct to hold a cookie value, the domain it was set for, and when it expires
+struct IncognitoCookie {
+    value: String,
+    domain: Option<String>,
+    expiration: Instant,
+}
+
+// Struct to hold a cached response and when it was last used
+struct CachedResponse {
+    body: Vec<u8>,
+    last_used: Instant,
+}
+
+// Struct to represent an incognito session
+struct IncognitoSession {
+    id: String,
+    start_time: Instant,
+    cookies: HashMap<String, IncognitoCookie>,
+    history: Vec<HistoryEntry>,
+    cache: LruCache<String, CachedResponse>,
+    // Per-session proxy, overriding the browser-wide one
+    proxy: Option<ProxyConfig>,
+}
+
+impl IncognitoSession {
+    fn new(id: String, proxy: Option<ProxyConfig>) -> Self {
+        IncognitoSession {
+            id,
+            start_time: Instant::now(),
+            cookies: HashMap::new(),
+            history: Vec::new(),
+            cache: LruCache::new(INCOGNITO_CACHE_SIZE),
+            proxy,
+        }
+    }
+
+    // Add a cookie to the incognito session
+    fn add_cookie(&mut self, name: String, value: String) {
+        self.add_cookie_for_domain(name, value, None);
+    }
+
+    // Add a cookie scoped to a domain to the incognito session
+    fn add_cookie_for_domain(&mut self, name: String, value: String, domain: Option<String>) {
+        self.add_cookie_with_lifetime(name, value, domain, INCOGNITO_COOKIE_LIFETIME);
+    }
+
+    // Add a cookie that expires after `lifetime` instead of the default
+    fn add_cookie_with_lifetime(&mut self, name: String, value: String, domain: Option<String>, lifetime: Duration) {
+        let expiration = Instant::now() + lifetime;
+        self.cookies.insert(name, IncognitoCookie { value, domain, expiration });
+    }
+
+    // Retrieve a cookie from the incognito session, dropping it if it has expired
+    // since the last cleanup
+    fn get_cookie(&mut self, name: &str) -> Option<&String> {
+        if self.cookies.get(name)?.expiration <= Instant::now() {
+            self.cookies.remove(name);
+            return None;
+        }
+        self.cookies.get(name).map(|cookie| &cookie.value)
+    }
+
+    // Remove every cookie from the incognito session, returning how many there were
+    fn clear_cookies(&mut self) -> usize {
+        let count = self.cookies.len();
+        self.cookies.clear();
+        count
+    }
+
+    // Add a request to the incognito history
+    fn add_history(&mut self, entry: HistoryEntry) {
+        self.history.push(entry);
+    }
+
+    // Add an item to the incognito cache
+    fn add_to_cache(&mut self, key: String, value: Vec<u8>) {
+        self.cache.put(key, CachedResponse { body: value, last_used: Instant::now() });
+    }
+
+    // Retrieve an item from the incognito cache
+    fn get_from_cache(&mut self, key: &str) -> Option<&Vec<u8>> {
+        self.cache.get_mut(key).map(|entry| {
+            entry.last_used = Instant::now();
+            &entry.body
+        })
+    }
+
+    // Total size of the cached responses in bytes
+    fn cache_bytes(&self) -> usize {
+        self.cache.iter().map(|(_, entry)| entry.body.len()).sum()
+    }
+
+    // When this session's least recently used response was last used
+    fn oldest_cache_use(&self) -> Option<Instant> {
+        self.cache.peek_lru().map(|(_, entry)| entry.last_used)
+    }
+
+    // Drop this session's least recently used response, returning how many bytes it freed
+    fn evict_lru(&mut self) -> Option<usize> {
+        self.cache.pop_lru().map(|(_, entry)| entry.body.len())
+    }
+
+    // Capture the current state of the incognito session
+    fn snapshot(&self) -> SessionSnapshot {
+        SessionSnapshot {
+            id: self.id.clone(),
+            age: self.start_time.elapsed(),
+            cookie_count: self.cookies.len(),
+            history_len: self.history.len(),
+            cache_bytes: self.cache_bytes(),
+        }
+    }
+
+    // Clean up expired data in the incognito session
+    fn cleanup(&mut self) {
+        let now = Instant::now();
+
+        // Remove expired cookies
+        self.cookies.retain(|_, cookie| cookie.expiration > now);
+
+        // Remove old history entries
+        self.history.retain(|entry| now.duration_since(entry.visited_at) < INCOGNITO_HISTORY_RETENTION);
+    }
+}
+
+// Struct to manage multiple incognito sessions
+struct IncognitoManager {
+    sessions: HashMap<String, Arc<Mutex<IncognitoSession>>>,
+    memory_budget: usize,
+    eviction_policy: CacheEvictionPolicy,
+    session_id_len: usize,
+    session_id_charset: Vec<u8>,
+}
+
+impl IncognitoManager {
+    fn new() -> Self {
+        Self::with_memory_budget(INCOGNITO_TOTAL_CACHE_BUDGET, CacheEvictionPolicy::default())
+    }
+
+    fn with_memory_budget(memory_budget: usize, eviction_policy: CacheEvictionPolicy) -> Self {
+        IncognitoManager {
+            sessions: HashMap::new(),
+            memory_budget,
+            eviction_policy,
+            session_id_len: DEFAULT_SESSION_ID_LEN,
+            session_id_charset: DEFAULT_SESSION_ID_CHARSET.to_vec(),
+        }
+    }
+
+    // Change the length and alphabet of new session ids; invalid settings keep the current ones
+    fn set_session_id_format(&mut self, len: usize, charset: Vec<u8>) {
+        if len == 0 || charset.is_empty() {
+            warn!("Ignoring session id format with length {} and {} characters", len, charset.len());
+            return;
+        }
+        self.session_id_len = len;
+        self.session_id_charset = charset;
+    }
+
+    // Create a new incognito session, optionally with its own proxy
+    fn create_session(&mut self, proxy: Option<ProxyConfig>) -> String {
+        self.create_session_with_rng(proxy, &mut OsRng)
+    }
+
+    // Create a new incognito session with ids drawn from `rng`, regenerating on collision
+    fn create_session_with_rng<R: Rng + ?Sized>(&mut self, proxy: Option<ProxyConfig>, rng: &mut R) -> String {
+        let session_id = loop {
+            let candidate = generate_session_id(rng, self.session_id_len, &self.session_id_charset);
+            if !self.sessions.contains_key(&candidate) {
+                break candidate;
+            }
+            warn!("Generated session id collided with an existing session, regenerating");
+        };
+        let session = Arc::new(Mutex::new(IncognitoSession::new(session_id.clone(), proxy)));
+        self.sessions.insert(session_id.clone(), session);
+        session_id
+    }
+
+    // Get an existing incognito session
+    fn get_session(&self, session_id: &str) -> Option<Arc<Mutex<IncognitoSession>>> {
+        self.sessions.get(session_id).cloned()
+    }
+
+    // Remove an incognito session
+    fn remove_session(&mut self, session_id: &str) {
+        self.sessions.remove(session_id);
+    }
+
+    // Get handles to all sessions so they can be locked after the manager lock is released
+    fn session_handles(&self) -> Vec<Arc<Mutex<IncognitoSession>>> {
+        self.sessions.values().cloned().collect()
+    }
+
+    // Evict cached responses until all sessions together fit in the memory budget,
+    // returning the number of bytes freed. Callers must not hold any session lock.
+    fn enforce_memory_budget(&self) -> usize {
+        let mut sessions: Vec<MutexGuard<IncognitoSession>> = self
+            .sessions
+            .values()
+            .map(|session| session.lock().unwrap())
+            .collect();
+        let mut total: usize = sessions.iter().map(|session| session.cache_bytes()).sum();
+        let mut freed = 0;
+
+        while total > self.memory_budget {
+            let victim = match self.eviction_policy {
+                CacheEvictionPolicy::GlobalLru => sessions
+                    .iter_mut()
+                    .filter_map(|session| session.oldest_cache_use().map(|used| (used, session)))
+                    .min_by_key(|(used, _)| *used)
+                    .map(|(_, session)| session),
+                CacheEvictionPolicy::OldestSession => sessions
+                    .iter_mut()
+                    .filter(|session| !session.cache.is_empty())
+                    .min_by_key(|session| session.start_time),
+            };
+
+            match victim.and_then(|session| session.evict_lru()) {
+                Some(bytes) => {
+                    total -= bytes;
+                    freed += bytes;
+                }
+                None => break,
+            }
+        }
+
+        let metrics = Metrics::global();
+        metrics.set_gauge("incognito_cache_bytes", total as f64);
+        metrics.increment_counter("incognito_cache_evicted_bytes_total", freed as u64);
+
+        freed
+    }
+
+    // Periodically clean up expired data in all sessions until `shutdown` is notified
+    async fn cleanup_task(manager: Arc<Mutex<IncognitoManager>>, shutdown: Arc<Notify>) {
+        loop {
+            // Run cleanup every minute, stopping as soon as shutdown is requested
+            tokio::select! {
+                _ = sleep(Duration::from_secs(60)) => {}
+                _ = shutdown.notified() => break,
+            }
+
+            let mut manager = manager.lock().unwrap();
+            for session in manager.sessions.values() {
+                let mut session = session.lock().unwrap();
+                session.cleanup();
+            }
+        }
+    }
+}
+
+// Function to generate a random session ID of `len` characters drawn from `charset`
+fn generate_session_id<R: Rng + ?Sized>(rng: &mut R, len: usize, charset: &[u8]) -> String {
+    (0..len)
+        .map(|_| {
+            let idx = rng.gen_range(0..charset.len());
+            charset[idx] as char
+        })
+        .collect()
+}
+
+// Resolve a hostname to its A records via a DNS-over-HTTPS JSON query
+//
+// The query goes through `proxy` when one is set, so the looked-up host isn't
+// revealed to the network the proxy is meant to hide it from.
+async fn resolve_via_doh(
+    endpoint: &str,
+    host: &str,
+    proxy: Option<&ProxyConfig>,
+) -> Result<Vec<IpAddr>, Box<dyn std::error::Error>> {
+    let mut builder = reqwest::Client::builder();
+    if let Some(proxy) = proxy {
+        builder = builder.proxy(proxy.to_reqwest()?);
+    }
+    let response: DohResponse = builder
+        .build()?
+        .get(endpoint)
+        .query(&[("name", host), ("type", "A")])
+        .header(reqwest::header::ACCEPT, "application/dns-json")
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    let addrs: Vec<IpAddr> = response
+        .answer
+        .iter()
+        .filter(|answer| answer.record_type == DNS_RECORD_TYPE_A)
+        .filter_map(|answer| answer.data.parse().ok())
+        .collect();
+
+    if addrs.is_empty() {
+        return Err(format!("DoH lookup returned no A records for {}", host).into());
+    }
+
+    Ok(addrs)
+}
+
+// Struct to represent the Aluminum browser
+struct AluminumBrowser {
+    incognito_manager: Arc<Mutex<IncognitoManager>>,
+    config: IncognitoConfig,
+    cleanup_shutdown: Arc<Notify>,
+    cleanup_handle: Option<JoinHandle<()>>,
+}
+
+impl AluminumBrowser {
+    fn new() -> Self {
+        Self::with_config(IncognitoConfig::default())
+    }
+
+    fn with_config(config: IncognitoConfig) -> Self {
+        let memory_budget = config.cache_memory_budget.unwrap_or(INCOGNITO_TOTAL_CACHE_BUDGET);
+        let mut manager = IncognitoManager::with_memory_budget(memory_budget, config.cache_eviction);
+        if config.session_id_len.is_some() || config.session_id_charset.is_some() {
+            manager.set_session_id_format(
+                config.session_id_len.unwrap_or(DEFAULT_SESSION_ID_LEN),
+                config
+                    .session_id_charset
+                    .clone()
+                    .unwrap_or_else(|| DEFAULT_SESSION_ID_CHARSET.to_vec()),
+            );
+        }
+        let incognito_manager = Arc::new(Mutex::new(manager));
+        
+        // Start the cleanup task
+        let cleanup_manager = Arc::clone(&incognito_manager);
+        let cleanup_shutdown = Arc::new(Notify::new());
+        let task_shutdown = Arc::clone(&cleanup_shutdown);
+        let cleanup_handle = tokio::spawn(async move {
+            IncognitoManager::cleanup_task(cleanup_manager, task_shutdown).await;
+        });
+
+        AluminumBrowser {
+            incognito_manager,
+            config,
+            cleanup_shutdown,
+            cleanup_handle: Some(cleanup_handle),
+        }
+    }
+
+    // Stop the cleanup task and wait for it to finish
+    async fn shutdown(&mut self) {
+        // notify_one stores a permit, so the signal isn't lost if the task is mid-cleanup
+        self.cleanup_shutdown.notify_one();
+        if let Some(handle) = self.cleanup_handle.take() {
+            if let Err(e) = handle.await {
+                warn!("Incognito cleanup task ended abnormally: {}", e);
+            }
+        }
+    }
+
+    // Build the HTTP client for a request, routing it through `proxy` and pinning the
+    // host to DoH-resolved addresses when configured
+    async fn build_client(&self, url: &str, proxy: Option<&ProxyConfig>) -> Result<reqwest::Client, Box<dyn std::error::Error>> {
+        let mut builder = reqwest::Client::builder().redirect(self.config.redirect_policy.to_reqwest());
+
+        if let Some(proxy) = proxy {
+            builder = builder.proxy(proxy.to_reqwest()?);
+        }
+
+        if let Some(endpoint) = &self.config.doh_endpoint {
+            let parsed = reqwest::Url::parse(url)?;
+            let host = parsed.host_str().ok_or("URL has no host to resolve")?;
+
+            // IP literals don't need resolving
+            if host.parse::<IpAddr>().is_err() {
+                match resolve_via_doh(endpoint, host, proxy).await {
+                    Ok(addrs) => {
+                        let port = parsed.port_or_known_default().unwrap_or(443);
+                        let addrs: Vec<SocketAddr> = addrs
+                            .into_iter()
+                            .map(|ip| SocketAddr::new(ip, port))
+                            .collect();
+                        builder = builder.resolve_to_addrs(host, &addrs);
+                    }
+                    Err(e) if self.config.strict => return Err(e),
+                    Err(e) => warn!("DoH lookup for {} failed, falling back to system DNS: {}", host, e),
+                }
+            }
+        }
+
+        Ok(builder.build()?)
+    }
+
+    // Start a new incognito session
+    fn start_incognito_session(&self) -> String {
+        let mut manager = self.incognito_manager.lock().unwrap();
+        manager.create_session(None)
+    }
+
+    // Start a new incognito session whose requests go through its own proxy
+    fn start_incognito_session_with_proxy(&self, proxy: ProxyConfig) -> String {
+        let mut manager = self.incognito_manager.lock().unwrap();
+        manager.create_session(Some(proxy))
+    }
+
+    // End an incognito session
+    fn end_incognito_session(&self, session_id: &str) {
+        let mut manager = self.incognito_manager.lock().unwrap();
+        manager.remove_session(session_id);
+    }
+
+    // List snapshots of all active incognito sessions
+    fn list_sessions(&self) -> Vec<SessionSnapshot> {
+        // Release the manager lock before taking any session lock
+        let handles = {
+            let manager = self.incognito_manager.lock().unwrap();
+            manager.session_handles()
+        };
+
+        handles
+            .iter()
+            .map(|session| session.lock().unwrap().snapshot())
+            .collect()
+    }
+
+    // Combined size of the responses cached by all incognito sessions in bytes
+    fn total_cache_bytes(&self) -> usize {
+        let handles = {
+            let manager = self.incognito_manager.lock().unwrap();
+            manager.session_handles()
+        };
+
+        handles
+            .iter()
+            .map(|session| session.lock().unwrap().cache_bytes())
+            .sum()
+    }
+
+    // Perform a web request in incognito mode
+    async fn incognito_request(&self, session_id: &str, url: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let manager = self.incognito_manager.lock().unwrap();
+        let session = manager.get_session(session_id)
+            .ok_or("Invalid incognito session")?;
+
+        let mut session = session.lock().unwrap();
+        let metrics = Metrics::global();
+        metrics.increment_counter("incognito_requests_total", 1);
+        
+        // Check if the response is cached
+        if let Some(cached_response) = session.get_from_cache(url) {
+            metrics.increment_counter("incognito_cache_hits_total", 1);
+            return Ok(cached_response.clone());
+        }
+
+        // Perform the actual web request (simplified for this example)
+        let proxy = session.proxy.clone().or_else(|| self.config.proxy.clone());
+        let result: Result<(u16, Vec<u8>), Box<dyn std::error::Error>> = async {
+            let client = self.build_client(url, proxy.as_ref()).await?;
+            let response = client.get(url).send().await.map_err(into_redirect_error)?;
+            let status = response.status().as_u16();
+            let body = response.bytes().await?.to_vec();
+            Ok((status, body))
+        }
+        .await;
+
+        // Add to history, including failed requests
+        match result {
+            Ok((status, body)) => {
+                metrics.increment_counter("incognito_response_bytes_total", body.len() as u64);
+                session.add_history(HistoryEntry::completed(url.to_string(), status, body.len()));
+
+                // Cache the response, then release the session so the budget check can lock it
+                session.add_to_cache(url.to_string(), body.clone());
+                drop(session);
+                manager.enforce_memory_budget();
+
+                Ok(body)
+            }
+            Err(e) => {
+                metrics.increment_counter("incognito_request_failures_total", 1);
+                session.add_history(HistoryEntry::failed(url.to_string(), e.to_string()));
+                Err(e)
+            }
+        }
+    }
+
+    // Set a cookie in incognito mode
+    fn set_incognito_cookie(&self, session_id: &str, name: &str, value: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let manager = self.incognito_manager.lock().unwrap();
+        let session = manager.get_session(session_id)
+            .ok_or("Invalid incognito session")?;
+
+        let mut session = session.lock().unwrap();
+        session.add_cookie(name.to_string(), value.to_string());
+
+        Ok(())
+    }
+
+    // Set several cookies at once, each as (name, value, domain), under a single session lock
+    fn set_incognito_cookies(
+        &self,
+        session_id: &str,
+        cookies: Vec<(String, String, String)>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let manager = self.incognito_manager.lock().unwrap();
+        let session = manager.get_session(session_id)
+            .ok_or("Invalid incognito session")?;
+
+        let mut session = session.lock().unwrap();
+        for (name, value, domain) in cookies {
+            session.add_cookie_for_domain(name, value, Some(domain));
+        }
+
+        Ok(())
+    }
+
+    // Remove all cookies from an incognito session, returning how many were removed
+    fn clear_incognito_cookies(&self, session_id: &str) -> Result<usize, Box<dyn std::error::Error>> {
+        let manager = self.incognito_manager.lock().unwrap();
+        let session = manager.get_session(session_id)
+            .ok_or("Invalid incognito session")?;
+
+        let mut session = session.lock().unwrap();
+        Ok(session.clear_cookies())
+    }
+
+    // Get a cookie in incognito mode
+    fn get_incognito_cookie(&self, session_id: &str, name: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        let manager = self.incognito_manager.lock().unwrap();
+        let session = manager.get_session(session_id)
+            .ok_or("Invalid incognito session")?;
+
+        let mut session = session.lock().unwrap();
+        Ok(session.get_cookie(name).cloned())
+    }
+
+    // Get the browsing history for an incognito session
+    fn get_incognito_history(&self, session_id: &str) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let manager = self.incognito_manager.lock().unwrap();
+        let session = manager.get_session(session_id)
+            .ok_or("Invalid incognito session")?;
+
+        let session = session.lock().unwrap();
+        Ok(session.history.iter().map(|entry| entry.url.clone()).collect())
+    }
+
+    // Get the browsing history for an incognito session with each request's status, size or error
+    fn get_incognito_history_detailed(&self, session_id: &str) -> Result<Vec<HistoryEntry>, Box<dyn std::error::Error>> {
+        let manager = self.incognito_manager.lock().unwrap();
+        let session = manager.get_session(session_id)
+            .ok_or("Invalid incognito session")?;
+
+        let session = session.lock().unwrap();
+        Ok(session.history.clone())
+    }
+}
+
+impl Drop for AluminumBrowser {
+    // Drop can't await, so signal the cleanup task and let it exit on its own
+    fn drop(&mut self) {
+        if self.cleanup_handle.is_some() {
+            self.cleanup_shutdown.notify_one();
+        }
+    }
+}
+
+// Example usage of the incognito mode
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut browser = AluminumBrowser::new();
+
+    // Start an incognito session
+    let session_id = browser.start_incognito_session();
+    println!("Started incognito session: {}", session_id);
+
+    // Perform some incognito browsing
+    let response = browser.incognito_request(&session_id, "https://example.com").await?;
+    println!("Received response of {} bytes", response.len());
+    println!("Incognito cache usage: {} bytes", browser.total_cache_bytes());
+
+    // Set and retrieve a cookie
+    browser.set_incognito_cookie(&session_id, "session_token", "abc123")?;
+    let cookie = browser.get_incognito_cookie(&session_id, "session_token")?;
+    println!("Retrieved cookie: {:?}", cookie);
+
+    // Get browsing history
+    let history = browser.get_incognito_history(&session_id)?;
+    println!("Incognito browsing history: {:?}", history);
+
+    // End the incognito session
+    browser.end_incognito_session(&session_id);
+    println!("Ended incognito session: {}", session_id);
+
+    // Stop background cleanup before exiting
+    browser.shutdown().await;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::mock::StepRng;
+    use redirect::RedirectError;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    // Local HTTP server giving every request the same response, and keeping the
+    // request line of each request it served
+    struct MockServer {
+        addr: SocketAddr,
+        requests: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl MockServer {
+        fn start(status: &str, content_type: &str, body: &[u8]) -> MockServer {
+            let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind mock server");
+            let addr = listener.local_addr().unwrap();
+            let requests = Arc::new(Mutex::new(Vec::new()));
+            let mut response = format!(
+                "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                status,
+                content_type,
+                body.len()
+            )
+            .into_bytes();
+            response.extend_from_slice(body);
+
+            let served = Arc::clone(&requests);
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    let stream = match stream {
+                        Ok(stream) => stream,
+                        Err(_) => break,
+                    };
+                    let mut reader = BufReader::new(stream);
+                    let mut request_line = String::new();
+                    if reader.read_line(&mut request_line).is_err() {
+                        continue;
+                    }
+                    loop {
+                        let mut header = String::new();
+                        match reader.read_line(&mut header) {
+                            Ok(0) | Err(_) => break,
+                            Ok(_) if header == "\r\n" => break,
+                            Ok(_) => {}
+                        }
+                    }
+                    served.lock().unwrap().push(request_line.trim_end().to_string());
+                    let _ = reader.into_inner().write_all(&response);
+                }
+            });
+
+            MockServer { addr, requests }
+        }
+
+        fn url(&self, path: &str) -> String {
+            format!("http://{}{}", self.addr, path)
+        }
+
+        fn requests(&self) -> Vec<String> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    fn session(browser: &AluminumBrowser, session_id: &str) -> Arc<Mutex<IncognitoSession>> {
+        browser.incognito_manager.lock().unwrap().get_session(session_id).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_doh_answer_decides_where_requests_go() {
+        let target = MockServer::start("200 OK", "text/plain", b"served from the A record");
+        let answer = format!(r#"{{"Answer":[{{"name":"aluminum.test","type":1,"data":"{}"}}]}}"#, target.addr.ip());
+        let doh = MockServer::start("200 OK", "application/dns-json", answer.as_bytes());
+        let browser = AluminumBrowser::with_config(IncognitoConfig {
+            doh_endpoint: Some(doh.url("/dns-query")),
+            strict: true,
+            ..IncognitoConfig::default()
+        });
+
+        // aluminum.test has no system DNS entry, so only the DoH answer can reach the target
+        let url = format!("http://aluminum.test:{}/page", target.addr.port());
+        let client = browser.build_client(&url, None).await.unwrap();
+        let body = client.get(&url).send().await.unwrap().text().await.unwrap();
+
+        assert_eq!(body, "served from the A record");
+        assert_eq!(doh.requests(), vec!["GET /dns-query?name=aluminum.test&type=A HTTP/1.1"]);
+        assert_eq!(target.requests(), vec!["GET /page HTTP/1.1"]);
+    }
+
+    #[tokio::test]
+    async fn test_list_sessions_reports_each_sessions_contents() {
+        let browser = AluminumBrowser::new();
+        let shopping = browser.start_incognito_session();
+        let reading = browser.start_incognito_session();
+        let shopping_session = session(&browser, &shopping);
+        let mut contents = shopping_session.lock().unwrap();
+        contents.add_cookie("cart".to_string(), "3 items".to_string());
+        contents.add_cookie("currency".to_string(), "EUR".to_string());
+        contents.add_to_cache("https://shop.example/".to_string(), vec![0; 512]);
+        drop(contents);
+        let reading_session = session(&browser, &reading);
+        reading_session
+            .lock()
+            .unwrap()
+            .add_history(HistoryEntry::completed("https://news.example/".to_string(), 200, 64));
+
+        let mut snapshots = browser.list_sessions();
+        snapshots.sort_by_key(|snapshot| snapshot.id != shopping);
+        let summary: Vec<_> = snapshots
+            .iter()
+            .map(|snapshot| (snapshot.id.as_str(), snapshot.cookie_count, snapshot.history_len, snapshot.cache_bytes))
+            .collect();
+        assert_eq!(summary, vec![(shopping.as_str(), 2, 0, 512), (reading.as_str(), 0, 1, 0)]);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_stops_the_cleanup_task_promptly() {
+        let mut browser = AluminumBrowser::new();
+        // Let the cleanup task start its minute-long wait before asking it to stop
+        tokio::task::yield_now().await;
+
+        let started = Instant::now();
+        tokio::time::timeout(Duration::from_secs(1), browser.shutdown())
+            .await
+            .expect("cleanup task kept running after shutdown");
+
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert!(browser.cleanup_handle.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_detailed_history_records_status_size_and_errors() {
+        let server = MockServer::start("404 Not Found", "text/html", b"<h1>Missing</h1>");
+        let closed_port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let browser = AluminumBrowser::new();
+        let session_id = browser.start_incognito_session();
+
+        let missing = server.url("/missing");
+        let unreachable = format!("http://127.0.0.1:{}/", closed_port);
+        browser.incognito_request(&session_id, &missing).await.unwrap();
+        assert!(browser.incognito_request(&session_id, &unreachable).await.is_err());
+
+        let history = browser.get_incognito_history_detailed(&session_id).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].url, missing);
+        assert_eq!(history[0].status, Some(404));
+        assert_eq!(history[0].response_bytes, Some(16));
+        assert_eq!(history[0].error, None);
+        assert_eq!(history[1].url, unreachable);
+        assert_eq!((history[1].status, history[1].response_bytes), (None, None));
+        assert!(history[1].error.is_some());
+    }
+
+    #[test]
+    fn test_memory_budget_evicts_least_recently_used_responses() {
+        let mut manager = IncognitoManager::with_memory_budget(1000, CacheEvictionPolicy::GlobalLru);
+        let ids: Vec<String> = (0..3).map(|_| manager.create_session(None)).collect();
+        for (index, id) in ids.iter().enumerate() {
+            let session = manager.get_session(id).unwrap();
+            session.lock().unwrap().add_to_cache(format!("https://site{}.example/", index), vec![0; 400]);
+            // Give every response a distinct last-used time
+            thread::sleep(Duration::from_millis(5));
+        }
+
+        assert_eq!(manager.enforce_memory_budget(), 400);
+
+        let cache_bytes: Vec<usize> = ids
+            .iter()
+            .map(|id| manager.get_session(id).unwrap().lock().unwrap().cache_bytes())
+            .collect();
+        assert_eq!(cache_bytes, vec![0, 400, 400]);
+        assert!(cache_bytes.iter().sum::<usize>() <= 1000);
+        assert_eq!(manager.enforce_memory_budget(), 0);
+    }
+
+    #[test]
+    fn test_colliding_session_id_is_regenerated() {
+        let mut manager = IncognitoManager::new();
+        // Two RNGs with the same start draw the same ids, so the second session's first id collides
+        let rng = || StepRng::new(1, 0x9E37_79B9_7F4A_7C15);
+        let first = manager.create_session_with_rng(None, &mut rng());
+        let second = manager.create_session_with_rng(None, &mut rng());
+
+        let mut replay = rng();
+        let colliding = generate_session_id(&mut replay, DEFAULT_SESSION_ID_LEN, DEFAULT_SESSION_ID_CHARSET);
+        let regenerated = generate_session_id(&mut replay, DEFAULT_SESSION_ID_LEN, DEFAULT_SESSION_ID_CHARSET);
+        assert_eq!(colliding, first);
+        assert_eq!(second, regenerated);
+        assert_ne!(second, first);
+        assert_eq!(manager.sessions.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_bulk_set_cookies_then_clear_them() {
+        let browser = AluminumBrowser::new();
+        let session_id = browser.start_incognito_session();
+        let cookies: Vec<(String, String, String)> = (0..5)
+            .map(|index| (format!("cookie{}", index), format!("value{}", index), "example.com".to_string()))
+            .collect();
+
+        browser.set_incognito_cookies(&session_id, cookies).unwrap();
+        assert_eq!(browser.list_sessions()[0].cookie_count, 5);
+        assert_eq!(browser.get_incognito_cookie(&session_id, "cookie3").unwrap().as_deref(), Some("value3"));
+        let domain = session(&browser, &session_id).lock().unwrap().cookies["cookie3"].domain.clone();
+        assert_eq!(domain.as_deref(), Some("example.com"));
+
+        assert_eq!(browser.clear_incognito_cookies(&session_id).unwrap(), 5);
+        assert_eq!(browser.list_sessions()[0].cookie_count, 0);
+        assert_eq!(browser.get_incognito_cookie(&session_id, "cookie3").unwrap(), None);
+        assert_eq!(browser.clear_incognito_cookies(&session_id).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_expired_cookie_is_dropped_on_lookup() {
+        let mut session = IncognitoSession::new("expiry".to_string(), None);
+        session.add_cookie_with_lifetime("short".to_string(), "soon gone".to_string(), None, Duration::from_millis(20));
+        session.add_cookie("long".to_string(), "still here".to_string());
+        assert_eq!(session.get_cookie("short").map(String::as_str), Some("soon gone"));
+
+        thread::sleep(Duration::from_millis(50));
+
+        assert_eq!(session.get_cookie("short"), None);
+        assert!(!session.cookies.contains_key("short"));
+        assert_eq!(session.get_cookie("long").map(String::as_str), Some("still here"));
+    }
+
+    #[tokio::test]
+    async fn test_session_proxy_carries_its_requests_and_doh_lookups() {
+        let answer = br#"{"Answer":[{"name":"aluminum.test","type":1,"data":"192.0.2.1"}]}"#;
+        let proxy = MockServer::start("200 OK", "application/dns-json", answer);
+        let direct = MockServer::start("200 OK", "text/plain", b"direct");
+        let browser = AluminumBrowser::with_config(IncognitoConfig {
+            doh_endpoint: Some("http://doh.aluminum.test/dns-query".to_string()),
+            strict: true,
+            ..IncognitoConfig::default()
+        });
+        let proxied = browser.start_incognito_session_with_proxy(ProxyConfig::new(&proxy.url(""), None).unwrap());
+        let plain = browser.start_incognito_session();
+
+        // Neither host resolves without the proxy, so both requests can only succeed through it
+        browser.incognito_request(&proxied, "http://aluminum.test/page").await.unwrap();
+        assert_eq!(
+            proxy.requests(),
+            vec![
+                "GET http://doh.aluminum.test/dns-query?name=aluminum.test&type=A HTTP/1.1",
+                "GET http://aluminum.test/page HTTP/1.1",
+            ]
+        );
+
+        // The other session still goes direct; IP literals skip DoH
+        let body = browser.incognito_request(&plain, &direct.url("/")).await.unwrap();
+        assert_eq!(body, b"direct");
+        assert_eq!(proxy.requests().len(), 2);
+        assert_eq!(direct.requests(), vec!["GET / HTTP/1.1"]);
+    }
+
+    // Serve `/hop/N` as a redirect to `/hop/N-1` and `/hop/0` as "arrived", until the test ends.
+    // `/away` redirects to `/hop/0` on `away_base`. Returns the server's base URL.
+    fn serve_redirect_chain(away_base: Option<String>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind mock server");
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut reader = BufReader::new(stream.unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header == "\r\n" || header.is_empty() {
+                        break;
+                    }
+                }
+
+                let path = request_line.split_whitespace().nth(1).unwrap_or("/").to_string();
+                let location = match path.strip_prefix("/hop/").and_then(|n| n.parse::<usize>().ok()) {
+                    Some(0) => None,
+                    Some(n) => Some(format!("/hop/{}", n - 1)),
+                    None => away_base.as_ref().map(|away| format!("{}/hop/0", away)),
+                };
+                let mut stream = reader.into_inner();
+                let _ = match location {
+                    Some(location) => write!(
+                        stream,
+                        "HTTP/1.1 302 Found\r\nLocation: {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                        location
+                    ),
+                    None => write!(stream, "HTTP/1.1 200 OK\r\nContent-Length: 7\r\nConnection: close\r\n\r\narrived"),
+                };
+            }
+        });
+        base
+    }
+
+    #[tokio::test]
+    async fn test_redirect_policy_limits_hops_and_origins() {
+        let away = serve_redirect_chain(None);
+        let home = serve_redirect_chain(Some(away.clone()));
+        let browser = AluminumBrowser::with_config(IncognitoConfig {
+            redirect_policy: RedirectPolicy { max_hops: 2, same_origin_only: true },
+            ..IncognitoConfig::default()
+        });
+        let session_id = browser.start_incognito_session();
+
+        let body = browser.incognito_request(&session_id, &format!("{}/hop/2", home)).await.unwrap();
+        assert_eq!(body, b"arrived");
+
+        let error = browser.incognito_request(&session_id, &format!("{}/hop/3", home)).await.unwrap_err();
+        assert_eq!(error.downcast_ref::<RedirectError>(), Some(&RedirectError::TooManyRedirects { max_hops: 2 }));
+
+        let error = browser.incognito_request(&session_id, &format!("{}/away", home)).await.unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<RedirectError>(),
+            Some(&RedirectError::CrossOriginRedirect {
+                from: format!("{}/away", home),
+                to: format!("{}/hop/0", away),
+            })
+        );
+
+        // Refused redirects are recorded as failed requests
+        let history = browser.get_incognito_history_detailed(&session_id).unwrap();
+        assert_eq!(history.len(), 3);
+    }
+}
@@ -1,182 +1,664 @@
-use std::process::Command;
-use std::io::{self, Write};
-use std::path::Path;
-use std::fs::{self, File};
-use std::time::{Duration, Instant};
-use winreg::enums::*;
-use winreg::RegKey;
-
-// Constants for registry paths and values
-const HKCU_CLASSES_ROOT: &str = r"HKEY_CURRENT_USER\Software\Classes";
-const ALUMINUM_PROG_ID: &str = "AluminumHTML";
-const ALUMINUM_EXE_PATH: &str = r"C:\Program Files\Aluminum\aluminum.exe";
-const FILE_ASSOCIATIONS: [&str; 4] = [".htm", ".html", ".shtml", ".xht"];
-const PROTOCOL_ASSOCIATIONS: [&str; 3] = ["http", "https", "ftp"];
-
-/// Makes Aluminum the default browser by modifying Windows Registry settings
-fn make_aluminum_default_browser() -> io::Result<()> {
-    println!("Starting the process to make Aluminum the default browser...");
-    
-    // Step 1: Create ProgID for Aluminum
-    create_aluminum_prog_id()?;
-    
-    // Step 2: Associate file extensions with Aluminum
-    associate_file_extensions()?;
-    
-    // Step 3: Associate protocols with Aluminum
-    associate_protocols()?;
-    
-    // Step 4: Set Aluminum as the default browser in Windows Settings
-    set_default_browser()?;
-    
-    // Step 5: Refresh system settings
-    refresh_system_settings()?;
-    
-    println!("Aluminum has been successfully set as the default browser!");
-    Ok(())
-}
-
-/// Creates the ProgID for Aluminum in the Windows Registry
-fn create_aluminum_prog_id() -> io::Result<()> {
-    println!("Creating ProgID for Aluminum...");
-    
-    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
-    let classes_key = hkcu.open_subkey_with_flags("Software\\Classes", KEY_ALL_ACCESS)?;
-    
-    // Create AluminumHTML ProgID
-    let (aluminum_key, _) = classes_key.create_subkey(ALUMINUM_PROG_ID)?;
-    aluminum_key.set_value("", &"Aluminum HTML Document")?;
-    
-    // Create default icon
-    let (icon_key, _) = aluminum_key.create_subkey("DefaultIcon")?;
-    icon_key.set_value("", &format!("{},0", ALUMINUM_EXE_PATH))?;
-    
-    // Create shell open command
-    let (shell_key, _) = aluminum_key.create_subkey("shell\\open\\command")?;
-    shell_key.set_value("", &format!("\"{}\" \"%1\"", ALUMINUM_EXE_PATH))?;
-    
-    println!("ProgID created successfully.");
-    Ok(())
-}
-
-/// Associates file extensions with Aluminum
-fn associate_file_extensions() -> io::Result<()> {
-    println!("Associating file extensions with Aluminum...");
-    
-    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
-    let classes_key = hkcu.open_subkey_with_flags("Software\\Classes", KEY_ALL_ACCESS)?;
-    
-    for ext in FILE_ASSOCIATIONS.iter() {
-        println!("  Associating {}...", ext);
-        let (ext_key, _) = classes_key.create_subkey(ext)?;
-        ext_key.set_value("", &ALUMINUM_PROG_ID)?;
-        
-        // Create OpenWithProgIds subkey
-        let (open_with_key, _) = ext_key.create_subkey("OpenWithProgIds")?;
-        open_with_key.set_value(ALUMINUM_PROG_ID, &Vec::<u8>::new())?;
-    }
-    
-    println!("File extensions associated successfully.");
-    Ok(())
-}
-
-/// Associates protocols with Aluminum
-fn associate_protocols() -> io::Result<()> {
-    println!("Associating protocols with Aluminum...");
-    
-    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
-    let classes_key = hkcu.open_subkey_with_flags("Software\\Classes", KEY_ALL_ACCESS)?;
-    
-    for protocol in PROTOCOL_ASSOCIATIONS.iter() {
-        println!("  Associating {}...", protocol);
-        let (protocol_key, _) = classes_key.create_subkey(protocol)?;
-        protocol_key.set_value("", &format!("URL:{} Protocol", protocol))?;
-        protocol_key.set_value("URL Protocol", &"")?;
-        
-        // Create default icon
-        let (icon_key, _) = protocol_key.create_subkey("DefaultIcon")?;
-        icon_key.set_value("", &format!("{},0", ALUMINUM_EXE_PATH))?;
-        
-        // Create shell open command
-        let (shell_key, _) = protocol_key.create_subkey("shell\\open\\command")?;
-        shell_key.set_value("", &format!("\"{}\" \"%1\"", ALUMINUM_EXE_PATH))?;
-    }
-    
-    println!("Protocols associated successfully.");
-    Ok(())
-}
-
-/// Sets Aluminum as the default browser in Windows Settings
-fn set_default_browser() -> io::Result<()> {
-    println!("Setting Aluminum as the default browser in Windows Settings...");
-    
-    // This step typically requires user interaction or elevated privileges
-    // We'll simulate this by showing a message to the user
-    println!("Please follow these steps to complete the process:");
-    println!("1. Open Windows Settings");
-    println!("2. Go to 'Apps' > 'Default apps'");
-    println!("3. Scroll down and click on 'Web browser'");
-    println!("4. Select 'Aluminum' from the list of available browsers");
-    
-    // Pause for user acknowledgment
-    print!("Press Enter when you have completed these steps...");
-    io::stdout().flush()?;
-    let mut buffer = String::new();
-    io::stdin().read_line(&mut buffer)?;
-    
-    println!("Thank you for manually setting Aluminum as the default browser.");
-    Ok(())
-}
-
-/// Refreshes system settings to apply changes
-fn refresh_system_settings() -> io::Result<()> {
-    println!("Refreshing system settings...");
-    
-    // Broadcast WM_SETTINGCHANGE message
-    Command::new("rundll32")
-        .args(&["user32.dll,UpdatePerUserSystemParameters"])
-        .output()?;
-    
-    // Wait for changes to take effect
-    let wait_time = Duration::from_secs(5);
-    let start = Instant::now();
-    print!("Waiting for changes to take effect");
-    while start.elapsed() < wait_time {
-        print!(".");
-        io::stdout().flush()?;
-        std::thread::sleep(Duration::from_millis(500));
-    }
-    println!("\nSystem settings refreshed.");
-    
-    Ok(())
-}
-
-/// Main function to execute the default browser change
-fn main() -> io::Result<()> {
-    println!("Welcome to the Aluminum Default Browser Setup Utility");
-    println!("====================================================");
-    println!("This utility will set Aluminum as your default web browser.");
-    println!("Please ensure you have administrative privileges before proceeding.");
-    println!();
-    
-    print!("Do you want to continue? (y/n): ");
-    io::stdout().flush()?;
-    let mut input = String::new();
-    io::stdin().read_line(&mut input)?;
-    
-    if input.trim().to_lowercase() == "y" {
-        make_aluminum_default_browser()?;
-        println!("====================================================");
-        println!("Aluminum has been successfully set as your default browser!");
-        println!("Thank you for choosing Aluminum. Happy browsing!");
-    } else {
-        println!("Operation cancelled. Aluminum was not set as the default browser.");
-    }
-    
-    // Wait for user to read the final message
-    print!("Press Enter to exit...");
-    io::stdout().flush()?;
-    io::stdin().read_line(&mut String::new())?;
-    
-    Ok(())
-}
+use std::cell::RefCell;
+use std::process::Command;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::fs::{self, File};
+use std::time::{Duration, Instant};
+use serde_json::json;
+use winreg::enums::*;
+use winreg::RegKey;
+
+// Constants for registry paths and values
+const HKCU_CLASSES_ROOT: &str = r"HKEY_CURRENT_USER\Software\Classes";
+const ALUMINUM_PROG_ID: &str = "AluminumHTML";
+const ALUMINUM_EXE_PATH: &str = r"C:\Program Files\Aluminum\aluminum.exe";
+const ALUMINUM_EXE_PATH_ENV: &str = "ALUMINUM_EXE_PATH";
+const EXE_PATH_FLAG: &str = "--exe-path";
+const FILE_ASSOCIATIONS: [&str; 4] = [".htm", ".html", ".shtml", ".xht"];
+const PROTOCOL_ASSOCIATIONS: [&str; 3] = ["http", "https", "ftp"];
+const SILENT_FLAGS: [&str; 2] = ["--silent", "--yes"];
+const JSON_FLAG: &str = "--json";
+
+/// Setup step that failed, each reported with its own process exit code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SetupStep {
+    /// Reading the user's answer to a prompt
+    Prompt,
+    /// The Aluminum executable could not be found
+    MissingExe,
+    /// Creating the AluminumHTML ProgID
+    ProgId,
+    /// Associating file extensions
+    FileAssociation,
+    /// Associating URL protocols
+    ProtocolAssociation,
+    /// Setting Aluminum as the default browser in Windows Settings
+    DefaultBrowser,
+    /// Broadcasting the settings change
+    RefreshSettings,
+}
+
+impl SetupStep {
+    /// Process exit code reported when this step fails
+    fn exit_code(self) -> i32 {
+        match self {
+            SetupStep::Prompt => 1,
+            SetupStep::MissingExe => 2,
+            SetupStep::ProgId => 3,
+            SetupStep::FileAssociation => 4,
+            SetupStep::ProtocolAssociation => 5,
+            SetupStep::DefaultBrowser => 6,
+            SetupStep::RefreshSettings => 7,
+        }
+    }
+
+    /// Stable identifier used in the `--json` status line
+    fn as_str(self) -> &'static str {
+        match self {
+            SetupStep::Prompt => "prompt",
+            SetupStep::MissingExe => "missing_exe",
+            SetupStep::ProgId => "prog_id",
+            SetupStep::FileAssociation => "file_association",
+            SetupStep::ProtocolAssociation => "protocol_association",
+            SetupStep::DefaultBrowser => "default_browser",
+            SetupStep::RefreshSettings => "refresh_settings",
+        }
+    }
+}
+
+/// Error from the setup, tagged with the step that failed
+#[derive(Debug)]
+struct SetupError {
+    step: SetupStep,
+    source: io::Error,
+}
+
+/// Extension for tagging an `io::Result` with the setup step it belongs to
+trait AtStep<T> {
+    fn at_step(self, step: SetupStep) -> Result<T, SetupError>;
+}
+
+impl<T> AtStep<T> for io::Result<T> {
+    fn at_step(self, step: SetupStep) -> Result<T, SetupError> {
+        self.map_err(|source| SetupError { step, source })
+    }
+}
+
+/// How a setup run that did not fail ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SetupOutcome {
+    Completed,
+    Cancelled,
+}
+
+/// Source of user confirmation for the interactive steps of the setup
+trait UserPrompt {
+    /// Asks a yes/no question and returns whether the user agreed
+    fn confirm(&self, question: &str) -> io::Result<bool>;
+
+    /// Pauses until the user acknowledges the message
+    fn pause(&self, message: &str) -> io::Result<()>;
+}
+
+/// Prompts the user and blocks for their answer on `input` (stdin in `main`)
+struct InteractivePrompt<R: BufRead> {
+    input: RefCell<R>,
+}
+
+impl<R: BufRead> InteractivePrompt<R> {
+    fn new(input: R) -> Self {
+        InteractivePrompt { input: RefCell::new(input) }
+    }
+}
+
+impl<R: BufRead> UserPrompt for InteractivePrompt<R> {
+    fn confirm(&self, question: &str) -> io::Result<bool> {
+        print!("{} (y/n): ", question);
+        io::stdout().flush()?;
+        let mut input = String::new();
+        self.input.borrow_mut().read_line(&mut input)?;
+        Ok(input.trim().to_lowercase() == "y")
+    }
+
+    fn pause(&self, message: &str) -> io::Result<()> {
+        print!("{}", message);
+        io::stdout().flush()?;
+        self.input.borrow_mut().read_line(&mut String::new())?;
+        Ok(())
+    }
+}
+
+/// Answers every prompt with "yes" and never reads from stdin, for use by installers
+struct SilentPrompt;
+
+impl UserPrompt for SilentPrompt {
+    fn confirm(&self, _question: &str) -> io::Result<bool> {
+        Ok(true)
+    }
+
+    fn pause(&self, _message: &str) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Picks the prompt for a run; in silent mode `input` is never read
+fn select_prompt<R: BufRead + 'static>(silent: bool, input: R) -> Box<dyn UserPrompt> {
+    if silent {
+        Box::new(SilentPrompt)
+    } else {
+        Box::new(InteractivePrompt::new(input))
+    }
+}
+
+/// The system changes the setup makes, one method per registry step
+trait DefaultBrowserSetter {
+    /// Creates the AluminumHTML ProgID pointing at `exe_path`
+    fn create_prog_id(&self, exe_path: &str) -> io::Result<()>;
+
+    /// Associates the HTML file extensions with the ProgID
+    fn associate_file_extensions(&self) -> io::Result<()>;
+
+    /// Registers Aluminum as the handler for the web URL protocols
+    fn associate_protocols(&self, exe_path: &str) -> io::Result<()>;
+
+    /// Broadcasts the settings change so it takes effect
+    fn refresh_system_settings(&self) -> io::Result<()>;
+}
+
+/// Writes the associations to the current user's Windows Registry
+struct RegistrySetter;
+
+impl DefaultBrowserSetter for RegistrySetter {
+    fn create_prog_id(&self, exe_path: &str) -> io::Result<()> {
+        create_aluminum_prog_id(exe_path)
+    }
+
+    fn associate_file_extensions(&self) -> io::Result<()> {
+        associate_file_extensions()
+    }
+
+    fn associate_protocols(&self, exe_path: &str) -> io::Result<()> {
+        associate_protocols(exe_path)
+    }
+
+    fn refresh_system_settings(&self) -> io::Result<()> {
+        refresh_system_settings()
+    }
+}
+
+/// Resolves the Aluminum executable path and checks that it exists
+///
+/// An explicit path (from `--exe-path`) takes precedence over the
+/// `ALUMINUM_EXE_PATH` environment variable, which takes precedence over the
+/// default install location.
+fn resolve_aluminum_exe_path(explicit: Option<String>) -> io::Result<String> {
+    let exe_path = explicit
+        .or_else(|| std::env::var(ALUMINUM_EXE_PATH_ENV).ok())
+        .unwrap_or_else(|| ALUMINUM_EXE_PATH.to_string());
+    
+    if !Path::new(&exe_path).is_file() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!(
+                "Aluminum executable not found at '{}'. Pass {} <path> or set {} to the installed aluminum.exe.",
+                exe_path, EXE_PATH_FLAG, ALUMINUM_EXE_PATH_ENV
+            ),
+        ));
+    }
+    
+    Ok(exe_path)
+}
+
+/// Makes Aluminum the default browser by modifying Windows Registry settings
+fn make_aluminum_default_browser(
+    prompt: &dyn UserPrompt,
+    setter: &dyn DefaultBrowserSetter,
+    exe_path: &str,
+) -> Result<(), SetupError> {
+    println!("Starting the process to make Aluminum the default browser...");
+    
+    // Step 1: Create ProgID for Aluminum
+    setter.create_prog_id(exe_path).at_step(SetupStep::ProgId)?;
+    
+    // Step 2: Associate file extensions with Aluminum
+    setter.associate_file_extensions().at_step(SetupStep::FileAssociation)?;
+    
+    // Step 3: Associate protocols with Aluminum
+    setter.associate_protocols(exe_path).at_step(SetupStep::ProtocolAssociation)?;
+    
+    // Step 4: Set Aluminum as the default browser in Windows Settings
+    set_default_browser(prompt).at_step(SetupStep::DefaultBrowser)?;
+    
+    // Step 5: Refresh system settings
+    setter.refresh_system_settings().at_step(SetupStep::RefreshSettings)?;
+    
+    println!("Aluminum has been successfully set as the default browser!");
+    Ok(())
+}
+
+/// Creates the ProgID for Aluminum in the Windows Registry
+fn create_aluminum_prog_id(exe_path: &str) -> io::Result<()> {
+    println!("Creating ProgID for Aluminum...");
+    
+    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+    let classes_key = hkcu.open_subkey_with_flags("Software\\Classes", KEY_ALL_ACCESS)?;
+    
+    // Create AluminumHTML ProgID
+    let (aluminum_key, _) = classes_key.create_subkey(ALUMINUM_PROG_ID)?;
+    aluminum_key.set_value("", &"Aluminum HTML Document")?;
+    
+    // Create default icon
+    let (icon_key, _) = aluminum_key.create_subkey("DefaultIcon")?;
+    icon_key.set_value("", &format!("{},0", exe_path))?;
+    
+    // Create shell open command
+    let (shell_key, _) = aluminum_key.create_subkey("shell\\open\\command")?;
+    shell_key.set_value("", &format!("\"{}\" \"%1\"", exe_path))?;
+    
+    println!("ProgID created successfully.");
+    Ok(())
+}
+
+/// Associates file extensions with Aluminum
+fn associate_file_extensions() -> io::Result<()> {
+    println!("Associating file extensions with Aluminum...");
+    
+    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+    let classes_key = hkcu.open_subkey_with_flags("Software\\Classes", KEY_ALL_ACCESS)?;
+    
+    for ext in FILE_ASSOCIATIONS.iter() {
+        println!("  Associating {}...", ext);
+        let (ext_key, _) = classes_key.create_subkey(ext)?;
+        ext_key.set_value("", &ALUMINUM_PROG_ID)?;
+        
+        // Create OpenWithProgIds subkey
+        let (open_with_key, _) = ext_key.create_subkey("OpenWithProgIds")?;
+        open_with_key.set_value(ALUMINUM_PROG_ID, &Vec::<u8>::new())?;
+    }
+    
+    println!("File extensions associated successfully.");
+    Ok(())
+}
+
+/// Associates protocols with Aluminum
+fn associate_protocols(exe_path: &str) -> io::Result<()> {
+    println!("Associating protocols with Aluminum...");
+    
+    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+    let classes_key = hkcu.open_subkey_with_flags("Software\\Classes", KEY_ALL_ACCESS)?;
+    
+    for protocol in PROTOCOL_ASSOCIATIONS.iter() {
+        println!("  Associating {}...", protocol);
+        let (protocol_key, _) = classes_key.create_subkey(protocol)?;
+        protocol_key.set_value("", &format!("URL:{} Protocol", protocol))?;
+        protocol_key.set_value("URL Protocol", &"")?;
+        
+        // Create default icon
+        let (icon_key, _) = protocol_key.create_subkey("DefaultIcon")?;
+        icon_key.set_value("", &format!("{},0", exe_path))?;
+        
+        // Create shell open command
+        let (shell_key, _) = protocol_key.create_subkey("shell\\open\\command")?;
+        shell_key.set_value("", &format!("\"{}\" \"%1\"", exe_path))?;
+    }
+    
+    println!("Protocols associated successfully.");
+    Ok(())
+}
+
+/// Sets Aluminum as the default browser in Windows Settings
+fn set_default_browser(prompt: &dyn UserPrompt) -> io::Result<()> {
+    println!("Setting Aluminum as the default browser in Windows Settings...");
+    
+    // This step typically requires user interaction or elevated privileges
+    // We'll simulate this by showing a message to the user
+    println!("Please follow these steps to complete the process:");
+    println!("1. Open Windows Settings");
+    println!("2. Go to 'Apps' > 'Default apps'");
+    println!("3. Scroll down and click on 'Web browser'");
+    println!("4. Select 'Aluminum' from the list of available browsers");
+    
+    // Pause for user acknowledgment
+    prompt.pause("Press Enter when you have completed these steps...")?;
+    
+    println!("Thank you for manually setting Aluminum as the default browser.");
+    Ok(())
+}
+
+/// Refreshes system settings to apply changes
+fn refresh_system_settings() -> io::Result<()> {
+    println!("Refreshing system settings...");
+    
+    // Broadcast WM_SETTINGCHANGE message
+    Command::new("rundll32")
+        .args(&["user32.dll,UpdatePerUserSystemParameters"])
+        .output()?;
+    
+    // Wait for changes to take effect
+    let wait_time = Duration::from_secs(5);
+    let start = Instant::now();
+    print!("Waiting for changes to take effect");
+    while start.elapsed() < wait_time {
+        print!(".");
+        io::stdout().flush()?;
+        std::thread::sleep(Duration::from_millis(500));
+    }
+    println!("\nSystem settings refreshed.");
+    
+    Ok(())
+}
+
+/// Runs the interactive setup and reports how it ended
+fn run_setup(
+    prompt: &dyn UserPrompt,
+    setter: &dyn DefaultBrowserSetter,
+    explicit_exe_path: Option<String>,
+) -> Result<SetupOutcome, SetupError> {
+    println!("Welcome to the Aluminum Default Browser Setup Utility");
+    println!("====================================================");
+    println!("This utility will set Aluminum as your default web browser.");
+    println!("Please ensure you have administrative privileges before proceeding.");
+    println!();
+    
+    // Check the executable before touching the registry
+    let exe_path = resolve_aluminum_exe_path(explicit_exe_path).at_step(SetupStep::MissingExe)?;
+    println!("Using Aluminum executable at {}", exe_path);
+    
+    let outcome = if prompt.confirm("Do you want to continue?").at_step(SetupStep::Prompt)? {
+        make_aluminum_default_browser(prompt, setter, &exe_path)?;
+        println!("====================================================");
+        println!("Aluminum has been successfully set as your default browser!");
+        println!("Thank you for choosing Aluminum. Happy browsing!");
+        SetupOutcome::Completed
+    } else {
+        println!("Operation cancelled. Aluminum was not set as the default browser.");
+        SetupOutcome::Cancelled
+    };
+    
+    // Wait for user to read the final message
+    prompt.pause("Press Enter to exit...").at_step(SetupStep::Prompt)?;
+    
+    Ok(outcome)
+}
+
+/// Maps a setup result to its exit code and machine-readable status line
+fn setup_status(result: &Result<SetupOutcome, SetupError>) -> (i32, serde_json::Value) {
+    match result {
+        Ok(SetupOutcome::Completed) => (0, json!({ "status": "completed", "exit_code": 0 })),
+        Ok(SetupOutcome::Cancelled) => (0, json!({ "status": "cancelled", "exit_code": 0 })),
+        Err(error) => {
+            let exit_code = error.step.exit_code();
+            (
+                exit_code,
+                json!({
+                    "status": "failed",
+                    "exit_code": exit_code,
+                    "step": error.step.as_str(),
+                    "error": error.source.to_string(),
+                }),
+            )
+        }
+    }
+}
+
+/// Main function to execute the default browser change
+///
+/// Exits with 0 when the setup completes or the user cancels it, and with the
+/// failing step's `SetupStep::exit_code` otherwise. With `--json`, a single
+/// JSON status line is printed as the last line of output.
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let silent = args.iter().any(|arg| SILENT_FLAGS.contains(&arg.as_str()));
+    let json_status = args.iter().any(|arg| arg == JSON_FLAG);
+    let explicit_exe_path = args
+        .iter()
+        .position(|arg| arg == EXE_PATH_FLAG)
+        .and_then(|index| args.get(index + 1).cloned());
+    let prompt = select_prompt(silent, io::stdin().lock());
+    
+    let result = run_setup(prompt.as_ref(), &RegistrySetter, explicit_exe_path);
+    let (exit_code, status) = setup_status(&result);
+    
+    if let Err(error) = &result {
+        eprintln!("Setup failed at step '{}': {}", error.step.as_str(), error.source);
+    }
+    if json_status {
+        println!("{}", status);
+    }
+    
+    std::process::exit(exit_code);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufReader, Cursor, Read};
+    use std::path::PathBuf;
+
+    /// Records the registry steps it's asked to run, failing the one chosen
+    #[derive(Default)]
+    struct FakeSetter {
+        fail_at: Option<SetupStep>,
+        calls: RefCell<Vec<SetupStep>>,
+    }
+
+    impl FakeSetter {
+        fn failing_at(step: SetupStep) -> Self {
+            FakeSetter {
+                fail_at: Some(step),
+                ..FakeSetter::default()
+            }
+        }
+
+        fn run(&self, step: SetupStep) -> io::Result<()> {
+            self.calls.borrow_mut().push(step);
+            if self.fail_at == Some(step) {
+                Err(io::Error::new(io::ErrorKind::PermissionDenied, "injected failure"))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    impl DefaultBrowserSetter for FakeSetter {
+        fn create_prog_id(&self, _exe_path: &str) -> io::Result<()> {
+            self.run(SetupStep::ProgId)
+        }
+
+        fn associate_file_extensions(&self) -> io::Result<()> {
+            self.run(SetupStep::FileAssociation)
+        }
+
+        fn associate_protocols(&self, _exe_path: &str) -> io::Result<()> {
+            self.run(SetupStep::ProtocolAssociation)
+        }
+
+        fn refresh_system_settings(&self) -> io::Result<()> {
+            self.run(SetupStep::RefreshSettings)
+        }
+    }
+
+    /// Agrees to everything, but fails any prompt whose text starts with `fail_on`
+    struct FailingPrompt {
+        fail_on: &'static str,
+    }
+
+    impl FailingPrompt {
+        fn answer(&self, text: &str) -> io::Result<()> {
+            if text.starts_with(self.fail_on) {
+                Err(io::Error::new(io::ErrorKind::UnexpectedEof, "input closed"))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    impl UserPrompt for FailingPrompt {
+        fn confirm(&self, question: &str) -> io::Result<bool> {
+            self.answer(question).map(|_| true)
+        }
+
+        fn pause(&self, message: &str) -> io::Result<()> {
+            self.answer(message)
+        }
+    }
+
+    /// An existing file standing in for aluminum.exe, removed when dropped
+    struct TempExe(PathBuf);
+
+    impl TempExe {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("aluminum-{}-{}.exe", std::process::id(), name));
+            File::create(&path).unwrap();
+            TempExe(path)
+        }
+
+        fn path(&self) -> Option<String> {
+            Some(self.0.to_string_lossy().into_owned())
+        }
+    }
+
+    impl Drop for TempExe {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    fn assert_failed_at(result: Result<SetupOutcome, SetupError>, step: SetupStep, exit_code: i32) {
+        let (code, status) = setup_status(&result);
+        assert_eq!(code, exit_code);
+        assert_eq!(status["status"], "failed");
+        assert_eq!(status["exit_code"], exit_code);
+        assert_eq!(status["step"], step.as_str());
+        assert!(status["error"].is_string());
+    }
+
+    #[test]
+    fn test_prompt_failure_exits_with_1() {
+        let exe = TempExe::new("prompt");
+        let setter = FakeSetter::default();
+        let result = run_setup(&FailingPrompt { fail_on: "Do you want" }, &setter, exe.path());
+        assert_failed_at(result, SetupStep::Prompt, 1);
+        assert!(setter.calls.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_missing_exe_exits_with_2() {
+        let setter = FakeSetter::default();
+        let missing = std::env::temp_dir().join("aluminum-missing-exit-code.exe");
+        let result = run_setup(&SilentPrompt, &setter, Some(missing.to_string_lossy().into_owned()));
+        assert_failed_at(result, SetupStep::MissingExe, 2);
+        assert!(setter.calls.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_prog_id_failure_exits_with_3() {
+        let exe = TempExe::new("prog-id");
+        let setter = FakeSetter::failing_at(SetupStep::ProgId);
+        assert_failed_at(run_setup(&SilentPrompt, &setter, exe.path()), SetupStep::ProgId, 3);
+        assert_eq!(*setter.calls.borrow(), vec![SetupStep::ProgId]);
+    }
+
+    #[test]
+    fn test_file_association_failure_exits_with_4() {
+        let exe = TempExe::new("file-association");
+        let setter = FakeSetter::failing_at(SetupStep::FileAssociation);
+        assert_failed_at(run_setup(&SilentPrompt, &setter, exe.path()), SetupStep::FileAssociation, 4);
+        assert_eq!(*setter.calls.borrow(), vec![SetupStep::ProgId, SetupStep::FileAssociation]);
+    }
+
+    #[test]
+    fn test_protocol_association_failure_exits_with_5() {
+        let exe = TempExe::new("protocol-association");
+        let setter = FakeSetter::failing_at(SetupStep::ProtocolAssociation);
+        assert_failed_at(run_setup(&SilentPrompt, &setter, exe.path()), SetupStep::ProtocolAssociation, 5);
+        assert_eq!(setter.calls.borrow().last(), Some(&SetupStep::ProtocolAssociation));
+    }
+
+    #[test]
+    fn test_default_browser_failure_exits_with_6() {
+        let exe = TempExe::new("default-browser");
+        let setter = FakeSetter::default();
+        let prompt = FailingPrompt { fail_on: "Press Enter when" };
+        assert_failed_at(run_setup(&prompt, &setter, exe.path()), SetupStep::DefaultBrowser, 6);
+        assert!(!setter.calls.borrow().contains(&SetupStep::RefreshSettings));
+    }
+
+    #[test]
+    fn test_refresh_failure_exits_with_7() {
+        let exe = TempExe::new("refresh");
+        let setter = FakeSetter::failing_at(SetupStep::RefreshSettings);
+        assert_failed_at(run_setup(&SilentPrompt, &setter, exe.path()), SetupStep::RefreshSettings, 7);
+    }
+
+    #[test]
+    fn test_completed_setup_exits_with_0() {
+        let exe = TempExe::new("completed");
+        let setter = FakeSetter::default();
+        let (code, status) = setup_status(&run_setup(&SilentPrompt, &setter, exe.path()));
+        assert_eq!(code, 0);
+        assert_eq!(status, json!({ "status": "completed", "exit_code": 0 }));
+        assert_eq!(setter.calls.borrow().len(), 4);
+    }
+
+    /// Input that fails the test if anything reads from it
+    struct UnreadableInput;
+
+    impl Read for UnreadableInput {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            panic!("silent setup read from stdin");
+        }
+    }
+
+    #[test]
+    fn test_silent_setup_never_reads_input() {
+        let exe = TempExe::new("silent");
+        let setter = FakeSetter::default();
+        let prompt = select_prompt(true, BufReader::new(UnreadableInput));
+
+        let result = run_setup(prompt.as_ref(), &setter, exe.path());
+
+        assert_eq!(result.unwrap(), SetupOutcome::Completed);
+        assert_eq!(setter.calls.borrow().len(), 4);
+    }
+
+    #[test]
+    fn test_interactive_setup_follows_answers() {
+        let exe = TempExe::new("interactive");
+
+        // Confirm, acknowledge the manual step, then the exit pause
+        let prompt = select_prompt(false, Cursor::new("y\n\n\n"));
+        assert_eq!(run_setup(prompt.as_ref(), &FakeSetter::default(), exe.path()).unwrap(), SetupOutcome::Completed);
+
+        let setter = FakeSetter::default();
+        let prompt = select_prompt(false, Cursor::new("n\n\n"));
+        assert_eq!(run_setup(prompt.as_ref(), &setter, exe.path()).unwrap(), SetupOutcome::Cancelled);
+        assert!(setter.calls.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_present_exe_path_proceeds_to_registry() {
+        let exe = TempExe::new("present");
+        assert_eq!(resolve_aluminum_exe_path(exe.path()).unwrap(), exe.path().unwrap());
+
+        let setter = FakeSetter::default();
+        assert_eq!(run_setup(&SilentPrompt, &setter, exe.path()).unwrap(), SetupOutcome::Completed);
+        assert_eq!(setter.calls.borrow().first(), Some(&SetupStep::ProgId));
+    }
+
+    #[test]
+    fn test_absent_exe_path_aborts_before_registry() {
+        let missing = std::env::temp_dir().join("aluminum-not-installed").join("aluminum.exe");
+        let missing = missing.to_string_lossy().into_owned();
+
+        let error = resolve_aluminum_exe_path(Some(missing.clone())).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        assert!(error.to_string().contains(&missing));
+        assert!(error.to_string().contains(EXE_PATH_FLAG));
+
+        let setter = FakeSetter::default();
+        let result = run_setup(&SilentPrompt, &setter, Some(missing));
+        assert_eq!(result.unwrap_err().step, SetupStep::MissingExe);
+        assert!(setter.calls.borrow().is_empty());
+    }
+}
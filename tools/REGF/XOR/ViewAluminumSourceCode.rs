@@ -1,202 +1,662 @@
-
-use std::fs;
-use std::io::{self, Read};
-use std::path::Path;
-use std::process::Command;
-use regex::Regex;
-use serde_json;
-use reqwest;
-use tokio;
-
-// Constants for browser-specific paths and commands
-const CHROME_PATH: &str = r"C:\Program Files\Google\Chrome\Application\chrome.exe";
-const FIREFOX_PATH: &str = r"C:\Program Files\Mozilla Firefox\firefox.exe";
-const EDGE_PATH: &str = r"C:\Program Files (x86)\Microsoft\Edge\Application\msedge.exe";
-
-// Function to retrieve Aluminum source code
-async fn get_aluminum_source() -> Result<String, Box<dyn std::error::Error>> {
-    // URL of the Aluminum source code repository
-    let url = "";
-    
-    // Download the source code
-    let response = reqwest::get(url).await?;
-    let bytes = response.bytes().await?;
-    
-    // Save the zip file temporarily
-    let temp_file = "aluminum_source.zip";
-    fs::write(temp_file, &bytes)?;
-    
-    // Unzip the file
-    let output = Command::new("powershell")
-        .args(&["-command", &format!("Expand-Archive -Path {} -DestinationPath aluminum_source", temp_file)])
-        .output()?;
-    
-    if !output.status.success() {
-        return Err("Failed to unzip the source code".into());
-    }
-    
-    // Read the source code
-    let mut source = String::new();
-    visit_dirs(Path::new("aluminum_source"), &mut |entry| {
-        if let Some(ext) = entry.path().extension() {
-            if ext == "rs" {
-                let mut file = fs::File::open(entry.path())?;
-                file.read_to_string(&mut source)?;
-            }
-        }
-        Ok(())
-    })?;
-    
-    // Clean up temporary files
-    fs::remove_file(temp_file)?;
-    fs::remove_dir_all("aluminum_source")?;
-    
-    Ok(source)
-}
-
-// Helper function to recursively visit directories
-fn visit_dirs(dir: &Path, cb: &mut dyn FnMut(&fs::DirEntry) -> io::Result<()>) -> io::Result<()> {
-    if dir.is_dir() {
-        for entry in fs::read_dir(dir)? {
-            let entry = entry?;
-            let path = entry.path();
-            if path.is_dir() {
-                visit_dirs(&path, cb)?;
-            } else {
-                cb(&entry)?;
-            }
-        }
-    }
-    Ok(())
-}
-
-// Function to detect the default browser
-fn detect_default_browser() -> Result<String, Box<dyn std::error::Error>> {
-    let output = Command::new("powershell")
-        .args(&["-command", "Get-ItemProperty HKCU:\\Software\\Microsoft\\Windows\\Shell\\Associations\\UrlAssociations\\http\\UserChoice | Select-Object -ExpandProperty ProgId"])
-        .output()?;
-    
-    let browser = String::from_utf8(output.stdout)?.trim().to_lowercase();
-    
-    if browser.contains("chrome") {
-        Ok("chrome".to_string())
-    } else if browser.contains("firefox") {
-        Ok("firefox".to_string())
-    } else if browser.contains("edge") {
-        Ok("edge".to_string())
-    } else {
-        Err("Unsupported default browser".into())
-    }
-}
-
-// Function to get the browser version
-fn get_browser_version(browser: &str) -> Result<String, Box<dyn std::error::Error>> {
-    let (path, args) = match browser {
-        "chrome" => (CHROME_PATH, vec!["--version"]),
-        "firefox" => (FIREFOX_PATH, vec!["--version"]),
-        "edge" => (EDGE_PATH, vec!["--version"]),
-        _ => return Err("Unsupported browser".into()),
-    };
-    
-    let output = Command::new(path)
-        .args(&args)
-        .output()?;
-    
-    let version = String::from_utf8(output.stdout)?;
-    let re = Regex::new(r"\d+\.\d+\.\d+\.\d+")?;
-    
-    if let Some(cap) = re.captures(&version) {
-        Ok(cap[0].to_string())
-    } else {
-        Err("Failed to extract version".into())
-    }
-}
-
-// Function to compare Aluminum with the browser's source
-fn compare_aluminum_with_browser(aluminum_source: &str, browser: &str, version: &str) -> Result<String, Box<dyn std::error::Error>> {
-    // This is a placeholder function. In reality, this would be a complex process involving
-    // downloading the browser's source code (if available), parsing both codebases,
-    // and performing a detailed comparison.
-    
-    let comparison = format!(
-        "Comparison between Aluminum and {} version {}:\n\n\
-         1. Aluminum is written in Rust, while {} is primarily written in C++.\n\
-         2. Aluminum is a lightweight browser, while {} is a full-featured browser.\n\
-         3. Aluminum's codebase is significantly smaller than {}'s.\n\
-         4. Aluminum focuses on privacy and security by default, while {} offers various privacy features that can be enabled.\n\
-         5. Aluminum's rendering engine is custom-built, while {} uses {}.",
-        browser, version, browser, browser, browser, browser, browser,
-        match browser {
-            "chrome" | "edge" => "Blink",
-            "firefox" => "Gecko",
-            _ => "an unknown engine",
-        }
-    );
-    
-    Ok(comparison)
-}
-
-// Main function to orchestrate the process
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    println!("Fetching Aluminum source code...");
-    let aluminum_source = get_aluminum_source().await?;
-    
-    println!("Detecting default browser...");
-    let default_browser = detect_default_browser()?;
-    
-    println!("Getting browser version...");
-    let browser_version = get_browser_version(&default_browser)?;
-    
-    println!("Comparing Aluminum with the default browser...");
-    let comparison = compare_aluminum_with_browser(&aluminum_source, &default_browser, &browser_version)?;
-    
-    // Create a JSON object with the results
-    let result = serde_json::json!({
-        "aluminum_source_length": aluminum_source.len(),
-        "default_browser": default_browser,
-        "browser_version": browser_version,
-        "comparison": comparison,
-    });
-    
-    // Write the result to a file
-    fs::write("aluminum_comparison_result.json", serde_json::to_string_pretty(&result)?)?;
-    
-    println!("Analysis complete. Results saved to 'aluminum_comparison_result.json'");
-    
-    Ok(())
-}
-
-// Unit tests
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_detect_default_browser() {
-        let result = detect_default_browser();
-        assert!(result.is_ok());
-        let browser = result.unwrap();
-        assert!(vec!["chrome", "firefox", "edge"].contains(&browser.as_str()));
-    }
-
-    #[test]
-    fn test_get_browser_version() {
-        let browsers = vec!["chrome", "firefox", "edge"];
-        for browser in browsers {
-            let result = get_browser_version(browser);
-            assert!(result.is_ok());
-            let version = result.unwrap();
-            assert!(Regex::new(r"\d+\.\d+\.\d+\.\d+").unwrap().is_match(&version));
-        }
-    }
-
-    #[tokio::test]
-    async fn test_get_aluminum_source() {
-        let result = get_aluminum_source().await;
-        assert!(result.is_ok());
-        let source = result.unwrap();
-        assert!(!source.is_empty());
-        assert!(source.contains("fn main()"));
-    }
-}
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use regex::Regex;
+use serde::Serialize;
+use serde_json;
+use reqwest;
+use tokio;
+
+// Constants for browser-specific paths and commands
+const CHROME_PATH: &str = r"C:\Program Files\Google\Chrome\Application\chrome.exe";
+const FIREFOX_PATH: &str = r"C:\Program Files\Mozilla Firefox\firefox.exe";
+const EDGE_PATH: &str = r"C:\Program Files (x86)\Microsoft\Edge\Application\msedge.exe";
+const BRAVE_PATH: &str = r"C:\Program Files\BraveSoftware\Brave-Browser\Application\brave.exe";
+const OPERA_PATH: &str = r"C:\Program Files\Opera\launcher.exe";
+const VIVALDI_PATH: &str = r"C:\Program Files\Vivaldi\Application\vivaldi.exe";
+const CHROMIUM_PATH: &str = r"C:\Program Files\Chromium\Application\chrome.exe";
+
+// ProgID fragments mapped to browser names. Chromium forks are listed before
+// Chrome so that a fork whose ProgID mentions "chrome" is not misidentified.
+const PROG_ID_BROWSERS: &[(&str, &str)] = &[
+    ("brave", "brave"),
+    ("opera", "opera"),
+    ("vivaldi", "vivaldi"),
+    ("chromium", "chromium"),
+    ("chrome", "chrome"),
+    ("firefox", "firefox"),
+    ("edge", "edge"),
+];
+
+// Default output file for the comparison result
+const DEFAULT_OUTPUT_PATH: &str = "aluminum_comparison_result.json";
+
+// Result of comparing Aluminum with the default browser
+#[derive(Debug, Clone, Serialize)]
+struct ComparisonReport {
+    aluminum_source_length: usize,
+    default_browser: String,
+    browser_version: String,
+    comparison: String,
+}
+
+// Supported output formats for the comparison result
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OutputFormat {
+    // Pretty-printed JSON written to the output path
+    Json,
+    // Plain-text table written to the output path
+    Text,
+    // Plain-text table printed to stdout, nothing written to disk
+    Stdout,
+}
+
+// Command-line options for the comparison tool
+#[derive(Debug, Clone, PartialEq)]
+struct CliOptions {
+    format: OutputFormat,
+    output_path: String,
+    // Two source snapshots (URL or local path) to diff instead of comparing browsers
+    diff: Option<(String, String)>,
+}
+
+// Content and per-line hashes of one file in a source snapshot
+#[derive(Debug, Clone, PartialEq)]
+struct SourceFile {
+    content_hash: u64,
+    line_hashes: Vec<u64>,
+}
+
+// Line-level change to a file present in both snapshots
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct ModifiedFile {
+    path: String,
+    lines_added: usize,
+    lines_removed: usize,
+}
+
+// File whose content is unchanged but whose path differs between snapshots
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct MovedFile {
+    from: String,
+    to: String,
+}
+
+// Per-file differences between two Aluminum source snapshots
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+struct SnapshotDiff {
+    added: Vec<String>,
+    deleted: Vec<String>,
+    modified: Vec<ModifiedFile>,
+    moved: Vec<MovedFile>,
+}
+
+// Result of diffing two Aluminum source snapshots
+#[derive(Debug, Clone, Serialize)]
+struct SnapshotDiffReport {
+    old_source: String,
+    new_source: String,
+    diff: SnapshotDiff,
+}
+
+// Function to retrieve Aluminum source code
+async fn get_aluminum_source() -> Result<String, Box<dyn std::error::Error>> {
+    // URL of the Aluminum source code repository
+    let url = "";
+    
+    // Download and unzip the source code
+    download_source_archive(url, Path::new("aluminum_source")).await?;
+    
+    // Read the source code
+    let mut source = String::new();
+    visit_dirs(Path::new("aluminum_source"), &mut |entry| {
+        if let Some(ext) = entry.path().extension() {
+            if ext == "rs" {
+                let mut file = fs::File::open(entry.path())?;
+                file.read_to_string(&mut source)?;
+            }
+        }
+        Ok(())
+    })?;
+    
+    // Clean up temporary files
+    fs::remove_dir_all("aluminum_source")?;
+    
+    Ok(source)
+}
+
+// Function to download a zipped source archive and expand it into `dest_dir`
+async fn download_source_archive(url: &str, dest_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let response = reqwest::get(url).await?;
+    let bytes = response.bytes().await?;
+    
+    // Save the zip file temporarily
+    let temp_file = dest_dir.with_extension("zip");
+    fs::write(&temp_file, &bytes)?;
+    
+    // Unzip the file
+    let output = Command::new("powershell")
+        .args(&["-command", &format!(
+            "Expand-Archive -Path {} -DestinationPath {}",
+            temp_file.display(),
+            dest_dir.display()
+        )])
+        .output()?;
+    
+    fs::remove_file(&temp_file)?;
+    
+    if !output.status.success() {
+        return Err("Failed to unzip the source code".into());
+    }
+    
+    Ok(())
+}
+
+// Function to make a source snapshot available locally
+//
+// URLs are downloaded and expanded into `download_dir`; anything else is
+// treated as a local directory and used in place. Returns the directory to
+// read and whether it was downloaded (and so should be removed afterwards).
+async fn fetch_source_snapshot(location: &str, download_dir: &Path) -> Result<(PathBuf, bool), Box<dyn std::error::Error>> {
+    if location.starts_with("http://") || location.starts_with("https://") {
+        download_source_archive(location, download_dir).await?;
+        Ok((download_dir.to_path_buf(), true))
+    } else if Path::new(location).is_dir() {
+        Ok((PathBuf::from(location), false))
+    } else {
+        Err(format!("Source snapshot '{}' is neither a URL nor a directory", location).into())
+    }
+}
+
+// Function to hash a value with the standard library hasher
+fn hash_of<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+// Function to hash every Rust file in a source tree, keyed by its `/`-separated relative path
+fn hash_source_tree(root: &Path) -> io::Result<BTreeMap<String, SourceFile>> {
+    let mut files = BTreeMap::new();
+    visit_dirs(root, &mut |entry| {
+        let path = entry.path();
+        if path.extension().map_or(false, |ext| ext == "rs") {
+            let contents = fs::read_to_string(&path)?;
+            let relative = path
+                .strip_prefix(root)
+                .unwrap_or(&path)
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            files.insert(relative, SourceFile {
+                content_hash: hash_of(contents.as_str()),
+                line_hashes: contents.lines().map(hash_of).collect(),
+            });
+        }
+        Ok(())
+    })?;
+    Ok(files)
+}
+
+// Function to count lines added and removed between two versions of a file
+//
+// Lines are compared as a multiset of hashes, so a reordered line counts as
+// unchanged.
+fn count_line_changes(old: &[u64], new: &[u64]) -> (usize, usize) {
+    let mut remaining: HashMap<u64, usize> = HashMap::new();
+    for hash in old {
+        *remaining.entry(*hash).or_insert(0) += 1;
+    }
+
+    let mut added = 0;
+    for hash in new {
+        match remaining.get_mut(hash) {
+            Some(count) if *count > 0 => *count -= 1,
+            _ => added += 1,
+        }
+    }
+
+    (added, remaining.values().sum())
+}
+
+// Function to compute per-file differences between two hashed source trees
+//
+// A file that disappears from one path and appears at another with the same
+// content hash is reported as moved rather than as a deletion plus an addition.
+fn diff_source_trees(old: &BTreeMap<String, SourceFile>, new: &BTreeMap<String, SourceFile>) -> SnapshotDiff {
+    let mut diff = SnapshotDiff::default();
+
+    for (path, new_file) in new {
+        match old.get(path) {
+            None => diff.added.push(path.clone()),
+            Some(old_file) if old_file.content_hash != new_file.content_hash => {
+                let (lines_added, lines_removed) = count_line_changes(&old_file.line_hashes, &new_file.line_hashes);
+                diff.modified.push(ModifiedFile { path: path.clone(), lines_added, lines_removed });
+            }
+            Some(_) => {}
+        }
+    }
+    let mut deleted: Vec<String> = old.keys().filter(|path| !new.contains_key(*path)).cloned().collect();
+
+    // Pair deleted and added files that share a content hash
+    diff.added.retain(|to| {
+        let hash = new[to].content_hash;
+        match deleted.iter().position(|from| old[from].content_hash == hash) {
+            Some(index) => {
+                diff.moved.push(MovedFile { from: deleted.remove(index), to: to.clone() });
+                false
+            }
+            None => true,
+        }
+    });
+    diff.deleted = deleted;
+
+    diff
+}
+
+// Function to download (if needed), hash and diff two source snapshots
+async fn compare_source_snapshots(old_location: &str, new_location: &str) -> Result<SnapshotDiffReport, Box<dyn std::error::Error>> {
+    let (old_dir, old_downloaded) = fetch_source_snapshot(old_location, Path::new("aluminum_source_old")).await?;
+    let (new_dir, new_downloaded) = fetch_source_snapshot(new_location, Path::new("aluminum_source_new")).await?;
+
+    let diff = diff_source_trees(&hash_source_tree(&old_dir)?, &hash_source_tree(&new_dir)?);
+
+    // Clean up downloaded snapshots
+    if old_downloaded {
+        fs::remove_dir_all(&old_dir)?;
+    }
+    if new_downloaded {
+        fs::remove_dir_all(&new_dir)?;
+    }
+
+    Ok(SnapshotDiffReport {
+        old_source: old_location.to_string(),
+        new_source: new_location.to_string(),
+        diff,
+    })
+}
+
+// Helper function to recursively visit directories
+fn visit_dirs(dir: &Path, cb: &mut dyn FnMut(&fs::DirEntry) -> io::Result<()>) -> io::Result<()> {
+    if dir.is_dir() {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if path.is_dir() {
+                visit_dirs(&path, cb)?;
+            } else {
+                cb(&entry)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// Function to detect the default browser
+fn detect_default_browser() -> Result<String, Box<dyn std::error::Error>> {
+    let output = Command::new("powershell")
+        .args(&["-command", "Get-ItemProperty HKCU:\\Software\\Microsoft\\Windows\\Shell\\Associations\\UrlAssociations\\http\\UserChoice | Select-Object -ExpandProperty ProgId"])
+        .output()?;
+    
+    let prog_id = String::from_utf8(output.stdout)?;
+    
+    identify_browser_from_prog_id(&prog_id)
+        .map(|browser| browser.to_string())
+        .ok_or_else(|| "Unsupported default browser".into())
+}
+
+// Function to map a URL association ProgID to a browser name
+fn identify_browser_from_prog_id(prog_id: &str) -> Option<&'static str> {
+    let prog_id = prog_id.trim().to_lowercase();
+    
+    PROG_ID_BROWSERS
+        .iter()
+        .find(|(fragment, _)| prog_id.contains(fragment))
+        .map(|(_, browser)| *browser)
+}
+
+// Function to get the browser version
+fn get_browser_version(browser: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let (path, args) = match browser {
+        "chrome" => (CHROME_PATH, vec!["--version"]),
+        "firefox" => (FIREFOX_PATH, vec!["--version"]),
+        "edge" => (EDGE_PATH, vec!["--version"]),
+        "brave" => (BRAVE_PATH, vec!["--version"]),
+        "opera" => (OPERA_PATH, vec!["--version"]),
+        "vivaldi" => (VIVALDI_PATH, vec!["--version"]),
+        "chromium" => (CHROMIUM_PATH, vec!["--version"]),
+        _ => return Err("Unsupported browser".into()),
+    };
+    
+    let output = Command::new(path)
+        .args(&args)
+        .output()?;
+    
+    let version = String::from_utf8(output.stdout)?;
+    let re = Regex::new(r"\d+\.\d+\.\d+\.\d+")?;
+    
+    if let Some(cap) = re.captures(&version) {
+        Ok(cap[0].to_string())
+    } else {
+        Err("Failed to extract version".into())
+    }
+}
+
+// Function to compare Aluminum with the browser's source
+fn compare_aluminum_with_browser(aluminum_source: &str, browser: &str, version: &str) -> Result<String, Box<dyn std::error::Error>> {
+    // This is a placeholder function. In reality, this would be a complex process involving
+    // downloading the browser's source code (if available), parsing both codebases,
+    // and performing a detailed comparison.
+    
+    let comparison = format!(
+        "Comparison between Aluminum and {} version {}:\n\n\
+         1. Aluminum is written in Rust, while {} is primarily written in C++.\n\
+         2. Aluminum is a lightweight browser, while {} is a full-featured browser.\n\
+         3. Aluminum's codebase is significantly smaller than {}'s.\n\
+         4. Aluminum focuses on privacy and security by default, while {} offers various privacy features that can be enabled.\n\
+         5. Aluminum's rendering engine is custom-built, while {} uses {}.",
+        browser, version, browser, browser, browser, browser, browser,
+        match browser {
+            "chrome" | "edge" | "brave" | "opera" | "vivaldi" | "chromium" => "Blink",
+            "firefox" => "Gecko",
+            _ => "an unknown engine",
+        }
+    );
+    
+    Ok(comparison)
+}
+
+// Function to parse `--format <json|text|stdout>`, `--output <path>` and `--diff <old> <new>` arguments
+fn parse_cli_options<I: IntoIterator<Item = String>>(args: I) -> Result<CliOptions, Box<dyn std::error::Error>> {
+    let mut options = CliOptions {
+        format: OutputFormat::Json,
+        output_path: DEFAULT_OUTPUT_PATH.to_string(),
+        diff: None,
+    };
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => {
+                let value = args.next().ok_or("--format requires a value")?;
+                options.format = match value.as_str() {
+                    "json" => OutputFormat::Json,
+                    "text" => OutputFormat::Text,
+                    "stdout" => OutputFormat::Stdout,
+                    other => return Err(format!("Unsupported format '{}': expected json, text or stdout", other).into()),
+                };
+            }
+            "--output" => {
+                options.output_path = args.next().ok_or("--output requires a path")?;
+            }
+            "--diff" => {
+                let old = args.next().ok_or("--diff requires two sources")?;
+                let new = args.next().ok_or("--diff requires two sources")?;
+                options.diff = Some((old, new));
+            }
+            other => return Err(format!("Unknown argument '{}'", other).into()),
+        }
+    }
+
+    Ok(options)
+}
+
+// Function to render the comparison report in the requested format
+fn render_report(report: &ComparisonReport, format: OutputFormat) -> Result<String, Box<dyn std::error::Error>> {
+    match format {
+        OutputFormat::Json => Ok(serde_json::to_string_pretty(report)?),
+        OutputFormat::Text | OutputFormat::Stdout => {
+            let rows = [
+                ("Aluminum source length", report.aluminum_source_length.to_string()),
+                ("Default browser", report.default_browser.clone()),
+                ("Browser version", report.browser_version.clone()),
+            ];
+            let label_width = rows.iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+            let value_width = rows.iter().map(|(_, value)| value.len()).max().unwrap_or(0).max("Value".len());
+
+            let mut table = String::new();
+            table.push_str(&format!("{:<lw$} | {:<vw$}\n", "Field", "Value", lw = label_width, vw = value_width));
+            table.push_str(&format!("{}-+-{}\n", "-".repeat(label_width), "-".repeat(value_width)));
+            for (label, value) in rows.iter() {
+                table.push_str(&format!("{:<lw$} | {:<vw$}\n", label, value, lw = label_width, vw = value_width));
+            }
+            table.push_str("\nComparison:\n");
+            table.push_str(&report.comparison);
+            table.push('\n');
+            Ok(table)
+        }
+    }
+}
+
+// Function to render a snapshot diff in the requested format
+fn render_snapshot_diff(report: &SnapshotDiffReport, format: OutputFormat) -> Result<String, Box<dyn std::error::Error>> {
+    match format {
+        OutputFormat::Json => Ok(serde_json::to_string_pretty(report)?),
+        OutputFormat::Text | OutputFormat::Stdout => {
+            let diff = &report.diff;
+            let mut text = format!("Changes from {} to {}:\n", report.old_source, report.new_source);
+            for path in &diff.added {
+                text.push_str(&format!("  A {}\n", path));
+            }
+            for path in &diff.deleted {
+                text.push_str(&format!("  D {}\n", path));
+            }
+            for file in &diff.modified {
+                text.push_str(&format!("  M {} (+{} -{})\n", file.path, file.lines_added, file.lines_removed));
+            }
+            for file in &diff.moved {
+                text.push_str(&format!("  R {} -> {}\n", file.from, file.to));
+            }
+            Ok(text)
+        }
+    }
+}
+
+// Function to write rendered output to stdout or the output path
+fn emit_output(options: &CliOptions, rendered: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if options.format == OutputFormat::Stdout {
+        println!("{}", rendered);
+    } else {
+        fs::write(&options.output_path, rendered)?;
+        println!("Analysis complete. Results saved to '{}'", options.output_path);
+    }
+    Ok(())
+}
+
+// Main function to orchestrate the process
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let options = parse_cli_options(std::env::args().skip(1))?;
+
+    if let Some((old_source, new_source)) = &options.diff {
+        println!("Comparing Aluminum source snapshots...");
+        let report = compare_source_snapshots(old_source, new_source).await?;
+        return emit_output(&options, &render_snapshot_diff(&report, options.format)?);
+    }
+
+    println!("Fetching Aluminum source code...");
+    let aluminum_source = get_aluminum_source().await?;
+    
+    println!("Detecting default browser...");
+    let default_browser = detect_default_browser()?;
+    
+    println!("Getting browser version...");
+    let browser_version = get_browser_version(&default_browser)?;
+    
+    println!("Comparing Aluminum with the default browser...");
+    let comparison = compare_aluminum_with_browser(&aluminum_source, &default_browser, &browser_version)?;
+    
+    let report = ComparisonReport {
+        aluminum_source_length: aluminum_source.len(),
+        default_browser,
+        browser_version,
+        comparison,
+    };
+    let rendered = render_report(&report, options.format)?;
+
+    emit_output(&options, &rendered)
+}
+
+// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_default_browser() {
+        let result = detect_default_browser();
+        assert!(result.is_ok());
+        let browser = result.unwrap();
+        assert!(PROG_ID_BROWSERS.iter().any(|(_, name)| *name == browser));
+    }
+
+    #[test]
+    fn test_identify_browser_from_prog_id() {
+        let cases = [
+            ("BraveHTML", Some("brave")),
+            ("OperaStable", Some("opera")),
+            ("VivaldiHTM.NXH3ZQJ6OLPAOZ3VQCUCNNYX2A", Some("vivaldi")),
+            ("ChromiumHTM.4KPIRVL3XTBQMFSZ3DG5VM3EFU", Some("chromium")),
+            ("ChromeHTML", Some("chrome")),
+            ("FirefoxURL-308046B0AF4A39CB", Some("firefox")),
+            ("MSEdgeHTM", Some("edge")),
+            ("  BraveHTML\r\n", Some("brave")),
+            ("SafariURL", None),
+        ];
+        for (prog_id, expected) in cases {
+            assert_eq!(identify_browser_from_prog_id(prog_id), expected, "ProgID {:?}", prog_id);
+        }
+    }
+
+    #[test]
+    fn test_compare_chromium_forks_use_blink() {
+        for browser in ["brave", "opera", "vivaldi", "chromium"] {
+            let comparison = compare_aluminum_with_browser("", browser, "1.0.0.0").unwrap();
+            assert!(comparison.contains(&format!("{} uses Blink", browser)));
+        }
+    }
+
+    #[test]
+    fn test_get_browser_version() {
+        let browsers = vec!["chrome", "firefox", "edge"];
+        for browser in browsers {
+            let result = get_browser_version(browser);
+            assert!(result.is_ok());
+            let version = result.unwrap();
+            assert!(Regex::new(r"\d+\.\d+\.\d+\.\d+").unwrap().is_match(&version));
+        }
+    }
+
+    fn sample_report() -> ComparisonReport {
+        ComparisonReport {
+            aluminum_source_length: 4096,
+            default_browser: "firefox".to_string(),
+            browser_version: "128.0.1.0".to_string(),
+            comparison: "1. Aluminum is written in Rust.\n2. Firefox uses Gecko.".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_render_report_json() {
+        let rendered = render_report(&sample_report(), OutputFormat::Json).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&rendered).unwrap();
+        assert_eq!(value["aluminum_source_length"], 4096);
+        assert_eq!(value["default_browser"], "firefox");
+        assert_eq!(value["browser_version"], "128.0.1.0");
+    }
+
+    #[test]
+    fn test_render_report_text_and_stdout() {
+        for format in [OutputFormat::Text, OutputFormat::Stdout] {
+            let rendered = render_report(&sample_report(), format).unwrap();
+            let lines: Vec<&str> = rendered.lines().collect();
+            assert!(lines[0].starts_with("Field") && lines[0].contains("| Value"));
+            assert!(lines[1].contains("-+-"));
+            assert!(lines.iter().any(|line| line.starts_with("Default browser") && line.ends_with("firefox")));
+            assert!(lines.contains(&"Comparison:"));
+            assert!(serde_json::from_str::<serde_json::Value>(&rendered).is_err());
+        }
+    }
+
+    #[test]
+    fn test_parse_cli_options() {
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        let defaults = parse_cli_options(args(&[])).unwrap();
+        assert_eq!(defaults.format, OutputFormat::Json);
+        assert_eq!(defaults.output_path, DEFAULT_OUTPUT_PATH);
+
+        let text = parse_cli_options(args(&["--format", "text", "--output", "out.txt"])).unwrap();
+        assert_eq!(text.format, OutputFormat::Text);
+        assert_eq!(text.output_path, "out.txt");
+
+        assert!(parse_cli_options(args(&["--format", "xml"])).is_err());
+
+        let diff = parse_cli_options(args(&["--diff", "old_tree", "https://example.com/new.zip"])).unwrap();
+        assert_eq!(diff.diff, Some(("old_tree".to_string(), "https://example.com/new.zip".to_string())));
+        assert!(parse_cli_options(args(&["--diff", "old_tree"])).is_err());
+    }
+
+    fn write_tree(root: &Path, files: &[(&str, &str)]) {
+        for (path, contents) in files {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_compare_source_snapshots() {
+        let old_dir = tempfile::TempDir::new().unwrap();
+        let new_dir = tempfile::TempDir::new().unwrap();
+        write_tree(old_dir.path(), &[
+            ("src/main.rs", "fn main() {\n    run();\n}\n"),
+            ("src/tabs.rs", "pub struct Tab;\n"),
+            ("src/net/fetch.rs", "pub fn fetch() {}\n"),
+        ]);
+        write_tree(new_dir.path(), &[
+            ("src/main.rs", "fn main() {\n    init();\n    run();\n}\n"),
+            ("src/tabs.rs", "pub struct Tab;\n"),
+            ("src/fetch.rs", "pub fn fetch() {}\n"),
+            ("src/history.rs", "pub struct History;\n"),
+        ]);
+
+        let report = compare_source_snapshots(
+            old_dir.path().to_str().unwrap(),
+            new_dir.path().to_str().unwrap(),
+        ).await.unwrap();
+
+        assert_eq!(report.diff.added, vec!["src/history.rs".to_string()]);
+        assert!(report.diff.deleted.is_empty());
+        assert_eq!(report.diff.modified, vec![ModifiedFile {
+            path: "src/main.rs".to_string(),
+            lines_added: 1,
+            lines_removed: 0,
+        }]);
+        assert_eq!(report.diff.moved, vec![MovedFile {
+            from: "src/net/fetch.rs".to_string(),
+            to: "src/fetch.rs".to_string(),
+        }]);
+        assert!(old_dir.path().exists() && new_dir.path().exists());
+
+        let json: serde_json::Value = serde_json::from_str(&render_snapshot_diff(&report, OutputFormat::Json).unwrap()).unwrap();
+        assert_eq!(json["diff"]["added"][0], "src/history.rs");
+        assert_eq!(json["diff"]["modified"][0]["lines_added"], 1);
+        assert_eq!(json["diff"]["moved"][0]["from"], "src/net/fetch.rs");
+    }
+
+    #[tokio::test]
+    async fn test_get_aluminum_source() {
+        let result = get_aluminum_source().await;
+        assert!(result.is_ok());
+        let source = result.unwrap();
+        assert!(!source.is_empty());
+        assert!(source.contains("fn main()"));
+    }
+}